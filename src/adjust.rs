@@ -0,0 +1,199 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Display adjustments (`c` grayscale, `i` invert, `matte` background, `#` grid overlay),
+//! shared by all images in Single mode.
+//!
+//! They are applied by the workers after resize and before encode, so only the displayed
+//! pixels are converted, and are part of the render cache key. Tile pages, yanked images and
+//! saved files are never adjusted.
+
+use std::borrow::Cow;
+
+use anyhow::{Context, Result};
+use image::DynamicImage;
+
+use crate::grid::Grid;
+use crate::zoom::CropRect;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Adjustments {
+    /// Show luminance only (Rec. 709 weights).
+    pub grayscale: bool,
+    /// Negative colors (alpha kept), for light-background plots on a dark terminal or scanned
+    /// negatives.
+    pub invert: bool,
+    /// Background composited under transparent pixels (`matte` option, `:matte`).
+    pub matte: Option<[u8; 3]>,
+    /// Overlay drawn last, over the adjusted colors.
+    pub grid: Grid,
+}
+
+impl Adjustments {
+    pub fn is_none(self) -> bool {
+        self == Self::default()
+    }
+
+    /// Status text for the active adjustments (e.g. `gray inverted`), or None.
+    pub fn label(self) -> Option<String> {
+        let grid = self.grid.label();
+        let matte = self
+            .matte
+            .map(|[r, g, b]| format!("matte #{r:02x}{g:02x}{b:02x}"));
+        let parts: Vec<&str> = [(self.grayscale, "gray"), (self.invert, "inverted")]
+            .into_iter()
+            .filter_map(|(on, name)| on.then_some(name))
+            .chain(matte.as_deref())
+            .chain(grid.as_deref())
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
+
+    /// Apply the adjustments to a resized rendering of `region` (source pixels). Grayscale
+    /// output is RGB(A) 8-bit again, which every protocol encoder takes as is.
+    pub fn apply(self, img: Cow<'_, DynamicImage>, region: CropRect) -> Cow<'_, DynamicImage> {
+        if self.is_none() {
+            return img;
+        }
+        let img = match self.matte {
+            Some(color) if img.color().has_alpha() => Cow::Owned(composite(&img, color)),
+            _ => img,
+        };
+        let mut out = if self.grayscale {
+            let gray = img.grayscale();
+            if gray.color().has_alpha() {
+                DynamicImage::ImageRgba8(gray.to_rgba8())
+            } else {
+                DynamicImage::ImageRgb8(gray.to_rgb8())
+            }
+        } else {
+            img.into_owned()
+        };
+        if self.invert {
+            out.invert();
+        }
+        Cow::Owned(crate::grid::draw(out, self.grid, region))
+    }
+}
+
+/// Parse a matte color: `#rrggbb`, `#rgb` (the `#` is optional) or a basic color name. An empty
+/// value, `none` or `off` means no matte.
+pub fn parse_matte(s: &str) -> Result<Option<[u8; 3]>> {
+    let s = s.trim().to_ascii_lowercase();
+    let named = match s.as_str() {
+        "" | "none" | "off" => return Ok(None),
+        "white" => Some([255, 255, 255]),
+        "black" => Some([0, 0, 0]),
+        "gray" | "grey" => Some([128, 128, 128]),
+        _ => None,
+    };
+    if named.is_some() {
+        return Ok(named);
+    }
+    let hex = s.strip_prefix('#').unwrap_or(&s);
+    let digits: Vec<u8> = hex
+        .chars()
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<_>>()
+        .with_context(|| format!("matte: not a color: {s}"))?;
+    match digits[..] {
+        [r, g, b] => Ok(Some([r * 17, g * 17, b * 17])),
+        [r1, r0, g1, g0, b1, b0] => Ok(Some([r1 * 16 + r0, g1 * 16 + g0, b1 * 16 + b0])),
+        _ => anyhow::bail!("matte: expected #rrggbb, #rgb or a color name: {s}"),
+    }
+}
+
+/// Blend every pixel over an opaque `color` (the alpha channel is dropped).
+fn composite(img: &DynamicImage, color: [u8; 3]) -> DynamicImage {
+    let mut rgba = img.to_rgba8();
+    for pixel in rgba.pixels_mut() {
+        let [r, g, b, a] = pixel.0;
+        let a = u32::from(a);
+        let blend =
+            |c: u8, bg: u8| ((u32::from(c) * a + u32::from(bg) * (255 - a) + 127) / 255) as u8;
+        pixel.0 = [
+            blend(r, color[0]),
+            blend(g, color[1]),
+            blend(b, color[2]),
+            255,
+        ];
+    }
+    DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(rgba).to_rgb8())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grayscale_keeps_alpha() {
+        let img = DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+            1,
+            1,
+            image::Rgba([255, 0, 0, 128]),
+        ));
+        let none = Adjustments::default();
+        assert!(matches!(
+            none.apply(Cow::Borrowed(&img), CropRect::full((1, 1))),
+            Cow::Borrowed(_)
+        ));
+
+        let gray = Adjustments {
+            grayscale: true,
+            ..Default::default()
+        }
+        .apply(Cow::Borrowed(&img), CropRect::full((1, 1)));
+        let DynamicImage::ImageRgba8(out) = gray.as_ref() else {
+            panic!("expected RGBA output");
+        };
+        let [r, g, b, a] = out.get_pixel(0, 0).0;
+        assert!(r == g && g == b && r < 128);
+        assert_eq!(a, 128);
+    }
+
+    #[test]
+    fn test_invert_keeps_alpha() {
+        let img = DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+            1,
+            1,
+            image::Rgba([255, 200, 0, 128]),
+        ));
+        let adjust = Adjustments {
+            invert: true,
+            ..Default::default()
+        };
+        let out = adjust
+            .apply(Cow::Borrowed(&img), CropRect::full((1, 1)))
+            .to_rgba8();
+        assert_eq!(out.get_pixel(0, 0).0, [0, 55, 255, 128]);
+        assert_eq!(adjust.label().as_deref(), Some("inverted"));
+        assert_eq!(Adjustments::default().label(), None);
+    }
+
+    #[test]
+    fn test_matte() {
+        assert_eq!(parse_matte("").unwrap(), None);
+        assert_eq!(parse_matte("off").unwrap(), None);
+        assert_eq!(parse_matte("White").unwrap(), Some([255, 255, 255]));
+        assert_eq!(parse_matte("#fa0").unwrap(), Some([255, 170, 0]));
+        assert_eq!(parse_matte("102030").unwrap(), Some([16, 32, 48]));
+        assert!(parse_matte("#12345").is_err());
+        assert!(parse_matte("blurple").is_err());
+
+        let img = DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+            1,
+            1,
+            image::Rgba([0, 0, 0, 0]),
+        ));
+        let adjust = Adjustments {
+            matte: Some([255, 255, 255]),
+            ..Default::default()
+        };
+        let out = adjust.apply(Cow::Borrowed(&img), CropRect::full((1, 1)));
+        let DynamicImage::ImageRgb8(out) = out.as_ref() else {
+            panic!("expected RGB output");
+        };
+        assert_eq!(out.get_pixel(0, 0).0, [255, 255, 255]);
+        assert_eq!(adjust.label().as_deref(), Some("matte #ffffff"));
+    }
+}
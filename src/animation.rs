@@ -0,0 +1,199 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Animated GIF playback in Single mode.
+//!
+//! The still first frame is shown like any other image. Once it is on screen, the worker
+//! decodes every frame and renders it for the same view (`ImageWorker::request_animation`);
+//! the main loop then sends the frames to the writer as their delays expire. `a` pauses and
+//! resumes; `n` / `p` pause and step one frame, to inspect sprite sheets or GIF artifacts.
+//!
+//! With KGP the frames are instead sent once, as frames of the placed image (`a=f`), and the
+//! terminal plays them (`App::tick_animation`); only pause and resume are sent afterwards.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Delay used for frames that ask for (almost) none, as browsers do.
+const DEFAULT_DELAY: Duration = Duration::from_millis(100);
+
+/// Shortest delay honoured as given.
+const MIN_DELAY: Duration = Duration::from_millis(20);
+
+/// Frame delay as shown: very short delays mean "as fast as possible" in practice, which
+/// viewers play at [`DEFAULT_DELAY`].
+pub fn frame_delay(delay: Duration) -> Duration {
+    if delay < MIN_DELAY {
+        DEFAULT_DELAY
+    } else {
+        delay
+    }
+}
+
+/// One rendered frame, ready for the writer.
+pub struct AnimationFrame {
+    pub encoded_chunks: Arc<Vec<Vec<u8>>>,
+    pub delay: Duration,
+}
+
+/// Frames of the animated image on screen and where playback is.
+pub struct Animation {
+    pub frames: Vec<AnimationFrame>,
+    pub playback: Playback,
+    /// Render epoch of the frame being written (the next one waits for it).
+    pub in_flight: Option<u64>,
+    /// Transmit epoch of the still the terminal holds the frames for (terminal-side playback).
+    pub loaded: Option<u64>,
+}
+
+impl Animation {
+    pub fn new(frames: Vec<AnimationFrame>, now: Instant) -> Self {
+        let delays = frames.iter().map(|f| f.delay).collect();
+        Self {
+            frames,
+            playback: Playback::new(delays, now),
+            in_flight: None,
+            loaded: None,
+        }
+    }
+
+    /// The frame to show now.
+    pub fn frame(&self) -> &AnimationFrame {
+        &self.frames[self.playback.index()]
+    }
+}
+
+/// Which frame to show and when the next one is due.
+pub struct Playback {
+    delays: Vec<Duration>,
+    index: usize,
+    due: Instant,
+    paused: bool,
+    /// A stepped-to frame still has to be shown (even while paused).
+    redraw: bool,
+    /// Stepped since the animation started, so `index` is the frame on screen even when the
+    /// terminal plays it.
+    stepped: bool,
+}
+
+impl Playback {
+    /// Start on frame 0 (already on screen, as the still image).
+    pub fn new(delays: Vec<Duration>, now: Instant) -> Self {
+        let due = now + delays.first().copied().unwrap_or(DEFAULT_DELAY);
+        Self {
+            delays,
+            index: 0,
+            due,
+            paused: false,
+            redraw: false,
+            stepped: false,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.delays.len()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn is_stepped(&self) -> bool {
+        self.stepped
+    }
+
+    /// When the next frame is due (None while paused).
+    pub fn due(&self) -> Option<Instant> {
+        (!self.paused).then_some(self.due)
+    }
+
+    /// Pause and move `delta` frames (looping). Returns the new frame index.
+    pub fn step(&mut self, delta: i32) -> usize {
+        let len = self.delays.len().max(1) as i64;
+        self.index = (self.index as i64 + i64::from(delta)).rem_euclid(len) as usize;
+        self.paused = true;
+        self.redraw = true;
+        self.stepped = true;
+        self.index
+    }
+
+    /// Step to the next frame (looping) if it is due, or show a frame stepped to. A late
+    /// frame is not caught up: the new frame gets its full delay from `now`.
+    pub fn advance(&mut self, now: Instant) -> bool {
+        if std::mem::take(&mut self.redraw) {
+            return true;
+        }
+        if self.paused || self.delays.len() < 2 || now < self.due {
+            return false;
+        }
+        self.index = (self.index + 1) % self.delays.len();
+        self.due = now + self.delays[self.index];
+        true
+    }
+
+    /// Pause or resume; resuming shows the current frame for its full delay.
+    pub fn toggle_pause(&mut self, now: Instant) -> bool {
+        self.paused = !self.paused;
+        if !self.paused {
+            self.due = now
+                + self
+                    .delays
+                    .get(self.index)
+                    .copied()
+                    .unwrap_or(DEFAULT_DELAY);
+        }
+        self.paused
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_playback_loops_and_pauses() {
+        let ms = Duration::from_millis;
+        let start = Instant::now();
+        let mut playback = Playback::new(vec![ms(50), ms(30)], start);
+        assert!(!playback.advance(start + ms(49)));
+        assert!(playback.advance(start + ms(50)));
+        assert_eq!(playback.index(), 1);
+        assert_eq!(playback.due(), Some(start + ms(80)));
+        assert!(playback.advance(start + ms(100)));
+        assert_eq!(playback.index(), 0);
+
+        assert!(playback.toggle_pause(start + ms(100)));
+        assert_eq!(playback.due(), None);
+        assert!(!playback.advance(start + ms(1000)));
+        assert!(!playback.toggle_pause(start + ms(1000)));
+        assert_eq!(playback.due(), Some(start + ms(1050)));
+    }
+
+    #[test]
+    fn test_step_pauses_and_wraps() {
+        let ms = Duration::from_millis;
+        let start = Instant::now();
+        let mut playback = Playback::new(vec![ms(50), ms(30), ms(40)], start);
+        assert_eq!(playback.step(-1), 2);
+        assert!(playback.is_paused());
+        // The stepped-to frame is shown once, then playback stays paused.
+        assert!(playback.advance(start));
+        assert!(!playback.advance(start + ms(1000)));
+        assert_eq!(playback.step(2), 1);
+        assert!(!playback.toggle_pause(start));
+        assert_eq!(playback.due(), Some(start + ms(30)));
+    }
+
+    #[test]
+    fn test_frame_delay() {
+        assert_eq!(frame_delay(Duration::ZERO), DEFAULT_DELAY);
+        assert_eq!(
+            frame_delay(Duration::from_millis(40)),
+            Duration::from_millis(40)
+        );
+    }
+}
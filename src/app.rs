@@ -12,19 +12,50 @@
 //! Most methods are intentionally non-blocking; heavy work is pushed to the worker/writer.
 
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::Result;
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui_image::picker::Picker;
 
+use crate::adjust::Adjustments;
+use crate::animation::Animation;
+use crate::bookmarks::Bookmarks;
+use crate::browser::Browser;
+use crate::calibrate::Calibration;
+use crate::command::CommandLine;
 use crate::config::Config;
+use crate::dicom::WindowLevel;
+use crate::disk_cache::DiskCache;
 use crate::fit::{FitMode, ViewMode};
+use crate::help::Help;
+use crate::jump::{Jump, JumpLabels};
+use crate::keymap::Keymap;
 use crate::kgp::KgpState;
+use crate::layout::{DayBand, TileLayout};
+use crate::listing::Listing;
+use crate::pages::Pages;
+use crate::panes::Panes;
+use crate::placeholder::Placeholder;
+use crate::power::Power;
 use crate::prefetch::{PrefetchRequest, PrefetchWorker};
-use crate::sender::{StatusIndicator, TerminalWriter, WriterRequest, WriterResultKind};
-use crate::worker::{ImageRequest, ImageWorker};
+use crate::protocol::{Capabilities, Protocol};
+use crate::rotation::{Rotation, RotationStore};
+use crate::scan::PathScan;
+use crate::sender::{
+    StatusIndicator, TerminalWriter, WriterOptions, WriterRequest, WriterResultKind,
+};
+use crate::slideshow::{Setting, Slideshow, SlideshowOptions, Step};
+use crate::trash::Trashed;
+use crate::virtual_path::VirtualPath;
+use crate::worker::{ImageRequest, ImageWorker, SaveRequest, SaveResult, StageTimings};
+use crate::yank::Yank;
+use crate::zoom::{CropRect, HALF_PAGE_STEPS, Selection, ZOOM_STEP};
+
+/// Maximum number of placeholders kept (a few hundred bytes each).
+const MAX_PLACEHOLDERS: usize = 4096;
 
 /// Cache key for rendered images.
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
@@ -35,6 +66,14 @@ pub struct CacheKey {
     pub target: (u32, u32),
     /// Fit mode (Normal or Fit).
     pub fit_mode: FitMode,
+    /// Zoomed region of the source image (None = whole image).
+    pub crop: Option<CropRect>,
+    /// User rotation (always `None` for tile pages).
+    pub rotation: Rotation,
+    /// DICOM window/level adjustment (always the default for other images and tile pages).
+    pub window: WindowLevel,
+    /// Color adjustments (always the default for tile pages).
+    pub adjust: Adjustments,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -45,12 +84,18 @@ struct PrefetchSignature {
     prefetch_count: usize,
     anchor: usize,
     grid: Option<(usize, usize)>,
+    direction: i32,
+    crop: Option<CropRect>,
+    /// Zoomed region of the current image (its zoom steps are prefetched).
+    zoom: Option<CropRect>,
 }
 
 pub struct RenderedImage {
     pub original_size: (u32, u32),
     pub actual_size: (u32, u32),
     pub encoded_chunks: Arc<Vec<Vec<u8>>>,
+    /// Worker stage durations (None when loaded from the disk cache).
+    pub timings: Option<StageTimings>,
 }
 
 pub struct App {
@@ -62,6 +107,93 @@ pub struct App {
     pub view_mode: ViewMode,
     pub tile_cursor: usize,
     prev_tile_cursor: Option<usize>,
+    /// Tile size steps from the default grid (`+` / `-` in Tile mode), positive for larger.
+    tile_step: i32,
+    tile_layout: TileLayout,
+    /// Aspect ratio of each image read so far, for the justified layout (before rotation).
+    tile_aspects: HashMap<PathBuf, f64>,
+    /// Background read of the aspect ratios not known yet (`src/scan.rs`).
+    aspect_scan: Option<PathScan<f64>>,
+    /// Capture day of each image read so far, for `group_by_date`.
+    capture_days: HashMap<PathBuf, Option<String>>,
+    /// Background read of the capture days not known yet.
+    day_scan: Option<PathScan<Option<String>>>,
+    /// Zoomed region of the current image (Single mode).
+    pub crop: Option<CropRect>,
+    /// Keep `crop` when moving to another image (`Z`), to compare the same region.
+    zoom_lock: bool,
+    /// Zoomed region of each image left zoomed, restored when coming back to it.
+    views: HashMap<PathBuf, CropRect>,
+    selection: Option<Selection>,
+    drawn_selection: Option<Rect>,
+    /// Tile jump labels (`s`), while waiting for a label to be typed.
+    jump: Option<JumpLabels>,
+    /// Tiles (index within the page) whose labels are on screen.
+    drawn_jump: Vec<usize>,
+    rotations: RotationStore,
+    /// DICOM window/level adjustment, shared by all DICOM images.
+    window: WindowLevel,
+    /// Display adjustments (`c`, `i`, `:matte`, `#`), shared by all images.
+    adjust: Adjustments,
+    /// Reference image of the pixel diff (`:diff`, `--diff`).
+    diff: Option<PathBuf>,
+    /// Reference image blended over the current one (`:blend`).
+    blend: Option<PathBuf>,
+    /// Opacity of the blended reference in percent (`(` / `)`).
+    blend_opacity: u8,
+    /// Reference image shown right of the wipe split (`:wipe`).
+    wipe: Option<PathBuf>,
+    /// Wipe split position in percent of the width (`,` / `.`).
+    wipe_position: u8,
+    /// Two-page spreads (`b`): each image is shown with the next one.
+    spread: bool,
+    /// Split view (`|`): the pane without focus.
+    panes: Option<Panes>,
+    /// Slideshow settings (`slideshow_*` options, `:slideshow`), kept while stopped.
+    slideshow_options: SlideshowOptions,
+    /// Running slideshow (`S`).
+    slideshow: Option<Slideshow>,
+    /// Sign of the last Single-mode move (0 = unknown), used to bias prefetching.
+    nav_direction: i32,
+    /// Blurred previews of decoded images, keyed by path and rotation.
+    placeholders: HashMap<(PathBuf, Rotation), Arc<Placeholder>>,
+    /// Render key whose placeholder is on screen (avoids redrawing it every tick).
+    drawn_placeholder: Option<CacheKey>,
+    /// Frames of the animated GIF on screen, with its render key (Single mode).
+    animation: Option<(CacheKey, Animation)>,
+    /// Render key whose frames were requested (stills are not asked again).
+    animation_requested: Option<CacheKey>,
+    /// Animations are played by the terminal (KGP frames) instead of frame by frame.
+    server_animation: bool,
+    /// Page count and shown page of the last multi-page TIFF viewed.
+    pages: Option<Pages>,
+    /// Start index of each run of images from the same directory (source boundaries).
+    /// More than one run enables the directory counter and the tile breadcrumb.
+    dir_starts: Vec<usize>,
+    /// Why the list is shorter than what was opened (`--max-images`, `--sample`).
+    list_note: Option<String>,
+    /// Background listing still appending images (None once complete).
+    listing: Option<Listing>,
+    /// Startup pick the view follows while the listing runs: the first image found, then the
+    /// first of the sorted list, until the user moves off it (None once they do).
+    listing_start: Option<PathBuf>,
+    /// Images appended so far by a `:add` listing (None for other listings).
+    added: Option<usize>,
+    command_line: Option<CommandLine>,
+    /// Images moved to the trash this session, with their list index and listed path (`u` undoes).
+    trashed: Vec<(usize, PathBuf, Trashed)>,
+    /// Bookmarked directories (`B`, `:bookmarks`).
+    bookmarks: Bookmarks,
+    /// Directory browser (`-`), drawn full-screen instead of the image while open.
+    browser: Option<Browser>,
+    /// Cell-aspect calibration screen (`:calibrate`), drawn like the browser.
+    calibration: Option<Calibration>,
+    /// Help screen (`?`), drawn like the browser until any key closes it.
+    help: Option<Help>,
+    /// Battery / low-power state for reducing prefetch (`power_save`).
+    power: Power,
+    /// Number of completion menu (or browser) rows currently drawn.
+    menu_len: usize,
     pub kgp_state: KgpState,
     config: Config,
     worker: ImageWorker,
@@ -71,15 +203,243 @@ pub struct App {
     render_cache: HashMap<CacheKey, RenderedImage>,
     render_cache_order: VecDeque<CacheKey>,
     render_cache_limit: usize,
+    /// Persistent render cache (None unless `disk_cache` is enabled).
+    disk_cache: Option<DiskCache>,
     kgp_id: u32,
+    /// Output protocol (half-blocks when the terminal lacks Kitty graphics).
+    protocol: Protocol,
+    /// Why the half-block fallback is active (shown once at startup).
+    capability_warning: Option<String>,
     in_flight_transmit: bool,
+    /// Latest partial tile page from the worker, shown while the rest of its thumbnails decode.
+    partial_tile: Option<(CacheKey, RenderedImage)>,
+    /// The transmit in flight is a partial tile page, so the finished one replaces it.
+    partial_in_flight: bool,
+    /// Epoch of the last Single-mode image transmit.
+    transmit_epoch: u64,
+    /// Data URI being encoded on a background thread (`"uy`).
+    pending_copy: Option<std::sync::mpsc::Receiver<Result<String>>>,
+    /// Image copy running through the Windows tools under WSL (see `poll_image_copy`).
+    pending_image_copy: Option<std::sync::mpsc::Receiver<bool>>,
+    /// Crop being written by the worker (`:w`): destination and the written size.
+    pending_save: Option<(PathBuf, std::sync::mpsc::Receiver<SaveResult>)>,
+    /// Duration of the last completed transmit (debug status).
+    last_transmit: Option<std::time::Duration>,
     pending_display: Option<Rect>,
     render_epoch: u64,
     clear_after_nav: bool,
     is_tmux: bool,
+    /// KGP data is sent through temporary files, so the workers encode it raw.
+    file_transfer: bool,
+    /// Running under WSL: clipboard copies go through the Windows tools (`src/wsl.rs`).
+    is_wsl: bool,
     last_prefetch_signature: Option<PrefetchSignature>,
 }
 
+/// Compact pagination: one dot per page for few pages, otherwise a proportional bar.
+fn pagination(page: usize, page_count: usize) -> String {
+    const MAX_DOTS: usize = 10;
+    const BAR_WIDTH: usize = 10;
+
+    if page_count <= MAX_DOTS {
+        return (0..page_count)
+            .map(|i| if i == page { '●' } else { '○' })
+            .collect();
+    }
+    let filled = ((page + 1) * BAR_WIDTH).div_ceil(page_count);
+    (0..BAR_WIDTH)
+        .map(|i| if i < filled { '▰' } else { '▱' })
+        .collect()
+}
+
+/// Cell area of an image of `size` pixels, centered in `image_area`.
+pub(crate) fn placement_area(image_area: Rect, size: (u32, u32), cell_size: (u16, u16)) -> Rect {
+    let (cell_w, cell_h) = (u32::from(cell_size.0.max(1)), u32::from(cell_size.1.max(1)));
+    let cells_w = size.0.div_ceil(cell_w).min(u32::from(image_area.width)) as u16;
+    let cells_h = size.1.div_ceil(cell_h).min(u32::from(image_area.height)) as u16;
+    let offset_x = (image_area.width.saturating_sub(cells_w)) / 2;
+    let offset_y = (image_area.height.saturating_sub(cells_h)) / 2;
+    Rect::new(
+        image_area.x + offset_x,
+        image_area.y + offset_y,
+        cells_w,
+        cells_h,
+    )
+}
+
+/// Offsets to prefetch around the current image, nearest first.
+///
+/// Without a known direction this is `+1, -1, +2, -2, ...` (`count` each way). After moving in
+/// `direction`, the same budget (`2 * count`) is split so that only about a quarter goes behind.
+fn prefetch_offsets(count: usize, direction: i32) -> Vec<i64> {
+    let (ahead, behind) = if direction == 0 {
+        (count, count)
+    } else {
+        let behind = count.div_ceil(4);
+        (count * 2 - behind, behind)
+    };
+    let sign = if direction < 0 { -1 } else { 1 };
+    let mut offsets = Vec::with_capacity(ahead + behind);
+    for i in 1..=ahead.max(behind) as i64 {
+        if i as usize <= ahead {
+            offsets.push(sign * i);
+        }
+        if i as usize <= behind {
+            offsets.push(-sign * i);
+        }
+    }
+    offsets
+}
+
+/// Per-stage durations for the debug status, e.g. ` dec 12ms rsz 3ms enc 8ms tx 20ms`.
+/// Stages are `-` for renders loaded from the disk cache.
+fn format_timings(timings: Option<StageTimings>, transmit: Option<std::time::Duration>) -> String {
+    let ms = |d: std::time::Duration| format!("{}ms", d.as_millis());
+    let stages = match timings {
+        Some(t) => format!(
+            " dec {} rsz {} enc {}",
+            ms(t.decode),
+            ms(t.resize),
+            ms(t.encode)
+        ),
+        None => " dec - rsz - enc -".to_string(),
+    };
+    let tx = transmit.map(ms).unwrap_or_else(|| "-".to_string());
+    format!("{stages} tx {tx}")
+}
+
+/// Byte count for the debug status, e.g. `12.3M`.
+pub fn format_size(bytes: usize) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+    format!("{:.1}M", bytes as f64 / MIB)
+}
+
+/// Start indices of the runs of consecutive images sharing a directory.
+fn dir_starts(images: &[PathBuf]) -> Vec<usize> {
+    let mut starts = Vec::new();
+    for (i, path) in images.iter().enumerate() {
+        if i == 0 || images[i - 1].parent() != path.parent() {
+            starts.push(i);
+        }
+    }
+    starts
+}
+
+/// Last two components of the directory containing `path` (e.g. `photos/2023`).
+fn short_dir(path: &std::path::Path) -> String {
+    let Some(dir) = path.parent() else {
+        return String::new();
+    };
+    let parts: Vec<_> = dir.components().rev().take(2).collect();
+    let short: PathBuf = parts.into_iter().rev().collect();
+    short.display().to_string()
+}
+
+/// Settings that change the encoded output, for keying the disk cache.
+fn render_settings(config: &Config, is_tmux: bool, file_transfer: bool) -> String {
+    format!(
+        "tmux={is_tmux} file={file_transfer} compress={:?} max_pixels={} filter={} sharpen={}/{} stretch={}",
+        config.compression_level(),
+        config.tmux_kitty_max_pixels,
+        config.resize_filter.trim().to_lowercase(),
+        config.upscale_sharpen,
+        config.downscale_sharpen,
+        config.bit_depth_stretch,
+    )
+}
+
+fn file_name(path: &std::path::Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn is_tile_page(path: &std::path::Path) -> bool {
+    tile_page_start(path).is_some()
+}
+
+/// The `count` paths of the tile page starting at `page_start`.
+fn page_paths(images: &[PathBuf], page_start: usize, count: usize) -> &[PathBuf] {
+    let start = page_start.min(images.len());
+    &images[start..(start + count).min(images.len())]
+}
+
+/// Values of `paths` from `known`, after taking in what `scan` has read; None while any is
+/// still unknown.
+fn scanned<T: Clone>(
+    scan: &mut Option<PathScan<T>>,
+    known: &mut HashMap<PathBuf, T>,
+    paths: &[PathBuf],
+) -> Option<Vec<T>> {
+    if let Some(running) = scan.as_mut() {
+        let (results, done) = running.poll();
+        known.extend(results);
+        if done {
+            *scan = None;
+        }
+    }
+    paths.iter().map(|path| known.get(path).cloned()).collect()
+}
+
+/// Read the values of `images` missing from `known` in the background, from index `start` on
+/// (then from the top), unless a scan from there is already running or nothing is missing.
+fn start_scan<T: Send + 'static>(
+    scan: &mut Option<PathScan<T>>,
+    known: &HashMap<PathBuf, T>,
+    images: &[PathBuf],
+    start: usize,
+    read: impl Fn(&Path) -> T + Send + 'static,
+) {
+    if scan
+        .as_ref()
+        .is_some_and(|running| running.start() == start)
+    {
+        return;
+    }
+    let start = start.min(images.len());
+    let paths: Vec<PathBuf> = images[start..]
+        .iter()
+        .chain(&images[..start])
+        .filter(|path| !known.contains_key(*path))
+        .cloned()
+        .collect();
+    if !paths.is_empty() {
+        *scan = Some(PathScan::spawn(paths, start, read));
+    }
+}
+
+/// Layout of a tile page (`App::page_layout`).
+struct PageLayout {
+    /// Tile rectangles (cells, relative to the image area).
+    rects: Vec<Rect>,
+    /// Capture day of each tile (`group_by_date`).
+    days: Option<Vec<Option<String>>>,
+    /// Labeled separator rows between days.
+    bands: Vec<DayBand>,
+}
+
+/// Put an image on the OS clipboard with arboard.
+fn set_clipboard_image(img: &image::DynamicImage) -> bool {
+    use arboard::{Clipboard, ImageData};
+
+    let rgba = img.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    let image_data = ImageData {
+        width: width as usize,
+        height: height as usize,
+        bytes: rgba.into_raw().into(),
+    };
+    let Ok(mut clipboard) = Clipboard::new() else {
+        return false;
+    };
+    clipboard.set_image(image_data).is_ok()
+}
+
+/// First image index of a tile page cache path (`__tile_page_{start}`).
+fn tile_page_start(path: &std::path::Path) -> Option<usize> {
+    path.to_str()?.strip_prefix("__tile_page_")?.parse().ok()
+}
+
 /// Check if running inside tmux.
 ///
 /// Returns `true` if the `TMUX` environment variable is set.
@@ -87,7 +447,22 @@ pub fn is_tmux_env() -> bool {
     std::env::var_os("TMUX").is_some()
 }
 
-fn ensure_tmux_allow_passthrough_on(is_tmux: bool) {
+/// Check if running in Ghostty, which does not play KGP animation frames. Ghostty's own
+/// variables also reach shells inside tmux.
+fn is_ghostty_env() -> bool {
+    std::env::var_os("GHOSTTY_RESOURCES_DIR").is_some()
+        || std::env::var("TERM").is_ok_and(|t| t == "xterm-ghostty")
+        || std::env::var("TERM_PROGRAM").is_ok_and(|p| p == "ghostty")
+}
+
+/// Check if running over SSH (the terminal cannot read local files).
+fn is_ssh_env() -> bool {
+    ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"]
+        .iter()
+        .any(|key| std::env::var_os(key).is_some())
+}
+
+pub(crate) fn ensure_tmux_allow_passthrough_on(is_tmux: bool) {
     use std::process::Command;
 
     if is_tmux {
@@ -101,14 +476,51 @@ fn ensure_tmux_allow_passthrough_on(is_tmux: bool) {
 impl App {
     /// Create a new application instance.
     pub fn new(images: Vec<PathBuf>, config: Config) -> Result<Self> {
+        // Decode the first image while tmux is set up and the terminal is queried; the target
+        // size and protocol are only known afterwards.
+        let preload = images.first().map(|path| {
+            ImageWorker::preload(
+                path.clone(),
+                WindowLevel::default(),
+                config.decode_limits(),
+                config.bit_depth_stretch,
+            )
+        });
         let is_tmux = is_tmux_env();
         ensure_tmux_allow_passthrough_on(is_tmux);
 
-        let picker = Picker::from_query_stdio().unwrap_or_else(|_| Picker::from_fontsize((8, 16)));
+        let Capabilities {
+            picker,
+            protocol,
+            reason,
+            warning,
+        } = Capabilities::detect(&config.protocol);
+        // Debug mode reports the protocol decision at startup.
+        let capability_warning = warning.or_else(|| {
+            config
+                .debug
+                .then(|| format!("Protocol {protocol:?}: {reason}"))
+        });
+        // Files are only used when the terminal confirms it reads them.
+        let file_transfer = protocol == Protocol::Kitty
+            && !is_tmux
+            && !is_ssh_env()
+            && !config.kgp_no_file_transfer
+            && crate::kgp::probe_file_transfer(&crate::kgp::transfer_dir());
         let render_cache_limit = config.render_cache_size;
-        let prefetch_threads = config.prefetch_threads;
-        let tile_threads = config.tile_threads;
+        let power = Power::new(&config.power_save);
+        let (prefetch_threads, tile_threads) =
+            power.threads(config.prefetch_threads, config.tile_threads);
         let kgp_id = Self::generate_kgp_id();
+        let rotations = RotationStore::new(&images, config.persist_rotation);
+        // Cached entries are KGP-encoded.
+        let disk_cache = (config.disk_cache && protocol == Protocol::Kitty)
+            .then(|| {
+                let settings = render_settings(&config, is_tmux, file_transfer);
+                DiskCache::open(config.disk_cache_mb, &settings)
+            })
+            .flatten();
+        let dir_starts = dir_starts(&images);
         let app = App {
             images,
             current_index: 0,
@@ -118,21 +530,91 @@ impl App {
             view_mode: ViewMode::default(),
             tile_cursor: 0,
             prev_tile_cursor: None,
+            tile_step: 0,
+            tile_layout: TileLayout::parse(&config.tile_layout),
+            tile_aspects: HashMap::new(),
+            aspect_scan: None,
+            capture_days: HashMap::new(),
+            day_scan: None,
+            crop: None,
+            zoom_lock: false,
+            views: HashMap::new(),
+            selection: None,
+            drawn_selection: None,
+            jump: None,
+            drawn_jump: Vec::new(),
+            dir_starts,
+            list_note: None,
+            listing: None,
+            listing_start: None,
+            added: None,
+            rotations,
+            window: WindowLevel::default(),
+            diff: None,
+            blend: None,
+            blend_opacity: crate::blend::DEFAULT_OPACITY,
+            wipe: None,
+            wipe_position: crate::wipe::DEFAULT_POSITION,
+            spread: false,
+            panes: None,
+            slideshow_options: SlideshowOptions::from_config(&config),
+            slideshow: None,
+            adjust: Adjustments {
+                // An invalid color falls back to no matte, like other string options.
+                matte: crate::adjust::parse_matte(&config.matte).ok().flatten(),
+                ..Adjustments::default()
+            },
+            nav_direction: 0,
+            placeholders: HashMap::new(),
+            drawn_placeholder: None,
+            animation: None,
+            animation_requested: None,
+            server_animation: protocol == Protocol::Kitty
+                && !is_ghostty_env()
+                && !config.kgp_client_animation,
+            pages: None,
+            trashed: Vec::new(),
+            bookmarks: Bookmarks::load(),
+            command_line: None,
+            browser: None,
+            calibration: None,
+            help: None,
+            power,
+            menu_len: 0,
             kgp_state: KgpState::default(),
+            writer: TerminalWriter::new(WriterOptions {
+                monochrome: config.monochrome,
+                status_overlay: config.status_overlay,
+                direct_placement: protocol == Protocol::Kitty
+                    && !is_tmux
+                    && !config.kgp_placeholders,
+                file_transfer,
+            }),
             config,
-            worker: ImageWorker::new(tile_threads),
+            worker: ImageWorker::new(tile_threads, preload),
             prefetch_worker: PrefetchWorker::new(prefetch_threads),
-            writer: TerminalWriter::new(),
             pending_request: None,
             render_cache: HashMap::with_capacity(render_cache_limit),
             render_cache_order: VecDeque::with_capacity(render_cache_limit),
             render_cache_limit,
+            disk_cache,
             kgp_id,
+            protocol,
+            capability_warning,
             in_flight_transmit: false,
+            partial_tile: None,
+            partial_in_flight: false,
+            transmit_epoch: 0,
+            pending_copy: None,
+            pending_image_copy: None,
+            pending_save: None,
+            last_transmit: None,
             pending_display: None,
             render_epoch: 0,
             clear_after_nav: false,
             is_tmux,
+            file_transfer,
+            is_wsl: crate::wsl::detect(),
             last_prefetch_signature: None,
         };
 
@@ -177,11 +659,30 @@ impl App {
         if delta == 0 || self.images.is_empty() {
             return;
         }
+        // A spread shows two images, so each step skips the second one.
+        let delta = if self.spread { delta * 2 } else { delta };
         let len = self.images.len() as i32;
         self.current_index = (self.current_index as i32 + delta).rem_euclid(len) as usize;
+        self.nav_direction = delta.signum();
+        self.reset_crop_unless_locked();
         self.invalidate_render();
     }
 
+    /// `delta` for `move_by` (Single mode) or `move_tile_cursor_row` (Tile mode), shortened so
+    /// the cursor stops at the first or last image instead of wrapping around.
+    pub fn clamp_steps(&self, delta: i32, terminal_size: Rect) -> i32 {
+        let (index, step) = match self.view_mode {
+            ViewMode::Single => (self.current_index, if self.spread { 2 } else { 1 }),
+            ViewMode::Tile => (
+                self.tile_cursor,
+                self.page_grid(terminal_size).0.max(1) as i32,
+            ),
+        };
+        let index = index as i32;
+        let last = self.images.len().saturating_sub(1) as i32;
+        ((index + delta.saturating_mul(step)).clamp(0, last) - index) / step
+    }
+
     /// Toggle between `Normal` (shrink-only) and `Fit` (allow upscale).
     pub fn toggle_fit_mode(&mut self) {
         self.fit_mode = self.fit_mode.next();
@@ -203,6 +704,7 @@ impl App {
                 self.view_mode = ViewMode::Single;
             }
         }
+        self.reset_crop_unless_locked();
         self.invalidate_render();
     }
 
@@ -233,11 +735,14 @@ impl App {
         page_changed
     }
 
-    /// Move tile cursor to next/prev row.
+    /// Move tile cursor to next/prev row (rows of the current page's layout).
     /// Returns true if page changed.
-    pub fn move_tile_cursor_row(&mut self, delta: i32, grid: (usize, usize)) -> bool {
-        let (cols, _) = grid;
-        self.move_tile_cursor(delta.saturating_mul(cols as i32), grid)
+    pub fn move_tile_cursor_row(&mut self, delta: i32, terminal_size: Rect) -> bool {
+        let (cols, _) = self.page_grid(terminal_size);
+        self.move_tile_cursor(
+            delta.saturating_mul(cols as i32),
+            self.tile_grid(terminal_size),
+        )
     }
 
     /// Move tile page (Shift+H/J/K/L).
@@ -245,13 +750,10 @@ impl App {
     pub fn move_tile_page(&mut self, delta: i32, grid: (usize, usize)) {
         let (cols, rows) = grid;
         let tiles_per_page = cols * rows;
-        let len = self.images.len();
-        if len == 0 || tiles_per_page == 0 {
+        let Some((current_page, page_count)) = self.tile_page_position(grid) else {
             return;
-        }
-
-        let current_page = self.tile_cursor / tiles_per_page;
-        let max_page = (len - 1) / tiles_per_page;
+        };
+        let max_page = page_count - 1;
         let new_page = (current_page as i32 + delta).clamp(0, max_page as i32) as usize;
 
         if new_page == current_page {
@@ -263,24 +765,126 @@ impl App {
         self.invalidate_render();
     }
 
+    /// Current tile page (0-based) and total page count, or None if there is nothing to page.
+    pub fn tile_page_position(&self, grid: (usize, usize)) -> Option<(usize, usize)> {
+        let tiles_per_page = grid.0 * grid.1;
+        let len = self.images.len();
+        if len == 0 || tiles_per_page == 0 {
+            return None;
+        }
+        Some((
+            self.tile_cursor / tiles_per_page,
+            len.div_ceil(tiles_per_page),
+        ))
+    }
+
     /// Draw tile cursor via ANSI overlay (fast, no image re-render).
-    pub fn draw_tile_cursor(&self, terminal_size: Rect) {
-        let grid = Self::calculate_tile_grid(terminal_size, self.config.cell_aspect_ratio);
-        let image_area = Self::image_area(terminal_size);
-        let (cols, rows) = grid;
+    pub fn draw_tile_cursor(&mut self, terminal_size: Rect) {
+        let (cols, rows) = self.tile_grid(terminal_size);
+        let image_area = self.image_area(terminal_size);
         let tiles_per_page = cols * rows;
         if tiles_per_page == 0 {
             return;
         }
         let cursor_in_page = self.tile_cursor % tiles_per_page;
         let prev_cursor_in_page = self.prev_tile_cursor.map(|prev| prev % tiles_per_page);
+        let labels = if self.config.tile_labels {
+            let page_start = self.tile_cursor - cursor_in_page;
+            self.images
+                .iter()
+                .skip(page_start)
+                .take(tiles_per_page)
+                .map(|path| file_name(path))
+                .collect()
+        } else {
+            Vec::new()
+        };
 
+        let rects = self.displayed_page_rects(terminal_size);
         self.writer.send(WriterRequest::TileCursor {
-            grid,
+            rects,
             cursor_idx: cursor_in_page,
             image_area,
             prev_cursor_idx: prev_cursor_in_page,
-            cell_size: self.picker.font_size(),
+            cell_size: self.cell_size(),
+            labels,
+        });
+    }
+
+    /// Show jump labels over the tiles of the displayed page (Tile mode only).
+    /// Returns false if no tile page is currently on screen.
+    pub fn start_jump(&mut self, terminal_size: Rect) -> bool {
+        let grid = self.tile_grid(terminal_size);
+        let tiles_per_page = grid.0 * grid.1;
+        if self.view_mode != ViewMode::Tile
+            || tiles_per_page == 0
+            || self.in_flight_transmit
+            || self.pending_display.is_some()
+            || self.kgp_state.last_kgp_id() != Some(self.kgp_id)
+        {
+            return false;
+        }
+        let page_start = (self.tile_cursor / tiles_per_page) * tiles_per_page;
+        let count = self
+            .images
+            .len()
+            .saturating_sub(page_start)
+            .min(tiles_per_page);
+        self.jump = Some(JumpLabels::new(count));
+        self.draw_jump(terminal_size);
+        true
+    }
+
+    pub fn is_jumping(&self) -> bool {
+        self.jump.is_some()
+    }
+
+    /// Type a label key. Completing a label moves the tile cursor to that tile; a key that
+    /// matches no label closes the labels.
+    pub fn jump_key(&mut self, c: char, terminal_size: Rect) {
+        let Some(jump) = self.jump.as_mut() else {
+            return;
+        };
+        match jump.push(c) {
+            Jump::Pending => self.draw_jump(terminal_size),
+            Jump::Target(i) => {
+                let grid = self.tile_grid(terminal_size);
+                let tiles_per_page = grid.0 * grid.1;
+                let page_start = (self.tile_cursor / tiles_per_page) * tiles_per_page;
+                let old_cursor = self.tile_cursor;
+                self.cancel_jump(terminal_size);
+                self.prev_tile_cursor = Some(old_cursor);
+                self.tile_cursor = page_start + i;
+                self.draw_tile_cursor(terminal_size);
+            }
+            Jump::NoMatch => self.cancel_jump(terminal_size),
+        }
+    }
+
+    /// Remove the jump labels and restore the image (and tile cursor) under them.
+    pub fn cancel_jump(&mut self, terminal_size: Rect) {
+        if self.jump.take().is_none() {
+            return;
+        }
+        self.draw_jump(terminal_size);
+        // Restoring the label rows also removes parts of the cursor border.
+        self.prev_tile_cursor = None;
+        self.draw_tile_cursor(terminal_size);
+    }
+
+    fn draw_jump(&mut self, terminal_size: Rect) {
+        let labels = self
+            .jump
+            .as_ref()
+            .map(JumpLabels::remaining)
+            .unwrap_or_default();
+        let drawn: Vec<usize> = labels.iter().map(|(i, _)| *i).collect();
+        let rects = self.displayed_page_rects(terminal_size);
+        self.writer.send(WriterRequest::JumpLabels {
+            rects,
+            image_area: self.image_area(terminal_size),
+            labels,
+            prev: std::mem::replace(&mut self.drawn_jump, drawn),
         });
     }
 
@@ -291,6 +895,7 @@ impl App {
         }
         self.current_index = self.tile_cursor.min(self.images.len().saturating_sub(1));
         self.view_mode = ViewMode::Single;
+        self.reset_crop_unless_locked();
         self.invalidate_render();
     }
 
@@ -299,6 +904,8 @@ impl App {
         self.cancel_image_output();
         self.render_cache.clear();
         self.render_cache_order.clear();
+        self.placeholders.clear();
+        self.drawn_placeholder = None;
         self.pending_request = None;
         self.kgp_state = KgpState::default();
         self.prefetch_worker.cancel();
@@ -307,11 +914,19 @@ impl App {
 
     /// Handle terminal resize: clear display and force re-render.
     pub fn handle_resize(&mut self) {
+        // Selection is in terminal cells and no longer matches the new layout.
+        self.selection = None;
+        self.drawn_selection = None;
+        self.jump = None;
+        self.drawn_jump.clear();
+        // The screen is cleared on resize; the menu is redrawn on the next keystroke.
+        self.menu_len = 0;
         // Clear existing KGP image from terminal
         self.clear_kgp_overlay();
         // Clear render cache (images need re-rendering at new size)
         self.render_cache.clear();
         self.render_cache_order.clear();
+        self.drawn_placeholder = None;
         self.pending_request = None;
         self.kgp_state = KgpState::default();
         self.prefetch_worker.cancel();
@@ -327,144 +942,1679 @@ impl App {
         self.current_index = index;
         self.tile_cursor = index;
         self.prev_tile_cursor = None;
+        self.reset_crop_unless_locked();
         self.invalidate_render();
     }
 
-    fn invalidate_render(&mut self) {
-        self.pending_request = None;
-        // Cancel in-flight prefetch requests
-        self.prefetch_worker.cancel();
-        self.last_prefetch_signature = None;
-        // Note: Do NOT clear in_flight_transmit here.
-        // cancel_image_output() needs it to invalidate the correct cache entry.
+    /// Enter zoom selection mode over the displayed image (Single mode only).
+    /// Returns false if no image is currently on screen.
+    pub fn start_selection(&mut self) -> bool {
+        if self.view_mode != ViewMode::Single
+            || self.in_flight_transmit
+            || self.pending_display.is_some()
+            || self.kgp_state.last_kgp_id() != Some(self.kgp_id)
+        {
+            return false;
+        }
+        let Some(area) = self.kgp_state.last_area() else {
+            return false;
+        };
+        self.selection = Some(Selection::new(area));
+        self.draw_selection();
+        true
     }
 
-    fn current_path(&self) -> Option<&PathBuf> {
-        self.images.get(self.current_index)
+    pub fn is_selecting(&self) -> bool {
+        self.selection.is_some()
     }
 
-    /// Compute image area from terminal size (excluding status bar).
-    fn image_area(terminal_size: Rect) -> Rect {
-        let full = Rect::new(0, 0, terminal_size.width, terminal_size.height);
-        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(full)[0]
+    /// Move the selection rectangle by `(dx, dy)` cells.
+    pub fn move_selection(&mut self, dx: i32, dy: i32) {
+        if let Some(sel) = self.selection.as_mut() {
+            sel.move_by(dx, dy);
+            self.draw_selection();
+        }
     }
 
-    /// Calculate optimal tile grid size based on terminal dimensions.
-    /// Returns (cols, rows) for the tile grid.
-    pub fn calculate_tile_grid(terminal_size: Rect, cell_aspect_ratio: f64) -> (usize, usize) {
-        let image_area = Self::image_area(terminal_size);
-
-        // For visually square tiles, we need to account for the cell aspect ratio.
-        // cell_aspect_ratio = cell_height_pixels / cell_width_pixels (typically ~2.0)
-        const MIN_TILE_WIDTH: u16 = 16;
-        const MIN_TILE_HEIGHT: u16 = 4;
-        const MAX_COLS: usize = 6;
-        const MAX_ROWS: usize = 6;
-
-        // Calculate min tile height to get visually square tiles
-        let min_tile_height = (MIN_TILE_WIDTH as f64 / cell_aspect_ratio).round() as u16;
-        let min_tile_height = min_tile_height.max(MIN_TILE_HEIGHT);
+    /// Grow/shrink the selection rectangle by `(dw, dh)` cells.
+    pub fn resize_selection(&mut self, dw: i32, dh: i32) {
+        if let Some(sel) = self.selection.as_mut() {
+            sel.resize_by(dw, dh);
+            self.draw_selection();
+        }
+    }
 
-        let cols = (image_area.width / MIN_TILE_WIDTH) as usize;
-        let rows = (image_area.height / min_tile_height) as usize;
+    /// Leave selection mode and restore the image under the rectangle.
+    pub fn cancel_selection(&mut self) {
+        if self.selection.take().is_none() {
+            return;
+        }
+        self.writer.send(WriterRequest::Selection {
+            rect: None,
+            prev_rect: self.drawn_selection.take(),
+        });
+    }
 
-        // Clamp to reasonable bounds
-        let cols = cols.clamp(2, MAX_COLS);
-        let rows = rows.clamp(2, MAX_ROWS);
+    /// Zoom to the selected region (the worker renders the crop at full viewport size).
+    /// Returns true if the view changed.
+    pub fn zoom_to_selection(&mut self, terminal_size: Rect) -> bool {
+        let crop = self.selection_crop(terminal_size);
+        self.selection = None;
+        self.drawn_selection = None;
+        let Some(crop) = crop else {
+            return false;
+        };
+        self.set_crop(Some(crop));
+        self.invalidate_render();
+        true
+    }
 
-        (cols, rows)
+    /// Zoom in (`steps` > 0) or out by `ZOOM_STEP` per step around the center of the view; the
+    /// worker renders the visible region at the viewport size. Zooming out past the whole image
+    /// leaves zoom. Returns true if the view changed.
+    pub fn zoom_by(&mut self, steps: i32, terminal_size: Rect) -> bool {
+        if self.view_mode != ViewMode::Single {
+            return false;
+        }
+        let Some(crop) = self.zoom_step_crop(steps, terminal_size) else {
+            return false;
+        };
+        self.set_crop(crop);
+        self.invalidate_render();
+        true
     }
 
-    pub fn poll_worker(&mut self) {
-        // Poll main worker
-        while let Some(result) = self.worker.try_recv() {
-            let key = CacheKey {
-                path: result.path,
-                target: result.target,
-                fit_mode: result.fit_mode,
+    /// Zoomed region (None: the whole image) after zooming `steps` from the displayed view.
+    /// None if the current render is not cached yet or the view would not change.
+    fn zoom_step_crop(&self, steps: i32, terminal_size: Rect) -> Option<Option<CropRect>> {
+        let key = self.single_cache_key(terminal_size)?;
+        let (target, rendered) = (key.target, self.render_cache.get(&key)?);
+        let size = rendered.original_size;
+        let base = self.view_region(size, target);
+        let scale = f64::from(rendered.actual_size.0) / f64::from(base.width.max(1));
+        let scale = scale * ZOOM_STEP.powi(steps);
+        // The whole image as the zoomed region is rendered like `Fit`: a small image shown at
+        // its own size is enlarged to fill the view first, and zooming out of 1:1 ends there.
+        let crop = crate::zoom::zoom_crop(base, size, target, scale).or_else(|| {
+            let whole = if steps > 0 {
+                self.crop.is_none()
+            } else {
+                self.fit_mode == FitMode::Actual
             };
-            if self.pending_request.as_ref() == Some(&key) {
-                self.pending_request = None;
-            }
-            self.insert_to_cache(
-                key,
-                result.original_size,
-                result.actual_size,
-                result.encoded_chunks,
-            );
+            whole.then(|| CropRect::full(size))
+        });
+        (crop != self.crop).then_some(crop)
+    }
+
+    /// Pan the zoomed (or 1:1) region by `(dx, dy)` steps of an eighth of the view. Returns
+    /// true if the view changed (false when nothing to pan or at the image edge).
+    pub fn pan_by(&mut self, dx: i32, dy: i32, terminal_size: Rect) -> bool {
+        if !self.can_pan() {
+            return false;
+        }
+        let (Some(key), Some(size)) = (
+            self.single_cache_key(terminal_size),
+            self.current_image_resolution(),
+        ) else {
+            return false;
+        };
+        let region = self.view_region(size, key.target);
+        let panned = region.panned(dx, dy, size);
+        if panned == region {
+            return false;
         }
+        self.set_crop(Some(panned));
+        self.invalidate_render();
+        true
+    }
 
-        // Poll prefetch worker
-        while let Some(result) = self.prefetch_worker.try_recv() {
-            let key = CacheKey {
-                path: result.path,
-                target: result.target,
-                fit_mode: result.fit_mode,
-            };
-            // Skip if already in cache (main worker result takes precedence)
-            if !self.render_cache.contains_key(&key) {
-                self.insert_to_cache(
-                    key,
-                    result.original_size,
-                    result.actual_size,
-                    result.encoded_chunks,
-                );
-            }
+    /// Scroll a zoomed (or 1:1) image down (`pages` > 0) or up by half a view per page, like a
+    /// pager. Returns true if the view changed.
+    pub fn scroll_half_page(&mut self, pages: i32, terminal_size: Rect) -> bool {
+        self.pan_by(0, pages.saturating_mul(HALF_PAGE_STEPS), terminal_size)
+    }
+
+    /// Whether the view can pan: a region is zoomed, or images are shown 1:1 (Single mode).
+    pub fn can_pan(&self) -> bool {
+        self.view_mode == ViewMode::Single
+            && (self.crop.is_some() || self.fit_mode == FitMode::Actual)
+    }
+
+    /// Source region on screen for an image of `size` in a `target`-pixel view: the zoomed
+    /// region, the center of a larger image at 1:1, or the whole image.
+    fn view_region(&self, size: (u32, u32), target: (u32, u32)) -> CropRect {
+        match self.crop {
+            Some(crop) => crop.clamp_to(size),
+            None if self.fit_mode == FitMode::Actual => CropRect::centered(size, target),
+            None => CropRect::full(size),
         }
     }
 
-    /// Insert rendered image into cache with LRU eviction.
-    fn insert_to_cache(
-        &mut self,
-        key: CacheKey,
-        original_size: (u32, u32),
-        actual_size: (u32, u32),
-        encoded_chunks: Arc<Vec<Vec<u8>>>,
-    ) {
-        if self.render_cache.contains_key(&key) {
-            // Update existing entry and move to back of LRU order
-            self.render_cache_order.retain(|k| k != &key);
-        } else if self.render_cache.len() >= self.render_cache_limit {
-            // Evict oldest if at capacity
-            if let Some(oldest_key) = self.render_cache_order.pop_front() {
-                self.render_cache.remove(&oldest_key);
-            }
+    /// Leave zoom and show the whole image again. Returns false if not zoomed.
+    pub fn reset_zoom(&mut self) -> bool {
+        if self.crop.is_none() {
+            return false;
         }
-        self.render_cache_order.push_back(key.clone());
-        self.render_cache.insert(
-            key,
-            RenderedImage {
-                original_size,
-                actual_size,
-                encoded_chunks,
-            },
-        );
+        self.set_crop(None);
+        self.invalidate_render();
+        true
     }
 
-    /// Move cache entry to end of LRU order (mark as recently used).
-    fn touch_render_cache(&mut self, key: &CacheKey) {
-        if matches!(self.render_cache_order.back(), Some(k) if k == key) {
-            return; // Already at end
+    /// Zoom the current image to `crop` (None: the whole image) and remember it for the image.
+    fn set_crop(&mut self, crop: Option<CropRect>) {
+        self.crop = crop;
+        if let Some(path) = self.current_render_path() {
+            match crop {
+                Some(crop) => self.views.insert(path, crop),
+                None => self.views.remove(&path),
+            };
         }
-        if !self.render_cache.contains_key(key) {
-            return; // Not in cache
+    }
+
+    /// Toggle keeping the zoomed region across images. Returns the new state.
+    pub fn toggle_zoom_lock(&mut self) -> bool {
+        self.zoom_lock = !self.zoom_lock;
+        self.zoom_lock
+    }
+
+    /// Navigation restores the new image's own zoom (none if it was never zoomed) unless the
+    /// zoom is locked.
+    fn reset_crop_unless_locked(&mut self) {
+        if !self.zoom_lock {
+            self.crop = self
+                .current_render_path()
+                .and_then(|path| self.views.get(&path).copied());
         }
-        self.render_cache_order.retain(|k| k != key);
-        self.render_cache_order.push_back(key.clone());
     }
 
-    pub fn poll_writer(&mut self) {
-        while let Some(result) = self.writer.try_recv() {
-            if result.epoch != self.render_epoch {
-                continue;
-            }
-            if matches!(result.kind, WriterResultKind::TransmitDone { .. }) {
-                self.in_flight_transmit = false;
-            }
+    /// Zoom carried over to prefetched neighbors (only while locked).
+    fn prefetch_crop(&self) -> Option<CropRect> {
+        if self.zoom_lock { self.crop } else { None }
+    }
 
-            if let Some(area) = self.pending_display.take() {
-                self.kgp_state.set_last(area, self.kgp_id);
-            }
+    /// Adjust the DICOM window by `level` / `width` steps.
+    /// Returns false (nothing changes) if the current image is not a DICOM file.
+    pub fn adjust_window(&mut self, level: i32, width: i32) -> bool {
+        if !self
+            .current_path()
+            .is_some_and(|p| crate::dicom::is_dicom(p))
+        {
+            return false;
+        }
+        self.window.level = self.window.level.saturating_add(level);
+        self.window.width = self.window.width.saturating_add(width);
+        self.invalidate_render();
+        true
+    }
+
+    /// Return to the window stored in the DICOM files. Returns true if it was adjusted.
+    pub fn reset_window(&mut self) -> bool {
+        if self.window.is_default() {
+            return false;
+        }
+        self.window = WindowLevel::default();
+        self.invalidate_render();
+        true
+    }
+
+    /// Toggle grayscale display (`c`) for all images.
+    pub fn toggle_grayscale(&mut self) {
+        self.adjust.grayscale = !self.adjust.grayscale;
+        self.invalidate_render();
+    }
+
+    /// Composite transparent images over `color` (`:matte`; None removes the matte). Returns
+    /// true if it changed.
+    pub fn set_matte(&mut self, color: Option<[u8; 3]>) -> bool {
+        if self.adjust.matte == color {
+            return false;
+        }
+        self.adjust.matte = color;
+        self.invalidate_render();
+        true
+    }
+
+    /// Cycle the grid overlay (`#`): rule of thirds, then a line every `grid_spacing` pixels.
+    pub fn cycle_grid(&mut self) {
+        self.adjust.grid = self.adjust.grid.next(self.config.grid_spacing);
+        self.invalidate_render();
+    }
+
+    /// Compare every image against `reference` (None stops comparing). Returns true if it
+    /// changed.
+    pub fn set_diff(&mut self, reference: Option<PathBuf>) -> bool {
+        if self.diff == reference {
+            return false;
+        }
+        self.diff = reference;
+        self.invalidate_render();
+        true
+    }
+
+    /// Blend `reference` over every image (None stops blending). Returns true if it changed.
+    pub fn set_blend(&mut self, reference: Option<PathBuf>) -> bool {
+        if self.blend == reference {
+            return false;
+        }
+        self.blend = reference;
+        self.invalidate_render();
+        true
+    }
+
+    /// Change the blend opacity by `steps` (`(` / `)`). Returns false without a blend or at
+    /// the limit.
+    pub fn adjust_blend(&mut self, steps: i32) -> bool {
+        if self.blend.is_none() {
+            return false;
+        }
+        let opacity = (i32::from(self.blend_opacity) + steps * crate::blend::OPACITY_STEP)
+            .clamp(0, 100) as u8;
+        if opacity == self.blend_opacity {
+            return false;
+        }
+        self.blend_opacity = opacity;
+        self.invalidate_render();
+        true
+    }
+
+    /// Show `reference` right of a split over every image (None stops wiping). Returns true
+    /// if it changed.
+    pub fn set_wipe(&mut self, reference: Option<PathBuf>) -> bool {
+        if self.wipe == reference {
+            return false;
+        }
+        self.wipe = reference;
+        self.invalidate_render();
+        true
+    }
+
+    /// Move the wipe split by `steps` (`,` / `.`). Returns false without a wipe or at the edge.
+    pub fn move_wipe(&mut self, steps: i32) -> bool {
+        if self.wipe.is_none() {
+            return false;
+        }
+        let position = (i32::from(self.wipe_position) + steps * crate::wipe::POSITION_STEP)
+            .clamp(0, 100) as u8;
+        if position == self.wipe_position {
+            return false;
+        }
+        self.wipe_position = position;
+        self.invalidate_render();
+        true
+    }
+
+    /// Toggle two-page spreads (`b`).
+    pub fn toggle_spread(&mut self) {
+        self.spread = !self.spread;
+        self.reset_crop_unless_locked();
+        self.invalidate_render();
+    }
+
+    /// Toggle the split view (`|`). The new pane starts on the current image.
+    pub fn toggle_panes(&mut self) {
+        self.panes = match self.panes {
+            Some(_) => None,
+            None => Some(Panes {
+                other: self.current_index,
+                right_focused: true,
+            }),
+        };
+        self.reset_crop_unless_locked();
+        self.invalidate_render();
+    }
+
+    /// Move the focus to the other pane of the split view (Tab). Returns false without a split.
+    pub fn switch_pane(&mut self) -> bool {
+        let Some(panes) = &mut self.panes else {
+            return false;
+        };
+        std::mem::swap(&mut panes.other, &mut self.current_index);
+        panes.right_focused = !panes.right_focused;
+        self.reset_crop_unless_locked();
+        self.invalidate_render();
+        true
+    }
+
+    /// Start or stop the slideshow (`S`) at the current image. Returns true if it started.
+    pub fn toggle_slideshow(&mut self, now: Instant) -> bool {
+        self.slideshow = match self.slideshow {
+            Some(_) => None,
+            None => Some(Slideshow::new(
+                &self.slideshow_options,
+                self.images.len(),
+                self.current_index,
+                now,
+            )),
+        };
+        self.slideshow.is_some()
+    }
+
+    /// Apply `:slideshow <setting>`. Returns the message to show.
+    pub fn set_slideshow(&mut self, setting: Setting, now: Instant) -> String {
+        let options = &mut self.slideshow_options;
+        match setting {
+            Setting::Toggle => {
+                return if self.toggle_slideshow(now) {
+                    "Slideshow started".to_string()
+                } else {
+                    "Slideshow stopped".to_string()
+                };
+            }
+            Setting::Shuffle => options.shuffle = !options.shuffle,
+            Setting::Loop => options.stop_at_end = !options.stop_at_end,
+            Setting::SkipErrors => options.skip_errors = !options.skip_errors,
+            Setting::Interval(interval) => options.interval = interval,
+        }
+        // A running slideshow starts a new round with the new settings.
+        if self.slideshow.is_some() {
+            self.slideshow = Some(Slideshow::new(
+                &self.slideshow_options,
+                self.images.len(),
+                self.current_index,
+                now,
+            ));
+        }
+        self.slideshow_options.label()
+    }
+
+    /// Show the next slideshow image once it is due. Returns true when the slideshow just
+    /// stopped at the end of the list. Paused in Tile mode.
+    pub fn tick_slideshow(&mut self, now: Instant) -> bool {
+        let Some(slideshow) = self
+            .slideshow
+            .as_mut()
+            .filter(|_| self.view_mode == ViewMode::Single)
+        else {
+            return false;
+        };
+        match slideshow.tick(&self.slideshow_options, self.images.len(), now) {
+            Step::Wait => false,
+            Step::Show(index) => {
+                self.current_index = index;
+                self.nav_direction = 1;
+                self.reset_crop_unless_locked();
+                self.invalidate_render();
+                false
+            }
+            Step::End => {
+                self.slideshow = None;
+                true
+            }
+        }
+    }
+
+    /// When the slideshow shows its next image.
+    pub fn slideshow_due(&self) -> Option<Instant> {
+        self.slideshow.as_ref().map(Slideshow::due)
+    }
+
+    /// Toggle inverted colors (`i`) for all images.
+    pub fn toggle_invert(&mut self) {
+        self.adjust.invert = !self.adjust.invert;
+        self.invalidate_render();
+    }
+
+    /// Open the `:` command line.
+    pub fn start_command(&mut self) {
+        self.command_line = Some(CommandLine::new().with_bookmarks(self.bookmarks.dirs().to_vec()));
+    }
+
+    pub fn is_command_active(&self) -> bool {
+        self.command_line.is_some()
+    }
+
+    /// Edit the command line and redraw its completion menu.
+    pub fn edit_command(&mut self, edit: impl FnOnce(&mut CommandLine), terminal_size: Rect) {
+        if let Some(cmd) = self.command_line.as_mut() {
+            edit(cmd);
+            self.draw_menu(terminal_size);
+        }
+    }
+
+    /// Close the command line (restoring the image under the menu) and return it.
+    pub fn finish_command(&mut self, terminal_size: Rect) -> Option<CommandLine> {
+        let cmd = self.command_line.take();
+        self.draw_menu(terminal_size);
+        cmd
+    }
+
+    /// Directory containing the current image (for archive members, the archive's directory).
+    fn current_dir(&self) -> Option<PathBuf> {
+        let path = self.current_path()?;
+        // Archive members live in a virtual directory.
+        let file = match crate::archive::split_member_path(path) {
+            Some((archive, _)) => archive,
+            None => path.as_path(),
+        };
+        match file.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => Some(dir.to_path_buf()),
+            _ => Some(PathBuf::from(".")),
+        }
+    }
+
+    /// Bookmark the current image's directory. Returns false if it was already bookmarked.
+    pub fn bookmark_current_dir(&mut self) -> Result<bool> {
+        let Some(dir) = self.current_dir() else {
+            return Ok(false);
+        };
+        self.bookmarks.add(&dir)
+    }
+
+    /// Move the current image (Tile mode: the image under the cursor) to the trash.
+    /// Returns its file name.
+    pub fn trash_current(&mut self) -> Result<String> {
+        let index = match self.view_mode {
+            ViewMode::Single => self.current_index,
+            ViewMode::Tile => self.tile_cursor,
+        };
+        let Some(path) = self.images.get(index).cloned() else {
+            anyhow::bail!("No image");
+        };
+        if self.images.len() == 1 {
+            anyhow::bail!("Cannot trash the only image");
+        }
+        if crate::archive::split_member_path(&path).is_some() {
+            anyhow::bail!("Cannot trash an archive member");
+        }
+        let trashed = crate::trash::move_to_trash(&path)?;
+        let name = file_name(&path);
+        self.remove_image(index);
+        self.trashed.push((index, path, trashed));
+        Ok(name)
+    }
+
+    /// Remove image `index` from the list; the next image takes its place on screen (in the
+    /// other pane too, if it showed that image).
+    fn remove_image(&mut self, index: usize) {
+        let other = self.other_pane_path();
+        self.images.remove(index);
+        let index = index.min(self.images.len().saturating_sub(1));
+        self.keep_other_pane(other, index);
+        self.images_changed(index);
+    }
+
+    /// Insert `path` into the list at `index` and show it.
+    fn insert_image(&mut self, index: usize, path: PathBuf) {
+        let other = self.other_pane_path();
+        let index = index.min(self.images.len());
+        self.images.insert(index, path);
+        self.keep_other_pane(other, index);
+        self.images_changed(index);
+    }
+
+    /// Image shown in the other pane of the split view.
+    fn other_pane_path(&self) -> Option<PathBuf> {
+        self.panes
+            .and_then(|panes| self.images.get(panes.other).cloned())
+    }
+
+    /// Point the other pane at `path` again after the list changed, or at `fallback` if it is
+    /// no longer listed.
+    fn keep_other_pane(&mut self, path: Option<PathBuf>, fallback: usize) {
+        let position = path.and_then(|path| self.images.iter().position(|p| *p == path));
+        let last = self.images.len().saturating_sub(1);
+        if let Some(panes) = &mut self.panes {
+            panes.other = position.unwrap_or(fallback).min(last);
+        }
+    }
+
+    /// Reorder the image list (`:sort`), keeping the current image (and the other pane) on
+    /// screen.
+    pub fn sort_images(&mut self, key: crate::sort::SortKey, reverse: bool) {
+        let current = self.images.get(self.current_index).cloned();
+        let other = self.other_pane_path();
+        crate::sort::sort_paths(&mut self.images, key, reverse);
+        let index = current
+            .and_then(|path| self.images.iter().position(|p| *p == path))
+            .unwrap_or(0);
+        self.keep_other_pane(other, 0);
+        self.images_changed(index);
+    }
+
+    /// Restore the most recently trashed image to its place in the list.
+    /// Returns its file name, or None if nothing was trashed this session.
+    pub fn undo_trash(&mut self) -> Result<Option<String>> {
+        let Some((index, path, trashed)) = self.trashed.pop() else {
+            return Ok(None);
+        };
+        if let Err(e) = trashed.restore() {
+            self.trashed.push((index, path, trashed));
+            return Err(e);
+        }
+        let name = file_name(&path);
+        self.insert_image(index, path);
+        Ok(Some(name))
+    }
+
+    /// Refresh state after an image was removed from or inserted into the list.
+    fn images_changed(&mut self, index: usize) {
+        self.current_index = index;
+        self.tile_cursor = index;
+        self.prev_tile_cursor = None;
+        self.crop = None;
+        self.dir_starts = dir_starts(&self.images);
+        // Tile pages are cached by start index, so their contents shifted.
+        self.render_cache.retain(|k, _| !is_tile_page(&k.path));
+        self.render_cache_order.retain(|k| !is_tile_page(&k.path));
+        self.invalidate_render();
+    }
+
+    /// Open the directory browser at the directory of the current image.
+    pub fn start_browser(&mut self, terminal_size: Rect) -> Result<()> {
+        let Some(dir) = self.current_dir() else {
+            return Ok(());
+        };
+        let browser = Browser::open(&dir)?;
+        self.cancel_image_output();
+        self.clear_kgp_overlay();
+        self.browser = Some(browser);
+        self.draw_menu(terminal_size);
+        Ok(())
+    }
+
+    pub fn is_browsing(&self) -> bool {
+        self.browser.is_some()
+    }
+
+    /// Whether a prompt or screen that captures all keys is open (command line, browser,
+    /// calibration, help, zoom selection, jump labels).
+    pub fn is_capturing(&self) -> bool {
+        self.command_line.is_some()
+            || self.browser.is_some()
+            || self.calibration.is_some()
+            || self.help.is_some()
+            || self.selection.is_some()
+            || self.jump.is_some()
+    }
+
+    /// Update the directory browser and redraw it.
+    pub fn edit_browser(
+        &mut self,
+        edit: impl FnOnce(&mut Browser) -> Result<()>,
+        terminal_size: Rect,
+    ) -> Result<()> {
+        let Some(browser) = self.browser.as_mut() else {
+            return Ok(());
+        };
+        let result = edit(browser);
+        self.draw_menu(terminal_size);
+        result
+    }
+
+    /// Close the directory browser (the image is re-rendered on the next tick) and return it.
+    pub fn finish_browser(&mut self, terminal_size: Rect) -> Option<Browser> {
+        let browser = self.browser.take();
+        self.draw_menu(terminal_size);
+        self.invalidate_render();
+        browser
+    }
+
+    /// Bytes held by the render cache and the workers' decode/thumbnail caches.
+    fn cache_usage(&self) -> (usize, usize, usize) {
+        let render = self
+            .render_cache
+            .values()
+            .map(|r| r.encoded_chunks.iter().map(Vec::len).sum::<usize>())
+            .sum();
+        let (worker, prefetch) = (self.worker.usage(), self.prefetch_worker.usage());
+        (
+            render,
+            worker.decoded(),
+            worker.thumbnails() + prefetch.thumbnails(),
+        )
+    }
+
+    fn cache_usage_text(&self) -> String {
+        let (render, decoded, thumbnails) = self.cache_usage();
+        format!(
+            " mem render {} decode {} thumbs {}",
+            format_size(render),
+            format_size(decoded),
+            format_size(thumbnails)
+        )
+    }
+
+    /// Drop cached renders, placeholders and the workers' decode and thumbnail caches, and
+    /// return the bytes freed. Unlike `reload`, the render on screen (the current image or
+    /// tile page) is kept, so nothing is requested again until the next navigation.
+    pub fn clear_caches(&mut self, terminal_size: Rect) -> usize {
+        let (render, decoded, thumbnails) = self.cache_usage();
+        let current = self.displayed_cache_key(terminal_size);
+        let kept = current.and_then(|key| self.render_cache.remove_entry(&key));
+        self.render_cache.clear();
+        self.render_cache_order.clear();
+        let mut freed = render;
+        if let Some((key, rendered)) = kept {
+            freed -= rendered.encoded_chunks.iter().map(Vec::len).sum::<usize>();
+            self.render_cache_order.push_back(key.clone());
+            self.render_cache.insert(key, rendered);
+        }
+        self.placeholders.clear();
+        self.worker.clear_caches();
+        self.prefetch_worker.clear_caches();
+        freed + decoded + thumbnails
+    }
+
+    /// Open the cell-aspect calibration screen with the current ratio.
+    pub fn start_calibration(&mut self, terminal_size: Rect) {
+        self.cancel_image_output();
+        self.clear_kgp_overlay();
+        self.calibration = Some(Calibration::new(self.config.cell_aspect_ratio));
+        self.draw_menu(terminal_size);
+    }
+
+    pub fn is_calibrating(&self) -> bool {
+        self.calibration.is_some()
+    }
+
+    /// Change the calibrated ratio by `delta` and redraw the test pattern.
+    pub fn nudge_calibration(&mut self, delta: f64, terminal_size: Rect) {
+        if let Some(calibration) = self.calibration.as_mut() {
+            calibration.nudge(delta);
+            self.draw_menu(terminal_size);
+        }
+    }
+
+    /// Close the calibration screen. With `apply`, the ratio is used from now on and written to
+    /// the config file; returns the ratio and the file.
+    pub fn finish_calibration(
+        &mut self,
+        apply: bool,
+        terminal_size: Rect,
+    ) -> Option<Result<(f64, PathBuf)>> {
+        let calibration = self.calibration.take()?;
+        self.draw_menu(terminal_size);
+        self.invalidate_render();
+        if !apply {
+            return None;
+        }
+        let ratio = calibration.ratio();
+        if ratio != self.config.cell_aspect_ratio {
+            self.config.cell_aspect_ratio = ratio;
+            // The tile grid depends on the ratio; cached pages have the old layout.
+            self.render_cache.retain(|k, _| !is_tile_page(&k.path));
+            self.render_cache_order.retain(|k| !is_tile_page(&k.path));
+            self.last_prefetch_signature = None;
+        }
+        Some(
+            Config::save_value("cell_aspect_ratio", &format!("{ratio:.2}"))
+                .map(|path| (ratio, path)),
+        )
+    }
+
+    /// Open the help screen with the bindings of `keymap` and the current settings.
+    pub fn start_help(&mut self, keymap: &Keymap, terminal_size: Rect) {
+        self.cancel_image_output();
+        self.clear_kgp_overlay();
+        self.help = Some(Help::new(keymap, &self.config));
+        self.draw_menu(terminal_size);
+    }
+
+    pub fn is_showing_help(&self) -> bool {
+        self.help.is_some()
+    }
+
+    pub fn close_help(&mut self, terminal_size: Rect) {
+        if self.help.take().is_some() {
+            self.draw_menu(terminal_size);
+            self.invalidate_render();
+        }
+    }
+
+    /// Redraw the command-line menu or directory browser (e.g. after the screen was cleared).
+    pub fn redraw_menu(&mut self, terminal_size: Rect) {
+        self.draw_menu(terminal_size);
+    }
+
+    /// Set the status note for a limited list, e.g. `of 200000, sampled` (None = complete list).
+    pub fn set_list_note(&mut self, note: Option<String>) {
+        self.list_note = note;
+    }
+
+    /// Keep appending images found by a background listing (see `poll_listing`).
+    pub fn set_listing(&mut self, listing: Option<Listing>) {
+        self.listing_start = listing
+            .is_some()
+            .then(|| self.current_path().cloned())
+            .flatten();
+        self.listing = listing;
+        self.added = None;
+    }
+
+    /// Keep appending images found by a `:add` listing. The view stays where it is; the count
+    /// is reported by `take_added` once the listing completes.
+    pub fn add_listing(&mut self, listing: Option<Listing>) {
+        self.added = listing.is_some().then_some(0);
+        self.listing = listing;
+        self.listing_start = None;
+    }
+
+    /// Whether a background listing is still appending images.
+    pub fn is_listing(&self) -> bool {
+        self.listing.is_some()
+    }
+
+    /// How many images a completed `:add` listing appended (once).
+    pub fn take_added(&mut self) -> Option<usize> {
+        if self.listing.is_some() {
+            return None;
+        }
+        self.added.take()
+    }
+
+    pub fn images(&self) -> &[PathBuf] {
+        &self.images
+    }
+
+    /// Merge images found by the background listing into the list, keeping the current image
+    /// (and tile cursor) on the same path. Until the user navigates, the view instead moves to
+    /// the first image of the sorted list, not whichever one the directory read returned first.
+    /// Returns errors to show (inputs that could not be listed).
+    pub fn poll_listing(&mut self, terminal_size: Rect) -> Vec<String> {
+        if self.listing.is_none() {
+            return Vec::new();
+        }
+        let current = self.current_path().cloned();
+        let cursor = self.images.get(self.tile_cursor).cloned();
+        let other = self.other_pane_path();
+        let Some(listing) = self.listing.as_mut() else {
+            return Vec::new();
+        };
+        let listed = self.images.len();
+        let merged = listing.merge_into(&mut self.images);
+        if let Some(added) = &mut self.added {
+            *added += self.images.len() - listed;
+        }
+        let follow = self.listing_start.is_some()
+            && current == self.listing_start
+            && cursor == self.listing_start;
+        if merged.done {
+            self.listing = None;
+        }
+        let Some(first_changed) = merged.first_changed else {
+            if !follow || merged.done {
+                self.listing_start = None;
+            }
+            return merged.errors;
+        };
+
+        let position = |path: Option<PathBuf>, images: &[PathBuf]| {
+            path.and_then(|p| images.iter().position(|q| *q == p))
+        };
+        if follow {
+            self.current_index = 0;
+            self.tile_cursor = 0;
+        } else {
+            self.current_index = position(current, &self.images).unwrap_or(0);
+            self.tile_cursor = position(cursor, &self.images).unwrap_or(0);
+        }
+        self.keep_other_pane(other, 0);
+        self.listing_start = (follow && !merged.done)
+            .then(|| self.images.first().cloned())
+            .flatten();
+        self.prev_tile_cursor = None;
+        self.images_merged(first_changed, terminal_size);
+        merged.errors
+    }
+
+    /// Append images to the list (`:add`), skipping those already in it. Returns how many
+    /// were added.
+    pub fn append_images(&mut self, images: Vec<PathBuf>, terminal_size: Rect) -> usize {
+        let mut seen: HashSet<PathBuf> = self.images.iter().cloned().collect();
+        let first_changed = self.images.len();
+        self.images
+            .extend(images.into_iter().filter(|path| seen.insert(path.clone())));
+        let added = self.images.len() - first_changed;
+        if added > 0 {
+            self.images_merged(first_changed, terminal_size);
+        }
+        added
+    }
+
+    /// Update what depends on the list after the images from `first_changed` on changed,
+    /// keeping the current image.
+    fn images_merged(&mut self, first_changed: usize, terminal_size: Rect) {
+        self.dir_starts = dir_starts(&self.images);
+        self.rotations.load(&self.images[first_changed..]);
+
+        // Tile pages past the first change now show different images.
+        let grid = self.tile_grid(terminal_size);
+        let tiles_per_page = grid.0 * grid.1;
+        let stale = |key: &CacheKey| {
+            tile_page_start(&key.path).is_some_and(|start| start + tiles_per_page > first_changed)
+        };
+        self.render_cache.retain(|k, _| !stale(k));
+        self.render_cache_order.retain(|k| !stale(k));
+        if self.pending_request.as_ref().is_some_and(stale) {
+            self.pending_request = None;
+        }
+        if self.view_mode == ViewMode::Tile {
+            self.prefetch_worker.cancel();
+        }
+        // Prefetch targets are chosen by index.
+        self.last_prefetch_signature = None;
+    }
+
+    /// Replace the image list (e.g. `:open`), starting over at the first image.
+    pub fn open_images(&mut self, images: Vec<PathBuf>) {
+        self.listing = None;
+        self.listing_start = None;
+        self.added = None;
+        self.rotations = RotationStore::new(&images, self.config.persist_rotation);
+        self.dir_starts = dir_starts(&images);
+        self.images = images;
+        self.current_index = 0;
+        self.tile_cursor = 0;
+        self.prev_tile_cursor = None;
+        self.crop = None;
+        self.panes = None;
+        self.slideshow = None;
+        self.reload();
+    }
+
+    fn draw_menu(&mut self, terminal_size: Rect) {
+        const MAX_MENU_ROWS: usize = 10;
+
+        let (lines, selected) = match &self.command_line {
+            Some(cmd) if !cmd.candidates().is_empty() => {
+                let rows = MAX_MENU_ROWS.min(usize::from(terminal_size.height.saturating_sub(1)));
+                let first = cmd.selected().saturating_sub(rows.saturating_sub(1));
+                let lines: Vec<String> = cmd
+                    .candidates()
+                    .iter()
+                    .skip(first)
+                    .take(rows)
+                    .map(|c| c.name.clone())
+                    .collect();
+                (lines, Some(cmd.selected() - first))
+            }
+            _ => match (&self.browser, &self.calibration, &self.help) {
+                // The browser covers every row above the status bar.
+                (Some(browser), _, _) => {
+                    let rows = usize::from(terminal_size.height.saturating_sub(1));
+                    let first = browser.selected().saturating_sub(rows.saturating_sub(1));
+                    let mut lines: Vec<String> = browser
+                        .entries()
+                        .iter()
+                        .skip(first)
+                        .take(rows)
+                        .map(|e| format!("{} ({})", e.name, e.images))
+                        .collect();
+                    lines.resize(rows, String::new());
+                    (lines, Some(browser.selected() - first))
+                }
+                (None, Some(calibration), _) => (
+                    calibration.lines(terminal_size.width, terminal_size.height.saturating_sub(1)),
+                    None,
+                ),
+                (None, None, Some(help)) => (
+                    help.lines(terminal_size.width, terminal_size.height.saturating_sub(1)),
+                    None,
+                ),
+                (None, None, None) => (Vec::new(), None),
+            },
+        };
+        if lines.is_empty() && self.menu_len == 0 {
+            return;
+        }
+        let len = lines.len();
+        self.writer.send(WriterRequest::Menu {
+            lines,
+            selected,
+            prev_len: self.menu_len,
+            size: (terminal_size.width, terminal_size.height),
+        });
+        self.menu_len = len;
+    }
+
+    /// Rotate the current image (Single mode) or the image under the tile cursor (Tile mode)
+    /// by `quarter_turns` clockwise.
+    ///
+    /// The view is always updated; an error means the rotation could not be persisted.
+    pub fn rotate(&mut self, quarter_turns: i32) -> Result<()> {
+        let index = match self.view_mode {
+            ViewMode::Single => self.current_index,
+            ViewMode::Tile => self.tile_cursor,
+        };
+        let Some(path) = self.images.get(index).cloned() else {
+            return Ok(());
+        };
+        let rotation = self.rotations.get(&path).turn(quarter_turns);
+        // Rotated pixels no longer match the zoomed region.
+        self.crop = None;
+        self.views.retain(|p, _| {
+            crate::pages::split_page_path(p).map_or(p.as_path(), |(base, _)| base) != path
+        });
+        if self.view_mode == ViewMode::Tile {
+            // Tile pages are cached under synthetic paths; drop them so the page is rebuilt.
+            self.render_cache.retain(|k, _| !is_tile_page(&k.path));
+            self.render_cache_order.retain(|k| !is_tile_page(&k.path));
+        }
+        self.invalidate_render();
+        self.rotations.set(&path, rotation)
+    }
+
+    fn draw_selection(&mut self) {
+        let Some(sel) = self.selection else {
+            return;
+        };
+        self.writer.send(WriterRequest::Selection {
+            rect: Some(sel.rect),
+            prev_rect: self.drawn_selection,
+        });
+        self.drawn_selection = Some(sel.rect);
+    }
+
+    /// Source-pixel region covered by the selection rectangle.
+    fn selection_crop(&self, terminal_size: Rect) -> Option<CropRect> {
+        let sel = self.selection?;
+        let key = self.single_cache_key(terminal_size)?;
+        let rendered = self.render_cache.get(&key)?;
+        // A locked zoom may exceed a smaller image; the worker rendered the clamped region.
+        let base = self.view_region(rendered.original_size, key.target);
+        Some(sel.to_crop(rendered.actual_size, self.cell_size(), base))
+    }
+
+    /// Render cache key for the current image in Single mode.
+    fn single_cache_key(&self, terminal_size: Rect) -> Option<CacheKey> {
+        let path = self.current_render_path()?;
+        let image_area = self.image_area(terminal_size);
+        let (cell_w, cell_h) = self.cell_size();
+        Some(CacheKey {
+            path,
+            target: (
+                u32::from(image_area.width) * u32::from(cell_w),
+                u32::from(image_area.height) * u32::from(cell_h),
+            ),
+            fit_mode: self.fit_mode,
+            crop: self.crop,
+            rotation: self.current_rotation(),
+            window: self.current_window(),
+            adjust: self.adjust,
+        })
+    }
+
+    /// Render cache key of what the view shows: the current image in Single mode, the
+    /// cursor's page in Tile mode.
+    fn displayed_cache_key(&self, terminal_size: Rect) -> Option<CacheKey> {
+        if self.view_mode == ViewMode::Single {
+            return self.single_cache_key(terminal_size);
+        }
+        let (cols, rows) = self.tile_grid(terminal_size);
+        let tiles_per_page = cols * rows;
+        if tiles_per_page == 0 {
+            return None;
+        }
+        let page_start = (self.tile_cursor / tiles_per_page) * tiles_per_page;
+        let image_area = self.image_area(terminal_size);
+        let (cell_w, cell_h) = self.cell_size();
+        Some(CacheKey {
+            path: PathBuf::from(format!("__tile_page_{}", page_start)),
+            target: (
+                u32::from(image_area.width) * u32::from(cell_w),
+                u32::from(image_area.height) * u32::from(cell_h),
+            ),
+            fit_mode: self.fit_mode,
+            crop: None,
+            rotation: Rotation::None,
+            window: WindowLevel::default(),
+            adjust: Adjustments::default(),
+        })
+    }
+
+    fn invalidate_render(&mut self) {
+        self.pending_request = None;
+        self.partial_tile = None;
+        // Cancel in-flight prefetch requests
+        self.prefetch_worker.cancel();
+        self.last_prefetch_signature = None;
+        // Note: Do NOT clear in_flight_transmit here.
+        // cancel_image_output() needs it to invalidate the correct cache entry.
+    }
+
+    /// Cell size in pixels used for resizing and placement.
+    fn cell_size(&self) -> (u16, u16) {
+        self.protocol.cell_size(self.picker.font_size())
+    }
+
+    /// Warning to show at startup when graphics support could not be detected.
+    pub fn capability_warning(&self) -> Option<&str> {
+        self.capability_warning.as_deref()
+    }
+
+    /// `+` while images are still being listed, ` (of 200000, sampled)` when the list was
+    /// limited.
+    fn list_note_text(&self) -> String {
+        let more = if self.listing.is_some() { "+" } else { "" };
+        let note = self
+            .list_note
+            .as_ref()
+            .map(|note| format!(" ({note})"))
+            .unwrap_or_default();
+        format!("{more}{note}")
+    }
+
+    /// ` (dir 2/3: name)` for the current image when the images span several directories.
+    fn dir_position(&self) -> Option<String> {
+        if self.dir_starts.len() < 2 {
+            return None;
+        }
+        let run = self
+            .dir_starts
+            .partition_point(|&start| start <= self.current_index)
+            .checked_sub(1)?;
+        let name = self
+            .current_path()?
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| ".".to_string());
+        Some(format!(
+            " (dir {}/{}: {name})",
+            run + 1,
+            self.dir_starts.len()
+        ))
+    }
+
+    fn current_path(&self) -> Option<&PathBuf> {
+        self.images.get(self.current_index)
+    }
+
+    fn current_rotation(&self) -> Rotation {
+        self.current_path()
+            .map(|p| self.rotations.get(p))
+            .unwrap_or_default()
+    }
+
+    fn current_window(&self) -> WindowLevel {
+        self.current_path()
+            .map(|p| self.window.for_path(p))
+            .unwrap_or_default()
+    }
+
+    /// Path rendered for the current image: the shown page of a multi-page TIFF, else the
+    /// image itself.
+    fn current_render_path(&self) -> Option<PathBuf> {
+        let path = self.current_path()?;
+        let path = match &self.pages {
+            Some(pages) if &pages.path == path => crate::pages::page_path(path, pages.current),
+            _ => path.clone(),
+        };
+        Some(self.compose_render_path(self.current_index, path))
+    }
+
+    /// Path rendered for the image at `index` shown as `path`: the spread with the next image
+    /// and the diff against the reference, when enabled, next to the other pane of a split view.
+    fn compose_render_path(&self, index: usize, path: PathBuf) -> PathBuf {
+        let path = self.pane_render_path(index, path);
+        let Some(panes) = self.panes else {
+            return path;
+        };
+        let Some(other) = self.images.get(panes.other) else {
+            return path;
+        };
+        let other = self.pane_render_path(panes.other, other.clone());
+        let (left, right) = if panes.right_focused {
+            (&other, &path)
+        } else {
+            (&path, &other)
+        };
+        VirtualPath::Panes { left, right }.to_path()
+    }
+
+    /// Path rendered in one pane for the image at `index` shown as `path`.
+    fn pane_render_path(&self, index: usize, path: PathBuf) -> PathBuf {
+        let path = match self.images.get(index + 1).filter(|_| self.spread) {
+            Some(next) if self.config.spread_rtl => VirtualPath::Spread {
+                left: next,
+                right: &path,
+            }
+            .to_path(),
+            Some(next) => VirtualPath::Spread {
+                left: &path,
+                right: next,
+            }
+            .to_path(),
+            None => path,
+        };
+        let path = match &self.blend {
+            Some(reference) => VirtualPath::Blend {
+                image: &path,
+                reference,
+                opacity: self.blend_opacity,
+            }
+            .to_path(),
+            None => path,
+        };
+        let path = match &self.wipe {
+            Some(reference) => VirtualPath::Wipe {
+                image: &path,
+                reference,
+                position: self.wipe_position,
+            }
+            .to_path(),
+            None => path,
+        };
+        match &self.diff {
+            Some(reference) => VirtualPath::Diff {
+                image: &path,
+                reference,
+            }
+            .to_path(),
+            None => path,
+        }
+    }
+
+    /// Sign of the "next" direction for the left/right keys (`h` / `l`): reversed for
+    /// right-to-left spreads.
+    pub fn reading_direction(&self) -> i32 {
+        if self.spread && self.config.spread_rtl {
+            -1
+        } else {
+            1
+        }
+    }
+
+    /// Pages of the current image, if it is a multi-page TIFF whose pages were counted.
+    fn current_pages(&self) -> Option<&Pages> {
+        let path = self.current_path()?;
+        self.pages
+            .as_ref()
+            .filter(|pages| &pages.path == path && pages.count > 1)
+    }
+
+    /// Count the pages of the current image once if it is a TIFF.
+    fn load_page_count(&mut self) {
+        let Some(path) = self.current_path().filter(|p| crate::pages::is_tiff(p)) else {
+            return;
+        };
+        if self.pages.as_ref().is_some_and(|pages| &pages.path == path) {
+            return;
+        }
+        let path = path.clone();
+        let count = crate::pages::page_count(&path).unwrap_or(1);
+        self.pages = Some(Pages {
+            path,
+            current: 0,
+            count,
+        });
+    }
+
+    /// Show another page of a multi-page TIFF in Single mode (wraps around). Returns the
+    /// shown page (0-based) and the page count, or None if there is only one page.
+    pub fn move_page(&mut self, delta: i32) -> Option<(usize, usize)> {
+        if self.view_mode != ViewMode::Single {
+            return None;
+        }
+        self.load_page_count();
+        self.current_pages()?;
+        let pages = self.pages.as_mut()?;
+        pages.current = (pages.current as i32 + delta).rem_euclid(pages.count as i32) as usize;
+        let shown = (pages.current, pages.count);
+        self.reset_crop_unless_locked();
+        self.invalidate_render();
+        Some(shown)
+    }
+
+    /// Paths (with rotations) shown on the tile page starting at `page_start`.
+    fn tile_page_paths(
+        &self,
+        page_start: usize,
+        tiles_per_page: usize,
+    ) -> Vec<(PathBuf, Rotation)> {
+        self.images
+            .iter()
+            .skip(page_start)
+            .take(tiles_per_page)
+            .map(|p| (p.clone(), self.rotations.get(p)))
+            .collect()
+    }
+
+    /// Compute image area from terminal size (excluding the status bar, unless it is drawn
+    /// over the image).
+    fn image_area(&self, terminal_size: Rect) -> Rect {
+        let full = Rect::new(0, 0, terminal_size.width, terminal_size.height);
+        if self.config.status_overlay {
+            return full;
+        }
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(full)[0]
+    }
+
+    /// Tile grid (cols, rows) for the current terminal size and tile size.
+    pub fn tile_grid(&self, terminal_size: Rect) -> (usize, usize) {
+        Self::scaled_tile_grid(
+            self.image_area(terminal_size),
+            self.config.cell_aspect_ratio,
+            self.tile_step,
+        )
+    }
+
+    /// Grow (`steps` > 0) or shrink the tiles by a column per step, as far as the grid still
+    /// changes. Returns the new grid, or None if the tile size stayed.
+    pub fn resize_tiles(&mut self, steps: i32, terminal_size: Rect) -> Option<(usize, usize)> {
+        let (area, aspect) = (
+            self.image_area(terminal_size),
+            self.config.cell_aspect_ratio,
+        );
+        let grid = |step| Self::scaled_tile_grid(area, aspect, step);
+        let mut step = self.tile_step;
+        for _ in 0..steps.unsigned_abs() {
+            let next = step + steps.signum();
+            if grid(next) == grid(step) {
+                break;
+            }
+            step = next;
+        }
+        if step == self.tile_step {
+            return None;
+        }
+        self.tile_step = step;
+        // Tile pages are cached by start index, which now starts other pages.
+        self.render_cache.retain(|k, _| !is_tile_page(&k.path));
+        self.render_cache_order.retain(|k| !is_tile_page(&k.path));
+        self.prefetch_worker.cancel();
+        self.last_prefetch_signature = None;
+        self.prev_tile_cursor = None;
+        self.invalidate_render();
+        Some(grid(step))
+    }
+
+    /// Layout of the displayed tile page: the full grid, or a smaller one for a partial page.
+    pub fn page_grid(&self, terminal_size: Rect) -> (usize, usize) {
+        let grid = self.tile_grid(terminal_size);
+        let tiles_per_page = grid.0 * grid.1;
+        if tiles_per_page == 0 {
+            return grid;
+        }
+        let page_start = (self.tile_cursor / tiles_per_page) * tiles_per_page;
+        self.page_grid_at(grid, page_start, self.image_area(terminal_size))
+    }
+
+    fn page_grid_at(
+        &self,
+        grid: (usize, usize),
+        page_start: usize,
+        image_area: Rect,
+    ) -> (usize, usize) {
+        let count = self.images.len().saturating_sub(page_start);
+        Self::calculate_page_grid(grid, count, image_area, self.config.cell_aspect_ratio)
+    }
+
+    /// Tile rectangles of the displayed page.
+    fn displayed_page_rects(&mut self, terminal_size: Rect) -> Vec<Rect> {
+        let (cols, rows) = self.tile_grid(terminal_size);
+        let tiles_per_page = cols * rows;
+        if tiles_per_page == 0 {
+            return Vec::new();
+        }
+        let page_start = (self.tile_cursor / tiles_per_page) * tiles_per_page;
+        let grid = self.page_grid(terminal_size);
+        self.page_layout(page_start, grid, self.image_area(terminal_size))
+            .map(|layout| layout.rects)
+            .unwrap_or_default()
+    }
+
+    /// Layout of the page starting at `page_start` on its `grid`. With `group_by_date`, days
+    /// start new rows under labeled bands. None until what the layout needs from the page's
+    /// files (capture days, justified aspect ratios) has been read (`scan_page_from`).
+    fn page_layout(
+        &mut self,
+        page_start: usize,
+        grid: (usize, usize),
+        image_area: Rect,
+    ) -> Option<PageLayout> {
+        let mut layout = PageLayout {
+            rects: self.page_rects(page_start, grid, image_area)?,
+            days: None,
+            bands: Vec::new(),
+        };
+        if self.config.group_by_date {
+            let page = page_paths(&self.images, page_start, layout.rects.len());
+            let days = scanned(&mut self.day_scan, &mut self.capture_days, page)?;
+            let size = (image_area.width, image_area.height);
+            if let Some((rects, bands)) = crate::layout::grouped_rects(grid, size, &days) {
+                layout.rects = rects;
+                layout.bands = bands;
+            }
+            layout.days = Some(days);
+        }
+        Some(layout)
+    }
+
+    /// Read what page layouts need and is not known yet in the background, from image `start`
+    /// on (then from the top).
+    fn scan_page_from(&mut self, start: usize) {
+        if self.config.group_by_date {
+            let read = crate::exif::capture_day;
+            start_scan(
+                &mut self.day_scan,
+                &self.capture_days,
+                &self.images,
+                start,
+                read,
+            );
+        }
+        if self.tile_layout == TileLayout::Justified {
+            let limits = self.config.decode_limits();
+            let read = move |path: &Path| ImageWorker::image_aspect(path, &limits);
+            start_scan(
+                &mut self.aspect_scan,
+                &self.tile_aspects,
+                &self.images,
+                start,
+                read,
+            );
+        }
+    }
+
+    /// Tile rectangles (cells, relative to the image area) of the page starting at
+    /// `page_start`, laid out on its `grid`. None while a justified page's aspect ratios are
+    /// still being read.
+    fn page_rects(
+        &mut self,
+        page_start: usize,
+        grid: (usize, usize),
+        image_area: Rect,
+    ) -> Option<Vec<Rect>> {
+        let size = (image_area.width, image_area.height);
+        let count = self
+            .images
+            .len()
+            .saturating_sub(page_start)
+            .min(grid.0 * grid.1);
+        if self.tile_layout == TileLayout::Grid {
+            return Some(crate::layout::grid_rects(grid, size, count));
+        }
+        let page = page_paths(&self.images, page_start, count);
+        let aspects = scanned(&mut self.aspect_scan, &mut self.tile_aspects, page)?;
+        // As shown, after rotation.
+        let aspects: Vec<f64> = page
+            .iter()
+            .zip(aspects)
+            .map(|(path, aspect)| match self.rotations.get(path) {
+                Rotation::Cw90 | Rotation::Cw270 => 1.0 / aspect,
+                Rotation::None | Rotation::Cw180 => aspect,
+            })
+            .collect();
+        Some(crate::layout::justified_rects(grid, size, &aspects))
+    }
+
+    /// Grid for a page holding `count` tiles. A partly filled (last) page gets the layout
+    /// within `grid` that gives the largest tiles, so a few images do not sit in tiny
+    /// thumbnails with large gaps. Tile indices stay row-major within the page.
+    pub fn calculate_page_grid(
+        grid: (usize, usize),
+        count: usize,
+        image_area: Rect,
+        cell_aspect_ratio: f64,
+    ) -> (usize, usize) {
+        let (cols, rows) = grid;
+        if count == 0 || count >= cols * rows {
+            return grid;
+        }
+        // Visual tile size in cell widths: the smaller side of the tile bounds the thumbnail.
+        let tile_size = |c: usize, r: usize| {
+            let w = f64::from(image_area.width) / c as f64;
+            let h = f64::from(image_area.height) * cell_aspect_ratio / r as f64;
+            w.min(h)
+        };
+        let mut best = grid;
+        for c in 1..=cols.min(count) {
+            let r = count.div_ceil(c);
+            if r > rows {
+                continue;
+            }
+            let (size, best_size) = (tile_size(c, r), tile_size(best.0, best.1));
+            if size > best_size || (size == best_size && c * r < best.0 * best.1) {
+                best = (c, r);
+            }
+        }
+        best
+    }
+
+    /// Tile grid `step` columns fewer than the default grid (more for negative steps), with
+    /// rows for visually square tiles.
+    pub fn scaled_tile_grid(image_area: Rect, cell_aspect_ratio: f64, step: i32) -> (usize, usize) {
+        // Border and label rows need a few cells; columns stay readable at this width.
+        const MIN_TILE_WIDTH: u16 = 8;
+        const MIN_TILE_HEIGHT: u16 = 4;
+
+        let grid = Self::calculate_tile_grid(image_area, cell_aspect_ratio);
+        if step == 0 {
+            return grid;
+        }
+        let max_cols = (image_area.width / MIN_TILE_WIDTH).max(1) as i32;
+        let cols = (grid.0 as i32 - step).clamp(1, max_cols) as usize;
+        let tile_height = f64::from(image_area.width) / cols as f64 / cell_aspect_ratio;
+        let max_rows = usize::from((image_area.height / MIN_TILE_HEIGHT).max(1));
+        let rows = (f64::from(image_area.height) / tile_height).round() as usize;
+        (cols, rows.clamp(1, max_rows))
+    }
+
+    /// Calculate optimal tile grid size based on the image area.
+    /// Returns (cols, rows) for the tile grid.
+    pub fn calculate_tile_grid(image_area: Rect, cell_aspect_ratio: f64) -> (usize, usize) {
+        // For visually square tiles, we need to account for the cell aspect ratio.
+        // cell_aspect_ratio = cell_height_pixels / cell_width_pixels (typically ~2.0)
+        const MIN_TILE_WIDTH: u16 = 16;
+        const MIN_TILE_HEIGHT: u16 = 4;
+        const MAX_COLS: usize = 6;
+        const MAX_ROWS: usize = 6;
+
+        // Calculate min tile height to get visually square tiles
+        let min_tile_height = (MIN_TILE_WIDTH as f64 / cell_aspect_ratio).round() as u16;
+        let min_tile_height = min_tile_height.max(MIN_TILE_HEIGHT);
+
+        let cols = (image_area.width / MIN_TILE_WIDTH) as usize;
+        let rows = (image_area.height / min_tile_height) as usize;
+
+        // Clamp to reasonable bounds
+        let cols = cols.clamp(2, MAX_COLS);
+        let rows = rows.clamp(2, MAX_ROWS);
+
+        (cols, rows)
+    }
+
+    pub fn poll_worker(&mut self) {
+        // Poll main worker
+        while let Some(result) = self.worker.try_recv() {
+            let key = CacheKey {
+                path: result.path,
+                target: result.target,
+                fit_mode: result.fit_mode,
+                crop: result.crop,
+                rotation: result.rotation,
+                window: result.window,
+                adjust: result.adjust,
+            };
+            if result.partial {
+                if self.pending_request.as_ref() == Some(&key) {
+                    let rendered = RenderedImage {
+                        original_size: result.original_size,
+                        actual_size: result.actual_size,
+                        encoded_chunks: result.encoded_chunks,
+                        timings: Some(result.timings),
+                    };
+                    self.partial_tile = Some((key, rendered));
+                }
+                continue;
+            }
+            self.partial_tile = None;
+            if self.pending_request.as_ref() == Some(&key) {
+                self.pending_request = None;
+            }
+            self.store_to_disk(
+                &key,
+                result.original_size,
+                result.actual_size,
+                &result.encoded_chunks,
+            );
+            self.insert_to_cache(
+                key,
+                result.original_size,
+                result.actual_size,
+                result.encoded_chunks,
+                Some(result.timings),
+            );
+        }
+
+        while let Some(result) = self.worker.try_recv_animation() {
+            let key = CacheKey {
+                path: result.path,
+                target: result.target,
+                fit_mode: result.fit_mode,
+                crop: result.crop,
+                rotation: result.rotation,
+                window: result.window,
+                adjust: result.adjust,
+            };
+            if self.animation_requested.as_ref() == Some(&key) {
+                self.animation_requested = None;
+                self.animation = Some((key, Animation::new(result.frames, Instant::now())));
+            }
+        }
+
+        while let Some(path) = self.worker.try_recv_failed() {
+            if self.slideshow_options.skip_errors
+                && self.current_render_path().as_ref() == Some(&path)
+                && let Some(slideshow) = &mut self.slideshow
+            {
+                slideshow.skip(Instant::now());
+            }
+        }
+
+        while let Some((path, rotation, placeholder)) = self.worker.try_recv_placeholder() {
+            self.store_placeholder(path, rotation, placeholder);
+        }
+
+        // Poll prefetch worker
+        while let Some(result) = self.prefetch_worker.try_recv() {
+            if let Some(placeholder) = result.placeholder.clone() {
+                self.store_placeholder(result.path.clone(), result.rotation, placeholder);
+            }
+            let key = CacheKey {
+                path: result.path,
+                target: result.target,
+                fit_mode: result.fit_mode,
+                crop: result.crop,
+                rotation: result.rotation,
+                window: result.window,
+                adjust: result.adjust,
+            };
+            // Skip if already in cache (main worker result takes precedence)
+            if !self.render_cache.contains_key(&key) {
+                self.store_to_disk(
+                    &key,
+                    result.original_size,
+                    result.actual_size,
+                    &result.encoded_chunks,
+                );
+                self.insert_to_cache(
+                    key,
+                    result.original_size,
+                    result.actual_size,
+                    result.encoded_chunks,
+                    Some(result.timings),
+                );
+            }
+        }
+    }
+
+    /// Insert rendered image into cache with LRU eviction.
+    fn insert_to_cache(
+        &mut self,
+        key: CacheKey,
+        original_size: (u32, u32),
+        actual_size: (u32, u32),
+        encoded_chunks: Arc<Vec<Vec<u8>>>,
+        timings: Option<StageTimings>,
+    ) {
+        if self.render_cache.contains_key(&key) {
+            // Update existing entry and move to back of LRU order
+            self.render_cache_order.retain(|k| k != &key);
+        } else if self.render_cache.len() >= self.render_cache_limit {
+            // Evict oldest if at capacity
+            if let Some(oldest_key) = self.render_cache_order.pop_front() {
+                self.render_cache.remove(&oldest_key);
+            }
+        }
+        self.render_cache_order.push_back(key.clone());
+        self.render_cache.insert(
+            key,
+            RenderedImage {
+                original_size,
+                actual_size,
+                encoded_chunks,
+                timings,
+            },
+        );
+    }
+
+    /// Write a Single-mode render to the disk cache (tile pages are not persisted).
+    fn store_to_disk(
+        &self,
+        key: &CacheKey,
+        original_size: (u32, u32),
+        actual_size: (u32, u32),
+        encoded_chunks: &[Vec<u8>],
+    ) {
+        if let Some(disk_cache) = &self.disk_cache
+            && !is_tile_page(&key.path)
+        {
+            disk_cache.store(key, self.kgp_id, original_size, actual_size, encoded_chunks);
+        }
+    }
+
+    /// Fill the render cache from the disk cache, if it has the render.
+    fn load_from_disk(&mut self, key: &CacheKey) {
+        let Some(entry) = self
+            .disk_cache
+            .as_ref()
+            .and_then(|disk_cache| disk_cache.load(key, self.kgp_id))
+        else {
+            return;
+        };
+        self.insert_to_cache(
+            key.clone(),
+            entry.original_size,
+            entry.actual_size,
+            entry.encoded_chunks,
+            None,
+        );
+    }
+
+    /// Move cache entry to end of LRU order (mark as recently used).
+    fn touch_render_cache(&mut self, key: &CacheKey) {
+        if matches!(self.render_cache_order.back(), Some(k) if k == key) {
+            return; // Already at end
+        }
+        if !self.render_cache.contains_key(key) {
+            return; // Not in cache
+        }
+        self.render_cache_order.retain(|k| k != key);
+        self.render_cache_order.push_back(key.clone());
+    }
+
+    pub fn poll_writer(&mut self) {
+        while let Some(result) = self.writer.try_recv() {
+            if result.epoch != self.render_epoch {
+                continue;
+            }
+            let WriterResultKind::TransmitDone { elapsed, .. } = result.kind else {
+                if let Some((_, animation)) = self.animation.as_mut() {
+                    animation.in_flight = None;
+                }
+                continue;
+            };
+            self.in_flight_transmit = false;
+            self.last_transmit = Some(elapsed);
+
+            if let Some(area) = self.pending_display.take() {
+                self.kgp_state.set_last(area, self.kgp_id);
+                if std::mem::take(&mut self.partial_in_flight) {
+                    self.kgp_state.invalidate();
+                }
+            }
         }
     }
 
@@ -484,9 +2634,9 @@ impl App {
             return StatusIndicator::Busy;
         }
 
-        let image_area = Self::image_area(terminal_size);
+        let image_area = self.image_area(terminal_size);
 
-        let (cell_w, cell_h) = self.picker.font_size();
+        let (cell_w, cell_h) = self.cell_size();
         if cell_w == 0 || cell_h == 0 || image_area.width == 0 || image_area.height == 0 {
             return StatusIndicator::Busy;
         }
@@ -504,7 +2654,7 @@ impl App {
                 path.clone()
             }
             ViewMode::Tile => {
-                let grid = Self::calculate_tile_grid(terminal_size, self.config.cell_aspect_ratio);
+                let grid = self.tile_grid(terminal_size);
                 let tiles_per_page = grid.0 * grid.1;
                 if tiles_per_page == 0 {
                     return StatusIndicator::Busy;
@@ -514,28 +2664,35 @@ impl App {
             }
         };
 
+        let (crop, rotation, window, adjust) = match self.view_mode {
+            ViewMode::Single => (
+                self.crop,
+                self.current_rotation(),
+                self.current_window(),
+                self.adjust,
+            ),
+            ViewMode::Tile => (
+                None,
+                Rotation::None,
+                WindowLevel::default(),
+                Adjustments::default(),
+            ),
+        };
         let key = CacheKey {
             path: cache_path,
             target,
             fit_mode: self.fit_mode,
+            crop,
+            rotation,
+            window,
+            adjust,
         };
         let Some(rendered) = self.render_cache.get(&key) else {
             return StatusIndicator::Busy;
         };
 
         // Compute expected placement area and require it to match last successful display.
-        let cells_w = rendered.actual_size.0.div_ceil(u32::from(cell_w));
-        let cells_h = rendered.actual_size.1.div_ceil(u32::from(cell_h));
-        let cells_w = cells_w.min(u32::from(image_area.width)) as u16;
-        let cells_h = cells_h.min(u32::from(image_area.height)) as u16;
-        let offset_x = (image_area.width.saturating_sub(cells_w)) / 2;
-        let offset_y = (image_area.height.saturating_sub(cells_h)) / 2;
-        let area = Rect::new(
-            image_area.x + offset_x,
-            image_area.y + offset_y,
-            cells_w,
-            cells_h,
-        );
+        let area = placement_area(image_area, rendered.actual_size, (cell_w, cell_h));
 
         if self.kgp_state.last_area() != Some(area)
             || self.kgp_state.last_kgp_id() != Some(self.kgp_id)
@@ -556,14 +2713,37 @@ impl App {
     }
 
     /// Send the status row to the writer thread.
-    pub fn send_status(&self, text: String, size: (u16, u16), indicator: StatusIndicator) {
+    pub fn send_status(
+        &self,
+        text: String,
+        pending: String,
+        size: (u16, u16),
+        indicator: StatusIndicator,
+    ) {
         self.writer.send(WriterRequest::Status {
             text,
+            pending,
             size,
             indicator,
         });
     }
 
+    /// Stop the writer before the terminal is handed back (Ctrl-Z): the image is cleared and
+    /// nothing is written until the returned sender fires.
+    pub fn pause_output(&mut self) -> std::sync::mpsc::Sender<()> {
+        self.clear_kgp_overlay();
+        self.cancel_image_output();
+        let (paused_tx, paused_rx) = std::sync::mpsc::channel();
+        let (resume_tx, resume_rx) = std::sync::mpsc::channel();
+        self.writer.send(WriterRequest::Suspend {
+            paused: paused_tx,
+            resume: resume_rx,
+        });
+        // The writer only blocks on a terminal that stopped reading; do not hang with it.
+        let _ = paused_rx.recv_timeout(std::time::Duration::from_secs(1));
+        resume_tx
+    }
+
     /// Check if a transmit is currently in progress.
     pub fn is_transmitting(&self) -> bool {
         self.in_flight_transmit
@@ -582,6 +2762,7 @@ impl App {
         });
         self.clear_after_nav = true;
         self.in_flight_transmit = false;
+        self.partial_in_flight = false;
         self.pending_display = None;
         self.kgp_state.invalidate();
     }
@@ -592,7 +2773,12 @@ impl App {
     pub fn prepare_render_request(&mut self, terminal_size: Rect, allow_transmission: bool) {
         // Navigation/scrolling: do not do any image work (decode/resize/transmit/place).
         // This keeps status bar updates responsive by avoiding both stdout contention and CPU load.
-        if !allow_transmission {
+        // The directory browser, the calibration and help screens cover the image area.
+        if !allow_transmission
+            || self.browser.is_some()
+            || self.calibration.is_some()
+            || self.help.is_some()
+        {
             return;
         }
 
@@ -603,14 +2789,14 @@ impl App {
     }
 
     fn prepare_single_render(&mut self, terminal_size: Rect) {
-        let Some(path) = self.current_path().cloned() else {
+        let Some(path) = self.current_render_path() else {
             return;
         };
 
         let old_area = self.kgp_state.last_area();
-        let image_area = Self::image_area(terminal_size);
+        let image_area = self.image_area(terminal_size);
 
-        let (cell_w, cell_h) = self.picker.font_size();
+        let (cell_w, cell_h) = self.cell_size();
         if cell_w == 0 || cell_h == 0 || image_area.width == 0 || image_area.height == 0 {
             return;
         }
@@ -624,7 +2810,14 @@ impl App {
             path: path.clone(),
             target,
             fit_mode: self.fit_mode,
+            crop: self.crop,
+            rotation: self.current_rotation(),
+            window: self.current_window(),
+            adjust: self.adjust,
         };
+        if !self.render_cache.contains_key(&key) && self.pending_request.as_ref() != Some(&key) {
+            self.load_from_disk(&key);
+        }
         if let Some((actual_size, encoded_chunks)) = self
             .render_cache
             .get(&key)
@@ -633,23 +2826,15 @@ impl App {
             self.touch_render_cache(&key);
 
             // Calculate area for placement based on actual image size
-            let cells_w = actual_size.0.div_ceil(u32::from(cell_w));
-            let cells_h = actual_size.1.div_ceil(u32::from(cell_h));
-            let cells_w = cells_w.min(u32::from(image_area.width)) as u16;
-            let cells_h = cells_h.min(u32::from(image_area.height)) as u16;
-            let offset_x = (image_area.width.saturating_sub(cells_w)) / 2;
-            let offset_y = (image_area.height.saturating_sub(cells_h)) / 2;
-            let area = Rect::new(
-                image_area.x + offset_x,
-                image_area.y + offset_y,
-                cells_w,
-                cells_h,
-            );
+            let area = placement_area(image_area, actual_size, (cell_w, cell_h));
 
-            // Skip if already displayed.
+            // Skip if already displayed (an animated GIF then loads its frames, a TIFF counts
+            // its pages).
             if self.kgp_state.last_area() == Some(area)
                 && self.kgp_state.last_kgp_id() == Some(self.kgp_id)
             {
+                self.request_animation(&key);
+                self.load_page_count();
                 return;
             }
             if self.pending_display == Some(area) {
@@ -666,60 +2851,240 @@ impl App {
                     area: None,
                     is_tmux: self.is_tmux,
                 });
-                self.clear_after_nav = false;
+                self.clear_after_nav = false;
+            }
+
+            self.writer.send(WriterRequest::ImageTransmit {
+                encoded_chunks,
+                protocol: self.protocol,
+                area,
+                kgp_id: self.kgp_id,
+                old_area,
+                epoch: self.render_epoch,
+                is_tmux: self.is_tmux,
+            });
+            self.pending_display = Some(area);
+            self.transmit_epoch = self.render_epoch;
+            return;
+        }
+
+        // Request from worker if not already pending
+        self.draw_placeholder(&key, image_area, old_area);
+        if self.pending_request.as_ref() != Some(&key) {
+            self.worker.request(self.single_request(&key));
+            self.pending_request = Some(key);
+        }
+    }
+
+    /// Single-mode worker request for `key` with the current settings.
+    fn single_request(&self, key: &CacheKey) -> ImageRequest {
+        ImageRequest {
+            path: key.path.clone(),
+            target: key.target,
+            fit_mode: key.fit_mode,
+            kgp_id: self.kgp_id,
+            is_tmux: self.is_tmux,
+            file_transfer: self.file_transfer,
+            compress_level: self.config.compression_level(),
+            protocol: self.protocol,
+            tmux_kitty_max_pixels: self.config.tmux_kitty_max_pixels,
+            trace_worker: self.config.trace_worker,
+            resize_filter: crate::config::parse_filter_type(&self.config.resize_filter),
+            view_mode: ViewMode::Single,
+            tile_paths: None,
+            tile_rects: None,
+            cell_size: None,
+            tile_days: None,
+            tile_bands: Vec::new(),
+            tile_labels: false,
+            tile_filter: crate::config::parse_filter_type(&self.config.tile_filter),
+            upscale_sharpen: self.config.upscale_sharpen,
+            downscale_sharpen: self.config.downscale_sharpen,
+            bit_depth_stretch: self.config.bit_depth_stretch,
+            limits: self.config.decode_limits(),
+            crop: key.crop,
+            rotation: key.rotation,
+            window: key.window,
+            adjust: key.adjust,
+        }
+    }
+
+    /// Ask the worker for the frames of the GIF on screen (once per render key; stills
+    /// return nothing).
+    fn request_animation(&mut self, key: &CacheKey) {
+        let is_gif = key
+            .path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("gif"));
+        if !is_gif
+            || self.animation_requested.as_ref() == Some(key)
+            || self.animation.as_ref().is_some_and(|(k, _)| k == key)
+        {
+            return;
+        }
+        self.worker.request_animation(self.single_request(key));
+        self.animation_requested = Some(key.clone());
+    }
+
+    /// Show the next animation frame once it is due. Drops the animation when the view no
+    /// longer shows its image.
+    pub fn tick_animation(&mut self, terminal_size: Rect) {
+        let key = (self.view_mode == ViewMode::Single)
+            .then(|| self.single_cache_key(terminal_size))
+            .flatten();
+        if self.animation_requested.is_some() && self.animation_requested != key {
+            self.animation_requested = None;
+        }
+        let Some((anim_key, animation)) = self.animation.as_mut() else {
+            return;
+        };
+        if key.as_ref() != Some(anim_key) {
+            self.animation = None;
+            return;
+        }
+        // Wait for the still to be placed and for the previous frame to be written.
+        let Some(area) = self.kgp_state.last_area() else {
+            return;
+        };
+        if self.in_flight_transmit
+            || self.pending_display.is_some()
+            || self.kgp_state.last_kgp_id() != Some(self.kgp_id)
+            || animation.in_flight == Some(self.render_epoch)
+        {
+            return;
+        }
+        if self.server_animation {
+            // Transmitting the still again drops its frames; load them once per transmit.
+            if animation.loaded != Some(self.transmit_epoch) {
+                animation.loaded = Some(self.transmit_epoch);
+                animation.in_flight = Some(self.render_epoch);
+                self.writer.send(WriterRequest::AnimationLoad {
+                    frames: animation
+                        .frames
+                        .iter()
+                        .map(|frame| (Arc::clone(&frame.encoded_chunks), frame.delay))
+                        .collect(),
+                    kgp_id: self.kgp_id,
+                    epoch: self.render_epoch,
+                    is_tmux: self.is_tmux,
+                });
+                if animation.playback.is_paused() {
+                    let playback = &animation.playback;
+                    self.writer.send(WriterRequest::AnimationControl {
+                        kgp_id: self.kgp_id,
+                        run: false,
+                        frame: playback.is_stepped().then(|| playback.index() + 1),
+                        is_tmux: self.is_tmux,
+                    });
+                }
             }
+            return;
+        }
+        if !animation.playback.advance(Instant::now()) {
+            return;
+        }
+        animation.in_flight = Some(self.render_epoch);
+        self.writer.send(WriterRequest::AnimationFrame {
+            encoded_chunks: Arc::clone(&animation.frame().encoded_chunks),
+            protocol: self.protocol,
+            area,
+            epoch: self.render_epoch,
+        });
+    }
 
-            self.writer.send(WriterRequest::ImageTransmit {
-                encoded_chunks,
-                area,
+    /// When the next animation frame is due (None without a playing animation, or when the
+    /// terminal plays it).
+    pub fn animation_due(&self) -> Option<Instant> {
+        if self.server_animation {
+            return None;
+        }
+        self.animation
+            .as_ref()
+            .and_then(|(_, animation)| animation.playback.due())
+    }
+
+    /// Pause or resume the animation on screen. Returns whether it is now paused, or None if
+    /// the image is not animated.
+    pub fn toggle_animation(&mut self) -> Option<bool> {
+        let (_, animation) = self.animation.as_mut()?;
+        let paused = animation.playback.toggle_pause(Instant::now());
+        if self.server_animation && animation.loaded.is_some() {
+            self.writer.send(WriterRequest::AnimationControl {
                 kgp_id: self.kgp_id,
-                old_area,
-                epoch: self.render_epoch,
+                run: !paused,
+                frame: None,
                 is_tmux: self.is_tmux,
             });
-            self.pending_display = Some(area);
-            return;
         }
+        Some(paused)
+    }
 
-        // Request from worker if not already pending
-        let resize_filter = crate::config::parse_filter_type(&self.config.resize_filter);
-        let tile_filter = crate::config::parse_filter_type(&self.config.tile_filter);
-        let pending_key = CacheKey {
-            path,
-            target,
-            fit_mode: self.fit_mode,
-        };
-        if self.pending_request.as_ref() != Some(&pending_key) {
-            self.worker.request(ImageRequest {
-                path: pending_key.path.clone(),
-                target,
-                fit_mode: self.fit_mode,
+    /// Pause the animation on screen and step `delta` frames (`n` / `p`). Returns the new
+    /// frame number and the frame count, or None for stills.
+    pub fn step_animation(&mut self, delta: i32) -> Option<(usize, usize)> {
+        let (_, animation) = self.animation.as_mut()?;
+        let playback = &mut animation.playback;
+        let index = playback.step(delta);
+        if self.server_animation && animation.loaded.is_some() {
+            self.writer.send(WriterRequest::AnimationControl {
                 kgp_id: self.kgp_id,
+                run: false,
+                frame: Some(index + 1),
                 is_tmux: self.is_tmux,
-                compress_level: self.config.compression_level(),
-                tmux_kitty_max_pixels: self.config.tmux_kitty_max_pixels,
-                trace_worker: self.config.trace_worker,
-                resize_filter,
-                view_mode: ViewMode::Single,
-                tile_paths: None,
-                tile_grid: None,
-                cell_size: None,
-                tile_filter,
             });
-            self.pending_request = Some(pending_key);
         }
+        Some((index + 1, playback.frame_count()))
+    }
+
+    /// Draw the blurred placeholder of a not-yet-rendered image where it will be placed.
+    fn draw_placeholder(&mut self, key: &CacheKey, image_area: Rect, old_area: Option<Rect>) {
+        // A zoomed region (or the 1:1 center) does not match the whole-image preview; a running
+        // transmit would
+        // place its image over the placeholder.
+        if key.crop.is_some()
+            || key.fit_mode == FitMode::Actual
+            || self.in_flight_transmit
+            || self.drawn_placeholder.as_ref() == Some(key)
+        {
+            return;
+        }
+        let Some(placeholder) = self.placeholders.get(&(key.path.clone(), key.rotation)) else {
+            return;
+        };
+        let size = ImageWorker::compute_target(placeholder.source_size, key.target, key.fit_mode);
+        let area = placement_area(image_area, size, self.cell_size());
+        let cells = placeholder.cell_colors(area.width, area.height);
+        self.writer.send(WriterRequest::Placeholder {
+            area,
+            cells,
+            old_area,
+            epoch: self.render_epoch,
+        });
+        self.drawn_placeholder = Some(key.clone());
+        // The previous image has been erased.
+        self.kgp_state.invalidate();
+    }
+
+    fn store_placeholder(&mut self, path: PathBuf, rotation: Rotation, p: Arc<Placeholder>) {
+        if self.placeholders.len() >= MAX_PLACEHOLDERS
+            && let Some(evict) = self.placeholders.keys().next().cloned()
+        {
+            self.placeholders.remove(&evict);
+        }
+        self.placeholders.insert((path, rotation), p);
     }
 
     fn prepare_tile_render(&mut self, terminal_size: Rect) {
         let old_area = self.kgp_state.last_area();
-        let image_area = Self::image_area(terminal_size);
+        let image_area = self.image_area(terminal_size);
 
-        let (cell_w, cell_h) = self.picker.font_size();
+        let (cell_w, cell_h) = self.cell_size();
         if cell_w == 0 || cell_h == 0 || image_area.width == 0 || image_area.height == 0 {
             return;
         }
 
-        let grid = Self::calculate_tile_grid(terminal_size, self.config.cell_aspect_ratio);
+        let grid = self.tile_grid(terminal_size);
         let (cols, rows) = grid;
 
         // Calculate canvas size in pixels
@@ -730,17 +3095,12 @@ impl App {
         // Get tile paths for current page
         let tiles_per_page = cols * rows;
         let page_start = (self.tile_cursor / tiles_per_page) * tiles_per_page;
-        let tile_paths: Vec<PathBuf> = self
-            .images
-            .iter()
-            .skip(page_start)
-            .take(tiles_per_page)
-            .cloned()
-            .collect();
+        let tile_paths = self.tile_page_paths(page_start, tiles_per_page);
 
         if tile_paths.is_empty() {
             return;
         }
+        let grid = self.page_grid_at(grid, page_start, image_area);
 
         // Use a synthetic path for tile cache key (cursor is drawn via ANSI overlay, not part of cache)
         let cache_path = PathBuf::from(format!("__tile_page_{}", page_start));
@@ -748,6 +3108,10 @@ impl App {
             path: cache_path.clone(),
             target,
             fit_mode: self.fit_mode,
+            crop: None,
+            rotation: Rotation::None,
+            window: WindowLevel::default(),
+            adjust: Adjustments::default(),
         };
 
         // Check cache
@@ -757,43 +3121,20 @@ impl App {
             .map(|rendered| (rendered.actual_size, Arc::clone(&rendered.encoded_chunks)))
         {
             self.touch_render_cache(&key);
+            self.transmit_tile_page(actual_size, encoded_chunks, image_area, old_area);
+            return;
+        }
 
-            let cells_w = actual_size.0.div_ceil(u32::from(cell_w));
-            let cells_h = actual_size.1.div_ceil(u32::from(cell_h));
-            let cells_w = cells_w.min(u32::from(image_area.width)) as u16;
-            let cells_h = cells_h.min(u32::from(image_area.height)) as u16;
-            let area = Rect::new(image_area.x, image_area.y, cells_w, cells_h);
-
-            if self.kgp_state.last_area() == Some(area)
-                && self.kgp_state.last_kgp_id() == Some(self.kgp_id)
-            {
-                return;
-            }
-            if self.pending_display == Some(area) {
-                return;
-            }
-
-            if self.in_flight_transmit {
-                return;
-            }
-            self.in_flight_transmit = true;
-            if self.clear_after_nav {
-                self.writer.send(WriterRequest::ClearAll {
-                    area: None,
-                    is_tmux: self.is_tmux,
-                });
-                self.clear_after_nav = false;
+        // Show the page so far while its slower thumbnails are still decoding.
+        if self.pending_request.as_ref() == Some(&key)
+            && let Some((_, partial)) = self.partial_tile.as_ref().filter(|(k, _)| *k == key)
+        {
+            let (actual_size, encoded_chunks) =
+                (partial.actual_size, Arc::clone(&partial.encoded_chunks));
+            if self.transmit_tile_page(actual_size, encoded_chunks, image_area, old_area) {
+                self.partial_tile = None;
+                self.partial_in_flight = true;
             }
-
-            self.writer.send(WriterRequest::ImageTransmit {
-                encoded_chunks,
-                area,
-                kgp_id: self.kgp_id,
-                old_area,
-                epoch: self.render_epoch,
-                is_tmux: self.is_tmux,
-            });
-            self.pending_display = Some(area);
             return;
         }
 
@@ -801,28 +3142,96 @@ impl App {
         let resize_filter = crate::config::parse_filter_type(&self.config.resize_filter);
         let tile_filter = crate::config::parse_filter_type(&self.config.tile_filter);
         if self.pending_request.as_ref() != Some(&key) {
+            let Some(layout) = self.page_layout(page_start, grid, image_area) else {
+                // Composited once its files have been read.
+                self.scan_page_from(page_start);
+                return;
+            };
             self.worker.request(ImageRequest {
                 path: cache_path,
                 target,
                 fit_mode: self.fit_mode,
                 kgp_id: self.kgp_id,
                 is_tmux: self.is_tmux,
+                file_transfer: self.file_transfer,
                 compress_level: self.config.compression_level(),
+                protocol: self.protocol,
                 tmux_kitty_max_pixels: self.config.tmux_kitty_max_pixels,
                 trace_worker: self.config.trace_worker,
                 resize_filter,
                 view_mode: ViewMode::Tile,
                 tile_paths: Some(tile_paths),
-                tile_grid: Some(grid),
+                tile_rects: Some(layout.rects),
                 cell_size: Some((cell_w, cell_h)),
+                tile_days: layout.days,
+                tile_bands: layout.bands,
+                tile_labels: self.config.tile_labels,
                 tile_filter,
+                upscale_sharpen: self.config.upscale_sharpen,
+                downscale_sharpen: self.config.downscale_sharpen,
+                bit_depth_stretch: self.config.bit_depth_stretch,
+                limits: self.config.decode_limits(),
+                crop: None,
+                rotation: Rotation::None,
+                window: WindowLevel::default(),
+                adjust: Adjustments::default(),
             });
             self.pending_request = Some(key);
         }
     }
 
-    fn prefetch_count(&self) -> usize {
-        self.config.prefetch_count
+    /// Transmit a rendered tile page unless it is already shown or a transmit is in flight.
+    /// Returns false if nothing was sent.
+    fn transmit_tile_page(
+        &mut self,
+        actual_size: (u32, u32),
+        encoded_chunks: Arc<Vec<Vec<u8>>>,
+        image_area: Rect,
+        old_area: Option<Rect>,
+    ) -> bool {
+        let (cell_w, cell_h) = self.cell_size();
+        let cells_w = actual_size.0.div_ceil(u32::from(cell_w));
+        let cells_h = actual_size.1.div_ceil(u32::from(cell_h));
+        let cells_w = cells_w.min(u32::from(image_area.width)) as u16;
+        let cells_h = cells_h.min(u32::from(image_area.height)) as u16;
+        let area = Rect::new(image_area.x, image_area.y, cells_w, cells_h);
+
+        if self.kgp_state.last_area() == Some(area)
+            && self.kgp_state.last_kgp_id() == Some(self.kgp_id)
+        {
+            return false;
+        }
+        if self.pending_display == Some(area) {
+            return false;
+        }
+
+        if self.in_flight_transmit {
+            return false;
+        }
+        self.in_flight_transmit = true;
+        if self.clear_after_nav {
+            self.writer.send(WriterRequest::ClearAll {
+                area: None,
+                is_tmux: self.is_tmux,
+            });
+            self.clear_after_nav = false;
+        }
+
+        self.writer.send(WriterRequest::ImageTransmit {
+            encoded_chunks,
+            protocol: self.protocol,
+            area,
+            kgp_id: self.kgp_id,
+            old_area,
+            epoch: self.render_epoch,
+            is_tmux: self.is_tmux,
+        });
+        self.pending_display = Some(area);
+        true
+    }
+
+    fn prefetch_count(&mut self) -> usize {
+        self.power.prefetch_count(self.config.prefetch_count)
     }
 
     /// Prefetch adjacent images/pages into the render cache.
@@ -845,8 +3254,8 @@ impl App {
     /// Prefetch next/previous images in Single mode (with wrap-around).
     /// Uses dedicated PrefetchWorker for parallel processing.
     fn prefetch_adjacent_single(&mut self, terminal_size: Rect) {
-        let image_area = Self::image_area(terminal_size);
-        let (cell_w, cell_h) = self.picker.font_size();
+        let image_area = self.image_area(terminal_size);
+        let (cell_w, cell_h) = self.cell_size();
         if cell_w == 0 || cell_h == 0 || image_area.width == 0 || image_area.height == 0 {
             return;
         }
@@ -855,12 +3264,7 @@ impl App {
         let max_h_px = u32::from(image_area.height) * u32::from(cell_h);
         let target = (max_w_px, max_h_px);
 
-        let len = self.images.len();
-        if len <= 1 {
-            return;
-        }
-
-        // Build list of indices: alternating next/previous (+1, -1, +2, -2, ...)
+        // Build list of indices, nearest first, biased toward the navigation direction.
         let prefetch_count = self.prefetch_count();
         let signature = PrefetchSignature {
             view_mode: ViewMode::Single,
@@ -869,15 +3273,82 @@ impl App {
             prefetch_count,
             anchor: self.current_index,
             grid: None,
+            direction: self.nav_direction,
+            crop: self.prefetch_crop(),
+            zoom: self.crop,
         };
         if self.last_prefetch_signature == Some(signature) {
             return;
         }
-        let mut indices = Vec::with_capacity(prefetch_count * 2);
-        for i in 1..=prefetch_count {
-            indices.push((self.current_index + i) % len);
-            indices.push((self.current_index + len - i) % len);
+        self.last_prefetch_signature = Some(signature);
+
+        // The current image in the other fit mode first, so `f` shows it without a render.
+        // A zoomed region is always rendered as Fit, so there is no alternate then. Skipped
+        // while saving power.
+        if signature.crop.is_none()
+            && !self.power.is_low()
+            && let Some(path) = self.current_render_path()
+        {
+            let rotation = self.current_rotation();
+            let key = CacheKey {
+                path: path.clone(),
+                target,
+                fit_mode: self.fit_mode.next(),
+                crop: None,
+                rotation,
+                window: self.current_window(),
+                adjust: self.adjust,
+            };
+            if !self.render_cache.contains_key(&key) {
+                self.load_from_disk(&key);
+            }
+            if !self.render_cache.contains_key(&key) {
+                let req = self.prefetch_request(vec![(path, rotation)], target, key.fit_mode, None);
+                self.prefetch_worker.prefetch_batch(req);
+            }
+        }
+
+        // Then the next zoom step in (and out, while zoomed), so `+` / `_` show without a
+        // render. Also skipped while saving power.
+        if !self.power.is_low()
+            && let Some(path) = self.current_render_path()
+        {
+            let rotation = self.current_rotation();
+            for steps in [1, -1] {
+                let Some(crop) = self.zoom_step_crop(steps, terminal_size) else {
+                    continue;
+                };
+                let key = CacheKey {
+                    path: path.clone(),
+                    target,
+                    fit_mode: self.fit_mode,
+                    crop,
+                    rotation,
+                    window: self.current_window(),
+                    adjust: self.adjust,
+                };
+                if !self.render_cache.contains_key(&key) {
+                    self.load_from_disk(&key);
+                }
+                if !self.render_cache.contains_key(&key) {
+                    let paths = vec![(path.clone(), rotation)];
+                    let req = self.prefetch_request(paths, target, self.fit_mode, crop);
+                    self.prefetch_worker.prefetch_batch(req);
+                }
+            }
+        }
+
+        let len = self.images.len();
+        if len <= 1 {
+            return;
         }
+        let step = if self.spread { 2 } else { 1 };
+        let indices: Vec<usize> = prefetch_offsets(prefetch_count, self.nav_direction)
+            .into_iter()
+            .map(|offset| {
+                (self.current_index as i64 + offset * step).rem_euclid(len as i64) as usize
+            })
+            .collect();
 
         // Collect paths that need prefetching
         let mut seen = HashSet::with_capacity(indices.len());
@@ -886,14 +3357,24 @@ impl App {
             if !seen.insert(idx) {
                 continue;
             }
-            let path = &self.images[idx];
+            let source = &self.images[idx];
+            let rotation = self.rotations.get(source);
+            let window = self.window.for_path(source);
+            let path = self.compose_render_path(idx, source.clone());
             let key = CacheKey {
                 path: path.clone(),
                 target,
                 fit_mode: self.fit_mode,
+                crop: signature.crop,
+                rotation,
+                window,
+                adjust: self.adjust,
             };
             if !self.render_cache.contains_key(&key) {
-                paths.push(path.clone());
+                self.load_from_disk(&key);
+            }
+            if !self.render_cache.contains_key(&key) {
+                paths.push((path, rotation));
             }
         }
 
@@ -902,25 +3383,44 @@ impl App {
         }
 
         // Submit batch prefetch request
-        let resize_filter = crate::config::parse_filter_type(&self.config.resize_filter);
-        self.prefetch_worker.prefetch_batch(PrefetchRequest {
+        let req = self.prefetch_request(paths, target, self.fit_mode, signature.crop);
+        self.prefetch_worker.prefetch_batch(req);
+    }
+
+    /// Single-mode prefetch request for `paths` with the current settings.
+    fn prefetch_request(
+        &self,
+        paths: Vec<(PathBuf, Rotation)>,
+        target: (u32, u32),
+        fit_mode: FitMode,
+        crop: Option<CropRect>,
+    ) -> PrefetchRequest {
+        PrefetchRequest {
             paths,
             target,
-            fit_mode: self.fit_mode,
+            fit_mode,
             epoch: self.prefetch_worker.current_epoch(),
             kgp_id: self.kgp_id,
             is_tmux: self.is_tmux,
+            file_transfer: self.file_transfer,
             compress_level: self.config.compression_level(),
+            protocol: self.protocol,
             tmux_kitty_max_pixels: self.config.tmux_kitty_max_pixels,
-            resize_filter,
-        });
-        self.last_prefetch_signature = Some(signature);
+            resize_filter: crate::config::parse_filter_type(&self.config.resize_filter),
+            upscale_sharpen: self.config.upscale_sharpen,
+            downscale_sharpen: self.config.downscale_sharpen,
+            bit_depth_stretch: self.config.bit_depth_stretch,
+            limits: self.config.decode_limits(),
+            window: self.window,
+            adjust: self.adjust,
+            crop,
+        }
     }
 
     /// Prefetch next/previous pages in Tile mode (no wrap-around).
     fn prefetch_adjacent_tile(&mut self, terminal_size: Rect) {
-        let image_area = Self::image_area(terminal_size);
-        let (cell_w, cell_h) = self.picker.font_size();
+        let image_area = self.image_area(terminal_size);
+        let (cell_w, cell_h) = self.cell_size();
         if cell_w == 0 || cell_h == 0 || image_area.width == 0 || image_area.height == 0 {
             return;
         }
@@ -929,7 +3429,7 @@ impl App {
         let max_h_px = u32::from(image_area.height) * u32::from(cell_h);
         let target = (max_w_px, max_h_px);
 
-        let grid = Self::calculate_tile_grid(terminal_size, self.config.cell_aspect_ratio);
+        let grid = self.tile_grid(terminal_size);
         let (cols, rows) = grid;
         let tiles_per_page = cols * rows;
         if tiles_per_page == 0 {
@@ -951,6 +3451,9 @@ impl App {
             prefetch_count,
             anchor: current_page,
             grid: Some(grid),
+            direction: 0,
+            crop: None,
+            zoom: None,
         };
         if self.last_prefetch_signature == Some(signature) {
             return;
@@ -968,6 +3471,11 @@ impl App {
             }
         }
 
+        let resize_filter = crate::config::parse_filter_type(&self.config.resize_filter);
+        let tile_filter = crate::config::parse_filter_type(&self.config.tile_filter);
+        // Queue every uncached page; a batch for the previous anchor stops at its next page.
+        let mut pages = Vec::new();
+        let mut complete = true;
         for page in page_indices {
             let page_start = page * tiles_per_page;
             let cache_path = PathBuf::from(format!("__tile_page_{}", page_start));
@@ -975,45 +3483,67 @@ impl App {
                 path: cache_path.clone(),
                 target,
                 fit_mode: self.fit_mode,
+                crop: None,
+                rotation: Rotation::None,
+                window: WindowLevel::default(),
+                adjust: Adjustments::default(),
             };
 
             if self.render_cache.contains_key(&key) {
                 continue;
             }
 
-            let tile_paths: Vec<PathBuf> = self
-                .images
-                .iter()
-                .skip(page_start)
-                .take(tiles_per_page)
-                .cloned()
-                .collect();
+            let tile_paths = self.tile_page_paths(page_start, tiles_per_page);
 
             if tile_paths.is_empty() {
                 continue;
             }
+            let page_grid = self.page_grid_at(grid, page_start, image_area);
+            let Some(layout) = self.page_layout(page_start, page_grid, image_area) else {
+                // Try again once its files have been read.
+                complete = false;
+                continue;
+            };
 
-            let resize_filter = crate::config::parse_filter_type(&self.config.resize_filter);
-            let tile_filter = crate::config::parse_filter_type(&self.config.tile_filter);
-            self.worker.request(ImageRequest {
+            pages.push(ImageRequest {
                 path: cache_path,
                 target,
                 fit_mode: self.fit_mode,
                 kgp_id: self.kgp_id,
                 is_tmux: self.is_tmux,
+                file_transfer: self.file_transfer,
                 compress_level: self.config.compression_level(),
+                protocol: self.protocol,
                 tmux_kitty_max_pixels: self.config.tmux_kitty_max_pixels,
                 trace_worker: self.config.trace_worker,
                 resize_filter,
                 view_mode: ViewMode::Tile,
                 tile_paths: Some(tile_paths),
-                tile_grid: Some(grid),
+                tile_rects: Some(layout.rects),
                 cell_size: Some((cell_w, cell_h)),
+                tile_days: layout.days,
+                tile_bands: layout.bands,
+                tile_labels: self.config.tile_labels,
                 tile_filter,
+                upscale_sharpen: self.config.upscale_sharpen,
+                downscale_sharpen: self.config.downscale_sharpen,
+                bit_depth_stretch: self.config.bit_depth_stretch,
+                limits: self.config.decode_limits(),
+                crop: None,
+                rotation: Rotation::None,
+                window: WindowLevel::default(),
+                adjust: Adjustments::default(),
             });
+        }
+        if complete {
             self.last_prefetch_signature = Some(signature);
-            break;
         }
+        if pages.is_empty() {
+            return;
+        }
+        self.prefetch_worker.cancel();
+        let epoch = self.prefetch_worker.current_epoch();
+        self.prefetch_worker.prefetch_tile_pages(pages, epoch);
     }
 
     pub fn clear_kgp_overlay(&mut self) {
@@ -1027,42 +3557,179 @@ impl App {
         });
     }
 
-    /// Copy the current image's absolute path to clipboard via OSC 52.
-    pub fn copy_path_to_clipboard(&self) -> bool {
+    /// Copy the current image's path (or what the yank register selects) to clipboard via OSC 52.
+    /// `DataUri` only starts encoding; the copy happens in `poll_clipboard`.
+    pub fn copy_to_clipboard(&mut self, yank: Yank) -> bool {
         let Some(path) = self.current_path() else {
             return false;
         };
-        let Some(path_str) = path.to_str() else {
+        if yank == Yank::DataUri {
+            self.start_data_uri_copy(path.clone());
+            return true;
+        }
+        let size = match yank {
+            Yank::Size | Yank::Exif => self.current_image_resolution().or_else(|| {
+                let (w, h) = image::ImageReader::open(path)
+                    .ok()?
+                    .with_guessed_format()
+                    .ok()?
+                    .into_dimensions()
+                    .ok()?;
+                Some(if self.current_rotation().degrees() % 180 == 90 {
+                    (h, w)
+                } else {
+                    (w, h)
+                })
+            }),
+            _ => None,
+        };
+        let Some(text) = yank.text(path, size) else {
             return false;
         };
-        self.writer.send(WriterRequest::CopyToClipboard {
-            data: path_str.as_bytes().to_vec(),
-            is_tmux: self.is_tmux,
-        });
+        self.send_to_clipboard(text);
         true
     }
 
-    /// Copy the current image data to clipboard (local only, uses OS API).
-    pub fn copy_image_to_clipboard(&self) -> bool {
-        use arboard::{Clipboard, ImageData};
+    /// Copy text through `clip.exe` under WSL (on a background thread), else (or when that
+    /// fails) via OSC 52.
+    fn send_to_clipboard(&self, text: String) {
+        let is_tmux = self.is_tmux;
+        if !self.is_wsl {
+            self.writer.send(WriterRequest::CopyToClipboard {
+                data: text.into_bytes(),
+                is_tmux,
+            });
+            return;
+        }
+        let writer = self.writer.sender();
+        let text = Arc::new(text);
+        let fallback = Arc::clone(&text);
+        crate::wsl::copy_in_background(
+            move || crate::wsl::copy_text(&text),
+            move || {
+                let data = fallback.as_bytes().to_vec();
+                writer
+                    .send(WriterRequest::CopyToClipboard { data, is_tmux })
+                    .is_ok()
+            },
+        );
+    }
+
+    /// Decode, rotate and PNG-encode an image as a data URI off the UI thread.
+    fn start_data_uri_copy(&mut self, path: PathBuf) {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let window = self.current_window();
+        let rotation = self.current_rotation();
+        let limits = self.config.decode_limits();
+        let stretch = self.config.bit_depth_stretch;
+        std::thread::spawn(move || {
+            let result = ImageWorker::decode_image(&path, window, &limits)
+                .ok_or_else(|| anyhow::anyhow!("Failed to decode {}", file_name(&path)))
+                .and_then(|img| {
+                    let img = crate::depth::to_display_depth(img, stretch);
+                    crate::yank::data_uri(&rotation.apply(&img))
+                });
+            let _ = tx.send(result);
+            crate::wake::wake();
+        });
+        self.pending_copy = Some(rx);
+    }
 
-        let Some(path) = self.current_path() else {
-            return false;
+    /// Copy a finished data URI. Returns its length in bytes, or why encoding failed.
+    pub fn poll_clipboard(&mut self) -> Option<Result<usize>> {
+        let result = self.pending_copy.as_ref()?.try_recv();
+        let uri = match result {
+            Ok(uri) => uri,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return None,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                Err(anyhow::anyhow!("Data URI encoding stopped"))
+            }
         };
-        let Ok(img) = image::open(path) else {
-            return false;
+        self.pending_copy = None;
+        Some(uri.map(|uri| {
+            let len = uri.len();
+            self.send_to_clipboard(uri);
+            len
+        }))
+    }
+
+    /// Write the zoomed region of the current image (the whole image when not zoomed) to
+    /// `dest` on the worker (`:w`, `:w!` to overwrite). Poll with `poll_save`.
+    pub fn save_crop(&mut self, dest: PathBuf, force: bool) -> Result<()> {
+        let path = self
+            .current_render_path()
+            .ok_or_else(|| anyhow::anyhow!("No image"))?;
+        if self.pending_save.is_some() {
+            anyhow::bail!("Still writing the previous file");
+        }
+        let crop = match self.view_mode {
+            ViewMode::Single => self.crop,
+            ViewMode::Tile => None,
         };
-        let rgba = img.to_rgba8();
-        let (width, height) = rgba.dimensions();
-        let image_data = ImageData {
-            width: width as usize,
-            height: height as usize,
-            bytes: rgba.into_raw().into(),
+        let (reply, rx) = std::sync::mpsc::channel();
+        self.worker.save(SaveRequest {
+            path,
+            window: self.current_window(),
+            rotation: self.current_rotation(),
+            crop,
+            bit_depth_stretch: self.config.bit_depth_stretch,
+            limits: self.config.decode_limits(),
+            dest: dest.clone(),
+            force,
+            reply,
+        });
+        self.pending_save = Some((dest, rx));
+        Ok(())
+    }
+
+    /// Result of a finished `save_crop`: the destination and the written size.
+    pub fn poll_save(&mut self) -> Option<Result<(PathBuf, (u32, u32))>> {
+        let (_, rx) = self.pending_save.as_ref()?;
+        let result = match rx.try_recv() {
+            Ok(result) => result,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return None,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                Err(anyhow::anyhow!("Image worker stopped"))
+            }
         };
-        let Ok(mut clipboard) = Clipboard::new() else {
-            return false;
+        let (dest, _) = self.pending_save.take()?;
+        Some(result.map(|size| (dest, size)))
+    }
+
+    /// Copy the current image data to clipboard (local only, uses OS API). Under WSL the copy
+    /// goes through `powershell.exe` on a background thread, falling back to the OS API there:
+    /// None then, and `poll_image_copy` reports the result.
+    pub fn copy_image_to_clipboard(&mut self) -> Option<bool> {
+        let Some(path) = self.current_path() else {
+            return Some(false);
+        };
+        let Some(img) =
+            ImageWorker::decode_image(path, self.current_window(), &self.config.decode_limits())
+        else {
+            return Some(false);
+        };
+        if !self.is_wsl {
+            return Some(set_clipboard_image(&img));
+        }
+        let img = Arc::new(img);
+        let fallback = Arc::clone(&img);
+        self.pending_image_copy = Some(crate::wsl::copy_in_background(
+            move || crate::wsl::copy_image(&img),
+            move || set_clipboard_image(&fallback),
+        ));
+        None
+    }
+
+    /// Whether a background image copy (`copy_image_to_clipboard` under WSL) succeeded, once
+    /// it finished.
+    pub fn poll_image_copy(&mut self) -> Option<bool> {
+        let copied = match self.pending_image_copy.as_ref()?.try_recv() {
+            Ok(copied) => copied,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return None,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => false,
         };
-        clipboard.set_image(image_data).is_ok()
+        self.pending_image_copy = None;
+        Some(copied)
     }
 
     pub fn current_image_name(&self) -> String {
@@ -1076,36 +3743,154 @@ impl App {
 
     /// Get the original resolution of the current image from cache.
     fn current_image_resolution(&self) -> Option<(u32, u32)> {
-        let path = self.current_path()?;
+        let path = self.current_render_path()?;
+        let rotation = self.current_rotation();
         // Search by path in cache keys
         self.render_cache
             .iter()
-            .find(|(k, _)| &k.path == path)
+            .find(|(k, _)| k.path == path && k.rotation == rotation)
             .map(|(_, v)| v.original_size)
     }
 
     pub fn status_text(&self, terminal_size: Rect) -> String {
         // Nerdfont icons
         const ICON_IMAGE: &str = "\u{e60d}"; //  (nf-seti-image)
+        const ICON_FOLDER: &str = "\u{f07b}"; //  (nf-fa-folder)
         const SEP: &str = "\u{e0b1}"; //  (Powerline separator)
 
-        match self.view_mode {
-            ViewMode::Single => {
-                // terminal_size is only used in Tile mode for grid calculation
-                let resolution = self
-                    .current_image_resolution()
-                    .map(|(w, h)| format!(" [{w}x{h}]"))
-                    .unwrap_or_default();
+        if let Some(cmd) = &self.command_line {
+            return format!(":{}\u{2588}", cmd.input());
+        }
+        if let Some(calibration) = &self.calibration {
+            return format!("Calibrate: cell_aspect_ratio = {:.2}", calibration.ratio());
+        }
+        if self.help.is_some() {
+            return "Help".to_string();
+        }
+        if let Some(browser) = &self.browser {
+            return format!(
+                "{}/{} {} {} {}",
+                browser.selected() + 1,
+                browser.entries().len(),
+                SEP,
+                ICON_FOLDER,
+                browser.dir().display(),
+            );
+        }
+
+        match self.view_mode {
+            ViewMode::Single => {
+                let resolution = self
+                    .current_image_resolution()
+                    .map(|(w, h)| format!(" [{w}x{h}]"))
+                    .unwrap_or_default();
+
+                let mut status = format!(
+                    "{}/{}{}{} {} {} {}{}",
+                    self.current_index + 1,
+                    self.images.len(),
+                    self.list_note_text(),
+                    self.dir_position().unwrap_or_default(),
+                    SEP,
+                    ICON_IMAGE,
+                    self.current_image_name(),
+                    resolution,
+                );
+
+                if let Some(pages) = self.current_pages() {
+                    status.push_str(&format!(
+                        " {SEP} page {}/{}",
+                        pages.current + 1,
+                        pages.count
+                    ));
+                }
+
+                if self.selection.is_some() {
+                    let size = self
+                        .selection_crop(terminal_size)
+                        .map(|c| format!(" {}x{}", c.width, c.height))
+                        .unwrap_or_default();
+                    status.push_str(&format!(" {SEP} select{size} (Enter: zoom, Esc: cancel)"));
+                } else if let Some(c) = self.crop {
+                    status.push_str(&format!(
+                        " {SEP} zoom {}x{}+{}+{}",
+                        c.width, c.height, c.x, c.y
+                    ));
+                }
+                if self.zoom_lock {
+                    status.push_str(&format!(" {SEP} zoom locked"));
+                }
+                if self.fit_mode == FitMode::Actual && self.crop.is_none() {
+                    status.push_str(&format!(" {SEP} 1:1"));
+                }
+
+                let rotation = self.current_rotation();
+                if rotation != Rotation::None {
+                    status.push_str(&format!(" {SEP} rotated {}°", rotation.degrees()));
+                }
+
+                let window = self.current_window();
+                if !window.is_default() {
+                    status.push_str(&format!(" {SEP} W/L {:+}/{:+}", window.width, window.level));
+                }
+
+                if let Some(label) = self.adjust.label() {
+                    status.push_str(&format!(" {SEP} {label}"));
+                }
+
+                if self.spread {
+                    let order = if self.config.spread_rtl {
+                        "spread ←"
+                    } else {
+                        "spread"
+                    };
+                    status.push_str(&format!(" {SEP} {order}"));
+                }
+
+                if self.slideshow.is_some() {
+                    status.push_str(&format!(" {SEP} {}", self.slideshow_options.label()));
+                }
+
+                if let Some(panes) = self.panes
+                    && let Some(other) = self.images.get(panes.other)
+                {
+                    let side = if panes.right_focused { "left" } else { "right" };
+                    status.push_str(&format!(" {SEP} {side} pane: {}", file_name(other)));
+                }
+
+                if let Some(reference) = &self.blend {
+                    status.push_str(&format!(
+                        " {SEP} blend {}% {}",
+                        self.blend_opacity,
+                        file_name(reference)
+                    ));
+                }
+
+                if let Some(reference) = &self.wipe {
+                    status.push_str(&format!(
+                        " {SEP} wipe {}% | {}",
+                        self.wipe_position,
+                        file_name(reference)
+                    ));
+                }
+
+                if let Some(reference) = &self.diff {
+                    status.push_str(&format!(" {SEP} diff vs {}", file_name(reference)));
+                }
 
-                let mut status = format!(
-                    "{}/{} {} {} {}{}",
-                    self.current_index + 1,
-                    self.images.len(),
-                    SEP,
-                    ICON_IMAGE,
-                    self.current_image_name(),
-                    resolution,
-                );
+                if let Some((_, animation)) = &self.animation {
+                    let playback = &animation.playback;
+                    // The terminal does not report which frame it shows until one is stepped to.
+                    let position = if self.server_animation && !playback.is_stepped() {
+                        format!("{} frames", playback.frame_count())
+                    } else {
+                        format!("frame {}/{}", playback.index() + 1, playback.frame_count())
+                    };
+                    status.push_str(&format!(
+                        " {SEP} {position}{}",
+                        if playback.is_paused() { " paused" } else { "" },
+                    ));
+                }
 
                 if self.config.debug {
                     if self.is_tmux {
@@ -1114,14 +3899,22 @@ impl App {
                     status.push_str(&format!(
                         " caps:{:?} cell:{:?}",
                         self.picker.capabilities(),
-                        self.picker.font_size(),
+                        self.cell_size(),
                     ));
+                    let timings = self
+                        .single_cache_key(terminal_size)
+                        .and_then(|key| self.render_cache.get(&key))
+                        .map(|rendered| rendered.timings);
+                    if let Some(timings) = timings {
+                        status.push_str(&format_timings(timings, self.last_transmit));
+                    }
+                    status.push_str(&self.cache_usage_text());
                 }
 
                 status
             }
             ViewMode::Tile => {
-                let grid = Self::calculate_tile_grid(terminal_size, self.config.cell_aspect_ratio);
+                let grid = self.tile_grid(terminal_size);
                 let (cols, rows) = grid;
                 let tiles_per_page = cols * rows;
                 let page_start = (self.tile_cursor / tiles_per_page) * tiles_per_page;
@@ -1132,15 +3925,46 @@ impl App {
                     .and_then(|p| p.file_name())
                     .and_then(|n| n.to_str())
                     .unwrap_or("unknown");
-                format!(
-                    "[{}-{}/{}] {} {} {}",
+                let mut status = format!(
+                    "[{}-{}/{}]{} {} {} {}",
                     page_start + 1,
                     page_end,
                     self.images.len(),
+                    self.list_note_text(),
                     SEP,
                     ICON_IMAGE,
                     selected_name
-                )
+                );
+
+                if let Some((page, page_count)) = self.tile_page_position(grid) {
+                    status.push_str(&format!(
+                        " {SEP} page {}/{} {}",
+                        page + 1,
+                        page_count,
+                        pagination(page, page_count)
+                    ));
+                }
+
+                // Breadcrumb: directory of the selected tile (+ other directories on the page).
+                if self.dir_starts.len() > 1
+                    && let Some(selected) = self.images.get(self.tile_cursor)
+                {
+                    let page = &self.images[page_start..page_end];
+                    let other_dirs = page
+                        .iter()
+                        .filter_map(|p| p.parent())
+                        .collect::<HashSet<_>>()
+                        .len()
+                        .saturating_sub(1);
+                    status.push_str(&format!(" {SEP} {ICON_FOLDER} {}", short_dir(selected)));
+                    if other_dirs > 0 {
+                        status.push_str(&format!(" (+{other_dirs})"));
+                    }
+                }
+                if self.config.debug {
+                    status.push_str(&self.cache_usage_text());
+                }
+                status
             }
         }
     }
@@ -1164,21 +3988,78 @@ mod tests {
             view_mode: ViewMode::default(),
             tile_cursor: 0,
             prev_tile_cursor: None,
+            tile_step: 0,
+            tile_layout: TileLayout::Grid,
+            tile_aspects: HashMap::new(),
+            aspect_scan: None,
+            capture_days: HashMap::new(),
+            day_scan: None,
+            crop: None,
+            zoom_lock: false,
+            views: HashMap::new(),
+            selection: None,
+            drawn_selection: None,
+            jump: None,
+            drawn_jump: Vec::new(),
+            dir_starts: vec![0],
+            list_note: None,
+            listing: None,
+            listing_start: None,
+            added: None,
+            rotations: RotationStore::new(&[], false),
+            window: WindowLevel::default(),
+            adjust: Adjustments::default(),
+            diff: None,
+            blend: None,
+            blend_opacity: crate::blend::DEFAULT_OPACITY,
+            wipe: None,
+            wipe_position: crate::wipe::DEFAULT_POSITION,
+            spread: false,
+            panes: None,
+            slideshow_options: SlideshowOptions::from_config(&config),
+            slideshow: None,
+            nav_direction: 0,
+            placeholders: HashMap::new(),
+            drawn_placeholder: None,
+            animation: None,
+            animation_requested: None,
+            server_animation: false,
+            pages: None,
+            trashed: Vec::new(),
+            bookmarks: Bookmarks::default(),
+            command_line: None,
+            browser: None,
+            calibration: None,
+            help: None,
+            power: Power::new("off"),
+            menu_len: 0,
             kgp_state: KgpState::default(),
-            worker: ImageWorker::new(config.tile_threads),
+            worker: ImageWorker::new(config.tile_threads, None),
             prefetch_worker: PrefetchWorker::new(config.prefetch_threads),
             config,
-            writer: TerminalWriter::new(),
+            writer: TerminalWriter::new(WriterOptions::default()),
             pending_request: None,
             render_cache: HashMap::new(),
             render_cache_order: VecDeque::new(),
             render_cache_limit: 5,
+            disk_cache: None,
             kgp_id: App::generate_kgp_id(),
+            protocol: Protocol::Kitty,
+            capability_warning: None,
             in_flight_transmit: false,
+            partial_tile: None,
+            partial_in_flight: false,
+            transmit_epoch: 0,
+            pending_copy: None,
+            pending_image_copy: None,
+            pending_save: None,
+            last_transmit: None,
             pending_display: None,
             render_epoch: 0,
             clear_after_nav: false,
             is_tmux: false,
+            file_transfer: false,
+            is_wsl: false,
             last_prefetch_signature: None,
         }
     }
@@ -1225,6 +4106,140 @@ mod tests {
         assert!(status.contains("test0.png"));
     }
 
+    #[test]
+    fn test_status_dir_position() {
+        let mut app = create_test_app(1);
+        let terminal = Rect::new(0, 0, 80, 24);
+        assert!(!app.status_text(terminal).contains("dir"));
+
+        app.open_images(vec![
+            PathBuf::from("/data/shots/1.png"),
+            PathBuf::from("/data/renders/1.png"),
+            PathBuf::from("/data/renders/2.png"),
+            PathBuf::from("/data/plots/1.png"),
+        ]);
+        assert_eq!(app.dir_starts, vec![0, 1, 3]);
+        app.move_by(2);
+        assert!(app.status_text(terminal).contains("3/4 (dir 2/3: renders)"));
+        app.move_by(1);
+        assert!(app.status_text(terminal).contains("4/4 (dir 3/3: plots)"));
+    }
+
+    #[test]
+    fn test_status_list_note() {
+        let mut app = create_test_app(3);
+        let terminal = Rect::new(0, 0, 80, 24);
+        app.set_list_note(Some("of 500, sampled".to_string()));
+        assert!(app.status_text(terminal).contains("1/3 (of 500, sampled)"));
+        app.toggle_view_mode();
+        assert!(app.status_text(terminal).contains("/3] (of 500, sampled)"));
+    }
+
+    #[test]
+    fn test_poll_listing_selects_sorted_first_image() {
+        let dir = PathBuf::from("/tmp/stiv_test_poll_listing");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["d.png", "b.png", "a.png", "c.png"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        let terminal = Rect::new(0, 0, 80, 24);
+        let mut app = create_test_app(1);
+        let mut listing = Listing::spawn(vec![dir.clone()]);
+        app.open_images(listing.wait_first().unwrap());
+        app.set_listing(Some(listing));
+
+        while app.listing.is_some() {
+            assert!(app.poll_listing(terminal).is_empty());
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let names: Vec<_> = app.images.iter().map(|p| file_name(p)).collect();
+        assert_eq!(names, ["a.png", "b.png", "c.png", "d.png"]);
+        // The startup image was whichever the directory read returned first; with no
+        // navigation, the view moves to the first of the sorted list.
+        assert_eq!(app.current_index, 0);
+        assert_eq!(app.tile_cursor, 0);
+        assert_eq!(app.dir_starts, vec![0]);
+
+        // Once the user has moved to another image, it stays current as the list grows.
+        let mut listing = Listing::spawn(vec![dir.clone()]);
+        let mut images = listing.wait_first().unwrap();
+        let picked = dir.join("z.png");
+        images.push(picked.clone());
+        app.open_images(images);
+        app.set_listing(Some(listing));
+        app.current_index = 1;
+        app.tile_cursor = 1;
+        let first_found = app.images[0].clone();
+        app.panes = Some(Panes {
+            other: 0,
+            right_focused: true,
+        });
+        while app.listing.is_some() {
+            assert!(app.poll_listing(terminal).is_empty());
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert_eq!(app.images.len(), 5);
+        assert_eq!(app.images[app.current_index], picked);
+        assert_eq!(app.images[app.tile_cursor], picked);
+        // So does the image in the other pane.
+        assert_eq!(app.images[app.panes.unwrap().other], first_found);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_add_listing_appends_in_background() {
+        let dir = PathBuf::from("/tmp/stiv_test_add_listing");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["b.png", "a.png"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        let terminal = Rect::new(0, 0, 80, 24);
+        let mut app = create_test_app(3);
+        app.open_images(vec![dir.join("z.png"), dir.join("b.png")]);
+        app.current_index = 1;
+        app.add_listing(Some(Listing::spawn_after(vec![dir.clone()], &app.images)));
+
+        while app.is_listing() {
+            assert_eq!(app.take_added(), None);
+            assert!(app.poll_listing(terminal).is_empty());
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let names: Vec<_> = app.images.iter().map(|p| file_name(p)).collect();
+        assert_eq!(names, ["z.png", "b.png", "a.png"]);
+        // The view stays on its image, and the count is reported once.
+        assert_eq!(app.current_index, 1);
+        assert_eq!(app.take_added(), Some(1));
+        assert_eq!(app.take_added(), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_other_pane_follows_list_changes() {
+        let mut app = create_test_app(4);
+        app.toggle_panes();
+        app.move_by(3);
+        let other = |app: &App| app.images[app.panes.unwrap().other].clone();
+        app.panes.as_mut().unwrap().other = 1;
+        let shown = other(&app);
+
+        app.remove_image(0);
+        assert_eq!(other(&app), shown);
+        app.insert_image(0, PathBuf::from("test0.png"));
+        assert_eq!(other(&app), shown);
+        app.sort_images(crate::sort::SortKey::Name, true);
+        assert_eq!(other(&app), shown);
+        assert_eq!(app.images[app.current_index], PathBuf::from("test0.png"));
+
+        // Removing the image it shows moves it to the next one, like the current pane.
+        let index = app.panes.unwrap().other;
+        app.remove_image(index);
+        assert_eq!(app.panes.unwrap().other, index);
+    }
+
     #[test]
     fn test_go_to_index_with_tile() {
         let mut app = create_test_app(3);
@@ -1255,9 +4270,285 @@ mod tests {
         app.toggle_fit_mode();
         assert_eq!(app.fit_mode, FitMode::Fit);
         app.toggle_fit_mode();
+        assert_eq!(app.fit_mode, FitMode::Actual);
+        app.toggle_fit_mode();
         assert_eq!(app.fit_mode, FitMode::Normal);
     }
 
+    #[test]
+    fn test_navigation_resets_zoom() {
+        let mut app = create_test_app(3);
+        app.crop = Some(CropRect::full((10, 10)));
+        app.move_by(1);
+        assert!(app.crop.is_none());
+        app.crop = Some(CropRect::full((10, 10)));
+        app.go_to_index_with_tile(0);
+        assert!(app.crop.is_none());
+    }
+
+    #[test]
+    fn test_zoom_restored_per_image() {
+        let mut app = create_test_app(3);
+        let crop = CropRect::full((10, 10));
+        app.set_crop(Some(crop));
+        app.move_by(1);
+        assert!(app.crop.is_none());
+        app.move_by(-1);
+        assert_eq!(app.crop, Some(crop));
+
+        assert!(app.reset_zoom());
+        app.move_by(1);
+        app.move_by(-1);
+        assert!(app.crop.is_none());
+    }
+
+    #[test]
+    fn test_zoom_lock_keeps_crop_across_images() {
+        let mut app = create_test_app(3);
+        let crop = CropRect::full((10, 10));
+        app.crop = Some(crop);
+        assert!(app.toggle_zoom_lock());
+        app.move_by(1);
+        app.go_to_index_with_tile(2);
+        assert_eq!(app.crop, Some(crop));
+        assert!(
+            app.status_text(Rect::new(0, 0, 200, 40))
+                .contains("zoom locked")
+        );
+
+        assert!(!app.toggle_zoom_lock());
+        app.move_by(1);
+        assert!(app.crop.is_none());
+    }
+
+    #[test]
+    fn test_reset_zoom() {
+        let mut app = create_test_app(1);
+        assert!(!app.reset_zoom());
+        app.crop = Some(CropRect::full((10, 10)));
+        assert!(app.reset_zoom());
+        assert!(app.crop.is_none());
+    }
+
+    #[test]
+    fn test_start_selection_requires_displayed_image() {
+        let mut app = create_test_app(1);
+        assert!(!app.start_selection());
+        app.kgp_state.set_last(Rect::new(0, 0, 10, 5), app.kgp_id);
+        assert!(app.start_selection());
+        assert!(app.is_selecting());
+        app.cancel_selection();
+        assert!(!app.is_selecting());
+    }
+
+    #[test]
+    fn test_jump_labels_move_tile_cursor() {
+        let size = Rect::new(0, 0, 80, 24);
+        let mut app = create_test_app(5);
+        assert!(!app.start_jump(size));
+        app.toggle_view_mode();
+        assert!(!app.start_jump(size));
+        app.kgp_state.set_last(Rect::new(0, 0, 80, 23), app.kgp_id);
+
+        assert!(app.start_jump(size));
+        assert_eq!(app.drawn_jump, vec![0, 1, 2, 3, 4]);
+        app.jump_key('d', size);
+        assert!(!app.is_jumping());
+        assert!(app.drawn_jump.is_empty());
+        assert_eq!(app.tile_cursor, 2);
+
+        // A key that matches no label just closes the labels.
+        assert!(app.start_jump(size));
+        app.jump_key('z', size);
+        assert!(!app.is_jumping());
+        assert_eq!(app.tile_cursor, 2);
+    }
+
+    #[test]
+    fn test_rotate_current_image() {
+        let mut app = create_test_app(2);
+        app.crop = Some(CropRect::full((10, 10)));
+        app.rotate(1).unwrap();
+        assert_eq!(app.current_rotation(), Rotation::Cw90);
+        assert!(app.crop.is_none());
+        app.rotate(-2).unwrap();
+        assert_eq!(app.current_rotation(), Rotation::Cw270);
+        app.move_by(1);
+        assert_eq!(app.current_rotation(), Rotation::None);
+    }
+
+    #[test]
+    fn test_rotate_in_tile_mode_drops_tile_pages() {
+        let mut app = create_test_app(2);
+        app.view_mode = ViewMode::Tile;
+        app.tile_cursor = 1;
+        let key = CacheKey {
+            path: PathBuf::from("__tile_page_0"),
+            target: (1, 1),
+            fit_mode: FitMode::Normal,
+            crop: None,
+            rotation: Rotation::None,
+            window: WindowLevel::default(),
+            adjust: Adjustments::default(),
+        };
+        app.insert_to_cache(key.clone(), (1, 1), (1, 1), Arc::new(Vec::new()), None);
+        app.rotate(1).unwrap();
+        assert!(!app.render_cache.contains_key(&key));
+        assert_eq!(app.rotations.get(&app.images[1]), Rotation::Cw90);
+        assert_eq!(app.tile_page_paths(0, 2)[1].1, Rotation::Cw90);
+    }
+
+    #[test]
+    fn test_command_line_status_and_open() {
+        let mut app = create_test_app(3);
+        let terminal = Rect::new(0, 0, 80, 24);
+        app.start_command();
+        app.edit_command(|c| c.push('o'), terminal);
+        assert!(app.status_text(terminal).starts_with(":o"));
+        assert!(app.finish_command(terminal).is_some());
+        assert!(!app.is_command_active());
+
+        app.current_index = 2;
+        app.open_images(vec![PathBuf::from("new.png")]);
+        assert_eq!(app.images.len(), 1);
+        assert_eq!(app.current_index, 0);
+        assert!(app.status_text(terminal).contains("new.png"));
+    }
+
+    #[test]
+    fn test_trash_guards() {
+        let mut app = create_test_app(1);
+        assert!(app.undo_trash().unwrap().is_none());
+        // The only image is kept, and missing files are reported.
+        assert!(app.trash_current().is_err());
+        app.images.push(PathBuf::from("/tmp/stiv_test_missing.png"));
+        app.current_index = 1;
+        assert!(app.trash_current().is_err());
+        assert_eq!(app.images.len(), 2);
+    }
+
+    #[test]
+    fn test_browser_status_and_close() {
+        let dir = PathBuf::from("/tmp/stiv_test_app_browser");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("a.png"), b"").unwrap();
+
+        let mut app = create_test_app(1);
+        app.images = vec![dir.join("a.png")];
+        let terminal = Rect::new(0, 0, 80, 24);
+        app.start_browser(terminal).unwrap();
+        assert!(app.is_browsing());
+        assert_eq!(app.menu_len, 23);
+        let status = app.status_text(terminal);
+        assert!(status.starts_with("1/2"));
+        assert!(status.contains("stiv_test_app_browser"));
+
+        app.edit_browser(
+            |b| {
+                b.select_by(1);
+                b.descend()
+            },
+            terminal,
+        )
+        .unwrap();
+        assert!(app.status_text(terminal).ends_with("sub"));
+
+        assert!(app.finish_browser(terminal).is_some());
+        assert!(!app.is_browsing());
+        assert_eq!(app.menu_len, 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_format_timings() {
+        use std::time::Duration;
+        let timings = StageTimings {
+            decode: Duration::from_millis(12),
+            resize: Duration::from_micros(3_400),
+            encode: Duration::from_millis(8),
+        };
+        assert_eq!(
+            format_timings(Some(timings), Some(Duration::from_millis(20))),
+            " dec 12ms rsz 3ms enc 8ms tx 20ms"
+        );
+        assert_eq!(format_timings(None, None), " dec - rsz - enc - tx -");
+    }
+
+    #[test]
+    fn test_pagination() {
+        assert_eq!(pagination(0, 1), "●");
+        assert_eq!(pagination(2, 4), "○○●○");
+        assert_eq!(pagination(0, 100), "▰▱▱▱▱▱▱▱▱▱");
+        assert_eq!(pagination(49, 100), "▰▰▰▰▰▱▱▱▱▱");
+        assert_eq!(pagination(99, 100), "▰▰▰▰▰▰▰▰▰▰");
+    }
+
+    #[test]
+    fn test_calculate_page_grid() {
+        let area = Rect::new(0, 0, 120, 40);
+        let grid = (6, 4);
+        // Full and empty pages keep the grid.
+        assert_eq!(App::calculate_page_grid(grid, 24, area, 2.0), grid);
+        assert_eq!(App::calculate_page_grid(grid, 0, area, 2.0), grid);
+        // A few images get fewer, larger tiles within the grid.
+        assert_eq!(App::calculate_page_grid(grid, 1, area, 2.0), (1, 1));
+        assert_eq!(App::calculate_page_grid(grid, 3, area, 2.0), (3, 1));
+        let (cols, rows) = App::calculate_page_grid(grid, 7, area, 2.0);
+        assert!(cols * rows >= 7 && cols <= 6 && rows <= 4);
+        assert!(cols * rows < 24);
+    }
+
+    #[test]
+    fn test_resize_tiles_steps_columns() {
+        let mut app = create_test_app(50);
+        app.view_mode = ViewMode::Tile;
+        let terminal = Rect::new(0, 0, 80, 24);
+        let default = app.tile_grid(terminal);
+        assert_eq!(
+            app.resize_tiles(1, terminal).map(|g| g.0),
+            Some(default.0 - 1)
+        );
+        assert_eq!(app.tile_grid(terminal).0, default.0 - 1);
+        // Stops at one tile, then reports the limit.
+        assert_eq!(app.resize_tiles(100, terminal), Some((1, 1)));
+        assert_eq!(app.resize_tiles(1, terminal), None);
+        // Smallest tiles are 8 cells wide.
+        assert_eq!(app.resize_tiles(-100, terminal).map(|g| g.0), Some(10));
+    }
+
+    #[test]
+    fn test_tile_page_position_matches_paging() {
+        let mut app = create_test_app(25);
+        let grid = (3, 2);
+        assert_eq!(app.tile_page_position(grid), Some((0, 5)));
+        app.move_tile_page(10, grid);
+        assert_eq!(app.tile_page_position(grid), Some((4, 5)));
+        app.view_mode = ViewMode::Tile;
+        let status = app.status_text(Rect::new(0, 0, 40, 20));
+        let grid = App::calculate_tile_grid(Rect::new(0, 0, 40, 19), 2.0);
+        let (page, count) = app.tile_page_position(grid).unwrap();
+        assert!(status.contains(&format!("page {}/{}", page + 1, count)));
+    }
+
+    #[test]
+    fn test_tile_status_breadcrumb() {
+        let mut app = create_test_app(1);
+        let terminal = Rect::new(0, 0, 80, 24);
+        app.open_images(vec![
+            PathBuf::from("/data/cats/1.png"),
+            PathBuf::from("/data/dogs/1.png"),
+        ]);
+        app.view_mode = ViewMode::Tile;
+        app.tile_cursor = 1;
+        let status = app.status_text(terminal);
+        assert!(status.contains("data/dogs (+1)"));
+
+        app.open_images(vec![PathBuf::from("/data/cats/1.png")]);
+        app.view_mode = ViewMode::Tile;
+        assert!(!app.status_text(terminal).contains("cats"));
+    }
+
     #[test]
     fn test_reload_clears_cache() {
         let mut app = create_test_app(2);
@@ -1265,6 +4556,10 @@ mod tests {
             path: PathBuf::from("x.png"),
             target: (1, 1),
             fit_mode: FitMode::Normal,
+            crop: None,
+            rotation: Rotation::None,
+            window: WindowLevel::default(),
+            adjust: Adjustments::default(),
         };
         app.render_cache.insert(
             key.clone(),
@@ -1272,6 +4567,7 @@ mod tests {
                 original_size: (100, 100),
                 actual_size: (1, 1),
                 encoded_chunks: Arc::new(vec![b"x".to_vec()]),
+                timings: None,
             },
         );
         app.render_cache_order.push_back(key);
@@ -1279,6 +4575,10 @@ mod tests {
             path: PathBuf::from("y.png"),
             target: (1, 1),
             fit_mode: FitMode::Normal,
+            crop: None,
+            rotation: Rotation::None,
+            window: WindowLevel::default(),
+            adjust: Adjustments::default(),
         });
         app.in_flight_transmit = true;
 
@@ -1287,4 +4587,338 @@ mod tests {
         assert!(app.pending_request.is_none());
         assert!(!app.in_flight_transmit);
     }
+
+    #[test]
+    fn test_clear_caches_keeps_current_render() {
+        let dir = std::env::temp_dir().join(format!("stiv_clear_caches_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut app = create_test_app(0);
+        for i in 0..3 {
+            let path = dir.join(format!("{i}.png"));
+            image::RgbaImage::new(4, 4).save(&path).unwrap();
+            app.images.push(path);
+        }
+        let terminal = Rect::new(0, 0, 80, 24);
+        let current = app.single_cache_key(terminal).unwrap();
+        app.insert_to_cache(
+            current.clone(),
+            (4, 4),
+            (4, 4),
+            Arc::new(vec![vec![0; 100]]),
+            None,
+        );
+
+        // Prefetched neighbours, the other fit mode and the zoom step arrive after the current
+        // render and are used more recently.
+        app.prefetch_adjacent(terminal);
+        let deadline = Instant::now() + std::time::Duration::from_secs(10);
+        while app.render_cache.len() < 5 && Instant::now() < deadline {
+            app.poll_worker();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert_eq!(app.render_cache.len(), 5);
+        assert_ne!(app.render_cache_order.back(), Some(&current));
+
+        let render = app.cache_usage().0;
+        assert_eq!(app.clear_caches(terminal), render - 100);
+        assert!(app.render_cache.contains_key(&current));
+        assert_eq!(app.render_cache.len(), 1);
+        assert_eq!(app.render_cache_order.len(), 1);
+
+        // In Tile mode the cursor's page is kept.
+        app.view_mode = ViewMode::Tile;
+        let page = app.displayed_cache_key(terminal).unwrap();
+        assert_eq!(page.path, PathBuf::from("__tile_page_0"));
+        app.insert_to_cache(
+            page.clone(),
+            (4, 4),
+            (4, 4),
+            Arc::new(vec![vec![0; 10]]),
+            None,
+        );
+        app.touch_render_cache(&current);
+        app.clear_caches(terminal);
+        assert_eq!(app.render_cache.keys().collect::<Vec<_>>(), [&page]);
+        assert_eq!(format_size(3 * 1024 * 1024 / 2), "1.5M");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_prefetch_renders_other_fit_mode_and_zoom_steps() {
+        let dir = std::env::temp_dir().join(format!("stiv_zoom_prefetch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("wide.png");
+        image::RgbaImage::new(400, 200).save(&path).unwrap();
+        let mut app = create_test_app(0);
+        app.images.push(path);
+        let terminal = Rect::new(0, 0, 80, 24);
+        let current = app.single_cache_key(terminal).unwrap();
+        let chunks = Arc::new(vec![vec![0; 100]]);
+        app.insert_to_cache(current.clone(), (400, 200), (400, 200), chunks, None);
+        let wait_for = |app: &mut App, len: usize| {
+            let deadline = Instant::now() + std::time::Duration::from_secs(10);
+            while app.render_cache.len() < len && Instant::now() < deadline {
+                app.poll_worker();
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+        };
+
+        // Unzoomed: the other fit mode and one zoom step in.
+        let zoom_in = app.zoom_step_crop(1, terminal).unwrap();
+        assert!(zoom_in.is_some());
+        assert_eq!(app.zoom_step_crop(-1, terminal), None);
+        app.prefetch_adjacent(terminal);
+        wait_for(&mut app, 3);
+        let other_fit = CacheKey {
+            fit_mode: app.fit_mode.next(),
+            ..current.clone()
+        };
+        assert!(app.render_cache.contains_key(&other_fit));
+        // Zooming in is then served from the cache.
+        assert!(app.zoom_by(1, terminal));
+        assert_eq!(app.crop, zoom_in);
+        assert!(
+            app.render_cache
+                .contains_key(&app.single_cache_key(terminal).unwrap())
+        );
+
+        // Zoomed: the next steps in and out (back to the whole image, already cached).
+        let deeper = app.zoom_step_crop(1, terminal).unwrap();
+        assert_eq!(app.zoom_step_crop(-1, terminal), Some(None));
+        app.prefetch_adjacent(terminal);
+        wait_for(&mut app, 4);
+        let deeper_key = CacheKey {
+            crop: deeper,
+            ..app.single_cache_key(terminal).unwrap()
+        };
+        assert!(app.render_cache.contains_key(&deeper_key));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_window_adjust_ignored_for_regular_images() {
+        let mut app = create_test_app(3);
+        assert!(!app.adjust_window(1, 1));
+        assert!(app.current_window().is_default());
+        assert!(!app.reset_window());
+    }
+
+    #[test]
+    fn test_diff_renders_virtual_path() {
+        let mut app = create_test_app(3);
+        let size = Rect::new(0, 0, 80, 24);
+        let plain = app.single_cache_key(size).unwrap().path;
+        assert!(app.set_diff(Some(PathBuf::from("golden.png"))));
+        assert!(!app.set_diff(Some(PathBuf::from("golden.png"))));
+        let path = app.single_cache_key(size).unwrap().path;
+        assert_eq!(
+            VirtualPath::parse(&path),
+            Some(VirtualPath::Diff {
+                image: &plain,
+                reference: Path::new("golden.png")
+            })
+        );
+    }
+
+    #[test]
+    fn test_spread_pairs_next_image() {
+        let mut app = create_test_app(5);
+        let size = Rect::new(0, 0, 80, 24);
+        app.toggle_spread();
+        let path = app.single_cache_key(size).unwrap().path;
+        assert_eq!(
+            VirtualPath::parse(&path),
+            Some(VirtualPath::Spread {
+                left: &app.images[0],
+                right: &app.images[1]
+            })
+        );
+        app.move_by(1);
+        assert_eq!(app.current_index, 2);
+        app.move_by(1);
+        // The last image has no partner and is shown alone.
+        assert_eq!(app.single_cache_key(size).unwrap().path, app.images[4]);
+        app.config.spread_rtl = true;
+        assert_eq!(app.reading_direction(), -1);
+        // Without wrap-around, the last spread cannot move on.
+        assert_eq!(app.clamp_steps(3, size), 0);
+        assert_eq!(app.clamp_steps(-3, size), -2);
+    }
+
+    #[test]
+    fn test_split_view_panes_keep_own_cursor() {
+        let mut app = create_test_app(4);
+        let size = Rect::new(0, 0, 80, 24);
+        assert!(!app.switch_pane());
+        app.toggle_panes();
+        app.move_by(2);
+        let path = app.single_cache_key(size).unwrap().path;
+        assert_eq!(
+            VirtualPath::parse(&path),
+            Some(VirtualPath::Panes {
+                left: &app.images[0],
+                right: &app.images[2]
+            })
+        );
+        // Focus moves to the left pane, which navigates on its own.
+        assert!(app.switch_pane());
+        assert_eq!(app.current_index, 0);
+        app.move_by(1);
+        let path = app.single_cache_key(size).unwrap().path;
+        assert_eq!(
+            VirtualPath::parse(&path),
+            Some(VirtualPath::Panes {
+                left: &app.images[1],
+                right: &app.images[2]
+            })
+        );
+    }
+
+    #[test]
+    fn test_slideshow_advances_and_stops() {
+        let mut app = create_test_app(2);
+        app.slideshow_options.stop_at_end = true;
+        let start = Instant::now();
+        assert!(app.toggle_slideshow(start));
+        assert!(!app.tick_slideshow(start));
+        let due = app.slideshow_due().unwrap();
+        assert!(!app.tick_slideshow(due));
+        assert_eq!(app.current_index, 1);
+        assert!(app.tick_slideshow(app.slideshow_due().unwrap()));
+        assert!(app.slideshow_due().is_none());
+    }
+
+    #[test]
+    fn test_blend_opacity_changes_render_path() {
+        let mut app = create_test_app(3);
+        let size = Rect::new(0, 0, 80, 24);
+        assert!(!app.adjust_blend(1));
+        assert!(app.set_blend(Some(PathBuf::from("golden.png"))));
+        let path = app.single_cache_key(size).unwrap().path;
+        let Some(VirtualPath::Blend {
+            reference, opacity, ..
+        }) = VirtualPath::parse(&path)
+        else {
+            panic!("not a blend path: {path:?}");
+        };
+        assert_eq!((reference, opacity), (Path::new("golden.png"), 50));
+        assert!(app.adjust_blend(-2));
+        assert!(app.adjust_blend(-9));
+        assert!(!app.adjust_blend(-1));
+        let path = app.single_cache_key(size).unwrap().path;
+        assert!(matches!(
+            VirtualPath::parse(&path),
+            Some(VirtualPath::Blend { opacity: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_wipe_moves_split() {
+        let mut app = create_test_app(3);
+        let size = Rect::new(0, 0, 80, 24);
+        assert!(app.set_wipe(Some(PathBuf::from("old.png"))));
+        assert!(app.move_wipe(3));
+        let path = app.single_cache_key(size).unwrap().path;
+        assert_eq!(
+            VirtualPath::parse(&path),
+            Some(VirtualPath::Wipe {
+                image: &app.images[0],
+                reference: Path::new("old.png"),
+                position: 65
+            })
+        );
+        assert!(app.move_wipe(20));
+        assert!(!app.move_wipe(1));
+    }
+
+    #[test]
+    fn test_grayscale_changes_cache_key() {
+        let mut app = create_test_app(3);
+        let size = Rect::new(0, 0, 80, 24);
+        let before = app.single_cache_key(size).unwrap();
+        app.toggle_grayscale();
+        let after = app.single_cache_key(size).unwrap();
+        assert!(after.adjust.grayscale);
+        assert_ne!(before, after);
+    }
+
+    #[test]
+    fn test_placement_area_centers_image() {
+        let image_area = Rect::new(0, 0, 20, 10);
+        // 80x40 px with 8x16 cells -> 10x3 cells (rounded up), centered.
+        assert_eq!(
+            placement_area(image_area, (80, 40), (8, 16)),
+            Rect::new(5, 3, 10, 3)
+        );
+        // Never larger than the image area.
+        assert_eq!(
+            placement_area(image_area, (1000, 1000), (8, 16)),
+            image_area
+        );
+    }
+
+    #[test]
+    fn test_prefetch_offsets_follow_direction() {
+        assert_eq!(prefetch_offsets(2, 0), vec![1, -1, 2, -2]);
+        assert_eq!(prefetch_offsets(2, 1), vec![1, -1, 2, 3]);
+        assert_eq!(prefetch_offsets(2, -1), vec![-1, 1, -2, -3]);
+        let offsets = prefetch_offsets(5, 1);
+        assert_eq!(offsets.len(), 10);
+        assert_eq!(offsets.iter().filter(|&&o| o < 0).count(), 2);
+    }
+
+    #[test]
+    fn test_move_by_tracks_direction() {
+        let mut app = create_test_app(5);
+        assert_eq!(app.nav_direction, 0);
+        app.move_by(-2);
+        assert_eq!(app.nav_direction, -1);
+        app.move_by(3);
+        assert_eq!(app.nav_direction, 1);
+    }
+
+    #[test]
+    fn test_page_layout_waits_for_capture_days() {
+        let mut app = create_test_app(4);
+        app.config.group_by_date = true;
+        let area = Rect::new(0, 0, 80, 40);
+
+        // Unknown days: the page waits for the background scan.
+        assert!(app.page_layout(0, (2, 2), area).is_none());
+
+        let days = ["2024-01-01", "2024-01-01", "2024-01-02", "2024-01-02"];
+        for (path, day) in app.images.clone().into_iter().zip(days) {
+            app.capture_days.insert(path, Some(day.to_string()));
+        }
+        let layout = app.page_layout(0, (2, 2), area).unwrap();
+        assert_eq!(layout.rects.len(), 4);
+        let labels: Vec<_> = layout.bands.iter().map(|(_, day)| day.as_deref()).collect();
+        assert_eq!(labels, [Some("2024-01-01"), Some("2024-01-02")]);
+        // The second day starts its own row, below its band.
+        assert!(layout.rects[2].y > layout.bands[1].0.y);
+        assert_eq!(layout.days.unwrap()[2].as_deref(), Some("2024-01-02"));
+    }
+
+    #[test]
+    fn test_page_rects_wait_for_justified_aspects() {
+        let mut app = create_test_app(2);
+        app.tile_layout = TileLayout::Justified;
+        let area = Rect::new(0, 0, 80, 40);
+
+        // Nothing is read on this thread: the page waits for the background scan.
+        assert!(app.page_rects(0, (2, 1), area).is_none());
+        app.scan_page_from(0);
+        assert!(app.aspect_scan.is_some());
+
+        app.aspect_scan = None;
+        app.tile_aspects.insert(app.images[0].clone(), 2.0);
+        app.tile_aspects.insert(app.images[1].clone(), 0.5);
+        let rects = app.page_rects(0, (2, 1), area).unwrap();
+        assert!(rects[0].width > rects[1].width);
+        // Rotation turns the stored aspect.
+        let path = app.images[1].clone();
+        app.rotations.set(&path, Rotation::Cw90).unwrap();
+        let rects = app.page_rects(0, (2, 1), area).unwrap();
+        assert_eq!(rects[0].width, rects[1].width);
+    }
 }
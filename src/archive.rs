@@ -0,0 +1,320 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Tar archive inputs (`.tar`, `.tar.gz`, `.tgz`).
+//!
+//! Archive members are addressed with virtual paths `<archive>/<member>`
+//! (e.g. `data.tar.gz/train/0001.png`), so the rest of stiv treats them like regular files.
+//!
+//! Members are read on demand, never extracted to disk. Each archive is read once to index its
+//! members (`ArchiveIndex`: where each member's data starts in the tar stream), so later reads
+//! do not scan it again:
+//! - plain `.tar`: a member is read by seeking to its data
+//! - compressed: the decompressed tar is kept in memory (the most recent archive, up to
+//!   `MAX_INFLATED`), so members are sliced out of it; larger ones are decompressed from the
+//!   start on each read
+//!
+//! The index also records where virtual paths split, so `split_member_path` does not stat the
+//! archive on every decode.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+
+/// Largest decompressed tar kept in memory.
+const MAX_INFLATED: usize = 512 * 1024 * 1024;
+
+/// Members of an indexed archive: the byte range of each one's data in the (decompressed) tar
+/// stream.
+type ArchiveIndex = HashMap<PathBuf, Range<u64>>;
+
+/// Indexed archives by path.
+static INDEXES: LazyLock<Mutex<HashMap<PathBuf, Arc<ArchiveIndex>>>> =
+    LazyLock::new(Default::default);
+
+/// The decompressed tar of the compressed archive read last (None if it is too large).
+#[allow(clippy::type_complexity)]
+static INFLATED: Mutex<Option<(PathBuf, Option<Arc<Vec<u8>>>)>> = Mutex::new(None);
+
+/// Check if the path has a supported archive extension.
+pub fn is_archive(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    let name = name.to_lowercase();
+    name.ends_with(".tar") || is_gzip_name(&name)
+}
+
+fn is_gzip_name(name: &str) -> bool {
+    name.ends_with(".tar.gz") || name.ends_with(".tgz")
+}
+
+fn is_gzip(archive: &Path) -> bool {
+    archive
+        .file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| is_gzip_name(&n.to_lowercase()))
+}
+
+/// Split a virtual member path into `(archive, member)`.
+/// Returns None for regular paths.
+pub fn split_member_path(path: &Path) -> Option<(&Path, PathBuf)> {
+    let archive = path
+        .ancestors()
+        .skip(1)
+        .find(|a| is_archive(a) && (is_indexed(a) || a.is_file()))?;
+    let member = path.strip_prefix(archive).ok()?;
+    Some((archive, member.to_path_buf()))
+}
+
+fn is_indexed(archive: &Path) -> bool {
+    INDEXES
+        .lock()
+        .is_ok_and(|indexes| indexes.contains_key(archive))
+}
+
+/// List image members as virtual paths, sorted like directory listings. The archive is indexed
+/// again, so a re-opened archive that changed is read correctly.
+pub fn list_images(archive: &Path, is_image: impl Fn(&Path) -> bool) -> Result<Vec<PathBuf>> {
+    let index =
+        build_index(archive).with_context(|| format!("Failed to read archive: {:?}", archive))?;
+    let mut images: Vec<PathBuf> = index
+        .keys()
+        .filter(|member| is_image(member))
+        .map(|member| archive.join(member))
+        .collect();
+    images.sort_by(|a, b| crate::sort::natural_cmp(a, b));
+    Ok(images)
+}
+
+/// Read a member's bytes from an archive.
+pub fn read_member(archive: &Path, member: &Path) -> Option<Vec<u8>> {
+    let index = index(archive)?;
+    let range = index.get(member)?.clone();
+    if !is_gzip(archive) {
+        let mut file = File::open(archive).ok()?;
+        file.seek(SeekFrom::Start(range.start)).ok()?;
+        let mut data = Vec::new();
+        file.take(range.end - range.start)
+            .read_to_end(&mut data)
+            .ok()?;
+        return Some(data);
+    }
+    match inflated(archive) {
+        Some(tar) => {
+            let range = usize::try_from(range.start).ok()?..usize::try_from(range.end).ok()?;
+            tar.get(range).map(<[u8]>::to_vec)
+        }
+        None => stream_member(archive, member),
+    }
+}
+
+/// The archive's index, built on first use (e.g. a member path restored from a session).
+fn index(archive: &Path) -> Option<Arc<ArchiveIndex>> {
+    if let Some(index) = INDEXES.lock().ok()?.get(archive) {
+        return Some(Arc::clone(index));
+    }
+    build_index(archive).ok()
+}
+
+/// Read the archive once to index its members (keeping a decompressed tar that fits in
+/// memory).
+fn build_index(archive: &Path) -> Result<Arc<ArchiveIndex>> {
+    let file = BufReader::new(File::open(archive)?);
+    let mut index = ArchiveIndex::new();
+    if is_gzip(archive) {
+        let mut tar = tar::Archive::new(Inflate {
+            inner: GzDecoder::new(file),
+            copy: Some(Vec::new()),
+        });
+        index_entries(tar.entries()?, &mut index)?;
+        let copy = tar.into_inner().copy.map(Arc::new);
+        if let Ok(mut slot) = INFLATED.lock() {
+            *slot = Some((archive.to_path_buf(), copy));
+        }
+    } else {
+        let mut tar = tar::Archive::new(file);
+        index_entries(tar.entries_with_seek()?, &mut index)?;
+    }
+    let index = Arc::new(index);
+    if let Ok(mut indexes) = INDEXES.lock() {
+        indexes.insert(archive.to_path_buf(), Arc::clone(&index));
+    }
+    Ok(index)
+}
+
+fn index_entries<R: Read>(entries: tar::Entries<'_, R>, index: &mut ArchiveIndex) -> Result<()> {
+    for entry in entries {
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let start = entry.raw_file_position();
+        index.insert(normalize(&entry.path()?), start..start + entry.size());
+    }
+    Ok(())
+}
+
+/// The decompressed tar of a compressed archive, decompressing it again if another archive
+/// was read since. None if it does not fit in memory.
+fn inflated(archive: &Path) -> Option<Arc<Vec<u8>>> {
+    let mut slot = INFLATED.lock().ok()?;
+    if let Some((path, tar)) = slot.as_ref()
+        && path == archive
+    {
+        return tar.clone();
+    }
+    // Held while decompressing, so parallel reads wait for one pass instead of each making one.
+    let mut reader = Inflate {
+        inner: GzDecoder::new(BufReader::new(File::open(archive).ok()?)),
+        copy: Some(Vec::new()),
+    };
+    std::io::copy(&mut reader, &mut std::io::sink()).ok()?;
+    let tar = reader.copy.map(Arc::new);
+    *slot = Some((archive.to_path_buf(), tar.clone()));
+    tar
+}
+
+/// Decompressing reader that keeps a copy of what it read, until that exceeds `MAX_INFLATED`.
+struct Inflate<R> {
+    inner: R,
+    copy: Option<Vec<u8>>,
+}
+
+impl<R: Read> Read for Inflate<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if let Some(copy) = &mut self.copy {
+            if copy.len() + n > MAX_INFLATED {
+                self.copy = None;
+            } else {
+                copy.extend_from_slice(&buf[..n]);
+            }
+        }
+        Ok(n)
+    }
+}
+
+/// Read a member by decompressing the archive from the start (too large to keep in memory).
+fn stream_member(archive: &Path, member: &Path) -> Option<Vec<u8>> {
+    let mut data = None;
+    for_each_member(archive, |path, entry| {
+        if path != member {
+            return false;
+        }
+        let mut buf = Vec::new();
+        data = entry.read_to_end(&mut buf).ok().map(|_| buf);
+        true
+    })
+    .ok()?;
+    data
+}
+
+/// Visit the regular-file members of a compressed archive in order until `visit` returns true.
+fn for_each_member(
+    archive: &Path,
+    mut visit: impl FnMut(&Path, &mut dyn Read) -> bool,
+) -> Result<()> {
+    let file = BufReader::new(File::open(archive)?);
+    let mut tar = tar::Archive::new(GzDecoder::new(file));
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let member = normalize(&entry.path()?);
+        if visit(&member, &mut entry) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Drop `./` components so `./a.png` and `a.png` address the same member.
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn png_bytes() -> Vec<u8> {
+        let mut buf = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::new_rgba8(3, 2)
+            .write_to(&mut buf, image::ImageFormat::Png)
+            .unwrap();
+        buf.into_inner()
+    }
+
+    fn build_tar(writer: impl Write) {
+        let png = png_bytes();
+        let mut builder = tar::Builder::new(writer);
+        for name in ["./b/2.png", "a.png", "notes.txt"] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(png.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, &png[..]).unwrap();
+        }
+        builder.into_inner().unwrap().flush().unwrap();
+    }
+
+    fn is_png(path: &Path) -> bool {
+        path.extension().is_some_and(|e| e == "png")
+    }
+
+    #[test]
+    fn test_is_archive() {
+        assert!(is_archive(Path::new("x.tar")));
+        assert!(is_archive(Path::new("x.TAR.GZ")));
+        assert!(is_archive(Path::new("x.tgz")));
+        assert!(!is_archive(Path::new("x.gz")));
+        assert!(!is_archive(Path::new("x.png")));
+    }
+
+    #[test]
+    fn test_list_and_read_members() {
+        let dir = PathBuf::from("/tmp/stiv_test_archive");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let plain = dir.join("data.tar");
+        build_tar(File::create(&plain).unwrap());
+        let gz = dir.join("data.tar.gz");
+        build_tar(flate2::write::GzEncoder::new(
+            File::create(&gz).unwrap(),
+            flate2::Compression::fast(),
+        ));
+
+        for archive in [&plain, &gz] {
+            let images = list_images(archive, is_png).unwrap();
+            assert_eq!(images, vec![archive.join("a.png"), archive.join("b/2.png")]);
+
+            let (found, member) = split_member_path(&images[1]).unwrap();
+            assert_eq!(found, archive.as_path());
+            assert_eq!(read_member(found, &member), Some(png_bytes()));
+            assert_eq!(read_member(found, Path::new("missing.png")), None);
+        }
+        assert!(split_member_path(&dir.join("a.png")).is_none());
+
+        // The compressed archive was decompressed once while listing: its members are read and
+        // split from the index and the kept tar, not the file.
+        let member = gz.join("b/2.png");
+        std::fs::remove_file(&gz).unwrap();
+        let (found, name) = split_member_path(&member).unwrap();
+        assert_eq!(found, gz.as_path());
+        assert_eq!(read_member(found, &name), Some(png_bytes()));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
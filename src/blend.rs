@@ -0,0 +1,52 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Overlay blend against a pinned reference image (`:blend`, `(` / `)` for the opacity).
+//!
+//! Like diffs, a blend renders through a virtual path, `<image>\0blend<percent>\0<reference>`
+//! (`VirtualPath::Blend`), so each opacity is cached like its own image. The worker scales the reference to the image
+//! size and mixes it over the image at that opacity, to check that two renders or plots line
+//! up.
+
+use image::DynamicImage;
+
+use crate::virtual_path::scaled_to;
+
+/// Opacity of a newly pinned reference, in percent.
+pub const DEFAULT_OPACITY: u8 = 50;
+
+/// Opacity change per `(` / `)` press, in percent.
+pub const OPACITY_STEP: i32 = 10;
+
+/// `reference`, scaled to the size of `image`, mixed over it at `opacity` percent.
+pub fn blend(image: &DynamicImage, reference: &DynamicImage, opacity: u8) -> DynamicImage {
+    let mut out = image.to_rgba8();
+    let (width, height) = out.dimensions();
+    let top = scaled_to(reference, width, height);
+    let alpha = u32::from(opacity.min(100));
+    for (pixel, over) in out.pixels_mut().zip(top.pixels()) {
+        // The reference's own alpha scales its weight, so transparent areas show the image.
+        let weight = alpha * u32::from(over.0[3]) / 255;
+        for (c, &o) in pixel.0.iter_mut().zip(&over.0).take(3) {
+            *c = ((u32::from(*c) * (100 - weight) + u32::from(o) * weight + 50) / 100) as u8;
+        }
+    }
+    DynamicImage::ImageRgba8(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::RgbaImage;
+
+    #[test]
+    fn test_blend_scales_reference() {
+        let image =
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(4, 2, image::Rgba([0, 0, 200, 255])));
+        let reference =
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 1, image::Rgba([200, 0, 0, 255])));
+        let out = blend(&image, &reference, 25).to_rgba8();
+        assert_eq!(out.dimensions(), (4, 2));
+        assert_eq!(out.get_pixel(3, 1).0, [50, 0, 150, 255]);
+    }
+}
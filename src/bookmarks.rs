@@ -0,0 +1,107 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Directory bookmarks.
+//!
+//! `B` bookmarks the directory of the current image; `:bookmarks` lists them in the command-line
+//! menu (fuzzy-filtered by the typed argument) and opens the selected one. Bookmarks are stored
+//! as canonical paths in `~/.local/share/stiv/bookmarks.toml`, in the order they were added.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// On-disk format.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+struct BookmarkFile {
+    dirs: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Bookmarks {
+    dirs: Vec<PathBuf>,
+    /// Storage file (None = in-memory only).
+    file: Option<PathBuf>,
+}
+
+impl Bookmarks {
+    /// Load bookmarks from the default location.
+    pub fn load() -> Self {
+        Self::with_file(dirs::data_dir().map(|p| p.join("stiv").join("bookmarks.toml")))
+    }
+
+    fn with_file(file: Option<PathBuf>) -> Self {
+        let stored: BookmarkFile = file
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|s| toml::from_str(&s).ok())
+            .unwrap_or_default();
+        Self {
+            dirs: stored.dirs.into_iter().map(PathBuf::from).collect(),
+            file,
+        }
+    }
+
+    pub fn dirs(&self) -> &[PathBuf] {
+        &self.dirs
+    }
+
+    /// Bookmark `dir`. Returns false if it was already bookmarked.
+    pub fn add(&mut self, dir: &Path) -> Result<bool> {
+        let dir = std::fs::canonicalize(dir)
+            .with_context(|| format!("Cannot resolve path: {:?}", dir))?;
+        if self.dirs.contains(&dir) {
+            return Ok(false);
+        }
+        self.dirs.push(dir);
+        if let Some(file) = &self.file {
+            self.save(file)?;
+        }
+        Ok(true)
+    }
+
+    fn save(&self, file: &Path) -> Result<()> {
+        if let Some(dir) = file.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let stored = BookmarkFile {
+            dirs: self
+                .dirs
+                .iter()
+                .filter_map(|d| d.to_str().map(str::to_string))
+                .collect(),
+        };
+        let content = toml::to_string(&stored)?;
+        // Write-then-rename so a crash never leaves a truncated file.
+        let tmp = file.with_extension("toml.tmp");
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, file)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bookmarks_persist_without_duplicates() {
+        let dir = PathBuf::from("/tmp/stiv_test_bookmarks");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("photos")).unwrap();
+        let file = dir.join("state").join("bookmarks.toml");
+
+        let mut bookmarks = Bookmarks::with_file(Some(file.clone()));
+        assert!(bookmarks.add(&dir.join("photos")).unwrap());
+        assert!(!bookmarks.add(&dir.join("photos/../photos")).unwrap());
+        assert!(bookmarks.add(&dir).unwrap());
+        assert!(bookmarks.add(&dir.join("missing")).is_err());
+
+        let reloaded = Bookmarks::with_file(Some(file));
+        assert_eq!(reloaded.dirs(), &[dir.join("photos"), dir.clone()]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Directory browser (`-`).
+//!
+//! Lists the subdirectories of a directory with the number of images directly inside each,
+//! so a tree of image folders can be walked without relaunching:
+//! - `l` descends into the selected directory, `h` ascends (keeping the directory just left
+//!   selected)
+//! - `Enter` opens the images of the selected directory; the first entry (`./`) is the
+//!   directory itself
+//!
+//! Hidden directories are skipped. Counts are not recursive.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+/// A directory in the listing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    /// Name as shown (`./` for the listed directory itself, `name/` otherwise).
+    pub name: String,
+    pub path: PathBuf,
+    /// Images directly inside the directory.
+    pub images: usize,
+}
+
+#[derive(Debug)]
+pub struct Browser {
+    dir: PathBuf,
+    entries: Vec<Entry>,
+    selected: usize,
+}
+
+impl Browser {
+    /// List `dir` (made absolute, so `h` can always ascend).
+    pub fn open(dir: &Path) -> Result<Self> {
+        let dir = std::fs::canonicalize(dir)
+            .with_context(|| format!("Failed to open directory: {:?}", dir))?;
+        let entries = list_entries(&dir)?;
+        Ok(Self {
+            dir,
+            entries,
+            selected: 0,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_entry(&self) -> Option<&Entry> {
+        self.entries.get(self.selected)
+    }
+
+    /// Move the selection by `delta` (clamped, no wrap-around).
+    pub fn select_by(&mut self, delta: i32) {
+        let last = self.entries.len().saturating_sub(1) as i64;
+        self.selected = (self.selected as i64 + i64::from(delta)).clamp(0, last) as usize;
+    }
+
+    /// Enter the selected subdirectory (no-op on `./`).
+    pub fn descend(&mut self) -> Result<()> {
+        let Some(entry) = self.selected_entry() else {
+            return Ok(());
+        };
+        if entry.path == self.dir {
+            return Ok(());
+        }
+        let path = entry.path.clone();
+        *self = Self::open(&path)?;
+        Ok(())
+    }
+
+    /// Go to the parent directory, selecting the directory just left.
+    pub fn ascend(&mut self) -> Result<()> {
+        let Some(parent) = self.dir.parent() else {
+            return Ok(());
+        };
+        let parent = parent.to_path_buf();
+        let child = self.dir.clone();
+        *self = Self::open(&parent)?;
+        if let Some(i) = self.entries.iter().position(|e| e.path == child) {
+            self.selected = i;
+        }
+        Ok(())
+    }
+}
+
+/// `./` followed by the visible subdirectories of `dir`, sorted by name (naturally).
+fn list_entries(dir: &Path) -> Result<Vec<Entry>> {
+    let mut subdirs: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {:?}", dir))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    subdirs.sort_by(|a, b| crate::sort::natural_cmp(a, b));
+
+    let mut entries = Vec::with_capacity(subdirs.len() + 1);
+    entries.push(Entry {
+        name: "./".to_string(),
+        path: dir.to_path_buf(),
+        images: count_images(dir),
+    });
+    entries.extend(subdirs.into_iter().map(|path| Entry {
+        name: format!(
+            "{}/",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        ),
+        images: count_images(&path),
+        path,
+    }));
+    Ok(entries)
+}
+
+/// Number of image files directly inside `dir` (0 if unreadable).
+fn count_images(dir: &Path) -> usize {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    read_dir
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| !t.is_dir()))
+        .filter(|entry| crate::has_image_extension(&entry.path()))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn setup(name: &str) -> PathBuf {
+        let root = PathBuf::from(format!("/tmp/stiv_test_browser_{name}"));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("b/nested")).unwrap();
+        fs::create_dir_all(root.join("a")).unwrap();
+        fs::create_dir_all(root.join(".hidden")).unwrap();
+        fs::write(root.join("top.png"), b"").unwrap();
+        fs::write(root.join("a/1.png"), b"").unwrap();
+        fs::write(root.join("a/2.jpg"), b"").unwrap();
+        fs::write(root.join("a/notes.txt"), b"").unwrap();
+        fs::write(root.join("b/nested/3.png"), b"").unwrap();
+        root
+    }
+
+    #[test]
+    fn test_lists_subdirectories_with_counts() {
+        let root = setup("list");
+        let browser = Browser::open(&root).unwrap();
+        let summary: Vec<_> = browser
+            .entries()
+            .iter()
+            .map(|e| (e.name.as_str(), e.images))
+            .collect();
+        // Hidden directories are skipped; counts are not recursive.
+        assert_eq!(summary, vec![("./", 1), ("a/", 2), ("b/", 0)]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_descend_and_ascend() {
+        let root = setup("walk");
+        let mut browser = Browser::open(&root).unwrap();
+        browser.select_by(2);
+        browser.descend().unwrap();
+        assert!(browser.dir().ends_with("b"));
+        assert_eq!(browser.entries()[1].name, "nested/");
+
+        browser.ascend().unwrap();
+        assert_eq!(browser.dir(), fs::canonicalize(&root).unwrap());
+        assert_eq!(browser.selected_entry().unwrap().name, "b/");
+
+        // `./` does not descend; the selection is clamped.
+        browser.select_by(-10);
+        browser.descend().unwrap();
+        assert_eq!(browser.dir(), fs::canonicalize(&root).unwrap());
+        browser.select_by(10);
+        assert_eq!(browser.selected(), 2);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
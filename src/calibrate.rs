@@ -0,0 +1,123 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Cell-aspect calibration screen (`:calibrate`).
+//!
+//! Draws a box with a centre cross whose height in rows is its width in columns divided by
+//! `cell_aspect_ratio`, so it is square exactly when the ratio matches the terminal font.
+//! `h`/`l` nudge the ratio by 0.05 (`H`/`L` by 0.01) until it looks square; `Enter` applies it
+//! and writes it to the config file, `Esc` leaves it unchanged.
+
+/// Coarse and fine adjustment steps.
+pub const STEP: f64 = 0.05;
+pub const FINE_STEP: f64 = 0.01;
+
+/// Same range as `Config::clamp_values`.
+const MIN_RATIO: f64 = 1.0;
+const MAX_RATIO: f64 = 4.0;
+
+/// Rows above the box (caption and a blank line).
+const CAPTION_ROWS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    ratio: f64,
+}
+
+impl Calibration {
+    pub fn new(ratio: f64) -> Self {
+        Self {
+            ratio: ratio.clamp(MIN_RATIO, MAX_RATIO),
+        }
+    }
+
+    pub fn ratio(&self) -> f64 {
+        self.ratio
+    }
+
+    /// Change the ratio by `delta`, rounded to hundredths.
+    pub fn nudge(&mut self, delta: f64) {
+        let ratio = ((self.ratio + delta) * 100.0).round() / 100.0;
+        self.ratio = ratio.clamp(MIN_RATIO, MAX_RATIO);
+    }
+
+    /// Test pattern for a screen of `width` columns and `rows` rows (above the status bar).
+    pub fn lines(&self, width: u16, rows: u16) -> Vec<String> {
+        let (width, rows) = (usize::from(width), usize::from(rows));
+        let mut lines = vec![String::new(); rows];
+        if let Some(caption) = lines.first_mut() {
+            *caption = format!(
+                " Nudge until the box is square: h/l \u{b1}{STEP}, H/L \u{b1}{FINE_STEP}, \
+                 Enter save, Esc cancel"
+            );
+        }
+
+        // Largest box that fits both ways; sides must be at least 3 cells for the cross.
+        let max_h = rows.saturating_sub(CAPTION_ROWS);
+        let box_w = width
+            .saturating_sub(2)
+            .min((max_h as f64 * self.ratio).floor() as usize);
+        let box_h = (box_w as f64 / self.ratio).round() as usize;
+        if box_w < 3 || box_h < 3 || box_h > max_h {
+            return lines;
+        }
+
+        let left = " ".repeat((width - box_w) / 2);
+        let top = CAPTION_ROWS + (max_h - box_h) / 2;
+        let (mid_x, mid_y) = (box_w / 2, box_h / 2);
+        for y in 0..box_h {
+            let row: String = (0..box_w)
+                .map(|x| {
+                    let edge_x = x == 0 || x == box_w - 1;
+                    let edge_y = y == 0 || y == box_h - 1;
+                    match (y, x) {
+                        (0, 0) => '┌',
+                        (0, _) if x == box_w - 1 => '┐',
+                        (_, 0) if y == box_h - 1 => '└',
+                        _ if edge_x && edge_y => '┘',
+                        _ if edge_y => '─',
+                        _ if edge_x => '│',
+                        _ if x == mid_x && y == mid_y => '┼',
+                        _ if y == mid_y => '─',
+                        _ if x == mid_x => '│',
+                        _ => ' ',
+                    }
+                })
+                .collect();
+            lines[top + y] = format!("{left}{row}");
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nudge_rounds_and_clamps() {
+        let mut c = Calibration::new(2.0);
+        c.nudge(STEP);
+        c.nudge(-FINE_STEP);
+        assert_eq!(c.ratio(), 2.04);
+        c.nudge(-10.0);
+        assert_eq!(c.ratio(), MIN_RATIO);
+        assert_eq!(Calibration::new(9.0).ratio(), MAX_RATIO);
+    }
+
+    #[test]
+    fn test_box_height_follows_ratio() {
+        let lines = Calibration::new(2.0).lines(80, 24);
+        assert_eq!(lines.len(), 24);
+        let rows: Vec<&String> = lines.iter().filter(|l| l.contains('│')).collect();
+        let top = lines.iter().find(|l| l.contains('┌')).unwrap();
+        let box_w = top.trim().chars().count();
+        // Width in columns is twice the height in rows (including the borders).
+        assert_eq!(box_w, 44);
+        assert_eq!(rows.len() + 2, 22);
+
+        // Too small for a box: caption only.
+        let lines = Calibration::new(2.0).lines(4, 3);
+        assert!(lines.iter().skip(1).all(String::is_empty));
+    }
+}
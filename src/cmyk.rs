@@ -0,0 +1,167 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! CMYK JPEGs.
+//!
+//! The `image` decoder converts 4-component JPEGs the way Adobe writes them: inverted values,
+//! flagged by an APP14 "Adobe" segment (which also marks YCCK). Files without that segment
+//! store plain ink amounts and came out with inverted colors, so they are decoded to raw CMYK
+//! here and converted to RGB before resize/encode.
+
+use std::path::Path;
+
+use image::DynamicImage;
+
+/// Start of frame markers (baseline, progressive, lossless and arithmetic variants).
+const SOF_MARKERS: [u8; 12] = [
+    0xC0, 0xC1, 0xC2, 0xC3, 0xC5, 0xC6, 0xC7, 0xC9, 0xCA, 0xCB, 0xCD, 0xCE,
+];
+const SOS: u8 = 0xDA;
+const APP14: u8 = 0xEE;
+
+/// Check if the path has a JPEG extension.
+pub fn is_jpeg(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("jpg") || e.eq_ignore_ascii_case("jpeg"))
+}
+
+/// Whether a JPEG stores 4 components without an Adobe APP14 segment (plain CMYK).
+pub fn is_plain_cmyk(bytes: &[u8]) -> bool {
+    if !bytes.starts_with(&[0xFF, 0xD8]) {
+        return false;
+    }
+    let mut adobe = false;
+    let mut at = 2;
+    while let Some(&[0xFF, marker]) = bytes.get(at..at + 2) {
+        // Fill bytes and markers without a payload.
+        if marker == 0xFF || marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            at += if marker == 0xFF { 1 } else { 2 };
+            continue;
+        }
+        let Some(&[hi, lo]) = bytes.get(at + 2..at + 4) else {
+            return false;
+        };
+        let payload = at + 4;
+        if marker == APP14 && bytes.get(payload..payload + 5) == Some(b"Adobe") {
+            adobe = true;
+        }
+        if SOF_MARKERS.contains(&marker) {
+            // Precision (1), height (2) and width (2) come before the component count.
+            return !adobe && bytes.get(payload + 5) == Some(&4);
+        }
+        if marker == SOS {
+            return false;
+        }
+        at += 2 + usize::from(u16::from_be_bytes([hi, lo]));
+    }
+    false
+}
+
+/// Decode a plain CMYK JPEG to RGB. Images exceeding `limits` fail to decode.
+pub fn decode(bytes: &[u8], limits: &image::Limits) -> Option<DynamicImage> {
+    use zune_jpeg::JpegDecoder;
+    use zune_jpeg::zune_core::bytestream::ZCursor;
+    use zune_jpeg::zune_core::colorspace::ColorSpace;
+    use zune_jpeg::zune_core::options::DecoderOptions;
+
+    let options = DecoderOptions::default().jpeg_set_out_colorspace(ColorSpace::CMYK);
+    let mut decoder = JpegDecoder::new_with_options(ZCursor::new(bytes), options);
+    decoder.decode_headers().ok()?;
+    let (width, height) = decoder.dimensions()?;
+    let (width, height) = (u32::try_from(width).ok()?, u32::try_from(height).ok()?);
+    limits.check_dimensions(width, height).ok()?;
+    if limits
+        .max_alloc
+        .is_some_and(|max| u64::from(width) * u64::from(height) * 4 > max)
+    {
+        return None;
+    }
+    let pixels = decoder.decode().ok()?;
+    let rgb = pixels
+        .chunks_exact(4)
+        .flat_map(|p| to_rgb([p[0], p[1], p[2], p[3]]))
+        .collect();
+    image::RgbImage::from_raw(width, height, rgb).map(DynamicImage::ImageRgb8)
+}
+
+/// Naive (profile-less) conversion of ink amounts to RGB.
+fn to_rgb([c, m, y, k]: [u8; 4]) -> [u8; 3] {
+    let white = 255 - u32::from(k);
+    let channel = |ink: u8| ((255 - u32::from(ink)) * white / 255) as u8;
+    [channel(c), channel(m), channel(y)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// SOI, optional APP14 "Adobe" segment, and a SOF0 header with `components`.
+    fn header(adobe: bool, components: u8) -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8];
+        if adobe {
+            data.extend_from_slice(&[0xFF, APP14, 0x00, 0x0E]);
+            data.extend_from_slice(b"Adobe\x00\x64\x00\x00\x00\x00\x00");
+        }
+        data.extend_from_slice(&[0xFF, 0xC0, 0x00, 0x0B, 8, 0, 2, 0, 2, components]);
+        data
+    }
+
+    #[test]
+    fn test_is_plain_cmyk() {
+        assert!(is_plain_cmyk(&header(false, 4)));
+        assert!(!is_plain_cmyk(&header(true, 4)));
+        assert!(!is_plain_cmyk(&header(false, 3)));
+        assert!(!is_plain_cmyk(b"\x89PNG"));
+    }
+
+    /// An 8x8 baseline JPEG with plain CMYK (0, 128, 0, 128) everywhere: DC-only blocks, a
+    /// quantization table of ones, and Huffman tables with just the codes it uses.
+    fn plain_cmyk_jpeg() -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8];
+        data.extend_from_slice(&[0xFF, 0xDB, 0x00, 0x43, 0x00]);
+        data.extend_from_slice(&[1; 64]);
+        data.extend_from_slice(&[0xFF, 0xC0, 0x00, 0x14, 8, 0, 8, 0, 8, 4]);
+        for id in 1..=4 {
+            data.extend_from_slice(&[id, 0x11, 0]);
+        }
+        // DC: category 0 is `0`, category 11 is `10`. AC: end of block is `0`.
+        data.extend_from_slice(&[0xFF, 0xC4, 0x00, 0x15, 0x00, 1, 1]);
+        data.extend_from_slice(&[0; 14]);
+        data.extend_from_slice(&[0x00, 0x0B]);
+        data.extend_from_slice(&[0xFF, 0xC4, 0x00, 0x14, 0x10, 1]);
+        data.extend_from_slice(&[0; 15]);
+        data.push(0x00);
+        data.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x0E, 4, 1, 0, 2, 0, 3, 0, 4, 0, 0, 63, 0]);
+        // C and Y: DC -1024 (`10` + `01111111111`), EOB. M and K: DC 0, EOB.
+        data.extend_from_slice(&[0x9F, 0xF8, 0x9F, 0xF8]);
+        data.extend_from_slice(&[0xFF, 0xD9]);
+        data
+    }
+
+    #[test]
+    fn test_decode_plain_cmyk_jpeg() {
+        let jpeg = plain_cmyk_jpeg();
+        assert!(is_plain_cmyk(&jpeg));
+        let rgb = decode(&jpeg, &image::Limits::default()).unwrap().to_rgb8();
+        assert_eq!(rgb.dimensions(), (8, 8));
+        let expected = to_rgb([0, 128, 0, 128]);
+        for pixel in rgb.pixels() {
+            for (got, want) in pixel.0.iter().zip(expected) {
+                assert!(got.abs_diff(want) <= 1, "{:?} != {:?}", pixel.0, expected);
+            }
+        }
+
+        let mut limits = image::Limits::default();
+        limits.max_image_width = Some(4);
+        assert!(decode(&jpeg, &limits).is_none());
+    }
+
+    #[test]
+    fn test_to_rgb() {
+        assert_eq!(to_rgb([0, 0, 0, 0]), [255, 255, 255]);
+        assert_eq!(to_rgb([0, 0, 0, 255]), [0, 0, 0]);
+        assert_eq!(to_rgb([255, 0, 255, 0]), [0, 255, 0]);
+        assert_eq!(to_rgb([0, 0, 0, 51]), [204, 204, 204]);
+    }
+}
@@ -0,0 +1,688 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Command line (`:`) with fuzzy path completion.
+//!
+//! - `CommandLine`: the input buffer plus completion candidates for the path argument.
+//! - `Command`: a parsed command, executed by the event loop.
+//!
+//! Candidates are the entries of the directory typed so far, ranked by `fuzzy_score` against
+//! the last path component. `:bookmarks` lists the bookmarked directories instead, ranked
+//! against the typed argument.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+/// A parsed command.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Replace the image list with the images at these paths (files, directories or archives).
+    Open(Vec<PathBuf>),
+    /// Append the images at these paths to the image list (`:add`).
+    Add(Vec<PathBuf>),
+    /// Open the cell-aspect calibration screen.
+    Calibrate,
+    /// Drop the render, decode and thumbnail caches (`:cache clear`).
+    ClearCache,
+    /// Compare every image against this reference (None = stop comparing).
+    Diff(Option<PathBuf>),
+    /// Blend this reference over every image (None = stop blending).
+    Blend(Option<PathBuf>),
+    /// Show this reference right of a movable split (None = stop wiping).
+    Wipe(Option<PathBuf>),
+    /// Write the zoomed region of the current image to a file (`:w!` overwrites).
+    Write { path: PathBuf, force: bool },
+    /// Composite transparent images over this color (None = no matte).
+    Matte(Option<[u8; 3]>),
+    /// Go to the image at this index (0-based; typed 1-based as `:42`).
+    GoTo(usize),
+    /// Reorder the image list (`:sort! key` reverses).
+    Sort {
+        key: crate::sort::SortKey,
+        reverse: bool,
+    },
+    /// Move the current image to the trash (like `D`).
+    Delete,
+    /// Start or stop the slideshow, or change one of its options.
+    Slideshow(crate::slideshow::Setting),
+}
+
+/// Completion candidate for the path argument.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Candidate {
+    /// Entry name as shown in the menu (directories end with `/`).
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Command line state while `:` is active.
+#[derive(Debug, Default)]
+pub struct CommandLine {
+    input: String,
+    candidates: Vec<Candidate>,
+    selected: usize,
+    /// The selected candidate was picked with the arrow keys since the input last changed, so
+    /// `Enter` runs it instead of the typed text.
+    accepted: bool,
+    /// Bookmarked directories offered by `:bookmarks`.
+    bookmarks: Vec<PathBuf>,
+}
+
+impl CommandLine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_bookmarks(mut self, bookmarks: Vec<PathBuf>) -> Self {
+        self.bookmarks = bookmarks;
+        self
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    pub fn candidates(&self) -> &[Candidate] {
+        &self.candidates
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.input.push(c);
+        self.refresh();
+    }
+
+    /// Delete the last character. Returns false if the input was already empty.
+    pub fn backspace(&mut self) -> bool {
+        if self.input.pop().is_none() {
+            return false;
+        }
+        self.refresh();
+        true
+    }
+
+    /// Move the candidate selection by `delta` (wraps around).
+    pub fn select_by(&mut self, delta: i32) {
+        let len = self.candidates.len() as i32;
+        if len > 0 {
+            self.selected = (self.selected as i32 + delta).rem_euclid(len) as usize;
+            self.accepted = true;
+        }
+    }
+
+    /// Replace the last path component (or the `:bookmarks` filter) with the selected
+    /// candidate.
+    pub fn complete(&mut self) {
+        let Some(candidate) = self.candidates.get(self.selected) else {
+            return;
+        };
+        if self.bookmark_query().is_some() {
+            self.input = format!("bookmarks {}", candidate.path.display());
+            self.refresh();
+            return;
+        }
+        let Some((cmd, arg)) = self.path_argument() else {
+            return;
+        };
+        let dir_part = match arg.rfind('/') {
+            Some(i) => &arg[..=i],
+            None => "",
+        };
+        self.input = format!("{cmd} {dir_part}{}", candidate.name);
+        self.refresh();
+    }
+
+    /// Parse the input into a command.
+    ///
+    /// A candidate is only used once accepted: completed with `Tab` (which puts it in the
+    /// input) or picked with the arrow keys. A typed path that does not exist is an error, not
+    /// whichever entry the fuzzy query happens to rank first.
+    pub fn parse(&self) -> Result<Command> {
+        let mut parts = self.input.trim().splitn(2, char::is_whitespace);
+        let name = parts.next().unwrap_or_default();
+        let arg = parts.next().unwrap_or_default().trim();
+        match name {
+            "open" | "e" => Ok(Command::Open(self.path_arguments(name, arg)?)),
+            "add" => Ok(Command::Add(self.path_arguments(name, arg)?)),
+            "bookmarks" => {
+                let typed = expand_tilde(arg.trim_end_matches('/'));
+                let bookmark = self.bookmarks.iter().find(|dir| **dir == typed);
+                match bookmark.or(self.accepted_candidate().map(|c| &c.path)) {
+                    Some(dir) => Ok(Command::Open(vec![dir.clone()])),
+                    None if self.bookmarks.is_empty() => {
+                        anyhow::bail!("bookmarks: no bookmarks (press B to add one)")
+                    }
+                    None if self.candidates.is_empty() => {
+                        anyhow::bail!("bookmarks: no match: {arg}")
+                    }
+                    None => anyhow::bail!("bookmarks: pick one with Tab or Up/Down"),
+                }
+            }
+            "w" | "w!" | "write" | "write!" if arg.is_empty() => {
+                anyhow::bail!("{name}: missing file name")
+            }
+            "w" | "w!" | "write" | "write!" => Ok(Command::Write {
+                path: expand_tilde(arg),
+                force: name.ends_with('!'),
+            }),
+            "diff" => Ok(Command::Diff(self.reference_argument(name, arg)?)),
+            "blend" => Ok(Command::Blend(self.reference_argument(name, arg)?)),
+            "wipe" => Ok(Command::Wipe(self.reference_argument(name, arg)?)),
+            "sort" | "sort!" => Ok(Command::Sort {
+                key: crate::sort::SortKey::parse(arg)?,
+                reverse: name.ends_with('!'),
+            }),
+            "delete" => Ok(Command::Delete),
+            _ if name.bytes().all(|b| b.is_ascii_digit()) && !name.is_empty() => {
+                match name.parse::<usize>() {
+                    Ok(n) if n > 0 => Ok(Command::GoTo(n - 1)),
+                    _ => anyhow::bail!("No image {name}"),
+                }
+            }
+            "calibrate" => Ok(Command::Calibrate),
+            "cache" if arg == "clear" => Ok(Command::ClearCache),
+            "cache" => anyhow::bail!("cache: expected `clear`"),
+            "matte" => Ok(Command::Matte(crate::adjust::parse_matte(arg)?)),
+            "slideshow" => Ok(Command::Slideshow(crate::slideshow::parse_setting(arg)?)),
+            "" => anyhow::bail!("Empty command"),
+            _ => anyhow::bail!("Unknown command: {name}"),
+        }
+    }
+
+    /// Paths of `:open` / `:add`: the argument if it exists as typed (names with spaces),
+    /// otherwise each word, with `*` and `?` expanded in the last path component. A single
+    /// word that does not exist gives the accepted candidate, if any.
+    fn path_arguments(&self, name: &str, arg: &str) -> Result<Vec<PathBuf>> {
+        let path = expand_tilde(arg);
+        if !arg.is_empty() && path.exists() {
+            return Ok(vec![path]);
+        }
+        let words: Vec<&str> = arg.split_whitespace().collect();
+        if words.len() > 1 || words.first().is_some_and(|word| is_pattern(word)) {
+            let mut paths = Vec::new();
+            for word in words {
+                let found = if is_pattern(word) {
+                    expand_pattern(word)
+                } else {
+                    Some(expand_tilde(word))
+                        .filter(|path| path.exists())
+                        .into_iter()
+                        .collect()
+                };
+                if found.is_empty() {
+                    anyhow::bail!("{name}: no such path: {word}");
+                }
+                paths.extend(found);
+            }
+            return Ok(paths);
+        }
+        match self.accepted_candidate() {
+            Some(candidate) => Ok(vec![candidate.path.clone()]),
+            None if arg.is_empty() => anyhow::bail!("{name}: missing path"),
+            None => anyhow::bail!("{name}: no such path: {arg}"),
+        }
+    }
+
+    /// The selected candidate, if it was picked with the arrow keys.
+    fn accepted_candidate(&self) -> Option<&Candidate> {
+        self.candidates.get(self.selected).filter(|_| self.accepted)
+    }
+
+    /// Reference image of `:diff`, `:blend` or `:wipe` (None without an argument). Gives the
+    /// accepted candidate like `:open`.
+    fn reference_argument(&self, name: &str, arg: &str) -> Result<Option<PathBuf>> {
+        if arg.is_empty() {
+            return Ok(None);
+        }
+        let path = expand_tilde(arg);
+        if path.is_file() {
+            return Ok(Some(path));
+        }
+        match self.accepted_candidate() {
+            Some(candidate) if candidate.path.is_file() => Ok(Some(candidate.path.clone())),
+            _ => anyhow::bail!("{name}: no such file: {arg}"),
+        }
+    }
+
+    /// Command name and the raw path argument, if the command takes a path.
+    fn path_argument(&self) -> Option<(&str, &str)> {
+        let (cmd, arg) = self.input.split_once(' ')?;
+        matches!(cmd, "open" | "e" | "add" | "diff" | "blend" | "wipe")
+            .then_some((cmd, arg.trim_start()))
+    }
+
+    /// Typed filter for `:bookmarks` (empty right after the command name).
+    fn bookmark_query(&self) -> Option<&str> {
+        let rest = self.input.trim_start().strip_prefix("bookmarks")?;
+        if rest.is_empty() {
+            return Some("");
+        }
+        rest.strip_prefix(' ').map(str::trim)
+    }
+
+    fn refresh(&mut self) {
+        self.selected = 0;
+        self.accepted = false;
+        self.candidates = match (self.path_argument(), self.bookmark_query()) {
+            (Some((_, arg)), _) => path_candidates(arg),
+            (None, Some(query)) => bookmark_candidates(&self.bookmarks, query),
+            (None, None) => Vec::new(),
+        };
+    }
+}
+
+/// Bookmarked directories ranked against `query` (bookmark order for equal scores).
+fn bookmark_candidates(bookmarks: &[PathBuf], query: &str) -> Vec<Candidate> {
+    let mut scored: Vec<(i32, Candidate)> = bookmarks
+        .iter()
+        .filter_map(|dir| {
+            let name = format!("{}/", dir.display());
+            let score = fuzzy_score(query, &name)?;
+            Some((
+                score,
+                Candidate {
+                    name,
+                    path: dir.clone(),
+                },
+            ))
+        })
+        .collect();
+    scored.sort_by(|(sa, _), (sb, _)| sb.cmp(sa));
+    scored.into_iter().map(|(_, c)| c).collect()
+}
+
+/// Entries of the directory part of `arg`, ranked against its last component.
+fn path_candidates(arg: &str) -> Vec<Candidate> {
+    let (dir_part, query) = match arg.rfind('/') {
+        Some(i) => (&arg[..=i], &arg[i + 1..]),
+        None => ("", arg),
+    };
+    let dir = if dir_part.is_empty() {
+        PathBuf::from(".")
+    } else {
+        expand_tilde(dir_part)
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+
+    let mut scored: Vec<(i32, Candidate)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            // Hidden entries only when explicitly asked for.
+            if name.starts_with('.') && !query.starts_with('.') {
+                return None;
+            }
+            let path = if dir_part.is_empty() {
+                PathBuf::from(&name)
+            } else {
+                dir.join(&name)
+            };
+            let is_dir = path.is_dir();
+            if !is_dir && !is_openable_file(&path) {
+                return None;
+            }
+            let score = fuzzy_score(query, &name)?;
+            let name = if is_dir { format!("{name}/") } else { name };
+            Some((score, Candidate { name, path }))
+        })
+        .collect();
+    scored.sort_by(|(sa, a), (sb, b)| sb.cmp(sa).then_with(|| a.name.cmp(&b.name)));
+    scored.into_iter().map(|(_, c)| c).collect()
+}
+
+fn is_openable_file(path: &Path) -> bool {
+    crate::has_image_extension(path) || crate::archive::is_archive(path)
+}
+
+fn is_pattern(word: &str) -> bool {
+    word.contains(['*', '?'])
+}
+
+/// Entries matching a pattern such as `~/shots/*.png`, in natural order. Only the last
+/// component may contain wildcards; hidden entries match only a pattern starting with `.`.
+fn expand_pattern(pattern: &str) -> Vec<PathBuf> {
+    let (dir, name) = match pattern.rfind('/') {
+        Some(i) => (expand_tilde(&pattern[..=i]), &pattern[i + 1..]),
+        None => (PathBuf::from("."), pattern),
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let entry_name = entry.file_name().to_string_lossy().into_owned();
+            (name.starts_with('.') || !entry_name.starts_with('.'))
+                && wildcard_match(name, &entry_name)
+        })
+        .map(|entry| {
+            if pattern.contains('/') {
+                entry.path()
+            } else {
+                PathBuf::from(entry.file_name())
+            }
+        })
+        .collect();
+    paths.sort_by(|a, b| crate::sort::natural_cmp(a, b));
+    paths
+}
+
+/// Match `name` against `pattern`, where `*` matches any run of characters and `?` one.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let (pattern, name): (Vec<char>, Vec<char>) =
+        (pattern.chars().collect(), name.chars().collect());
+    let (mut p, mut n) = (0, 0);
+    // Position after the last `*` and the name position it matched up to.
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((after, matched)) => {
+                    p = after;
+                    n = matched + 1;
+                    star = Some((after, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn expand_tilde(path: &str) -> PathBuf {
+    if path == "~" {
+        return dirs::home_dir().unwrap_or_else(|| PathBuf::from(path));
+    }
+    if let Some(rest) = path.strip_prefix("~/")
+        && let Some(home) = dirs::home_dir()
+    {
+        return home.join(rest);
+    }
+    PathBuf::from(path)
+}
+
+/// Score `candidate` against `query` as a case-insensitive subsequence match.
+///
+/// Returns None if `query` is not a subsequence. Consecutive matches and matches at word
+/// boundaries score higher; gaps and long names score lower.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    const MATCH: i32 = 16;
+    const CONSECUTIVE_BONUS: i32 = 16;
+    const BOUNDARY_BONUS: i32 = 8;
+    const MAX_GAP_PENALTY: i32 = 6;
+
+    if query.is_empty() {
+        // Keep plain name order when nothing has been typed yet.
+        return Some(0);
+    }
+    let chars: Vec<char> = candidate.chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut prev: Option<usize> = None;
+
+    for qc in query.chars().flat_map(char::to_lowercase) {
+        let found = (pos..chars.len()).find(|&i| chars[i].to_lowercase().eq([qc]))?;
+        score += MATCH;
+        match prev {
+            Some(p) if found == p + 1 => score += CONSECUTIVE_BONUS,
+            Some(p) => score -= ((found - p - 1) as i32).min(MAX_GAP_PENALTY),
+            None => score -= (found as i32).min(MAX_GAP_PENALTY),
+        }
+        let at_boundary = found == 0 || matches!(chars[found - 1], '-' | '_' | '.' | ' ');
+        if at_boundary {
+            score += BOUNDARY_BONUS;
+        }
+        prev = Some(found);
+        pos = found + 1;
+    }
+
+    // Prefer shorter names among equally good matches.
+    Some(score * 4 - chars.len() as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+
+    #[test]
+    fn test_fuzzy_score_requires_subsequence() {
+        assert!(fuzzy_score("abc", "a_b_c").is_some());
+        assert!(fuzzy_score("ABC", "abc").is_some());
+        assert!(fuzzy_score("acb", "abc").is_none());
+        assert_eq!(fuzzy_score("", "x"), Some(0));
+    }
+
+    #[test]
+    fn test_fuzzy_score_prefers_consecutive_and_prefix() {
+        let contiguous = fuzzy_score("cat", "cat.png").unwrap();
+        let scattered = fuzzy_score("cat", "crop_art.png").unwrap();
+        assert!(contiguous > scattered);
+        let prefix = fuzzy_score("pho", "photos").unwrap();
+        let inner = fuzzy_score("pho", "my_xphotos").unwrap();
+        assert!(prefix > inner);
+    }
+
+    #[test]
+    fn test_command_line_completes_paths() {
+        let dir = PathBuf::from("/tmp/stiv_test_command");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("photos")).unwrap();
+        File::create(dir.join("cat.png")).unwrap();
+        File::create(dir.join("notes.txt")).unwrap();
+
+        let mut cmd = CommandLine::new();
+        for c in format!("open {}/", dir.display()).chars() {
+            cmd.push(c);
+        }
+        let names: Vec<&str> = cmd.candidates().iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["cat.png", "photos/"]);
+
+        cmd.push('p');
+        cmd.push('h');
+        assert_eq!(cmd.candidates()[0].name, "photos/");
+        cmd.complete();
+        assert_eq!(cmd.input(), format!("open {}/photos/", dir.display()));
+        assert_eq!(
+            cmd.parse().unwrap(),
+            Command::Open(vec![dir.join("photos/")])
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_uses_only_accepted_candidate() {
+        let dir = PathBuf::from("/tmp/stiv_test_command_fallback");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        File::create(dir.join("a.png")).unwrap();
+        File::create(dir.join("b.png")).unwrap();
+
+        // A typo is an error, not whichever entry ranks first.
+        let mut cmd = CommandLine::new();
+        for c in format!("open {}/b", dir.display()).chars() {
+            cmd.push(c);
+        }
+        assert_eq!(cmd.candidates()[0].path, dir.join("b.png"));
+        let err = cmd.parse().unwrap_err().to_string();
+        assert!(err.contains("no such path"), "{err}");
+
+        // Picked with the arrow keys, or completed with Tab.
+        cmd.select_by(1);
+        cmd.select_by(-1);
+        assert_eq!(cmd.parse().unwrap(), Command::Open(vec![dir.join("b.png")]));
+        cmd.push('x');
+        assert!(cmd.parse().is_err());
+        cmd.backspace();
+        cmd.complete();
+        assert_eq!(cmd.parse().unwrap(), Command::Open(vec![dir.join("b.png")]));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_patterns_and_add() {
+        let dir = PathBuf::from("/tmp/stiv_test_command_patterns");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["a10.png", "a2.png", "b.jpg", ".hidden.png"] {
+            File::create(dir.join(name)).unwrap();
+        }
+
+        let mut cmd = CommandLine::new();
+        for c in format!("e {}/*.png", dir.display()).chars() {
+            cmd.push(c);
+        }
+        assert_eq!(
+            cmd.parse().unwrap(),
+            Command::Open(vec![dir.join("a2.png"), dir.join("a10.png")])
+        );
+
+        let mut cmd = CommandLine::new();
+        for c in format!("add {0}/b.jpg {0}/a?.png", dir.display()).chars() {
+            cmd.push(c);
+        }
+        assert_eq!(
+            cmd.parse().unwrap(),
+            Command::Add(vec![dir.join("b.jpg"), dir.join("a2.png")])
+        );
+
+        let mut cmd = CommandLine::new();
+        for c in format!("add {}/*.gif", dir.display()).chars() {
+            cmd.push(c);
+        }
+        assert!(cmd.parse().is_err());
+
+        assert!(wildcard_match("*a*b", "xaxxb"));
+        assert!(!wildcard_match("a*c", "abcd"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_bookmarks_are_listed_and_opened() {
+        let bookmarks = vec![PathBuf::from("/data/renders"), PathBuf::from("/shots")];
+        let mut cmd = CommandLine::new().with_bookmarks(bookmarks);
+        for c in "bookmarks".chars() {
+            cmd.push(c);
+        }
+        let names: Vec<&str> = cmd.candidates().iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["/data/renders/", "/shots/"]);
+
+        for c in " shot".chars() {
+            cmd.push(c);
+        }
+        assert_eq!(cmd.candidates()[0].name, "/shots/");
+        assert!(cmd.parse().is_err());
+        cmd.complete();
+        assert_eq!(cmd.input(), "bookmarks /shots");
+        assert_eq!(
+            cmd.parse().unwrap(),
+            Command::Open(vec![PathBuf::from("/shots")])
+        );
+        cmd.select_by(1);
+        assert_eq!(
+            cmd.parse().unwrap(),
+            Command::Open(vec![PathBuf::from("/shots")])
+        );
+
+        let mut empty = CommandLine::new();
+        for c in "bookmarks".chars() {
+            empty.push(c);
+        }
+        assert!(empty.parse().is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let mut cmd = CommandLine::new();
+        assert!(cmd.parse().is_err());
+        for c in "frobnicate".chars() {
+            cmd.push(c);
+        }
+        assert!(cmd.parse().is_err());
+
+        let mut cmd = CommandLine::new();
+        for c in "calibrate".chars() {
+            cmd.push(c);
+        }
+        assert_eq!(cmd.parse().unwrap(), Command::Calibrate);
+
+        let mut cmd = CommandLine::new();
+        for c in "cache".chars() {
+            cmd.push(c);
+        }
+        assert!(cmd.parse().is_err());
+        for c in " clear".chars() {
+            cmd.push(c);
+        }
+        assert_eq!(cmd.parse().unwrap(), Command::ClearCache);
+
+        let mut cmd = CommandLine::new();
+        for c in "matte #fff".chars() {
+            cmd.push(c);
+        }
+        assert_eq!(cmd.parse().unwrap(), Command::Matte(Some([255, 255, 255])));
+
+        let mut cmd = CommandLine::new();
+        cmd.push('w');
+        assert!(cmd.parse().is_err());
+        for c in "! crop.png".chars() {
+            cmd.push(c);
+        }
+        assert_eq!(
+            cmd.parse().unwrap(),
+            Command::Write {
+                path: PathBuf::from("crop.png"),
+                force: true
+            }
+        );
+
+        let mut cmd = CommandLine::new();
+        for c in "42".chars() {
+            cmd.push(c);
+        }
+        assert_eq!(cmd.parse().unwrap(), Command::GoTo(41));
+        cmd.backspace();
+        cmd.backspace();
+        cmd.push('0');
+        assert!(cmd.parse().is_err());
+
+        let mut cmd = CommandLine::new();
+        for c in "sort! mtime".chars() {
+            cmd.push(c);
+        }
+        assert_eq!(
+            cmd.parse().unwrap(),
+            Command::Sort {
+                key: crate::sort::SortKey::Mtime,
+                reverse: true
+            }
+        );
+
+        let mut cmd = CommandLine::new();
+        for c in "blend".chars() {
+            cmd.push(c);
+        }
+        assert_eq!(cmd.parse().unwrap(), Command::Blend(None));
+        for c in " /no/such/file.png".chars() {
+            cmd.push(c);
+        }
+        assert!(cmd.parse().is_err());
+    }
+}
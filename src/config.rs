@@ -8,10 +8,28 @@
 //! 2. Config file (~/.config/stiv/config.toml)
 //! 3. Default values
 
-use serde::Deserialize;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Deserialize)]
+/// Action name(s) of a `[keys]` entry: `j = "down"` or `a = ["pan_left", "animation"]`.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ActionNames {
+    One(String),
+    List(Vec<String>),
+}
+
+impl ActionNames {
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        match self {
+            Self::One(name) => std::slice::from_ref(name).iter(),
+            Self::List(names) => names.iter(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(default)]
 pub struct Config {
     pub nav_latch_ms: u64,
@@ -21,6 +39,9 @@ pub struct Config {
     pub prefetch_count: usize,
     pub debug: bool,
     pub kgp_no_compress: bool,
+    pub kgp_placeholders: bool,
+    pub kgp_no_file_transfer: bool,
+    pub kgp_client_animation: bool,
     pub compress_level: u32,
     pub tmux_kitty_max_pixels: u64,
     pub trace_worker: bool,
@@ -29,6 +50,36 @@ pub struct Config {
     pub tile_filter: String,
     pub prefetch_threads: usize,
     pub tile_threads: usize,
+    pub upscale_sharpen: f32,
+    pub downscale_sharpen: f32,
+    pub persist_rotation: bool,
+    pub bit_depth_stretch: f32,
+    pub disk_cache: bool,
+    pub disk_cache_mb: u64,
+    pub decode_max_alloc_mb: u64,
+    pub decode_max_dimension: u32,
+    pub group_by_date: bool,
+    pub tile_labels: bool,
+    pub tile_layout: String,
+    pub monochrome: bool,
+    pub status_message_ms: u64,
+    pub power_save: String,
+    pub protocol: String,
+    pub status_overlay: bool,
+    pub matte: String,
+    pub grid_spacing: u32,
+    pub spread_rtl: bool,
+    pub slideshow_interval_ms: u64,
+    pub slideshow_shuffle: bool,
+    pub slideshow_stop_at_end: bool,
+    pub slideshow_skip_errors: bool,
+    pub chord_timeout_ms: u64,
+    pub wheel_single: String,
+    pub wheel_tile: String,
+    pub wheel_step: u32,
+    pub wheel_reverse: bool,
+    /// `[keys]`: key bindings over the built-in ones (see `keymap.rs`).
+    pub keys: BTreeMap<String, ActionNames>,
 }
 
 impl Default for Config {
@@ -41,6 +92,9 @@ impl Default for Config {
             prefetch_count: 5,
             debug: false,
             kgp_no_compress: false,
+            kgp_placeholders: false,
+            kgp_no_file_transfer: false,
+            kgp_client_animation: false,
             compress_level: 6,
             tmux_kitty_max_pixels: 1_500_000,
             trace_worker: false,
@@ -49,10 +103,82 @@ impl Default for Config {
             tile_filter: "nearest".to_string(),
             prefetch_threads: 2,
             tile_threads: 4,
+            upscale_sharpen: 0.0,
+            downscale_sharpen: 0.0,
+            persist_rotation: false,
+            bit_depth_stretch: 0.0,
+            disk_cache: false,
+            disk_cache_mb: 1024,
+            decode_max_alloc_mb: 1024,
+            decode_max_dimension: 0,
+            group_by_date: false,
+            tile_labels: false,
+            tile_layout: "grid".to_string(),
+            monochrome: false,
+            status_message_ms: 1500,
+            power_save: "auto".to_string(),
+            protocol: "auto".to_string(),
+            status_overlay: false,
+            matte: String::new(),
+            grid_spacing: 16,
+            spread_rtl: false,
+            slideshow_interval_ms: 5_000,
+            slideshow_shuffle: false,
+            slideshow_stop_at_end: false,
+            slideshow_skip_errors: false,
+            chord_timeout_ms: 1_000,
+            wheel_single: "off".to_string(),
+            wheel_tile: "off".to_string(),
+            wheel_step: 1,
+            wheel_reverse: false,
+            keys: BTreeMap::new(),
         }
     }
 }
 
+/// `Config::save_value` on an explicit path. A file that cannot be read (other than a missing
+/// one) is an error rather than an empty config, and the new content is written to a temp file
+/// next to it and renamed over, so a failure never leaves a truncated or emptied config.
+fn save_value_at(path: &Path, key: &str, value: &str) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {:?}", path)),
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("toml.tmp");
+    std::fs::write(&tmp, set_value(&content, key, value))
+        .with_context(|| format!("Failed to write {:?}", tmp))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("Failed to write {:?}", path))?;
+    Ok(())
+}
+
+/// Replace the top-level `key = ...` line of a TOML document, or add one before the first table.
+fn set_value(content: &str, key: &str, value: &str) -> String {
+    let line = format!("{key} = {value}");
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let top_level = lines
+        .iter()
+        .position(|l| l.trim_start().starts_with('['))
+        .unwrap_or(lines.len());
+    let existing = lines[..top_level].iter().position(|l| {
+        l.trim_start()
+            .strip_prefix(key)
+            .is_some_and(|rest| rest.trim_start().starts_with('='))
+    });
+    match existing {
+        Some(i) => lines[i] = line,
+        None => lines.insert(top_level, line),
+    }
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
 /// Parse filter type string to image::imageops::FilterType.
 /// Returns Triangle as fallback for invalid values.
 pub fn parse_filter_type(s: &str) -> image::imageops::FilterType {
@@ -85,6 +211,16 @@ impl Config {
         dirs::config_dir().map(|p| p.join("stiv").join("config.toml"))
     }
 
+    /// Set a top-level `key = value` in the config file, keeping the rest of the file (comments
+    /// included). `value` is a TOML literal. Returns the file path.
+    pub fn save_value(key: &str, value: &str) -> anyhow::Result<PathBuf> {
+        use anyhow::Context;
+
+        let path = Self::config_path().context("No config directory")?;
+        save_value_at(&path, key, value)?;
+        Ok(path)
+    }
+
     fn load_from_file() -> Option<Self> {
         let path = Self::config_path()?;
         let content = std::fs::read_to_string(path).ok()?;
@@ -113,6 +249,15 @@ impl Config {
         if std::env::var_os("STIV_KGP_NO_COMPRESS").is_some() {
             self.kgp_no_compress = true;
         }
+        if std::env::var_os("STIV_KGP_PLACEHOLDERS").is_some() {
+            self.kgp_placeholders = true;
+        }
+        if std::env::var_os("STIV_KGP_NO_FILE_TRANSFER").is_some() {
+            self.kgp_no_file_transfer = true;
+        }
+        if std::env::var_os("STIV_KGP_CLIENT_ANIMATION").is_some() {
+            self.kgp_client_animation = true;
+        }
         if let Some(v) = Self::parse_env::<u32>("STIV_COMPRESS_LEVEL") {
             self.compress_level = v;
         }
@@ -137,6 +282,93 @@ impl Config {
         if let Some(v) = Self::parse_env::<usize>("STIV_TILE_THREADS") {
             self.tile_threads = v;
         }
+        if let Some(v) = Self::parse_env::<f32>("STIV_UPSCALE_SHARPEN") {
+            self.upscale_sharpen = v;
+        }
+        if let Some(v) = Self::parse_env::<f32>("STIV_DOWNSCALE_SHARPEN") {
+            self.downscale_sharpen = v;
+        }
+        if std::env::var_os("STIV_PERSIST_ROTATION").is_some() {
+            self.persist_rotation = true;
+        }
+        if let Some(v) = Self::parse_env::<f32>("STIV_BIT_DEPTH_STRETCH") {
+            self.bit_depth_stretch = v;
+        }
+        if std::env::var_os("STIV_DISK_CACHE").is_some() {
+            self.disk_cache = true;
+        }
+        if let Some(v) = Self::parse_env::<u64>("STIV_DISK_CACHE_MB") {
+            self.disk_cache_mb = v;
+        }
+        if let Some(v) = Self::parse_env::<u64>("STIV_DECODE_MAX_ALLOC_MB") {
+            self.decode_max_alloc_mb = v;
+        }
+        if let Some(v) = Self::parse_env::<u32>("STIV_DECODE_MAX_DIMENSION") {
+            self.decode_max_dimension = v;
+        }
+        if std::env::var_os("STIV_GROUP_BY_DATE").is_some() {
+            self.group_by_date = true;
+        }
+        if std::env::var_os("STIV_TILE_LABELS").is_some() {
+            self.tile_labels = true;
+        }
+        if let Ok(v) = std::env::var("STIV_TILE_LAYOUT") {
+            self.tile_layout = v;
+        }
+        // https://no-color.org: any non-empty value disables colors.
+        if std::env::var_os("STIV_MONOCHROME").is_some()
+            || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+        {
+            self.monochrome = true;
+        }
+        if let Some(v) = Self::parse_env::<u64>("STIV_STATUS_MESSAGE_MS") {
+            self.status_message_ms = v;
+        }
+        if let Ok(v) = std::env::var("STIV_POWER_SAVE") {
+            self.power_save = v;
+        }
+        if let Ok(v) = std::env::var("STIV_PROTOCOL") {
+            self.protocol = v;
+        }
+        if std::env::var_os("STIV_STATUS_OVERLAY").is_some() {
+            self.status_overlay = true;
+        }
+        if let Ok(v) = std::env::var("STIV_MATTE") {
+            self.matte = v;
+        }
+        if let Some(v) = Self::parse_env::<u32>("STIV_GRID_SPACING") {
+            self.grid_spacing = v;
+        }
+        if std::env::var_os("STIV_SPREAD_RTL").is_some() {
+            self.spread_rtl = true;
+        }
+        if let Some(v) = Self::parse_env::<u64>("STIV_SLIDESHOW_INTERVAL_MS") {
+            self.slideshow_interval_ms = v;
+        }
+        if std::env::var_os("STIV_SLIDESHOW_SHUFFLE").is_some() {
+            self.slideshow_shuffle = true;
+        }
+        if std::env::var_os("STIV_SLIDESHOW_STOP_AT_END").is_some() {
+            self.slideshow_stop_at_end = true;
+        }
+        if std::env::var_os("STIV_SLIDESHOW_SKIP_ERRORS").is_some() {
+            self.slideshow_skip_errors = true;
+        }
+        if let Some(v) = Self::parse_env::<u64>("STIV_CHORD_TIMEOUT_MS") {
+            self.chord_timeout_ms = v;
+        }
+        if let Ok(v) = std::env::var("STIV_WHEEL_SINGLE") {
+            self.wheel_single = v;
+        }
+        if let Ok(v) = std::env::var("STIV_WHEEL_TILE") {
+            self.wheel_tile = v;
+        }
+        if let Some(v) = Self::parse_env::<u32>("STIV_WHEEL_STEP") {
+            self.wheel_step = v;
+        }
+        if std::env::var_os("STIV_WHEEL_REVERSE").is_some() {
+            self.wheel_reverse = true;
+        }
     }
 
     fn clamp_values(&mut self) {
@@ -150,12 +382,45 @@ impl Config {
         self.cell_aspect_ratio = self.cell_aspect_ratio.clamp(1.0, 4.0);
         self.prefetch_threads = self.prefetch_threads.clamp(1, 8);
         self.tile_threads = self.tile_threads.clamp(1, 8);
+        self.upscale_sharpen = self.upscale_sharpen.clamp(0.0, 5.0);
+        self.downscale_sharpen = self.downscale_sharpen.clamp(0.0, 5.0);
+        self.bit_depth_stretch = self.bit_depth_stretch.clamp(0.0, 10.0);
+        self.disk_cache_mb = self.disk_cache_mb.clamp(16, 65_536);
+        self.decode_max_alloc_mb = self.decode_max_alloc_mb.clamp(64, 65_536);
+        self.status_message_ms = self.status_message_ms.clamp(200, 30_000);
+        self.grid_spacing = self.grid_spacing.clamp(1, 4096);
+        self.slideshow_interval_ms = self.slideshow_interval_ms.clamp(100, 3_600_000);
+        self.chord_timeout_ms = self.chord_timeout_ms.clamp(100, 10_000);
+        self.wheel_step = self.wheel_step.clamp(1, 20);
     }
 
     fn parse_env<T: std::str::FromStr>(key: &str) -> Option<T> {
         std::env::var(key).ok()?.parse().ok()
     }
 
+    /// `key = value` of every top-level setting, in name order (for the help screen).
+    pub fn values(&self) -> Vec<String> {
+        let Ok(toml::Value::Table(table)) = toml::Value::try_from(self) else {
+            return Vec::new();
+        };
+        table
+            .into_iter()
+            .filter(|(_, value)| !value.is_table())
+            .map(|(key, value)| format!("{key} = {value}"))
+            .collect()
+    }
+
+    /// Decoder limits applied to every image decode.
+    pub fn decode_limits(&self) -> image::Limits {
+        let mut limits = image::Limits::default();
+        limits.max_alloc = Some(self.decode_max_alloc_mb * 1024 * 1024);
+        if self.decode_max_dimension > 0 {
+            limits.max_image_width = Some(self.decode_max_dimension);
+            limits.max_image_height = Some(self.decode_max_dimension);
+        }
+        limits
+    }
+
     pub fn compression_level(&self) -> Option<u32> {
         if self.kgp_no_compress {
             None
@@ -179,7 +444,85 @@ mod tests {
         assert_eq!(config.tmux_kitty_max_pixels, 1_500_000);
         assert!(!config.force_alt_screen);
         assert!(!config.debug);
+        assert!(!config.kgp_placeholders);
+        assert!(!config.kgp_no_file_transfer);
+        assert!(!config.kgp_client_animation);
         assert_eq!(config.cell_aspect_ratio, 2.0);
+        assert_eq!(config.upscale_sharpen, 0.0);
+        assert_eq!(config.downscale_sharpen, 0.0);
+        assert!(!config.persist_rotation);
+        assert_eq!(config.bit_depth_stretch, 0.0);
+        assert!(!config.disk_cache);
+        assert_eq!(config.disk_cache_mb, 1024);
+        assert_eq!(config.decode_max_alloc_mb, 1024);
+        assert_eq!(config.decode_max_dimension, 0);
+        assert!(!config.group_by_date);
+        assert!(!config.tile_labels);
+        assert_eq!(config.tile_layout, "grid");
+        assert!(!config.monochrome);
+        assert_eq!(config.status_message_ms, 1500);
+        assert_eq!(config.power_save, "auto");
+        assert_eq!(config.protocol, "auto");
+        assert!(!config.status_overlay);
+        assert_eq!(config.matte, "");
+        assert_eq!(config.grid_spacing, 16);
+        assert!(!config.spread_rtl);
+        assert_eq!(config.slideshow_interval_ms, 5_000);
+        assert!(!config.slideshow_shuffle);
+        assert!(!config.slideshow_stop_at_end);
+        assert!(!config.slideshow_skip_errors);
+        assert_eq!(config.chord_timeout_ms, 1_000);
+        assert_eq!(config.wheel_single, "off");
+        assert_eq!(config.wheel_tile, "off");
+        assert_eq!(config.wheel_step, 1);
+        assert!(!config.wheel_reverse);
+        assert!(config.keys.is_empty());
+    }
+
+    #[test]
+    fn test_keys_section() {
+        let config: Config =
+            toml::from_str("debug = true\n[keys]\nj = \"down_no_wrap\"\na = [\"animation\"]\n")
+                .unwrap();
+        assert!(config.debug);
+        assert_eq!(
+            config.keys["j"],
+            ActionNames::One("down_no_wrap".to_string())
+        );
+        assert_eq!(config.keys["a"].iter().collect::<Vec<_>>(), ["animation"]);
+    }
+
+    #[test]
+    fn test_set_value_keeps_other_lines() {
+        let content = "# stiv\ncell_aspect_ratio = 2.0\ndebug = true\n";
+        assert_eq!(
+            set_value(content, "cell_aspect_ratio", "2.15"),
+            "# stiv\ncell_aspect_ratio = 2.15\ndebug = true\n"
+        );
+        // `cell_aspect_ratio_x` is a different key; new keys go before any table.
+        assert_eq!(
+            set_value("cell_aspect_ratio_x = 1\n[t]\n", "cell_aspect_ratio", "2.1"),
+            "cell_aspect_ratio_x = 1\ncell_aspect_ratio = 2.1\n[t]\n"
+        );
+        assert_eq!(set_value("", "debug", "true"), "debug = true\n");
+    }
+
+    #[test]
+    fn test_save_value_at_keeps_unreadable_file() {
+        let dir = std::env::temp_dir().join(format!("stiv_save_value_{}", std::process::id()));
+        let path = dir.join("config.toml");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // A missing file (and directory) is created.
+        save_value_at(&path, "debug", "true").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "debug = true\n");
+        assert!(!path.with_extension("toml.tmp").exists());
+
+        // A file that cannot be read as text is left alone instead of being overwritten.
+        std::fs::write(&path, b"debug = true\n\xff\n").unwrap();
+        assert!(save_value_at(&path, "cell_aspect_ratio", "2.0").is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), b"debug = true\n\xff\n");
+        let _ = std::fs::remove_dir_all(&dir);
     }
 
     #[test]
@@ -188,12 +531,53 @@ mod tests {
             nav_latch_ms: 10_000,
             render_cache_size: 1000,
             compress_level: 20,
+            upscale_sharpen: 10.0,
+            downscale_sharpen: -1.0,
+            bit_depth_stretch: 50.0,
+            disk_cache_mb: 1,
+            decode_max_alloc_mb: 1,
+            status_message_ms: 10,
+            slideshow_interval_ms: 1,
+            chord_timeout_ms: 50_000,
+            wheel_step: 0,
             ..Default::default()
         };
         config.clamp_values();
         assert_eq!(config.nav_latch_ms, 5_000);
         assert_eq!(config.render_cache_size, 500);
         assert_eq!(config.compress_level, 9);
+        assert_eq!(config.upscale_sharpen, 5.0);
+        assert_eq!(config.downscale_sharpen, 0.0);
+        assert_eq!(config.bit_depth_stretch, 10.0);
+        assert_eq!(config.disk_cache_mb, 16);
+        assert_eq!(config.decode_max_alloc_mb, 64);
+        assert_eq!(config.status_message_ms, 200);
+        assert_eq!(config.slideshow_interval_ms, 100);
+        assert_eq!(config.chord_timeout_ms, 10_000);
+        assert_eq!(config.wheel_step, 1);
+    }
+
+    #[test]
+    fn test_values_skip_tables() {
+        let values = Config::default().values();
+        assert!(values.contains(&"power_save = \"auto\"".to_string()));
+        assert!(values.contains(&"cell_aspect_ratio = 2.0".to_string()));
+        assert!(!values.iter().any(|v| v.starts_with("keys")));
+    }
+
+    #[test]
+    fn test_decode_limits() {
+        let limits = Config::default().decode_limits();
+        assert_eq!(limits.max_alloc, Some(1024 * 1024 * 1024));
+        assert_eq!(limits.max_image_width, None);
+
+        let config = Config {
+            decode_max_dimension: 8192,
+            ..Default::default()
+        };
+        let limits = config.decode_limits();
+        assert_eq!(limits.max_image_width, Some(8192));
+        assert_eq!(limits.max_image_height, Some(8192));
     }
 
     #[test]
@@ -0,0 +1,127 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! High bit-depth (16-bit and float) to 8-bit conversion.
+//!
+//! Decoded 16-bit/float images are converted right after decode, before rotation and resize:
+//! - by default, with proper rounding (`v * 255 / 65535`) and alpha preserved
+//! - with `bit_depth_stretch > 0`, the color channels are stretched so that the given percentage
+//!   of samples at each end is clipped. Scientific and astro images that only use a small part
+//!   of the 16-bit range become legible.
+//!
+//! 8-bit images are returned unchanged.
+
+use image::{DynamicImage, ImageBuffer, Rgba};
+
+/// Convert a high bit-depth image to 8 bits per channel (no-op for 8-bit images).
+pub fn to_display_depth(img: DynamicImage, stretch_percent: f32) -> DynamicImage {
+    if !is_high_depth(&img) {
+        return img;
+    }
+    let color = img.color();
+    let rgba = img.into_rgba16();
+    let (lo, hi) = if stretch_percent > 0.0 {
+        percentile_range(&rgba, stretch_percent)
+    } else {
+        (0, u16::MAX)
+    };
+    let span = f32::from(hi.saturating_sub(lo)).max(1.0);
+    let scale = |v: u16| -> u8 {
+        let t = f32::from(v.saturating_sub(lo)) / span;
+        (t.min(1.0) * 255.0).round() as u8
+    };
+
+    let (w, h) = rgba.dimensions();
+    let mut out = ImageBuffer::<Rgba<u8>, Vec<u8>>::new(w, h);
+    for (dst, src) in out.pixels_mut().zip(rgba.pixels()) {
+        let [r, g, b, a] = src.0;
+        // Alpha is never stretched.
+        *dst = Rgba([
+            scale(r),
+            scale(g),
+            scale(b),
+            ((u32::from(a) * 255 + 32767) / 65535) as u8,
+        ]);
+    }
+    let out = DynamicImage::ImageRgba8(out);
+    match (color.has_color(), color.has_alpha()) {
+        (false, false) => DynamicImage::ImageLuma8(out.to_luma8()),
+        (false, true) => DynamicImage::ImageLumaA8(out.to_luma_alpha8()),
+        (true, false) => DynamicImage::ImageRgb8(out.to_rgb8()),
+        (true, true) => out,
+    }
+}
+
+fn is_high_depth(img: &DynamicImage) -> bool {
+    matches!(
+        img,
+        DynamicImage::ImageLuma16(_)
+            | DynamicImage::ImageLumaA16(_)
+            | DynamicImage::ImageRgb16(_)
+            | DynamicImage::ImageRgba16(_)
+            | DynamicImage::ImageRgb32F(_)
+            | DynamicImage::ImageRgba32F(_)
+    )
+}
+
+/// Color sample values at `percent` and `100 - percent` (alpha ignored).
+fn percentile_range(img: &ImageBuffer<Rgba<u16>, Vec<u16>>, percent: f32) -> (u16, u16) {
+    let mut histogram = vec![0u64; 1 << 16];
+    for px in img.pixels() {
+        for &v in &px.0[..3] {
+            histogram[usize::from(v)] += 1;
+        }
+    }
+    let total: u64 = histogram.iter().sum();
+    let clip = (total as f64 * f64::from(percent) / 100.0) as u64;
+
+    let find = |bins: &mut dyn Iterator<Item = (usize, &u64)>| {
+        let mut seen = 0;
+        for (v, &count) in bins {
+            seen += count;
+            if seen > clip {
+                return v as u16;
+            }
+        }
+        0
+    };
+    let lo = find(&mut histogram.iter().enumerate());
+    let hi = find(&mut histogram.iter().enumerate().rev());
+    if hi > lo { (lo, hi) } else { (0, u16::MAX) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma, Rgba};
+
+    #[test]
+    fn test_eight_bit_unchanged() {
+        let img = DynamicImage::ImageLuma8(GrayImage::from_pixel(2, 2, Luma([7])));
+        let out = to_display_depth(img.clone(), 1.0);
+        assert_eq!(out, img);
+    }
+
+    #[test]
+    fn test_plain_conversion_rounds_and_keeps_alpha() {
+        let img =
+            ImageBuffer::<Rgba<u16>, Vec<u16>>::from_pixel(1, 1, Rgba([65535, 257, 0, 32768]));
+        let out = to_display_depth(DynamicImage::ImageRgba16(img), 0.0);
+        assert_eq!(out.as_rgba8().unwrap().get_pixel(0, 0).0, [255, 1, 0, 128]);
+    }
+
+    #[test]
+    fn test_stretch_expands_narrow_range() {
+        // Values only use 1000..=1099 of the 16-bit range.
+        let img = ImageBuffer::from_fn(100, 1, |x, _| Luma([1000u16 + x as u16]));
+        let img = DynamicImage::ImageLuma16(img);
+
+        let plain = to_display_depth(img.clone(), 0.0).to_luma8();
+        assert!(plain.pixels().all(|p| p.0[0] <= 5));
+
+        let stretched = to_display_depth(img, 1.0).to_luma8();
+        assert_eq!(stretched.get_pixel(0, 0).0[0], 0);
+        assert_eq!(stretched.get_pixel(99, 0).0[0], 255);
+        assert!(stretched.get_pixel(50, 0).0[0] > 100);
+    }
+}
@@ -0,0 +1,527 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! DICOM (`.dcm`) support, behind the `dicom` cargo feature.
+//!
+//! - `WindowLevel`: user window/level adjustment, relative to the window stored in the file
+//!   (or the full value range if the file has none). Shared by all DICOM images so a series
+//!   can be paged through with the same window.
+//! - `decode`: a minimal Part 10 reader for uncompressed pixel data (implicit/explicit VR
+//!   little endian, 8/16-bit monochrome or 8-bit RGB, first frame only).
+//!
+//! Without the feature, `.dcm` files are not collected and `decode` always returns None.
+
+use std::path::Path;
+
+use image::DynamicImage;
+
+/// Window width multiplier per step (`{` / `}`).
+const WIDTH_STEP: f64 = 1.25;
+
+/// Window center shift per step (`[` / `]`), as a fraction of the default width.
+const LEVEL_STEP: f64 = 0.05;
+
+/// Window/level adjustment in steps from the default window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct WindowLevel {
+    /// Center shift (positive = brighter tissue appears darker).
+    pub level: i32,
+    /// Width change (positive = wider window, less contrast).
+    pub width: i32,
+}
+
+impl WindowLevel {
+    pub fn is_default(self) -> bool {
+        self == Self::default()
+    }
+
+    /// The adjustment that applies to `path` (always the default for non-DICOM images, so
+    /// render cache keys of regular images do not depend on it).
+    pub fn for_path(self, path: &Path) -> Self {
+        if is_dicom(path) {
+            self
+        } else {
+            Self::default()
+        }
+    }
+
+    /// Effective `(center, width)` for a default window.
+    #[cfg_attr(not(feature = "dicom"), allow(dead_code))]
+    fn apply(self, center: f64, width: f64) -> (f64, f64) {
+        let adjusted_width = (width * WIDTH_STEP.powi(self.width)).max(1.0);
+        let adjusted_center = center + f64::from(self.level) * width * LEVEL_STEP;
+        (adjusted_center, adjusted_width)
+    }
+}
+
+/// Check if the path is a DICOM file stiv can open (`.dcm` with the `dicom` feature).
+pub fn is_dicom(path: &Path) -> bool {
+    cfg!(feature = "dicom")
+        && path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("dcm"))
+}
+
+#[cfg(not(feature = "dicom"))]
+pub fn decode(_bytes: &[u8], _window: WindowLevel) -> Option<DynamicImage> {
+    None
+}
+
+/// Decode the first frame of a DICOM file, applying the window for monochrome images.
+#[cfg(feature = "dicom")]
+pub fn decode(bytes: &[u8], window: WindowLevel) -> Option<DynamicImage> {
+    let dataset = parser::parse(bytes)?;
+    dataset.render(window)
+}
+
+#[cfg(feature = "dicom")]
+mod parser {
+    use image::{DynamicImage, GrayImage, RgbImage};
+
+    use super::WindowLevel;
+
+    const PREAMBLE_LEN: usize = 128;
+    const IMPLICIT_VR_LE: &str = "1.2.840.10008.1.2";
+    const EXPLICIT_VR_LE: &str = "1.2.840.10008.1.2.1";
+    /// Length value meaning "until the delimitation item".
+    const UNDEFINED_LEN: u32 = 0xFFFF_FFFF;
+
+    type Tag = (u16, u16);
+
+    const TRANSFER_SYNTAX: Tag = (0x0002, 0x0010);
+    const SAMPLES_PER_PIXEL: Tag = (0x0028, 0x0002);
+    const PHOTOMETRIC: Tag = (0x0028, 0x0004);
+    const PLANAR_CONFIGURATION: Tag = (0x0028, 0x0006);
+    const ROWS: Tag = (0x0028, 0x0010);
+    const COLUMNS: Tag = (0x0028, 0x0011);
+    const BITS_ALLOCATED: Tag = (0x0028, 0x0100);
+    const PIXEL_REPRESENTATION: Tag = (0x0028, 0x0103);
+    const WINDOW_CENTER: Tag = (0x0028, 0x1050);
+    const WINDOW_WIDTH: Tag = (0x0028, 0x1051);
+    const RESCALE_INTERCEPT: Tag = (0x0028, 0x1052);
+    const RESCALE_SLOPE: Tag = (0x0028, 0x1053);
+    const PIXEL_DATA: Tag = (0x7FE0, 0x0010);
+    const ITEM: Tag = (0xFFFE, 0xE000);
+    const ITEM_END: Tag = (0xFFFE, 0xE00D);
+    const SEQUENCE_END: Tag = (0xFFFE, 0xE0DD);
+
+    /// Header of one data element.
+    struct Element {
+        tag: Tag,
+        /// None = undefined length (sequence or encapsulated data).
+        len: Option<usize>,
+    }
+
+    struct Reader<'a> {
+        data: &'a [u8],
+        pos: usize,
+        explicit_vr: bool,
+    }
+
+    impl<'a> Reader<'a> {
+        fn u16(&mut self) -> Option<u16> {
+            let b = self.take(2)?;
+            Some(u16::from_le_bytes([b[0], b[1]]))
+        }
+
+        fn u32(&mut self) -> Option<u32> {
+            let b = self.take(4)?;
+            Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        }
+
+        fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+            let bytes = self.data.get(self.pos..self.pos.checked_add(len)?)?;
+            self.pos += len;
+            Some(bytes)
+        }
+
+        fn element(&mut self) -> Option<Element> {
+            let tag = (self.u16()?, self.u16()?);
+            // Item and delimitation tags never carry a VR.
+            let len = if !self.explicit_vr || tag.0 == 0xFFFE {
+                self.u32()?
+            } else {
+                let vr = self.take(2)?;
+                if matches!(
+                    vr,
+                    b"OB"
+                        | b"OD"
+                        | b"OF"
+                        | b"OL"
+                        | b"OV"
+                        | b"OW"
+                        | b"SQ"
+                        | b"SV"
+                        | b"UC"
+                        | b"UN"
+                        | b"UR"
+                        | b"UT"
+                        | b"UV"
+                ) {
+                    self.take(2)?;
+                    self.u32()?
+                } else {
+                    u32::from(self.u16()?)
+                }
+            };
+            let len = (len != UNDEFINED_LEN).then_some(len as usize);
+            Some(Element { tag, len })
+        }
+
+        /// Skip an element's value, including nested undefined-length sequences.
+        fn skip(&mut self, element: &Element) -> Option<()> {
+            match element.len {
+                Some(len) => self.take(len).map(|_| ()),
+                None => self.skip_sequence(),
+            }
+        }
+
+        fn skip_sequence(&mut self) -> Option<()> {
+            loop {
+                let item = self.element()?;
+                match item.tag {
+                    SEQUENCE_END => return Some(()),
+                    ITEM if item.len.is_some() => self.skip(&item)?,
+                    ITEM => loop {
+                        let element = self.element()?;
+                        if element.tag == ITEM_END {
+                            break;
+                        }
+                        self.skip(&element)?;
+                    },
+                    // Encapsulated pixel data fragments are items too; anything else is malformed.
+                    _ => return None,
+                }
+            }
+        }
+    }
+
+    /// The attributes needed to render the first frame.
+    #[derive(Default)]
+    pub struct Dataset<'a> {
+        rows: u32,
+        columns: u32,
+        samples_per_pixel: u16,
+        bits_allocated: u16,
+        signed: bool,
+        planar: bool,
+        monochrome1: bool,
+        slope: f64,
+        intercept: f64,
+        window: Option<(f64, f64)>,
+        pixels: &'a [u8],
+    }
+
+    pub fn parse(bytes: &[u8]) -> Option<Dataset<'_>> {
+        if bytes.get(PREAMBLE_LEN..PREAMBLE_LEN + 4)? != b"DICM" {
+            return None;
+        }
+        let mut reader = Reader {
+            data: bytes,
+            pos: PREAMBLE_LEN + 4,
+            explicit_vr: true,
+        };
+
+        // File meta group (always explicit VR little endian).
+        let mut transfer_syntax = String::new();
+        while reader.data.get(reader.pos..reader.pos + 2)? == [0x02, 0x00] {
+            let element = reader.element()?;
+            let value = reader.take(element.len?)?;
+            if element.tag == TRANSFER_SYNTAX {
+                transfer_syntax = text(value);
+            }
+        }
+        reader.explicit_vr = match transfer_syntax.as_str() {
+            IMPLICIT_VR_LE => false,
+            EXPLICIT_VR_LE => true,
+            // Big endian and compressed syntaxes are not supported.
+            _ => return None,
+        };
+
+        let mut ds = Dataset {
+            samples_per_pixel: 1,
+            bits_allocated: 16,
+            slope: 1.0,
+            ..Default::default()
+        };
+        let mut window_center = None;
+        let mut window_width = None;
+        loop {
+            let element = reader.element()?;
+            let Some(len) = element.len else {
+                // Encapsulated (compressed) pixel data is not supported.
+                if element.tag == PIXEL_DATA {
+                    return None;
+                }
+                reader.skip_sequence()?;
+                continue;
+            };
+            let value = reader.take(len)?;
+            match element.tag {
+                SAMPLES_PER_PIXEL => ds.samples_per_pixel = us(value)?,
+                PHOTOMETRIC => ds.monochrome1 = text(value) == "MONOCHROME1",
+                PLANAR_CONFIGURATION => ds.planar = us(value)? == 1,
+                ROWS => ds.rows = u32::from(us(value)?),
+                COLUMNS => ds.columns = u32::from(us(value)?),
+                BITS_ALLOCATED => ds.bits_allocated = us(value)?,
+                PIXEL_REPRESENTATION => ds.signed = us(value)? == 1,
+                WINDOW_CENTER => window_center = decimal(value),
+                WINDOW_WIDTH => window_width = decimal(value),
+                RESCALE_INTERCEPT => ds.intercept = decimal(value).unwrap_or(0.0),
+                RESCALE_SLOPE => ds.slope = decimal(value).unwrap_or(1.0),
+                PIXEL_DATA => {
+                    ds.pixels = value;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        ds.window = window_center.zip(window_width.filter(|w| *w > 0.0));
+        Some(ds)
+    }
+
+    impl Dataset<'_> {
+        pub fn render(&self, window: WindowLevel) -> Option<DynamicImage> {
+            let (w, h) = (self.columns, self.rows);
+            let count = (w as usize).checked_mul(h as usize)?;
+            match (self.samples_per_pixel, self.bits_allocated) {
+                (1, 8 | 16) => {
+                    let values = self.modality_values(count)?;
+                    let gray = self.apply_window(&values, window);
+                    GrayImage::from_raw(w, h, gray).map(DynamicImage::ImageLuma8)
+                }
+                (3, 8) => {
+                    let data = self.pixels.get(..count * 3)?;
+                    let rgb = if self.planar {
+                        (0..count)
+                            .flat_map(|i| [data[i], data[count + i], data[2 * count + i]])
+                            .collect()
+                    } else {
+                        data.to_vec()
+                    };
+                    RgbImage::from_raw(w, h, rgb).map(DynamicImage::ImageRgb8)
+                }
+                _ => None,
+            }
+        }
+
+        /// Stored values of the first frame with the rescale slope/intercept applied.
+        fn modality_values(&self, count: usize) -> Option<Vec<f64>> {
+            let raw: Vec<f64> = if self.bits_allocated == 8 {
+                let data = self.pixels.get(..count)?;
+                data.iter()
+                    .map(|&v| {
+                        if self.signed {
+                            f64::from(v as i8)
+                        } else {
+                            f64::from(v)
+                        }
+                    })
+                    .collect()
+            } else {
+                let data = self.pixels.get(..count * 2)?;
+                data.chunks_exact(2)
+                    .map(|b| {
+                        let v = u16::from_le_bytes([b[0], b[1]]);
+                        if self.signed {
+                            f64::from(v as i16)
+                        } else {
+                            f64::from(v)
+                        }
+                    })
+                    .collect()
+            };
+            Some(
+                raw.into_iter()
+                    .map(|v| v * self.slope + self.intercept)
+                    .collect(),
+            )
+        }
+
+        /// Map values to 8-bit gray with the linear VOI window (PS3.3 C.11.2.1.2).
+        fn apply_window(&self, values: &[f64], adjust: WindowLevel) -> Vec<u8> {
+            let (center, width) = self.window.unwrap_or_else(|| {
+                let (min, max) = values
+                    .iter()
+                    .fold((f64::MAX, f64::MIN), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+                ((min + max) / 2.0, (max - min).max(1.0))
+            });
+            let (center, width) = adjust.apply(center, width);
+            let width = width.max(2.0);
+            values
+                .iter()
+                .map(|&v| {
+                    let t = ((v - (center - 0.5)) / (width - 1.0) + 0.5).clamp(0.0, 1.0);
+                    let gray = (t * 255.0).round() as u8;
+                    if self.monochrome1 { 255 - gray } else { gray }
+                })
+                .collect()
+        }
+    }
+
+    fn us(value: &[u8]) -> Option<u16> {
+        Some(u16::from_le_bytes([*value.first()?, *value.get(1)?]))
+    }
+
+    /// String value without padding (trailing NUL or space).
+    fn text(value: &[u8]) -> String {
+        String::from_utf8_lossy(value)
+            .trim_end_matches(['\0', ' '])
+            .trim()
+            .to_string()
+    }
+
+    /// First value of a decimal string (multi-valued windows are `40\400`).
+    fn decimal(value: &[u8]) -> Option<f64> {
+        text(value).split('\\').next()?.trim().parse().ok()
+    }
+}
+
+#[cfg(all(test, feature = "dicom"))]
+mod tests {
+    use super::*;
+
+    /// Minimal Part 10 file writer for tests.
+    struct Builder {
+        bytes: Vec<u8>,
+        explicit_vr: bool,
+    }
+
+    impl Builder {
+        fn new(transfer_syntax: &str) -> Self {
+            let mut b = Self {
+                bytes: vec![0; 128],
+                explicit_vr: true,
+            };
+            b.bytes.extend_from_slice(b"DICM");
+            let mut uid = transfer_syntax.as_bytes().to_vec();
+            if uid.len() % 2 == 1 {
+                uid.push(0);
+            }
+            b.element((0x0002, 0x0010), b"UI", &uid);
+            b.explicit_vr = transfer_syntax == "1.2.840.10008.1.2.1";
+            b
+        }
+
+        fn header(&mut self, tag: (u16, u16), vr: &[u8; 2], len: u32) {
+            self.bytes.extend_from_slice(&tag.0.to_le_bytes());
+            self.bytes.extend_from_slice(&tag.1.to_le_bytes());
+            if !self.explicit_vr {
+                self.bytes.extend_from_slice(&len.to_le_bytes());
+            } else if matches!(vr, b"OB" | b"OW" | b"SQ") {
+                self.bytes.extend_from_slice(vr);
+                self.bytes.extend_from_slice(&[0, 0]);
+                self.bytes.extend_from_slice(&len.to_le_bytes());
+            } else {
+                self.bytes.extend_from_slice(vr);
+                self.bytes.extend_from_slice(&(len as u16).to_le_bytes());
+            }
+        }
+
+        fn element(&mut self, tag: (u16, u16), vr: &[u8; 2], value: &[u8]) -> &mut Self {
+            self.header(tag, vr, value.len() as u32);
+            self.bytes.extend_from_slice(value);
+            self
+        }
+
+        fn us(&mut self, tag: (u16, u16), v: u16) -> &mut Self {
+            self.element(tag, b"US", &v.to_le_bytes())
+        }
+
+        fn ds(&mut self, tag: (u16, u16), v: &str) -> &mut Self {
+            let mut s = v.as_bytes().to_vec();
+            if s.len() % 2 == 1 {
+                s.push(b' ');
+            }
+            self.element(tag, b"DS", &s)
+        }
+
+        /// Undefined-length sequence with one undefined-length item holding a nested sequence.
+        fn nested_sequence(&mut self) -> &mut Self {
+            let raw = |b: &mut Self, tag: (u16, u16), len: u32| {
+                b.bytes.extend_from_slice(&tag.0.to_le_bytes());
+                b.bytes.extend_from_slice(&tag.1.to_le_bytes());
+                b.bytes.extend_from_slice(&len.to_le_bytes());
+            };
+            self.header((0x0008, 0x1140), b"SQ", u32::MAX);
+            raw(self, (0xFFFE, 0xE000), u32::MAX);
+            self.header((0x0008, 0x1199), b"SQ", u32::MAX);
+            raw(self, (0xFFFE, 0xE000), 4);
+            self.bytes.extend_from_slice(&[1, 2, 3, 4]);
+            raw(self, (0xFFFE, 0xE0DD), 0);
+            raw(self, (0xFFFE, 0xE00D), 0);
+            raw(self, (0xFFFE, 0xE0DD), 0);
+            self
+        }
+
+        fn mono16(&mut self, w: u16, h: u16, values: &[i16]) -> Vec<u8> {
+            self.us((0x0028, 0x0002), 1)
+                .element((0x0028, 0x0004), b"CS", b"MONOCHROME2 ")
+                .us((0x0028, 0x0010), h)
+                .us((0x0028, 0x0011), w)
+                .us((0x0028, 0x0100), 16)
+                .us((0x0028, 0x0103), 1);
+            let data: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+            self.element((0x7FE0, 0x0010), b"OW", &data);
+            self.bytes.clone()
+        }
+    }
+
+    fn gray(img: &DynamicImage) -> Vec<u8> {
+        img.to_luma8().into_raw()
+    }
+
+    #[test]
+    fn test_is_dicom() {
+        assert!(is_dicom(Path::new("ct/0001.DCM")));
+        assert!(!is_dicom(Path::new("ct/0001.png")));
+        let adjusted = WindowLevel { level: 1, width: 0 };
+        assert!(adjusted.for_path(Path::new("a.png")).is_default());
+        assert_eq!(adjusted.for_path(Path::new("a.dcm")), adjusted);
+    }
+
+    #[test]
+    fn test_decode_explicit_vr_with_window() {
+        let bytes = Builder::new("1.2.840.10008.1.2.1")
+            .ds((0x0028, 0x1050), "40\\50")
+            .ds((0x0028, 0x1051), "400\\350")
+            .mono16(4, 1, &[-1000, -160, 240, 3000]);
+        let img = decode(&bytes, WindowLevel::default()).unwrap();
+        assert_eq!((img.width(), img.height()), (4, 1));
+        let px = gray(&img);
+        assert_eq!(px[0], 0);
+        assert!(px[1] <= 1);
+        assert!(px[2] >= 254);
+        assert_eq!(px[3], 255);
+
+        // Wider window: the same values move towards mid-gray.
+        let wide = gray(&decode(&bytes, WindowLevel { level: 0, width: 4 }).unwrap());
+        assert!(wide[1] > px[1] && wide[2] < px[2]);
+        // Higher level: everything gets darker.
+        let high = gray(&decode(&bytes, WindowLevel { level: 5, width: 0 }).unwrap());
+        assert!(high[2] < px[2]);
+    }
+
+    #[test]
+    fn test_decode_implicit_vr_skips_sequences_and_rescales() {
+        let mut builder = Builder::new("1.2.840.10008.1.2");
+        builder
+            .nested_sequence()
+            .ds((0x0028, 0x1052), "-1024")
+            .ds((0x0028, 0x1053), "1");
+        let bytes = builder.mono16(2, 2, &[0, 1024, 2048, 4095]);
+        // No window in the file: the full value range is used.
+        let px = gray(&decode(&bytes, WindowLevel::default()).unwrap());
+        assert_eq!(px[0], 0);
+        assert_eq!(px[3], 255);
+        assert!(px[0] < px[1] && px[1] < px[2]);
+    }
+
+    #[test]
+    fn test_decode_rejects_unsupported() {
+        assert!(decode(b"not a dicom file", WindowLevel::default()).is_none());
+        let jpeg = Builder::new("1.2.840.10008.1.2.4.50").mono16(1, 1, &[0]);
+        assert!(decode(&jpeg, WindowLevel::default()).is_none());
+    }
+}
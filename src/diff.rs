@@ -0,0 +1,69 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Pixel diff against a reference image (`:diff`, `--diff`).
+//!
+//! While a reference is set, Single mode renders the virtual path `<image>\0diff\0<reference>`
+//! (`VirtualPath::Diff`), so render caching, zoom and prefetching treat each comparison like its
+//! own image. The worker decodes both images and renders a heatmap: unchanged pixels are a
+//! dimmed grayscale of the image, changed ones go from red (small change) to yellow (largest),
+//! and the area covered by only one image (different sizes) is magenta.
+
+use image::{DynamicImage, Rgb, RgbImage, RgbaImage};
+
+/// Brightness of unchanged pixels, as a fraction of their luma.
+const CONTEXT_DIM: f32 = 0.3;
+
+const OUTSIDE: Rgb<u8> = Rgb([255, 0, 255]);
+
+/// Heatmap of the per-pixel difference between two images (the larger size of both).
+pub fn heatmap(image: &DynamicImage, reference: &DynamicImage) -> DynamicImage {
+    let (a, b) = (image.to_rgba8(), reference.to_rgba8());
+    let width = a.width().max(b.width());
+    let height = a.height().max(b.height());
+    let pixel = |img: &RgbaImage, x, y| {
+        (x < img.width() && y < img.height()).then(|| img.get_pixel(x, y).0)
+    };
+    DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+        match (pixel(&a, x, y), pixel(&b, x, y)) {
+            (Some(pa), Some(pb)) => {
+                let delta = pa.iter().zip(pb).map(|(&ca, cb)| ca.abs_diff(cb)).max();
+                match delta.unwrap_or(0) {
+                    0 => {
+                        let luma = 0.2126 * f32::from(pa[0])
+                            + 0.7152 * f32::from(pa[1])
+                            + 0.0722 * f32::from(pa[2]);
+                        let v = (luma * CONTEXT_DIM * f32::from(pa[3]) / 255.0).round() as u8;
+                        Rgb([v, v, v])
+                    }
+                    d => heat(d),
+                }
+            }
+            _ => OUTSIDE,
+        }
+    }))
+}
+
+/// Red for the smallest change to yellow for the largest; small changes are boosted so a
+/// single off-by-one pixel is still easy to spot.
+fn heat(delta: u8) -> Rgb<u8> {
+    let t = (f32::from(delta) / 255.0).sqrt();
+    Rgb([255, (t * 255.0) as u8, 0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heatmap() {
+        let a = RgbaImage::from_pixel(3, 2, image::Rgba([100, 100, 100, 255]));
+        let mut b = RgbaImage::from_pixel(2, 2, image::Rgba([100, 100, 100, 255]));
+        b.put_pixel(1, 1, image::Rgba([101, 100, 100, 255]));
+        let out = heatmap(&DynamicImage::ImageRgba8(a), &DynamicImage::ImageRgba8(b)).to_rgb8();
+        assert_eq!(out.dimensions(), (3, 2));
+        assert_eq!(out.get_pixel(0, 0).0, [30, 30, 30]);
+        assert_eq!(out.get_pixel(1, 1).0[0], 255);
+        assert_eq!(out.get_pixel(2, 0), &OUTSIDE);
+    }
+}
@@ -0,0 +1,440 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Persistent render cache (`disk_cache = true`).
+//!
+//! Encoded Single-mode renders are written to `~/.cache/stiv/render/`, so relaunching on the
+//! same directory with the same terminal size shows images without decoding them again.
+//!
+//! - Entries are keyed by the source file (canonical path, mtime and size), the render key
+//!   (target size, fit mode, crop, rotation, window/level) and the render settings (encoder,
+//!   filters, tmux), so any change simply misses. File names are the FNV-1a hash of an explicit
+//!   byte encoding of the key (`key_bytes`), so they stay the same across Rust releases.
+//! - The encoded chunks embed the KGP image ID of the run that wrote them; it is rewritten to
+//!   the current ID on load.
+//! - Writes and pruning run on a background thread. When the directory grows beyond
+//!   `disk_cache_mb`, the least recently used entries (oldest mtime; hits touch the file) are
+//!   removed until it is below 90% of the cap.
+
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, bail};
+
+use crate::app::CacheKey;
+use crate::archive;
+use crate::fit::FitMode;
+use crate::grid::Grid;
+use crate::kgp;
+use crate::pages;
+
+/// File header; bump the version when the layout, the key encoding or the encoder output
+/// changes.
+const MAGIC: &[u8; 8] = b"STIVRC2\n";
+
+/// Extension of cache entries (anything else in the directory is left alone).
+const EXTENSION: &str = "kgp";
+
+/// Prune after this many stores (besides once at startup).
+const PRUNE_INTERVAL: usize = 64;
+
+/// A render loaded from disk, already retargeted to the current KGP image ID.
+pub struct DiskEntry {
+    pub original_size: (u32, u32),
+    pub actual_size: (u32, u32),
+    pub encoded_chunks: Arc<Vec<Vec<u8>>>,
+}
+
+struct StoreJob {
+    file: PathBuf,
+    data: Vec<u8>,
+}
+
+pub struct DiskCache {
+    dir: PathBuf,
+    /// Hash of the settings that change encoder output.
+    settings: u64,
+    tx: Sender<StoreJob>,
+}
+
+impl DiskCache {
+    /// Open the cache in the default location (None if there is no cache directory).
+    pub fn open(max_mb: u64, settings: &str) -> Option<Self> {
+        let dir = dirs::cache_dir()?.join("stiv").join("render");
+        Some(Self::with_dir(
+            dir,
+            max_mb.saturating_mul(1024 * 1024),
+            settings,
+        ))
+    }
+
+    fn with_dir(dir: PathBuf, max_bytes: u64, settings: &str) -> Self {
+        let (tx, rx) = mpsc::channel::<StoreJob>();
+        let worker_dir = dir.clone();
+        thread::spawn(move || {
+            let mut stores = 0;
+            prune(&worker_dir, max_bytes);
+            while let Ok(StoreJob { file, data }) = rx.recv() {
+                _ = write_atomic(&worker_dir, &file, &data);
+                stores += 1;
+                if stores % PRUNE_INTERVAL == 0 {
+                    prune(&worker_dir, max_bytes);
+                }
+            }
+        });
+
+        Self {
+            dir,
+            settings: fnv1a(settings.as_bytes()),
+            tx,
+        }
+    }
+
+    /// Load a render for `key`, rewriting its image ID to `kgp_id`.
+    pub fn load(&self, key: &CacheKey, kgp_id: u32) -> Option<DiskEntry> {
+        let file = self.entry_path(key)?;
+        let mut bytes = Vec::new();
+        File::open(&file).ok()?.read_to_end(&mut bytes).ok()?;
+        let (stored_id, mut entry) = decode_entry(&bytes).ok()?;
+        if stored_id != kgp_id {
+            let chunks = kgp::retarget_chunks(&entry.encoded_chunks, stored_id, kgp_id)?;
+            entry.encoded_chunks = Arc::new(chunks);
+        }
+        // Mark as recently used for pruning.
+        if let Ok(f) = File::options().write(true).open(&file) {
+            _ = f.set_modified(SystemTime::now());
+        }
+        Some(entry)
+    }
+
+    /// Write a render in the background (no-op for sources that cannot be identified).
+    pub fn store(
+        &self,
+        key: &CacheKey,
+        kgp_id: u32,
+        original_size: (u32, u32),
+        actual_size: (u32, u32),
+        encoded_chunks: &[Vec<u8>],
+    ) {
+        let Some(file) = self.entry_path(key) else {
+            return;
+        };
+        if file.exists() {
+            return;
+        }
+        let data = encode_entry(kgp_id, original_size, actual_size, encoded_chunks);
+        _ = self.tx.send(StoreJob { file, data });
+    }
+
+    fn entry_path(&self, key: &CacheKey) -> Option<PathBuf> {
+        let source = source_identity(&key.path)?;
+        let hash = fnv1a(&key_bytes(self.settings, &source, key));
+        Some(self.dir.join(format!("{hash:016x}.{EXTENSION}")))
+    }
+}
+
+/// The entry key as bytes: settings hash, source identity and render key, little-endian, with
+/// enums written as fixed tags (not `Hash`, whose output may change between Rust releases).
+fn key_bytes(settings: u64, source: &(PathBuf, u128, u64), key: &CacheKey) -> Vec<u8> {
+    let (canonical, modified, len) = source;
+    let path = canonical.as_os_str().as_encoded_bytes();
+    let mut out = Vec::with_capacity(path.len() + 96);
+    out.extend_from_slice(&settings.to_le_bytes());
+    out.extend_from_slice(&(path.len() as u64).to_le_bytes());
+    out.extend_from_slice(path);
+    out.extend_from_slice(&modified.to_le_bytes());
+    out.extend_from_slice(&len.to_le_bytes());
+    for v in [key.target.0, key.target.1] {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    out.push(match key.fit_mode {
+        FitMode::Normal => 0,
+        FitMode::Fit => 1,
+        FitMode::Actual => 2,
+    });
+    match key.crop {
+        None => out.push(0),
+        Some(crop) => {
+            out.push(1);
+            for v in [crop.x, crop.y, crop.width, crop.height] {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+    }
+    out.extend_from_slice(&key.rotation.degrees().to_le_bytes());
+    for v in [key.window.level, key.window.width] {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    let adjust = key.adjust;
+    out.extend_from_slice(&[u8::from(adjust.grayscale), u8::from(adjust.invert)]);
+    match adjust.matte {
+        None => out.push(0),
+        Some(rgb) => {
+            out.push(1);
+            out.extend_from_slice(&rgb);
+        }
+    }
+    match adjust.grid {
+        Grid::Off => out.push(0),
+        Grid::Thirds => out.push(1),
+        Grid::Pixels(spacing) => {
+            out.push(2);
+            out.extend_from_slice(&spacing.to_le_bytes());
+        }
+    }
+    out
+}
+
+/// 64-bit FNV-1a, a hash whose output is fixed by its specification.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Canonical path, mtime (ns since epoch) and size of the file backing `path`.
+/// Archive members and TIFF pages are identified by their file.
+fn source_identity(path: &Path) -> Option<(PathBuf, u128, u64)> {
+    let (file, member) = match archive::split_member_path(path) {
+        Some((archive, member)) => (archive, Some(member)),
+        None => match pages::split_page_path(path) {
+            Some((file, _)) => (file, path.strip_prefix(file).ok().map(Path::to_path_buf)),
+            None => (path, None),
+        },
+    };
+    let meta = fs::metadata(file).ok()?;
+    let modified = meta
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_nanos();
+    let mut canonical = fs::canonicalize(file).ok()?;
+    if let Some(member) = member {
+        canonical.push(member);
+    }
+    Some((canonical, modified, meta.len()))
+}
+
+fn encode_entry(
+    kgp_id: u32,
+    original_size: (u32, u32),
+    actual_size: (u32, u32),
+    chunks: &[Vec<u8>],
+) -> Vec<u8> {
+    let payload: usize = chunks.iter().map(|c| c.len() + 4).sum();
+    let mut out = Vec::with_capacity(MAGIC.len() + 24 + payload);
+    out.extend_from_slice(MAGIC);
+    for v in [
+        kgp_id,
+        original_size.0,
+        original_size.1,
+        actual_size.0,
+        actual_size.1,
+        chunks.len() as u32,
+    ] {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    for chunk in chunks {
+        out.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        out.extend_from_slice(chunk);
+    }
+    out
+}
+
+/// Parse an entry into `(kgp_id, entry)`.
+fn decode_entry(bytes: &[u8]) -> Result<(u32, DiskEntry)> {
+    let Some(mut rest) = bytes.strip_prefix(MAGIC.as_slice()) else {
+        bail!("Not a stiv render cache entry");
+    };
+    let mut next_u32 = || -> Result<u32> {
+        let (head, tail) = rest.split_at_checked(4).context("Truncated entry")?;
+        rest = tail;
+        Ok(u32::from_le_bytes(head.try_into()?))
+    };
+    let kgp_id = next_u32()?;
+    let original_size = (next_u32()?, next_u32()?);
+    let actual_size = (next_u32()?, next_u32()?);
+    let count = next_u32()? as usize;
+
+    let mut chunks = Vec::with_capacity(count.min(4096));
+    for _ in 0..count {
+        let (head, tail) = rest.split_at_checked(4).context("Truncated entry")?;
+        let len = u32::from_le_bytes(head.try_into()?) as usize;
+        let (chunk, tail) = tail.split_at_checked(len).context("Truncated entry")?;
+        chunks.push(chunk.to_vec());
+        rest = tail;
+    }
+    if chunks.is_empty() || !rest.is_empty() {
+        bail!("Malformed entry");
+    }
+    Ok((
+        kgp_id,
+        DiskEntry {
+            original_size,
+            actual_size,
+            encoded_chunks: Arc::new(chunks),
+        },
+    ))
+}
+
+/// Write via a temporary file so readers never see a partial entry.
+fn write_atomic(dir: &Path, file: &Path, data: &[u8]) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    let tmp = file.with_extension(format!("tmp{}", std::process::id()));
+    File::create(&tmp)?.write_all(data)?;
+    fs::rename(&tmp, file).inspect_err(|_| {
+        _ = fs::remove_file(&tmp);
+    })?;
+    Ok(())
+}
+
+/// Delete least recently used entries until the directory is below 90% of `max_bytes`.
+fn prune(dir: &Path, max_bytes: u64) {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<(SystemTime, u64, PathBuf)> = read_dir
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == EXTENSION))
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            Some((meta.modified().ok()?, meta.len(), e.path()))
+        })
+        .collect();
+    let mut total: u64 = entries.iter().map(|(_, len, _)| len).sum();
+    if total <= max_bytes {
+        return;
+    }
+    let goal = max_bytes / 10 * 9;
+    entries.sort_by_key(|(modified, _, _)| *modified);
+    for (_, len, path) in entries {
+        if total <= goal {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total -= len;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adjust::Adjustments;
+    use crate::dicom::WindowLevel;
+    use crate::fit::FitMode;
+    use crate::rotation::Rotation;
+    use std::time::Duration;
+
+    fn test_key(path: &Path, target: (u32, u32)) -> CacheKey {
+        CacheKey {
+            path: path.to_path_buf(),
+            target,
+            fit_mode: FitMode::Normal,
+            crop: None,
+            rotation: Rotation::None,
+            window: WindowLevel::default(),
+            adjust: Adjustments::default(),
+        }
+    }
+
+    fn wait_for(path: &Path) {
+        for _ in 0..200 {
+            if path.exists() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("{path:?} was not written");
+    }
+
+    #[test]
+    fn test_entry_roundtrip() {
+        let chunks = vec![b"first".to_vec(), b"second".to_vec()];
+        let bytes = encode_entry(7, (100, 50), (20, 10), &chunks);
+        let (id, entry) = decode_entry(&bytes).unwrap();
+        assert_eq!(id, 7);
+        assert_eq!(entry.original_size, (100, 50));
+        assert_eq!(entry.actual_size, (20, 10));
+        assert_eq!(*entry.encoded_chunks, chunks);
+        assert!(decode_entry(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode_entry(b"garbage").is_err());
+    }
+
+    #[test]
+    fn test_store_and_load_retargets_id() {
+        let root = PathBuf::from("/tmp/stiv_test_disk_cache_roundtrip");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let image = root.join("a.png");
+        fs::write(&image, b"not really a png").unwrap();
+
+        let img = image::DynamicImage::new_rgb8(8, 8);
+        let chunks = kgp::encode_chunks(&img, 11, false, None, false);
+        let cache = DiskCache::with_dir(root.join("render"), 1 << 20, "settings");
+        let key = test_key(&image, (80, 80));
+        cache.store(&key, 11, (8, 8), (8, 8), &chunks);
+        wait_for(&cache.entry_path(&key).unwrap());
+
+        let entry = cache.load(&key, 22).unwrap();
+        assert_eq!(entry.actual_size, (8, 8));
+        assert!(String::from_utf8_lossy(&entry.encoded_chunks[0]).contains(",i=22,"));
+
+        // Other target size, other settings or a modified source miss.
+        assert!(cache.load(&test_key(&image, (40, 40)), 22).is_none());
+        let other = DiskCache::with_dir(root.join("render"), 1 << 20, "other");
+        assert!(other.load(&key, 22).is_none());
+        fs::write(&image, b"modified and longer").unwrap();
+        assert!(cache.load(&key, 22).is_none());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_entry_names_are_stable() {
+        // Pinned FNV-1a values: entry names must not change with the toolchain.
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        let key = test_key(Path::new("/img.png"), (640, 480));
+        let source = (PathBuf::from("/img.png"), 1, 2);
+        let bytes = key_bytes(3, &source, &key);
+        assert_eq!(
+            bytes.len(),
+            8 + 8 + 8 + 16 + 8 + 8 + 1 + 1 + 2 + 8 + 2 + 1 + 1
+        );
+        let mut rotated = key.clone();
+        rotated.rotation = Rotation::Cw90;
+        assert_ne!(bytes, key_bytes(3, &source, &rotated));
+    }
+
+    #[test]
+    fn test_prune_removes_oldest() {
+        let dir = PathBuf::from("/tmp/stiv_test_disk_cache_prune");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let now = SystemTime::now();
+        for i in 0..4u64 {
+            let path = dir.join(format!("{i}.{EXTENSION}"));
+            fs::write(&path, vec![0u8; 100]).unwrap();
+            let f = File::options().write(true).open(&path).unwrap();
+            f.set_modified(now - Duration::from_secs(100 - i)).unwrap();
+        }
+        fs::write(dir.join("keep.txt"), vec![0u8; 1000]).unwrap();
+
+        prune(&dir, 300);
+        // 400 > 300, so the oldest go until total <= 270.
+        assert!(!dir.join(format!("0.{EXTENSION}")).exists());
+        assert!(!dir.join(format!("1.{EXTENSION}")).exists());
+        assert!(dir.join(format!("2.{EXTENSION}")).exists());
+        assert!(dir.join(format!("3.{EXTENSION}")).exists());
+        assert!(dir.join("keep.txt").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,532 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Minimal EXIF reader.
+//!
+//! Reads the text and numeric tags of IFD0, the Exif sub-IFD and the GPS IFD: enough for the
+//! capture date used by date grouping and the metadata summary copied by `"ey`. JPEG files are
+//! scanned for their APP1 segment directly (reading only the file header); other formats go
+//! through the `image` decoder.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes read from the start of a JPEG file when looking for the APP1 segment.
+const JPEG_SCAN_BYTES: u64 = 128 * 1024;
+/// Values kept per numeric tag (longer arrays such as maker data are cut).
+const MAX_VALUES: usize = 16;
+
+const TAG_MAKE: u16 = 0x010F;
+const TAG_MODEL: u16 = 0x0110;
+/// `DateTime` (IFD0): file change date, `YYYY:MM:DD hh:mm:ss`.
+pub const TAG_DATE_TIME: u16 = 0x0132;
+const TAG_EXPOSURE_TIME: u16 = 0x829A;
+const TAG_F_NUMBER: u16 = 0x829D;
+const TAG_ISO: u16 = 0x8827;
+/// `DateTimeOriginal` (Exif IFD): capture date, `YYYY:MM:DD hh:mm:ss`.
+pub const TAG_DATE_TIME_ORIGINAL: u16 = 0x9003;
+const TAG_FOCAL_LENGTH: u16 = 0x920A;
+const TAG_LENS_MODEL: u16 = 0xA434;
+/// Pointer to the Exif sub-IFD.
+const TAG_EXIF_IFD: u16 = 0x8769;
+/// Pointer to the GPS IFD.
+const TAG_GPS_IFD: u16 = 0x8825;
+
+// GPS IFD tags (a separate tag namespace).
+const GPS_LATITUDE_REF: u16 = 0x0001;
+const GPS_LATITUDE: u16 = 0x0002;
+const GPS_LONGITUDE_REF: u16 = 0x0003;
+const GPS_LONGITUDE: u16 = 0x0004;
+
+const TYPE_ASCII: u16 = 2;
+const TYPE_SHORT: u16 = 3;
+const TYPE_LONG: u16 = 4;
+const TYPE_RATIONAL: u16 = 5;
+const TYPE_SRATIONAL: u16 = 10;
+
+/// Tags of one or more IFDs.
+#[derive(Debug, Default)]
+struct Tags {
+    ascii: BTreeMap<u16, String>,
+    numbers: BTreeMap<u16, Vec<f64>>,
+}
+
+#[derive(Debug, Default)]
+pub struct Exif {
+    /// IFD0 and the Exif sub-IFD (their tag numbers do not overlap).
+    main: Tags,
+    gps: Tags,
+}
+
+impl Exif {
+    /// Read the EXIF block of an image file (None if it has none or it cannot be parsed).
+    pub fn read(path: &Path) -> Option<Self> {
+        let data = read_jpeg_app1(path).or_else(|| read_with_decoder(path))?;
+        Self::parse(&data)
+    }
+
+    /// Parse a TIFF-structured EXIF block (starting at the `II`/`MM` byte order mark).
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let tiff = Tiff::new(data)?;
+        let mut exif = Self::default();
+        let ifd0 = tiff.u32(4)?;
+        let pointers = read_ifd(&tiff, ifd0, &mut exif.main);
+        if let Some(&sub) = pointers.get(&TAG_EXIF_IFD) {
+            read_ifd(&tiff, sub, &mut exif.main);
+        }
+        if let Some(&gps) = pointers.get(&TAG_GPS_IFD) {
+            read_ifd(&tiff, gps, &mut exif.gps);
+        }
+        Some(exif)
+    }
+
+    /// Text value of `tag` (trailing NULs and spaces removed).
+    pub fn ascii(&self, tag: u16) -> Option<&str> {
+        self.main.ascii.get(&tag).map(String::as_str)
+    }
+
+    /// First numeric value of `tag` (rationals as `f64`).
+    pub fn number(&self, tag: u16) -> Option<f64> {
+        self.main.numbers.get(&tag)?.first().copied()
+    }
+
+    /// Capture day as `YYYY-MM-DD` (`DateTimeOriginal`, else `DateTime`).
+    pub fn capture_day(&self) -> Option<String> {
+        [TAG_DATE_TIME_ORIGINAL, TAG_DATE_TIME]
+            .iter()
+            .find_map(|&tag| parse_day(self.ascii(tag)?))
+    }
+
+    /// Position as signed decimal degrees `(latitude, longitude)`.
+    pub fn gps(&self) -> Option<(f64, f64)> {
+        let coord = |value: u16, reference: u16, negative: &str| {
+            let dms = self.gps.numbers.get(&value)?;
+            let mut deg = dms.first()?
+                + dms.get(1).unwrap_or(&0.0) / 60.0
+                + dms.get(2).unwrap_or(&0.0) / 3600.0;
+            if self
+                .gps
+                .ascii
+                .get(&reference)
+                .is_some_and(|r| r == negative)
+            {
+                deg = -deg;
+            }
+            deg.is_finite().then_some(deg)
+        };
+        Some((
+            coord(GPS_LATITUDE, GPS_LATITUDE_REF, "S")?,
+            coord(GPS_LONGITUDE, GPS_LONGITUDE_REF, "W")?,
+        ))
+    }
+
+    /// Human-readable summary lines (camera, lens, date, exposure, GPS); only known fields.
+    pub fn summary_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let camera = match (self.ascii(TAG_MAKE), self.ascii(TAG_MODEL)) {
+            // Most models already start with the make ("Canon EOS R5").
+            (Some(make), Some(model)) if model.starts_with(make) => Some(model.to_string()),
+            (Some(make), Some(model)) => Some(format!("{make} {model}")),
+            (make, model) => make.or(model).map(str::to_string),
+        };
+        if let Some(camera) = camera.filter(|c| !c.is_empty()) {
+            lines.push(format!("Camera: {camera}"));
+        }
+        if let Some(lens) = self.ascii(TAG_LENS_MODEL).filter(|l| !l.is_empty()) {
+            lines.push(format!("Lens: {lens}"));
+        }
+        if let Some(date) = self
+            .ascii(TAG_DATE_TIME_ORIGINAL)
+            .or(self.ascii(TAG_DATE_TIME))
+        {
+            lines.push(format!("Taken: {date}"));
+        }
+        let exposure: Vec<String> = [
+            self.number(TAG_EXPOSURE_TIME).map(format_exposure),
+            self.number(TAG_F_NUMBER).map(|f| format!("f/{f:.1}")),
+            self.number(TAG_ISO).map(|iso| format!("ISO {iso}")),
+            self.number(TAG_FOCAL_LENGTH)
+                .map(|mm| format!("{mm:.0} mm")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !exposure.is_empty() {
+            lines.push(format!("Exposure: {}", exposure.join(", ")));
+        }
+        if let Some((lat, lon)) = self.gps() {
+            lines.push(format!("GPS: {lat:.6}, {lon:.6}"));
+        }
+        lines
+    }
+}
+
+/// Collect the entries of the IFD at `offset` into `tags`; returns the IFD pointers found.
+fn read_ifd(tiff: &Tiff, offset: u32, tags: &mut Tags) -> BTreeMap<u16, u32> {
+    let offset = offset as usize;
+    let mut pointers = BTreeMap::new();
+    let Some(count) = tiff.u16(offset) else {
+        return pointers;
+    };
+    for i in 0..usize::from(count) {
+        let entry = offset + 2 + i * 12;
+        let (Some(tag), Some(kind), Some(n)) =
+            (tiff.u16(entry), tiff.u16(entry + 2), tiff.u32(entry + 4))
+        else {
+            break;
+        };
+        let n = n as usize;
+        let size = match kind {
+            TYPE_ASCII => 1,
+            TYPE_SHORT => 2,
+            TYPE_LONG => 4,
+            TYPE_RATIONAL | TYPE_SRATIONAL => 8,
+            _ => continue,
+        };
+        // Values up to 4 bytes are stored in the entry itself.
+        let start = if n.saturating_mul(size) <= 4 {
+            entry + 8
+        } else {
+            match tiff.u32(entry + 8) {
+                Some(at) => at as usize,
+                None => continue,
+            }
+        };
+        if kind == TYPE_ASCII {
+            if let Some(bytes) = tiff.data.get(start..start.saturating_add(n)) {
+                let text = String::from_utf8_lossy(bytes);
+                let text = text.trim_end_matches(['\0', ' ']).to_string();
+                tags.ascii.insert(tag, text);
+            }
+            continue;
+        }
+        let values: Vec<f64> = (0..n.min(MAX_VALUES))
+            .map_while(|k| {
+                let at = start + k * size;
+                match kind {
+                    TYPE_SHORT => tiff.u16(at).map(f64::from),
+                    TYPE_LONG => tiff.u32(at).map(f64::from),
+                    TYPE_RATIONAL => {
+                        let (num, den) = (tiff.u32(at)?, tiff.u32(at + 4)?);
+                        (den != 0).then(|| f64::from(num) / f64::from(den))
+                    }
+                    _ => {
+                        let (num, den) = (tiff.u32(at)? as i32, tiff.u32(at + 4)? as i32);
+                        (den != 0).then(|| f64::from(num) / f64::from(den))
+                    }
+                }
+            })
+            .collect();
+        if matches!(tag, TAG_EXIF_IFD | TAG_GPS_IFD) && kind == TYPE_LONG {
+            if let Some(&ptr) = values.first() {
+                pointers.insert(tag, ptr as u32);
+            }
+        } else if !values.is_empty() {
+            tags.numbers.insert(tag, values);
+        }
+    }
+    pointers
+}
+
+/// `1/250 s` for short exposures, `2.5 s` for long ones.
+fn format_exposure(seconds: f64) -> String {
+    if seconds > 0.0 && seconds < 1.0 {
+        format!("1/{:.0} s", 1.0 / seconds)
+    } else {
+        format!("{seconds} s")
+    }
+}
+
+/// Metadata summary of an image for the clipboard: EXIF fields plus `size` (None if empty).
+pub fn summary(path: &Path, size: Option<(u32, u32)>) -> Option<String> {
+    let mut lines = Exif::read(path)
+        .map(|exif| exif.summary_lines())
+        .unwrap_or_default();
+    if let Some((w, h)) = size {
+        lines.push(format!("Dimensions: {w}x{h}"));
+    }
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Capture day of an image: the EXIF date, else the file modification date (local time, like
+/// the EXIF dates cameras write).
+pub fn capture_day(path: &Path) -> Option<String> {
+    if let Some(day) = Exif::read(path).and_then(|exif| exif.capture_day()) {
+        return Some(day);
+    }
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    let secs = modified
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+    let local = secs.checked_add_signed(utc_offset(secs))?;
+    let datetime = crate::trash::format_datetime(local);
+    Some(datetime[..10].to_string())
+}
+
+/// Offset of local time from UTC at `secs` since the Unix epoch, in seconds.
+#[cfg(unix)]
+fn utc_offset(secs: u64) -> i64 {
+    let Ok(time) = libc::time_t::try_from(secs) else {
+        return 0;
+    };
+    // SAFETY: `tm` is plain data that `localtime_r` fills in; both pointers are valid for the
+    // duration of the call.
+    unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&time, &mut tm).is_null() {
+            return 0;
+        }
+        tm.tm_gmtoff
+    }
+}
+
+#[cfg(not(unix))]
+fn utc_offset(_secs: u64) -> i64 {
+    0
+}
+
+/// `YYYY:MM:DD ...` to `YYYY-MM-DD` (None for malformed or zeroed dates).
+fn parse_day(value: &str) -> Option<String> {
+    let b = value.as_bytes();
+    let digits =
+        |r: std::ops::Range<usize>| b.get(r).is_some_and(|d| d.iter().all(u8::is_ascii_digit));
+    if !(digits(0..4)
+        && b.get(4) == Some(&b':')
+        && digits(5..7)
+        && b.get(7) == Some(&b':')
+        && digits(8..10))
+    {
+        return None;
+    }
+    if &value[5..7] == "00" || &value[8..10] == "00" {
+        return None;
+    }
+    Some(format!(
+        "{}-{}-{}",
+        &value[0..4],
+        &value[5..7],
+        &value[8..10]
+    ))
+}
+
+/// TIFF data with its byte order.
+pub(crate) struct Tiff<'a> {
+    pub(crate) data: &'a [u8],
+    little_endian: bool,
+}
+
+impl<'a> Tiff<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Option<Self> {
+        let little_endian = match data.get(0..4)? {
+            b"II*\0" => true,
+            b"MM\0*" => false,
+            _ => return None,
+        };
+        Some(Self {
+            data,
+            little_endian,
+        })
+    }
+
+    pub(crate) fn u16(&self, at: usize) -> Option<u16> {
+        let bytes: [u8; 2] = self.data.get(at..at + 2)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    }
+
+    pub(crate) fn u32(&self, at: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.data.get(at..at + 4)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+}
+
+/// EXIF payload of a JPEG file's APP1 segment (None for other files).
+fn read_jpeg_app1(path: &Path) -> Option<Vec<u8>> {
+    let mut head = Vec::new();
+    std::fs::File::open(path)
+        .ok()?
+        .take(JPEG_SCAN_BYTES)
+        .read_to_end(&mut head)
+        .ok()?;
+    if !head.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut pos = 2;
+    while let Some(&[0xFF, marker, hi, lo]) = head.get(pos..pos + 4) {
+        let len = usize::from(u16::from_be_bytes([hi, lo]));
+        // Start of scan: no metadata segments follow.
+        if marker == 0xDA || len < 2 {
+            break;
+        }
+        let payload = head.get(pos + 4..pos + 2 + len)?;
+        if marker == 0xE1 && payload.starts_with(b"Exif\0\0") {
+            return Some(payload[6..].to_vec());
+        }
+        pos += 2 + len;
+    }
+    None
+}
+
+fn read_with_decoder(path: &Path) -> Option<Vec<u8>> {
+    use image::ImageDecoder as _;
+
+    let reader = image::ImageReader::open(path)
+        .ok()?
+        .with_guessed_format()
+        .ok()?;
+    let mut decoder = reader.into_decoder().ok()?;
+    decoder.exif_metadata().ok()?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Little-endian TIFF block: IFD0 with `DateTime` and an Exif IFD with `DateTimeOriginal`.
+    fn sample_exif() -> Vec<u8> {
+        let mut d = b"II*\0".to_vec();
+        d.extend_from_slice(&8u32.to_le_bytes());
+        // IFD0 at 8: two entries, then the next-IFD offset.
+        d.extend_from_slice(&2u16.to_le_bytes());
+        d.extend_from_slice(&TAG_DATE_TIME.to_le_bytes());
+        d.extend_from_slice(&TYPE_ASCII.to_le_bytes());
+        d.extend_from_slice(&20u32.to_le_bytes());
+        d.extend_from_slice(&50u32.to_le_bytes());
+        d.extend_from_slice(&TAG_EXIF_IFD.to_le_bytes());
+        d.extend_from_slice(&TYPE_LONG.to_le_bytes());
+        d.extend_from_slice(&1u32.to_le_bytes());
+        d.extend_from_slice(&70u32.to_le_bytes());
+        d.extend_from_slice(&0u32.to_le_bytes());
+        // Strings at 50 (IFD0 ends at 8 + 2 + 24 + 4 = 38).
+        d.resize(50, 0);
+        d.extend_from_slice(b"2024:01:02 03:04:05\0");
+        // Exif IFD at 70.
+        d.extend_from_slice(&1u16.to_le_bytes());
+        d.extend_from_slice(&TAG_DATE_TIME_ORIGINAL.to_le_bytes());
+        d.extend_from_slice(&TYPE_ASCII.to_le_bytes());
+        d.extend_from_slice(&20u32.to_le_bytes());
+        d.extend_from_slice(&90u32.to_le_bytes());
+        d.extend_from_slice(&0u32.to_le_bytes());
+        d.resize(90, 0);
+        d.extend_from_slice(b"2023:12:31 23:59:59\0");
+        d
+    }
+
+    #[test]
+    fn test_parse_reads_exif_sub_ifd() {
+        let exif = Exif::parse(&sample_exif()).unwrap();
+        assert_eq!(exif.ascii(TAG_DATE_TIME), Some("2024:01:02 03:04:05"));
+        assert_eq!(exif.capture_day().as_deref(), Some("2023-12-31"));
+        assert!(Exif::parse(b"not exif").is_none());
+
+        assert_eq!(parse_day("0000:00:00 00:00:00"), None);
+        assert_eq!(parse_day("2024-01-02"), None);
+    }
+
+    #[test]
+    fn test_summary_reads_numeric_and_gps_tags() {
+        // Big-endian this time: IFD0 with Make/Model/FNumber/ISO and a GPS IFD.
+        let mut d = b"MM\0*".to_vec();
+        d.extend_from_slice(&8u32.to_be_bytes());
+        let entry = |d: &mut Vec<u8>, tag: u16, kind: u16, n: u32, value: [u8; 4]| {
+            d.extend_from_slice(&tag.to_be_bytes());
+            d.extend_from_slice(&kind.to_be_bytes());
+            d.extend_from_slice(&n.to_be_bytes());
+            d.extend_from_slice(&value);
+        };
+        d.extend_from_slice(&5u16.to_be_bytes());
+        entry(&mut d, TAG_MAKE, TYPE_ASCII, 6, 100u32.to_be_bytes());
+        entry(&mut d, TAG_MODEL, TYPE_ASCII, 11, 110u32.to_be_bytes());
+        entry(&mut d, TAG_F_NUMBER, TYPE_RATIONAL, 1, 130u32.to_be_bytes());
+        entry(&mut d, TAG_ISO, TYPE_SHORT, 1, [0x01, 0x90, 0, 0]);
+        entry(&mut d, TAG_GPS_IFD, TYPE_LONG, 1, 140u32.to_be_bytes());
+        d.extend_from_slice(&0u32.to_be_bytes());
+        d.resize(100, 0);
+        d.extend_from_slice(b"Canon\0");
+        d.resize(110, 0);
+        d.extend_from_slice(b"Canon EOS\0\0");
+        d.resize(130, 0);
+        d.extend_from_slice(&28u32.to_be_bytes());
+        d.extend_from_slice(&10u32.to_be_bytes());
+        // GPS IFD at 140: 35°30' S, 139°45' E.
+        d.resize(140, 0);
+        d.extend_from_slice(&4u16.to_be_bytes());
+        entry(&mut d, GPS_LATITUDE_REF, TYPE_ASCII, 2, *b"S\0\0\0");
+        entry(&mut d, GPS_LATITUDE, TYPE_RATIONAL, 3, 200u32.to_be_bytes());
+        entry(&mut d, GPS_LONGITUDE_REF, TYPE_ASCII, 2, *b"E\0\0\0");
+        entry(
+            &mut d,
+            GPS_LONGITUDE,
+            TYPE_RATIONAL,
+            3,
+            224u32.to_be_bytes(),
+        );
+        d.resize(200, 0);
+        for v in [35u32, 1, 30, 1, 0, 1, 139, 1, 45, 1, 0, 1] {
+            d.extend_from_slice(&v.to_be_bytes());
+        }
+
+        let exif = Exif::parse(&d).unwrap();
+        assert_eq!(
+            exif.summary_lines(),
+            vec![
+                "Camera: Canon EOS".to_string(),
+                "Exposure: f/2.8, ISO 400".to_string(),
+                "GPS: -35.500000, 139.750000".to_string(),
+            ]
+        );
+        assert_eq!(format_exposure(0.004), "1/250 s");
+        assert_eq!(format_exposure(2.5), "2.5 s");
+    }
+
+    #[test]
+    fn test_capture_day_from_jpeg_app1() {
+        let dir = std::path::PathBuf::from("/tmp/stiv_test_exif");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let exif = sample_exif();
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        jpeg.extend_from_slice(&((exif.len() + 8) as u16).to_be_bytes());
+        jpeg.extend_from_slice(b"Exif\0\0");
+        jpeg.extend_from_slice(&exif);
+        jpeg.extend_from_slice(&[0xFF, 0xDA, 0, 2]);
+        let path = dir.join("a.jpg");
+        std::fs::write(&path, &jpeg).unwrap();
+        assert_eq!(capture_day(&path).as_deref(), Some("2023-12-31"));
+
+        // Without EXIF the modification date is used.
+        let path = dir.join("b.png");
+        std::fs::write(&path, b"").unwrap();
+        assert_eq!(capture_day(&path).map(|d| d.len()), Some(10));
+
+        // ... in local time: 2023-11-14 22:13:20 UTC is already the 15th east of UTC+1:47.
+        let secs = 1_700_000_000;
+        let modified = std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let offset = utc_offset(secs);
+        assert!(offset.abs() <= 14 * 3600);
+        let expected = if offset >= 6_400 {
+            "2023-11-15"
+        } else {
+            "2023-11-14"
+        };
+        assert_eq!(capture_day(&path).as_deref(), Some(expected));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -5,17 +5,22 @@
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub enum FitMode {
+    /// Shrink to the viewport, never enlarge.
     #[default]
     Normal,
+    /// Scale to fill the viewport.
     Fit,
+    /// One image pixel per terminal pixel; larger images show their center (pannable).
+    Actual,
 }
 
 impl FitMode {
-    /// Toggle between `Normal` and `Fit`.
+    /// Cycle `Normal` → `Fit` → `Actual`.
     pub fn next(self) -> Self {
         match self {
             FitMode::Normal => FitMode::Fit,
-            FitMode::Fit => FitMode::Normal,
+            FitMode::Fit => FitMode::Actual,
+            FitMode::Actual => FitMode::Normal,
         }
     }
 }
@@ -0,0 +1,147 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Grid overlay (`#`) drawn onto the rendered image in Single mode.
+//!
+//! - `Thirds`: rule-of-thirds lines across the displayed region.
+//! - `Pixels`: a line every `grid_spacing` source pixels, anchored at the image origin so it
+//!   stays put while panning and zooming. Hidden when lines would be closer than
+//!   `MIN_GAP` display pixels.
+//!
+//! Lines are dashed black and white so they show on any background.
+
+use image::{DynamicImage, ImageBuffer, Pixel};
+
+use crate::zoom::CropRect;
+
+/// Smallest gap (in display pixels) between pixel-grid lines worth drawing.
+const MIN_GAP: f64 = 4.0;
+
+/// Dash length in display pixels.
+const DASH: u32 = 4;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Grid {
+    #[default]
+    Off,
+    Thirds,
+    /// A line every this many source pixels.
+    Pixels(u32),
+}
+
+impl Grid {
+    /// Cycle `Off` → `Thirds` → `Pixels(spacing)`.
+    pub fn next(self, spacing: u32) -> Self {
+        match self {
+            Grid::Off => Grid::Thirds,
+            Grid::Thirds => Grid::Pixels(spacing.max(1)),
+            Grid::Pixels(_) => Grid::Off,
+        }
+    }
+
+    /// Status text (e.g. `grid 16px`), or None when off.
+    pub fn label(self) -> Option<String> {
+        match self {
+            Grid::Off => None,
+            Grid::Thirds => Some("thirds".to_string()),
+            Grid::Pixels(spacing) => Some(format!("grid {spacing}px")),
+        }
+    }
+}
+
+/// Draw `grid` onto `img`, a rendering of `region` of the source image.
+pub fn draw(img: DynamicImage, grid: Grid, region: CropRect) -> DynamicImage {
+    let (width, height) = (img.width(), img.height());
+    let (cols, rows) = match grid {
+        Grid::Off => return img,
+        Grid::Thirds => (
+            vec![width / 3, width * 2 / 3],
+            vec![height / 3, height * 2 / 3],
+        ),
+        Grid::Pixels(spacing) => (
+            lines(region.x, region.width, width, spacing),
+            lines(region.y, region.height, height, spacing),
+        ),
+    };
+    if cols.is_empty() && rows.is_empty() {
+        return img;
+    }
+    match img {
+        DynamicImage::ImageRgba8(mut buf) => {
+            stroke(&mut buf, &cols, &rows);
+            DynamicImage::ImageRgba8(buf)
+        }
+        other => {
+            let mut buf = other.to_rgb8();
+            stroke(&mut buf, &cols, &rows);
+            DynamicImage::ImageRgb8(buf)
+        }
+    }
+}
+
+/// Display positions of the lines at multiples of `spacing` within `start..start + extent`
+/// (source pixels), for a rendering `size` pixels long.
+fn lines(start: u32, extent: u32, size: u32, spacing: u32) -> Vec<u32> {
+    let spacing = spacing.max(1);
+    let scale = f64::from(size) / f64::from(extent.max(1));
+    if f64::from(spacing) * scale < MIN_GAP {
+        return Vec::new();
+    }
+    let first = start.div_ceil(spacing) * spacing;
+    (first..start + extent)
+        .step_by(spacing as usize)
+        .filter(|&k| k > 0)
+        .map(|k| (f64::from(k - start) * scale).round() as u32)
+        .filter(|&pos| pos < size)
+        .collect()
+}
+
+fn stroke<P: Pixel<Subpixel = u8>>(buf: &mut ImageBuffer<P, Vec<u8>>, cols: &[u32], rows: &[u32]) {
+    let (width, height) = buf.dimensions();
+    let mut paint = |x: u32, y: u32, along: u32| {
+        let value = if (along / DASH).is_multiple_of(2) {
+            255
+        } else {
+            0
+        };
+        for c in buf.get_pixel_mut(x, y).channels_mut().iter_mut().take(3) {
+            *c = value;
+        }
+    };
+    for &x in cols {
+        for y in 0..height {
+            paint(x, y, y);
+        }
+    }
+    for &y in rows {
+        for x in 0..width {
+            paint(x, y, x);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pixel_grid_lines() {
+        // 100 source pixels shown at 2x: a line every 10 source pixels is 20 display pixels.
+        assert_eq!(lines(0, 100, 200, 10).len(), 9);
+        assert_eq!(lines(0, 100, 200, 10)[0], 20);
+        // Panned by 5: lines stay on source multiples of 10.
+        assert_eq!(lines(5, 100, 200, 10)[0], 10);
+        // Too dense at 1/4 scale.
+        assert!(lines(0, 400, 100, 10).is_empty());
+    }
+
+    #[test]
+    fn test_draw_thirds() {
+        let img = DynamicImage::new_rgb8(9, 9);
+        let full = CropRect::full((9, 9));
+        let out = draw(img, Grid::Thirds, full).to_rgb8();
+        assert_eq!(out.get_pixel(3, 0).0, [255, 255, 255]);
+        assert_eq!(out.get_pixel(0, 6).0, [255, 255, 255]);
+        assert_eq!(out.get_pixel(1, 1).0, [0, 0, 0]);
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Half-block image output, the fallback for terminals without Kitty graphics.
+//!
+//! Each cell shows two vertically stacked pixels: `▀` with the top pixel as the foreground
+//! color and the bottom pixel as the background color (24-bit SGR). The workers resize images
+//! with a virtual cell size of 1x2 pixels, so one resized pixel maps to half a cell.
+//!
+//! The "encoded" form is the raw RGB image (`width`, `height` as little-endian `u32`, then the
+//! pixels), so the writer can redraw any rows later (e.g. under a closed menu) without a
+//! retransmit.
+
+use image::DynamicImage;
+use ratatui::layout::Rect;
+
+/// Virtual cell size in pixels used for resizing.
+pub const CELL_SIZE: (u16, u16) = (1, 2);
+
+/// Encode an image for `rows`.
+pub fn encode(img: &DynamicImage) -> Vec<Vec<u8>> {
+    let rgb = img.to_rgb8();
+    let mut data = Vec::with_capacity(8 + rgb.as_raw().len());
+    data.extend_from_slice(&rgb.width().to_le_bytes());
+    data.extend_from_slice(&rgb.height().to_le_bytes());
+    data.extend_from_slice(rgb.as_raw());
+    vec![data]
+}
+
+/// Escape sequences drawing the encoded image into `area`, one entry per row (like
+/// `kgp::place_rows`). Cells beyond the image are cleared.
+pub fn rows(encoded: &[Vec<u8>], area: Rect) -> Vec<Vec<u8>> {
+    use std::io::Write as _;
+
+    let Some((width, height, pixels)) = encoded.first().and_then(|data| parse(data)) else {
+        return Vec::new();
+    };
+    let pixel = |x: u32, y: u32| -> Option<&[u8]> {
+        (x < width && y < height).then(|| {
+            let i = ((y * width + x) * 3) as usize;
+            &pixels[i..i + 3]
+        })
+    };
+
+    let mut out = Vec::with_capacity(usize::from(area.height));
+    for row in 0..area.height {
+        let mut line = Vec::with_capacity(usize::from(area.width) * 40);
+        _ = write!(line, "\x1b[{};{}H", area.y + row + 1, area.x + 1);
+        for col in 0..u32::from(area.width) {
+            let y = u32::from(row) * 2;
+            match (pixel(col, y), pixel(col, y + 1)) {
+                (Some(t), Some(b)) => {
+                    _ = write!(
+                        line,
+                        "\x1b[38;2;{};{};{};48;2;{};{};{}m\u{2580}",
+                        t[0], t[1], t[2], b[0], b[1], b[2]
+                    )
+                }
+                (Some(t), None) => {
+                    _ = write!(line, "\x1b[0;38;2;{};{};{}m\u{2580}", t[0], t[1], t[2])
+                }
+                _ => line.extend_from_slice(b"\x1b[0m "),
+            }
+        }
+        line.extend_from_slice(b"\x1b[0m");
+        out.push(line);
+    }
+    out
+}
+
+fn parse(data: &[u8]) -> Option<(u32, u32, &[u8])> {
+    let width = u32::from_le_bytes(data.get(0..4)?.try_into().ok()?);
+    let height = u32::from_le_bytes(data.get(4..8)?.try_into().ok()?);
+    let pixels = data.get(8..)?;
+    (pixels.len() == (width as usize) * (height as usize) * 3).then_some((width, height, pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn test_rows_pair_pixels_per_cell() {
+        let img = RgbImage::from_fn(2, 3, |x, y| Rgb([x as u8, y as u8, 9]));
+        let encoded = encode(&DynamicImage::ImageRgb8(img));
+        let rows = rows(&encoded, Rect::new(4, 1, 2, 2));
+        assert_eq!(rows.len(), 2);
+
+        let first = String::from_utf8(rows[0].clone()).unwrap();
+        assert!(first.starts_with("\x1b[2;5H"));
+        assert!(first.contains("\x1b[38;2;1;0;9;48;2;1;1;9m\u{2580}"));
+        // Odd height: the last row only has top pixels.
+        let second = String::from_utf8(rows[1].clone()).unwrap();
+        assert!(second.contains("\x1b[0;38;2;0;2;9m\u{2580}"));
+    }
+
+    #[test]
+    fn test_rows_rejects_malformed_data() {
+        assert!(rows(&[vec![1, 0, 0, 0, 1, 0, 0, 0]], Rect::new(0, 0, 1, 1)).is_empty());
+        assert!(rows(&[], Rect::new(0, 0, 1, 1)).is_empty());
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! HEIC/HEIF (`.heic`, `.heif`) support, behind the `heif` cargo feature.
+//!
+//! Decoding goes through libheif (`libheif-rs`), which must be installed on the system
+//! (`libheif-dev` >= 1.18). libheif applies the rotation and mirroring stored in the file,
+//! so iPhone photos come out upright.
+//!
+//! Without the feature, HEIF files are not collected and `decode` always returns None.
+
+use std::path::Path;
+
+use image::DynamicImage;
+
+/// Check if the path is a HEIF file stiv can open (`.heic` / `.heif` with the `heif` feature).
+pub fn is_heif(path: &Path) -> bool {
+    cfg!(feature = "heif")
+        && path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("heic") || e.eq_ignore_ascii_case("heif"))
+}
+
+#[cfg(not(feature = "heif"))]
+pub fn decode(_bytes: &[u8], _limits: &image::Limits) -> Option<DynamicImage> {
+    None
+}
+
+/// Decode the primary image of a HEIF file. Images exceeding `limits` are rejected before
+/// their pixels are decoded.
+#[cfg(feature = "heif")]
+pub fn decode(bytes: &[u8], limits: &image::Limits) -> Option<DynamicImage> {
+    use image::{RgbImage, RgbaImage};
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    let lib = LibHeif::new();
+    let context = HeifContext::read_from_bytes(bytes).ok()?;
+    let handle = context.primary_image_handle().ok()?;
+    limits
+        .check_dimensions(handle.width(), handle.height())
+        .ok()?;
+
+    let alpha = handle.has_alpha_channel();
+    let chroma = if alpha {
+        RgbChroma::Rgba
+    } else {
+        RgbChroma::Rgb
+    };
+    let image = lib.decode(&handle, ColorSpace::Rgb(chroma), None).ok()?;
+    let planes = image.planes();
+    let plane = planes.interleaved?;
+    let channels = if alpha { 4 } else { 3 };
+    let pixels = pack_rows(
+        plane.data,
+        plane.stride,
+        plane.width as usize * channels,
+        plane.height as usize,
+    )?;
+    if alpha {
+        RgbaImage::from_raw(plane.width, plane.height, pixels).map(DynamicImage::ImageRgba8)
+    } else {
+        RgbImage::from_raw(plane.width, plane.height, pixels).map(DynamicImage::ImageRgb8)
+    }
+}
+
+/// Copy `height` rows of `row_len` bytes out of a plane whose rows are `stride` bytes apart.
+#[cfg(feature = "heif")]
+fn pack_rows(data: &[u8], stride: usize, row_len: usize, height: usize) -> Option<Vec<u8>> {
+    let mut pixels = Vec::with_capacity(row_len * height);
+    for y in 0..height {
+        let start = y * stride;
+        pixels.extend_from_slice(data.get(start..start + row_len)?);
+    }
+    Some(pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_heif() {
+        let enabled = cfg!(feature = "heif");
+        assert_eq!(is_heif(Path::new("DCIM/IMG_0001.HEIC")), enabled);
+        assert_eq!(is_heif(Path::new("photo.heif")), enabled);
+        assert!(!is_heif(Path::new("photo.jpg")));
+    }
+
+    #[cfg(feature = "heif")]
+    #[test]
+    fn test_pack_rows_drops_padding() {
+        let data = [1, 2, 0, 3, 4, 0];
+        assert_eq!(pack_rows(&data, 3, 2, 2), Some(vec![1, 2, 3, 4]));
+        assert_eq!(pack_rows(&data, 3, 2, 3), None);
+    }
+
+    #[test]
+    fn test_decode_rejects_garbage() {
+        assert!(decode(b"not a heif file", &image::Limits::default()).is_none());
+    }
+}
@@ -0,0 +1,106 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Help screen (`?`): the active key bindings and the current settings.
+//!
+//! The bindings come from the keymap, so `[keys]` overrides show up as they are used, and the
+//! settings are the loaded config (file and `STIV_*` overrides, after clamping). Entries are
+//! laid out in as many columns as fit, drawn like the calibration screen; any key closes it.
+
+use crate::config::Config;
+use crate::keymap::Keymap;
+
+/// Rows above the entries (caption and a blank line).
+const CAPTION_ROWS: usize = 2;
+
+/// Blank columns between entry columns.
+const COLUMN_GAP: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Help {
+    entries: Vec<String>,
+}
+
+impl Help {
+    pub fn new(keymap: &Keymap, config: &Config) -> Self {
+        let mut entries = vec!["Keys".to_string()];
+        for (keys, actions) in keymap.bindings() {
+            let actions: Vec<&str> = actions.iter().map(|a| a.name()).collect();
+            entries.push(format!("{:>9}  {}", keys.join(" "), actions.join(", ")));
+        }
+        entries.push(String::new());
+        entries.push("Settings".to_string());
+        entries.extend(config.values());
+        Self { entries }
+    }
+
+    /// The help screen for `width` columns and `rows` rows (above the status bar). Entries are
+    /// filled column by column; those that do not fit are counted on the last row.
+    pub fn lines(&self, width: u16, rows: u16) -> Vec<String> {
+        let (width, rows) = (usize::from(width).saturating_sub(1), usize::from(rows));
+        let mut lines = vec![String::new(); rows];
+        if let Some(caption) = lines.first_mut() {
+            *caption = "Key bindings and settings (press any key to close)".to_string();
+        }
+        let height = rows.saturating_sub(CAPTION_ROWS);
+        if height == 0 {
+            return lines;
+        }
+
+        let mut columns = Vec::new();
+        let mut x = 0;
+        for column in self.entries.chunks(height) {
+            let column_width = column.iter().map(|e| e.chars().count()).max().unwrap_or(0);
+            if x + column_width > width {
+                break;
+            }
+            columns.push((x, column));
+            x += column_width + COLUMN_GAP;
+        }
+        let shown: usize = columns.iter().map(|(_, column)| column.len()).sum();
+        let hidden = self.entries.len() - shown;
+        let count = columns.len();
+        for (col, (x, column)) in columns.into_iter().enumerate() {
+            for (i, entry) in column.iter().enumerate() {
+                // The last shown entry counts the ones that did not fit.
+                let entry = if hidden > 0 && col + 1 == count && i + 1 == column.len() {
+                    format!("... {} more", hidden + 1)
+                } else {
+                    entry.clone()
+                };
+                let line = &mut lines[CAPTION_ROWS + i];
+                let pad = x.saturating_sub(line.chars().count());
+                line.push_str(&format!("{:pad$}{entry}", ""));
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_help_lists_bindings_and_settings() {
+        let help = Help::new(&Keymap::default(), &Config::default());
+        assert_eq!(help.entries[0], "Keys");
+        assert!(help.entries.contains(&"  j space  down".to_string()));
+        assert!(help.entries.contains(&"nav_latch_ms = 150".to_string()));
+    }
+
+    #[test]
+    fn test_lines_fill_columns() {
+        let help = Help {
+            entries: (0..7).map(|i| format!("entry{i}")).collect(),
+        };
+        let lines = help.lines(40, 5);
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[2], "entry0   entry3   entry6");
+        assert_eq!(lines[4], "entry2   entry5");
+        // Too narrow for the third column.
+        let lines = help.lines(20, 5);
+        assert_eq!(lines[2], "entry0   entry3");
+        assert_eq!(lines[4], "entry2   ... 2 more");
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! iTerm2 inline image output (OSC 1337), used by iTerm2 and terminals that speak its protocol
+//! (e.g. WezTerm).
+//!
+//! Inline images become part of the cells they cover, so they cannot be re-placed by ID like
+//! KGP images. The image is cut into one strip per cell row instead, each sent as its own PNG
+//! scaled to exactly one row. A row can then be redrawn on its own (e.g. under a closed menu)
+//! with `protocol::strip_rows`, like half-block rows. Strips are padded with transparent pixels
+//! to whole cells, so nothing is stretched, and are drawn with `doNotMoveCursor` so the bottom
+//! row never scrolls the screen.
+
+use std::io::Write;
+
+use image::{DynamicImage, GenericImageView, RgbaImage};
+
+/// Encode an image as one OSC 1337 sequence per cell row of `cell_size` pixels.
+pub fn encode(img: &DynamicImage, cell_size: (u16, u16), is_tmux: bool) -> Vec<Vec<u8>> {
+    let (cell_w, cell_h) = (u32::from(cell_size.0.max(1)), u32::from(cell_size.1.max(1)));
+    let (width, height) = img.dimensions();
+    let cols = width.div_ceil(cell_w);
+    let rows = height.div_ceil(cell_h);
+
+    (0..rows)
+        .map(|row| {
+            let y = row * cell_h;
+            let strip_h = cell_h.min(height - y);
+            let mut strip = RgbaImage::new(cols * cell_w, cell_h);
+            image::imageops::replace(&mut strip, &img.view(0, y, width, strip_h).to_image(), 0, 0);
+            sequence(&strip, cols, is_tmux)
+        })
+        .collect()
+}
+
+/// One strip as an OSC 1337 file transfer shown inline across `cols` cells of one row.
+fn sequence(strip: &RgbaImage, cols: u32, is_tmux: bool) -> Vec<u8> {
+    let mut png = Vec::new();
+    if DynamicImage::ImageRgba8(strip.clone())
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .is_err()
+    {
+        return Vec::new();
+    }
+    let b64 = base64_simd::STANDARD.encode_to_string(&png);
+
+    let mut buf = Vec::with_capacity(b64.len() + 128);
+    if is_tmux {
+        buf.extend_from_slice(b"\x1bPtmux;\x1b");
+    }
+    _ = write!(
+        buf,
+        "\x1b]1337;File=inline=1;size={};width={cols};height=1;preserveAspectRatio=0;doNotMoveCursor=1:{b64}\x07",
+        png.len()
+    );
+    if is_tmux {
+        buf.extend_from_slice(b"\x1b\\");
+    }
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_one_strip_per_row() {
+        // 10x20 pixels with 4x8 cells: 3 columns, 3 rows (the last one partly padded).
+        let img = DynamicImage::new_rgb8(10, 20);
+        let encoded = encode(&img, (4, 8), false);
+        assert_eq!(encoded.len(), 3);
+        let first = String::from_utf8(encoded[0].clone()).unwrap();
+        assert!(first.starts_with("\x1b]1337;File=inline=1;size="));
+        assert!(first.contains(";width=3;height=1;"));
+        assert!(first.ends_with('\x07'));
+
+        let tmux = encode(&img, (4, 8), true);
+        assert!(tmux[0].starts_with(b"\x1bPtmux;\x1b\x1b]1337;"));
+        assert!(tmux[0].ends_with(b"\x07\x1b\\"));
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Job control signals (Unix).
+//!
+//! `Ctrl-Z` arrives as a key in raw mode, but a stop can also come from outside: `kill -TSTP`
+//! (handled like `Ctrl-Z`), or an uncatchable `SIGSTOP` that leaves the terminal as it was.
+//! `JobSignals` catches `SIGTSTP` and `SIGCONT` on a thread and wakes the event loop, so the
+//! terminal is restored before stopping and set up again and redrawn on every continue.
+
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+/// How long `stop` waits for the `SIGCONT` of its own stop to come through.
+const CONTINUE_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobSignal {
+    /// `SIGTSTP`: suspend.
+    Stop,
+    /// `SIGCONT`: resumed (after a stop of any kind).
+    Continue,
+}
+
+pub struct JobSignals {
+    rx: Receiver<JobSignal>,
+    #[cfg(unix)]
+    handle: signal_hook::iterator::Handle,
+}
+
+impl JobSignals {
+    /// Catch `SIGTSTP` and `SIGCONT` from now on (nothing is caught off Unix).
+    #[cfg(unix)]
+    pub fn watch() -> std::io::Result<Self> {
+        use signal_hook::consts::{SIGCONT, SIGTSTP};
+
+        let mut signals = signal_hook::iterator::Signals::new([SIGTSTP, SIGCONT])?;
+        let handle = signals.handle();
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for signal in signals.forever() {
+                let signal = if signal == SIGTSTP {
+                    JobSignal::Stop
+                } else {
+                    JobSignal::Continue
+                };
+                if tx.send(signal).is_err() {
+                    break;
+                }
+                crate::wake::wake();
+            }
+        });
+        Ok(Self { rx, handle })
+    }
+
+    #[cfg(not(unix))]
+    pub fn watch() -> std::io::Result<Self> {
+        let (_, rx) = mpsc::channel();
+        Ok(Self { rx })
+    }
+
+    /// Next signal caught, if any.
+    pub fn poll(&self) -> Option<JobSignal> {
+        self.rx.try_recv().ok()
+    }
+
+    /// Stop the process as the default `SIGTSTP` action would, and return once continued. The
+    /// `SIGCONT` of this stop is taken here, so it is not handled as a stop from outside.
+    #[cfg(unix)]
+    pub fn stop(&self) {
+        if signal_hook::low_level::emulate_default_handler(signal_hook::consts::SIGTSTP).is_err() {
+            return;
+        }
+        while let Ok(signal) = self.rx.recv_timeout(CONTINUE_TIMEOUT) {
+            if signal == JobSignal::Continue {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for JobSignals {
+    fn drop(&mut self) {
+        self.handle.close();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_signals_are_caught() {
+        let signals = JobSignals::watch().unwrap();
+        let next = || signals.rx.recv_timeout(Duration::from_secs(5)).ok();
+
+        // Caught, so raising SIGTSTP does not stop the test process.
+        signal_hook::low_level::raise(signal_hook::consts::SIGTSTP).unwrap();
+        assert_eq!(next(), Some(JobSignal::Stop));
+        signal_hook::low_level::raise(signal_hook::consts::SIGCONT).unwrap();
+        assert_eq!(next(), Some(JobSignal::Continue));
+        assert_eq!(signals.poll(), None);
+    }
+}
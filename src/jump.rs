@@ -0,0 +1,110 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Easymotion-style jump labels for Tile mode (`s`).
+//!
+//! Every visible tile gets a short label drawn over it; typing a label moves the tile cursor
+//! straight to that tile. All labels have the same length (one key for up to 26 tiles, two keys
+//! beyond that), so a label is never the prefix of another one. Home-row keys come first.
+
+/// Label keys, most comfortable first.
+const LABEL_KEYS: &[u8] = b"asdfghjklqwertyuiopzxcvbnm";
+
+/// Result of typing a key while labels are shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Jump {
+    /// The key matched the start of some labels; wait for more keys.
+    Pending,
+    /// A label was completed; jump to this tile (index within the page).
+    Target(usize),
+    /// No label starts with the typed keys.
+    NoMatch,
+}
+
+#[derive(Debug)]
+pub struct JumpLabels {
+    labels: Vec<String>,
+    typed: String,
+}
+
+impl JumpLabels {
+    /// Labels for `count` tiles.
+    pub fn new(count: usize) -> Self {
+        Self {
+            labels: labels(count),
+            typed: String::new(),
+        }
+    }
+
+    /// Type one key.
+    pub fn push(&mut self, c: char) -> Jump {
+        self.typed.push(c);
+        if let Some(i) = self.labels.iter().position(|l| *l == self.typed) {
+            return Jump::Target(i);
+        }
+        if self.labels.iter().any(|l| l.starts_with(&self.typed)) {
+            Jump::Pending
+        } else {
+            Jump::NoMatch
+        }
+    }
+
+    /// Tiles still reachable, with the part of their label left to type.
+    pub fn remaining(&self) -> Vec<(usize, String)> {
+        self.labels
+            .iter()
+            .enumerate()
+            .filter_map(|(i, l)| {
+                l.strip_prefix(self.typed.as_str())
+                    .map(|r| (i, r.to_string()))
+            })
+            .collect()
+    }
+}
+
+/// `count` distinct labels of equal length.
+fn labels(count: usize) -> Vec<String> {
+    let base = LABEL_KEYS.len();
+    let mut len = 1;
+    let mut capacity = base;
+    while capacity < count {
+        len += 1;
+        capacity = capacity.saturating_mul(base);
+    }
+    (0..count)
+        .map(|mut i| {
+            let mut label = vec![0u8; len];
+            for slot in label.iter_mut().rev() {
+                *slot = LABEL_KEYS[i % base];
+                i /= base;
+            }
+            String::from_utf8(label).unwrap_or_default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels_grow_with_count() {
+        assert_eq!(labels(3), vec!["a", "s", "d"]);
+        let many = labels(36);
+        assert_eq!(&many[..3], &["aa", "as", "ad"]);
+        assert_eq!(many[26], "sa");
+        assert!(many.iter().all(|l| l.len() == 2));
+    }
+
+    #[test]
+    fn test_push_narrows_then_jumps() {
+        let mut jump = JumpLabels::new(36);
+        assert_eq!(jump.push('s'), Jump::Pending);
+        assert_eq!(jump.remaining().len(), 10);
+        assert_eq!(jump.remaining()[0], (26, "a".to_string()));
+        assert_eq!(jump.push('d'), Jump::Target(28));
+
+        let mut jump = JumpLabels::new(4);
+        assert_eq!(jump.push('z'), Jump::NoMatch);
+    }
+}
@@ -0,0 +1,751 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Key bindings: the dispatch table from keys to named actions.
+//!
+//! The built-in bindings are listed in `DEFAULTS`; the `[keys]` section of config.toml
+//! rebinds keys one by one, e.g. `"ctrl+n" = "down"`, `j = "down_no_wrap"` or `x = "none"`.
+//! A key can name several actions (`a = ["pan_left", "animation"]`): the first one that
+//! applies in the current mode runs. Modes that capture all keys (command line, browser,
+//! selection, jump labels) and count digits are handled before the keymap.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::config::Config;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    RecordMacro,
+    ReplayMacro,
+    Down,
+    Up,
+    DownNoWrap,
+    UpNoWrap,
+    Left,
+    Right,
+    PageDown,
+    PageUp,
+    OpenTile,
+    First,
+    Last,
+    Percent,
+    HalfPageDown,
+    HalfPageUp,
+    Fit,
+    Reload,
+    ToggleView,
+    NextFrame,
+    PrevFrame,
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    Animation,
+    Command,
+    Help,
+    Suspend,
+    Trash,
+    UndoTrash,
+    Bookmark,
+    Browse,
+    Select,
+    ZoomIn,
+    ZoomOut,
+    TileLarger,
+    TileSmaller,
+    Reset,
+    ZoomLock,
+    WindowWidthDown,
+    WindowWidthUp,
+    WindowLevelDown,
+    WindowLevelUp,
+    BlendDown,
+    BlendUp,
+    WipeLeft,
+    WipeRight,
+    Grayscale,
+    Spread,
+    Slideshow,
+    Split,
+    SwitchPane,
+    Grid,
+    Invert,
+    RotateRight,
+    RotateLeft,
+    Jump,
+    YankRegister,
+    Yank,
+    YankImage,
+}
+
+/// Action names used in `[keys]`.
+const NAMES: &[(&str, Action)] = &[
+    ("quit", Action::Quit),
+    ("record_macro", Action::RecordMacro),
+    ("replay_macro", Action::ReplayMacro),
+    ("down", Action::Down),
+    ("up", Action::Up),
+    ("down_no_wrap", Action::DownNoWrap),
+    ("up_no_wrap", Action::UpNoWrap),
+    ("left", Action::Left),
+    ("right", Action::Right),
+    ("page_down", Action::PageDown),
+    ("page_up", Action::PageUp),
+    ("open_tile", Action::OpenTile),
+    ("first", Action::First),
+    ("last", Action::Last),
+    ("percent", Action::Percent),
+    ("half_page_down", Action::HalfPageDown),
+    ("half_page_up", Action::HalfPageUp),
+    ("fit", Action::Fit),
+    ("reload", Action::Reload),
+    ("toggle_view", Action::ToggleView),
+    ("next_frame", Action::NextFrame),
+    ("prev_frame", Action::PrevFrame),
+    ("pan_left", Action::PanLeft),
+    ("pan_right", Action::PanRight),
+    ("pan_up", Action::PanUp),
+    ("pan_down", Action::PanDown),
+    ("animation", Action::Animation),
+    ("command", Action::Command),
+    ("help", Action::Help),
+    ("suspend", Action::Suspend),
+    ("trash", Action::Trash),
+    ("undo_trash", Action::UndoTrash),
+    ("bookmark", Action::Bookmark),
+    ("browse", Action::Browse),
+    ("select", Action::Select),
+    ("zoom_in", Action::ZoomIn),
+    ("zoom_out", Action::ZoomOut),
+    ("tile_larger", Action::TileLarger),
+    ("tile_smaller", Action::TileSmaller),
+    ("reset", Action::Reset),
+    ("zoom_lock", Action::ZoomLock),
+    ("window_width_down", Action::WindowWidthDown),
+    ("window_width_up", Action::WindowWidthUp),
+    ("window_level_down", Action::WindowLevelDown),
+    ("window_level_up", Action::WindowLevelUp),
+    ("blend_down", Action::BlendDown),
+    ("blend_up", Action::BlendUp),
+    ("wipe_left", Action::WipeLeft),
+    ("wipe_right", Action::WipeRight),
+    ("grayscale", Action::Grayscale),
+    ("spread", Action::Spread),
+    ("slideshow", Action::Slideshow),
+    ("split", Action::Split),
+    ("switch_pane", Action::SwitchPane),
+    ("grid", Action::Grid),
+    ("invert", Action::Invert),
+    ("rotate_right", Action::RotateRight),
+    ("rotate_left", Action::RotateLeft),
+    ("jump", Action::Jump),
+    ("yank_register", Action::YankRegister),
+    ("yank", Action::Yank),
+    ("yank_image", Action::YankImage),
+];
+
+/// Built-in bindings, in the order of the README keybinding table.
+const DEFAULTS: &[(&str, &[Action])] = &[
+    ("j", &[Action::Down]),
+    ("space", &[Action::Down]),
+    ("k", &[Action::Up]),
+    ("backspace", &[Action::Up]),
+    ("h", &[Action::Left]),
+    ("l", &[Action::Right]),
+    ("J", &[Action::PageDown]),
+    ("L", &[Action::PageDown]),
+    ("K", &[Action::PageUp]),
+    ("H", &[Action::PageUp]),
+    ("enter", &[Action::OpenTile]),
+    ("g", &[Action::First]),
+    ("G", &[Action::Last]),
+    ("%", &[Action::Percent]),
+    ("ctrl+d", &[Action::HalfPageDown]),
+    ("ctrl+u", &[Action::HalfPageUp]),
+    ("f", &[Action::Fit]),
+    ("r", &[Action::Reload]),
+    ("t", &[Action::ToggleView]),
+    ("n", &[Action::NextFrame]),
+    ("p", &[Action::PrevFrame]),
+    ("left", &[Action::PanLeft]),
+    ("right", &[Action::PanRight]),
+    ("up", &[Action::PanUp]),
+    ("down", &[Action::PanDown]),
+    ("a", &[Action::PanLeft, Action::Animation]),
+    ("d", &[Action::PanRight]),
+    ("w", &[Action::PanUp]),
+    ("s", &[Action::PanDown, Action::Jump]),
+    (":", &[Action::Command]),
+    ("?", &[Action::Help]),
+    ("ctrl+z", &[Action::Suspend]),
+    ("D", &[Action::Trash]),
+    ("u", &[Action::UndoTrash]),
+    ("B", &[Action::Bookmark]),
+    ("-", &[Action::TileSmaller, Action::Browse]),
+    ("v", &[Action::Select]),
+    ("+", &[Action::ZoomIn, Action::TileLarger]),
+    ("=", &[Action::ZoomIn, Action::TileLarger]),
+    ("_", &[Action::ZoomOut, Action::TileSmaller]),
+    ("esc", &[Action::Reset]),
+    ("Z", &[Action::ZoomLock]),
+    ("[", &[Action::WindowWidthDown]),
+    ("]", &[Action::WindowWidthUp]),
+    ("{", &[Action::WindowLevelDown]),
+    ("}", &[Action::WindowLevelUp]),
+    ("(", &[Action::BlendDown]),
+    (")", &[Action::BlendUp]),
+    (",", &[Action::WipeLeft]),
+    (".", &[Action::WipeRight]),
+    ("c", &[Action::Grayscale]),
+    ("b", &[Action::Spread]),
+    ("S", &[Action::Slideshow]),
+    ("|", &[Action::Split]),
+    ("tab", &[Action::SwitchPane]),
+    ("#", &[Action::Grid]),
+    ("i", &[Action::Invert]),
+    (">", &[Action::RotateRight]),
+    ("<", &[Action::RotateLeft]),
+    ("\"", &[Action::YankRegister]),
+    ("y", &[Action::Yank]),
+    ("Y", &[Action::YankImage]),
+    ("q", &[Action::Quit]),
+    ("Q", &[Action::RecordMacro]),
+    ("@", &[Action::ReplayMacro]),
+];
+
+/// Named keys accepted in `[keys]` besides single characters.
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("space", KeyCode::Char(' ')),
+    ("enter", KeyCode::Enter),
+    ("tab", KeyCode::Tab),
+    ("esc", KeyCode::Esc),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+];
+
+impl Action {
+    /// Whether the repeats of a held key can run as one move with a larger count.
+    pub fn coalesces(self) -> bool {
+        matches!(
+            self,
+            Action::Down
+                | Action::Up
+                | Action::DownNoWrap
+                | Action::UpNoWrap
+                | Action::Left
+                | Action::Right
+                | Action::PageDown
+                | Action::PageUp
+                | Action::HalfPageDown
+                | Action::HalfPageUp
+                | Action::NextFrame
+                | Action::PrevFrame
+                | Action::PanLeft
+                | Action::PanRight
+                | Action::PanUp
+                | Action::PanDown
+                | Action::ZoomIn
+                | Action::ZoomOut
+        )
+    }
+
+    /// Name used in `[keys]` and on the help screen.
+    pub fn name(self) -> &'static str {
+        NAMES
+            .iter()
+            .find(|&&(_, action)| action == self)
+            .map_or("?", |&(name, _)| name)
+    }
+}
+
+/// Parse an action name; `none` (no action, to unbind a key) gives None.
+fn parse_action(name: &str) -> Result<Option<Action>> {
+    if name == "none" {
+        return Ok(None);
+    }
+    match NAMES.iter().find(|(n, _)| *n == name) {
+        Some(&(_, action)) => Ok(Some(action)),
+        None => bail!("unknown action {name:?}"),
+    }
+}
+
+/// A key with its modifiers, as looked up in the keymap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    /// Shift is part of the character for character keys (`J`, `%`), so it is dropped there.
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if matches!(code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self { code, modifiers }
+    }
+
+    /// Parse a key such as `j`, `J`, `%`, `space`, `ctrl+d`, `alt+left`, `shift+tab` or `f5`.
+    pub fn parse(s: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        // `ctrl++` binds Ctrl and `+`: only strip prefixes while a key name is left after them.
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            let (prefix, modifier) = if lower.starts_with("ctrl+") {
+                ("ctrl+", KeyModifiers::CONTROL)
+            } else if lower.starts_with("alt+") {
+                ("alt+", KeyModifiers::ALT)
+            } else if lower.starts_with("shift+") {
+                ("shift+", KeyModifiers::SHIFT)
+            } else {
+                break;
+            };
+            if rest.len() == prefix.len() {
+                break;
+            }
+            modifiers |= modifier;
+            rest = &rest[prefix.len()..];
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => {
+                let name = rest.to_ascii_lowercase();
+                match KEY_NAMES.iter().find(|(n, _)| *n == name) {
+                    Some(&(_, code)) => code,
+                    None => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                        Some(n @ 1..=24) => KeyCode::F(n),
+                        _ => bail!("unknown key {s:?}"),
+                    },
+                }
+            }
+        };
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            code => code,
+        };
+        Ok(Self::new(code, modifiers))
+    }
+
+    /// The key as written in `[keys]` (`J`, `ctrl+d`, `space`).
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+        for (modifier, prefix) in [
+            (KeyModifiers::CONTROL, "ctrl+"),
+            (KeyModifiers::ALT, "alt+"),
+            (KeyModifiers::SHIFT, "shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                label.push_str(prefix);
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => label.push_str("space"),
+            KeyCode::Char(c) => label.push(c),
+            KeyCode::F(n) => label.push_str(&format!("f{n}")),
+            KeyCode::BackTab => label.push_str("shift+tab"),
+            code => match KEY_NAMES.iter().find(|&&(_, c)| c == code) {
+                Some((name, _)) => label.push_str(name),
+                None => label.push('?'),
+            },
+        }
+        label
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(key: KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers)
+    }
+}
+
+/// Parse a binding: one key, or a chord of several. A chord is written as characters
+/// (`gg`, `ZZ`) or as keys separated by spaces (`ctrl+w l`).
+fn parse_keys(s: &str) -> Result<Vec<Key>> {
+    if s.contains(' ') && s.trim() != "" {
+        return s.split_whitespace().map(Key::parse).collect();
+    }
+    match Key::parse(s) {
+        Ok(key) => Ok(vec![key]),
+        Err(_) if s.chars().count() > 1 && !s.contains('+') => Ok(s
+            .chars()
+            .map(|c| Key::new(KeyCode::Char(c), KeyModifiers::NONE))
+            .collect()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Label of a binding: `gg` for a chord of characters, `ctrl+w l` otherwise.
+fn keys_label(keys: &[Key]) -> String {
+    let labels: Vec<String> = keys.iter().map(Key::label).collect();
+    if labels.iter().all(|l| l.chars().count() == 1) {
+        labels.concat()
+    } else {
+        labels.join(" ")
+    }
+}
+
+/// Keys typed so far of a chord, waiting for the next key or the timeout.
+#[derive(Debug, Default)]
+pub struct Chord {
+    keys: Vec<Key>,
+    due: Option<Instant>,
+}
+
+impl Chord {
+    pub fn is_pending(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    /// When the chord times out.
+    pub fn due(&self) -> Option<Instant> {
+        self.due
+    }
+
+    /// The typed keys for the status bar (e.g. `g`).
+    pub fn label(&self) -> String {
+        keys_label(&self.keys)
+    }
+
+    fn take(&mut self) -> Vec<Key> {
+        self.due = None;
+        std::mem::take(&mut self.keys)
+    }
+}
+
+#[derive(Debug)]
+pub struct Keymap {
+    bindings: HashMap<Vec<Key>, Vec<Action>>,
+    /// How long a chord prefix waits for its next key (`chord_timeout_ms`).
+    timeout: Duration,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = DEFAULTS
+            .iter()
+            .map(|&(key, actions)| {
+                let keys = parse_keys(key).expect("valid default key");
+                (keys, actions.to_vec())
+            })
+            .collect();
+        Self {
+            bindings,
+            timeout: Duration::from_millis(Config::default().chord_timeout_ms),
+        }
+    }
+}
+
+impl Keymap {
+    /// The built-in bindings with the `[keys]` entries of the config applied. Invalid entries
+    /// are skipped and returned as messages.
+    pub fn from_config(config: &Config) -> (Self, Vec<String>) {
+        let mut keymap = Self {
+            timeout: Duration::from_millis(config.chord_timeout_ms),
+            ..Self::default()
+        };
+        let mut errors = Vec::new();
+        for (key, names) in &config.keys {
+            let parsed = parse_keys(key).and_then(|keys| {
+                let actions = names
+                    .iter()
+                    .map(|name| parse_action(name))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok((keys, actions.into_iter().flatten().collect()))
+            });
+            match parsed {
+                Ok((keys, actions)) => {
+                    keymap.bindings.insert(keys, actions);
+                }
+                Err(e) => errors.push(format!("[keys] {key}: {e}")),
+            }
+        }
+        (keymap, errors)
+    }
+
+    /// Bound keys grouped by their actions (`["j", "space"]` for `down`), in the order of the
+    /// action names, for the help screen.
+    pub fn bindings(&self) -> Vec<(Vec<String>, Vec<Action>)> {
+        let mut groups: Vec<(Vec<String>, Vec<Action>)> = Vec::new();
+        for (keys, actions) in self.bindings.iter().filter(|(_, a)| !a.is_empty()) {
+            match groups.iter_mut().find(|(_, a)| a == actions) {
+                Some((labels, _)) => labels.push(keys_label(keys)),
+                None => groups.push((vec![keys_label(keys)], actions.clone())),
+            }
+        }
+        let position = |action: &Action| NAMES.iter().position(|(_, a)| a == action);
+        for (labels, _) in &mut groups {
+            labels.sort();
+        }
+        groups.sort_by(|(ka, a), (kb, b)| {
+            let (a, b) = (a.iter().map(position), b.iter().map(position));
+            a.cmp(b).then_with(|| ka.cmp(kb))
+        });
+        groups
+    }
+
+    /// Whether `keys` start a longer (not unbound) chord.
+    fn is_prefix(&self, keys: &[Key]) -> bool {
+        self.bindings
+            .iter()
+            .any(|(k, a)| k.len() > keys.len() && k.starts_with(keys) && !a.is_empty())
+    }
+
+    /// Actions bound to a single key. A key with modifiers that has no binding of its own
+    /// falls back to the plain key (Alt+j moves like j).
+    fn lookup(&self, key: Key) -> Option<&[Action]> {
+        let plain = Key::new(key.code, KeyModifiers::NONE);
+        self.bindings
+            .get(&vec![key])
+            .or_else(|| {
+                self.bindings
+                    .get(&vec![plain])
+                    .filter(|_| !key.modifiers.is_empty())
+            })
+            .map(Vec::as_slice)
+    }
+
+    /// Feed a key press. Returns the bindings to run in order, each a list of actions of which
+    /// the first one that applies runs: none while a chord is incomplete, the typed prefix's
+    /// own binding first when the key does not continue it. Esc cancels a chord.
+    pub fn feed(&self, chord: &mut Chord, key: KeyEvent, now: Instant) -> Vec<&[Action]> {
+        let key = Key::from(key);
+        let mut runs = Vec::new();
+        if !chord.keys.is_empty() {
+            if key.code == KeyCode::Esc {
+                chord.take();
+                return runs;
+            }
+            let mut keys = chord.take();
+            keys.push(key);
+            if let Some(actions) = self.bindings.get(&keys) {
+                return vec![actions.as_slice()];
+            }
+            if self.is_prefix(&keys) {
+                chord.keys = keys;
+                chord.due = Some(now + self.timeout);
+                return runs;
+            }
+            keys.pop();
+            runs.extend(self.bindings.get(&keys).map(Vec::as_slice));
+        }
+        if self.is_prefix(&[key]) {
+            chord.keys = vec![key];
+            chord.due = Some(now + self.timeout);
+        } else {
+            runs.extend(self.lookup(key));
+        }
+        runs
+    }
+
+    /// The action `key` runs on its own (no chord typed or started), of those that `applies`.
+    pub fn single(
+        &self,
+        chord: &Chord,
+        key: KeyEvent,
+        applies: impl Fn(Action) -> bool,
+    ) -> Option<Action> {
+        let key = Key::from(key);
+        if chord.is_pending() || self.is_prefix(&[key]) {
+            return None;
+        }
+        self.lookup(key)?
+            .iter()
+            .copied()
+            .find(|&action| applies(action))
+    }
+
+    /// Once a chord prefix has waited `chord_timeout_ms`, give up on the chord; returns the
+    /// prefix's own binding (`g` when `gg` is a chord too) to run.
+    pub fn expire(&self, chord: &mut Chord, now: Instant) -> Option<&[Action]> {
+        if chord.due.is_none_or(|due| now < due) {
+            return None;
+        }
+        let keys = chord.take();
+        self.bindings.get(&keys).map(Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ActionNames;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_key() {
+        let key = |code, modifiers| Key::new(code, modifiers);
+        assert_eq!(
+            Key::parse("J").unwrap(),
+            key(KeyCode::Char('J'), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            Key::parse("shift+j").unwrap(),
+            key(KeyCode::Char('J'), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            Key::parse("Ctrl+d").unwrap(),
+            key(KeyCode::Char('d'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            Key::parse("ctrl++").unwrap(),
+            key(KeyCode::Char('+'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            Key::parse("shift+tab").unwrap(),
+            key(KeyCode::BackTab, KeyModifiers::NONE)
+        );
+        assert_eq!(
+            Key::parse("F5").unwrap(),
+            key(KeyCode::F(5), KeyModifiers::NONE)
+        );
+        assert!(Key::parse("ctrl+nope").is_err());
+        assert!(Key::parse("").is_err());
+        for label in ["J", "ctrl+d", "space", "shift+tab", "alt+left", "f5"] {
+            assert_eq!(Key::parse(label).unwrap().label(), label);
+        }
+    }
+
+    /// First applicable action of a key pressed without a pending chord.
+    fn action(keymap: &Keymap, key: KeyEvent, applies: impl Fn(Action) -> bool) -> Option<Action> {
+        let runs = keymap.feed(&mut Chord::default(), key, Instant::now());
+        runs.first()?.iter().copied().find(|&a| applies(a))
+    }
+
+    #[test]
+    fn test_defaults_parse() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.bindings.len(), DEFAULTS.len());
+        // Shifted characters arrive with the SHIFT modifier from some terminals.
+        let key = press(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(action(&keymap, key, |_| true), Some(Action::Last));
+        let key = press(KeyCode::Char('z'), KeyModifiers::CONTROL);
+        assert_eq!(action(&keymap, key, |_| true), Some(Action::Suspend));
+
+        let bindings = keymap.bindings();
+        assert_eq!(bindings[0], (vec!["q".to_string()], vec![Action::Quit]));
+        assert!(bindings.contains(&(
+            vec!["j".to_string(), "space".to_string()],
+            vec![Action::Down]
+        )));
+    }
+
+    fn config(keys: &[(&str, ActionNames)]) -> Config {
+        Config {
+            keys: keys
+                .iter()
+                .map(|(k, a)| (k.to_string(), a.clone()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_overrides_and_fallback() {
+        let one = |name: &str| ActionNames::One(name.to_string());
+        let (keymap, errors) = Keymap::from_config(&config(&[
+            ("j", one("down_no_wrap")),
+            ("q", one("none")),
+            (
+                "x",
+                ActionNames::List(vec!["jump".to_string(), "quit".to_string()]),
+            ),
+            ("y", one("teleport")),
+        ]));
+        assert_eq!(errors, ["[keys] y: unknown action \"teleport\""]);
+        let plain = |c| press(KeyCode::Char(c), KeyModifiers::NONE);
+        assert_eq!(
+            action(&keymap, plain('j'), |_| true),
+            Some(Action::DownNoWrap)
+        );
+        assert_eq!(action(&keymap, plain('q'), |_| true), None);
+        // The first applicable action wins; the invalid entry keeps the default.
+        assert_eq!(
+            action(&keymap, plain('x'), |a| a != Action::Jump),
+            Some(Action::Quit)
+        );
+        assert_eq!(action(&keymap, plain('y'), |_| true), Some(Action::Yank));
+        // Unbound modified keys fall back to the plain key, bound ones do not.
+        let alt_k = press(KeyCode::Char('k'), KeyModifiers::ALT);
+        assert_eq!(action(&keymap, alt_k, |_| true), Some(Action::Up));
+        let ctrl_d = press(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(
+            action(&keymap, ctrl_d, |_| true),
+            Some(Action::HalfPageDown)
+        );
+    }
+
+    #[test]
+    fn test_chords() {
+        let one = |name: &str| ActionNames::One(name.to_string());
+        let (keymap, errors) = Keymap::from_config(&config(&[
+            ("gg", one("first")),
+            ("ZZ", one("quit")),
+            ("ctrl+w l", one("right")),
+        ]));
+        assert!(errors.is_empty());
+        let plain = |c| press(KeyCode::Char(c), KeyModifiers::NONE);
+        let start = Instant::now();
+        let mut chord = Chord::default();
+
+        assert!(keymap.feed(&mut chord, plain('Z'), start).is_empty());
+        assert_eq!(chord.label(), "Z");
+        assert_eq!(
+            keymap.feed(&mut chord, plain('Z'), start),
+            [&[Action::Quit]]
+        );
+        assert_eq!(chord.label(), "");
+
+        // `g` alone still goes first once the chord times out.
+        keymap.feed(&mut chord, plain('g'), start);
+        assert_eq!(keymap.expire(&mut chord, start), None);
+        let later = start + keymap.timeout;
+        assert_eq!(keymap.expire(&mut chord, later), Some(&[Action::First][..]));
+
+        // A key that does not continue the chord runs after the prefix's own binding.
+        keymap.feed(&mut chord, plain('Z'), start);
+        let runs = keymap.feed(&mut chord, plain('j'), start);
+        assert_eq!(runs, [&[Action::ZoomLock][..], &[Action::Down][..]]);
+
+        let ctrl_w = press(KeyCode::Char('w'), KeyModifiers::CONTROL);
+        keymap.feed(&mut chord, ctrl_w, start);
+        assert_eq!(chord.label(), "ctrl+w");
+        assert!(
+            keymap
+                .feed(&mut chord, press(KeyCode::Esc, KeyModifiers::NONE), start)
+                .is_empty()
+        );
+        assert_eq!(keymap.expire(&mut chord, later), None);
+
+        // Only keys that run on their own are coalesced when held.
+        let single = |chord: &Chord, c| keymap.single(chord, plain(c), |_| true);
+        assert_eq!(single(&chord, 'j'), Some(Action::Down));
+        assert!(Action::Down.coalesces());
+        assert_eq!(single(&chord, 'g'), None);
+        keymap.feed(&mut chord, plain('Z'), start);
+        assert_eq!(single(&chord, 'j'), None);
+    }
+}
@@ -7,6 +7,8 @@
 //! in the terminal.
 
 use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use image::DynamicImage;
 use ratatui::layout::Rect;
@@ -108,6 +110,27 @@ pub fn place_rows(area: Rect, id: u32) -> Vec<Vec<u8>> {
     rows
 }
 
+/// z-index of direct placements: below text and non-default cell backgrounds, so overlays
+/// cover the image without re-placing it.
+const DIRECT_Z_INDEX: i32 = -1_073_741_825;
+
+/// Place image `id` at its native size with the top-left corner at `area` (native placement,
+/// no Unicode placeholders). Placing again replaces the previous placement. Erasing cells does
+/// not remove it; use `delete_by_id`.
+pub fn place_direct(area: Rect, id: u32) -> Vec<u8> {
+    if area.width == 0 || area.height == 0 {
+        return Vec::new();
+    }
+    let mut buf = Vec::with_capacity(64);
+    _ = write!(
+        buf,
+        "\x1b[{};{}H\x1b_Gq=2,a=p,i={id},p=1,C=1,z={DIRECT_Z_INDEX}\x1b\\",
+        area.y + 1,
+        area.x + 1
+    );
+    buf
+}
+
 pub fn erase_rows(area: Rect) -> Vec<Vec<u8>> {
     if area.width == 0 || area.height == 0 {
         return Vec::new();
@@ -128,11 +151,14 @@ pub fn erase_rows(area: Rect) -> Vec<Vec<u8>> {
     rows
 }
 
+/// Encode `img` for transmission: base64 chunks, or with `file_transfer` (outside tmux) one raw
+/// transmission that the writer sends through a file (see `transmission`).
 pub fn encode_chunks(
     img: &DynamicImage,
     id: u32,
     is_tmux: bool,
     compress_level: Option<u32>,
+    file_transfer: bool,
 ) -> Vec<Vec<u8>> {
     let (w, h) = (img.width(), img.height());
 
@@ -151,7 +177,32 @@ pub fn encode_chunks(
         raw
     };
 
-    let b64 = base64_simd::STANDARD.encode_to_string(&data).into_bytes();
+    let compression_opt = if compress_level.is_some() { ",o=z" } else { "" };
+    let control = format!("f={format},s={w},v={h},i={id}{compression_opt}");
+    transmit(&data, &control, is_tmux, file_transfer)
+}
+
+/// Encode PNG file data as it is (`f=100`; the terminal reads the size from the PNG).
+pub fn encode_png(png: &[u8], id: u32, is_tmux: bool, file_transfer: bool) -> Vec<Vec<u8>> {
+    transmit(png, &format!("f=100,i={id}"), is_tmux, file_transfer)
+}
+
+/// Transmit-and-display commands for `data`, with the format keys in `control`.
+fn transmit(data: &[u8], control: &str, is_tmux: bool, file_transfer: bool) -> Vec<Vec<u8>> {
+    let control = format!("q=2,a=T,C=1,U=1,{control}");
+    if file_transfer && !is_tmux {
+        let mut raw = Vec::with_capacity(RAW_MARK.len() + control.len() + data.len() + 4);
+        raw.extend_from_slice(RAW_MARK);
+        _ = write!(raw, "\x1b_G{control};");
+        raw.extend_from_slice(data);
+        return vec![raw];
+    }
+    base64_chunks(data, &control, is_tmux)
+}
+
+/// Chunked commands for `data`, base64-encoded, with the full control data in `control`.
+fn base64_chunks(data: &[u8], control: &str, is_tmux: bool) -> Vec<Vec<u8>> {
+    let b64 = base64_simd::STANDARD.encode_to_string(data).into_bytes();
 
     let mut it = b64.chunks(4096).peekable();
     let mut chunks: Vec<Vec<u8>> = Vec::with_capacity(it.len().max(1));
@@ -162,13 +213,11 @@ pub fn encode_chunks(
         ("\x1b", "\x1b", "")
     };
 
-    let compression_opt = if compress_level.is_some() { ",o=z" } else { "" };
-
     if let Some(first) = it.next() {
         let mut buf = Vec::with_capacity(first.len() + 128);
         _ = write!(
             &mut buf,
-            "{start}_Gq=2,a=T,C=1,U=1,f={format},s={w},v={h},i={id}{compression_opt},m={};",
+            "{start}_G{control},m={};",
             it.peek().is_some() as u8
         );
         buf.extend_from_slice(first);
@@ -187,6 +236,276 @@ pub fn encode_chunks(
     chunks
 }
 
+/// Marks a raw transmission: `RAW_MARK ESC _G <control> ; <data>`, the image data unencoded.
+/// It is never written as it is; `transmission` turns it into commands.
+const RAW_MARK: &[u8] = b"\0stiv-raw\0";
+
+/// Split a raw transmission produced with `file_transfer` into its control data and payload.
+fn raw_parts(chunks: &[Vec<u8>]) -> Option<(&str, &[u8])> {
+    let [chunk] = chunks else {
+        return None;
+    };
+    let body = chunk.strip_prefix(RAW_MARK)?.strip_prefix(b"\x1b_G")?;
+    let split = body.iter().position(|&b| b == b';')?;
+    Some((
+        std::str::from_utf8(&body[..split]).ok()?,
+        &body[split + 1..],
+    ))
+}
+
+/// Commands that send encoded chunks: a raw transmission goes through a file in `transfer_dir`
+/// when set, or is base64-encoded into chunks if that fails; other chunks are sent as they are.
+pub fn transmission(chunks: &[Vec<u8>], transfer_dir: Option<&Path>) -> Vec<Vec<u8>> {
+    let Some((control, data)) = raw_parts(chunks) else {
+        return chunks.to_vec();
+    };
+    match transfer_dir.and_then(|dir| file_transfer(control, data, dir)) {
+        Some(command) => vec![command],
+        None => base64_chunks(data, control, false),
+    }
+}
+
+/// Name prefix of the files written for file transmission. Terminals only delete transmitted
+/// temporary files whose name contains `tty-graphics-protocol`.
+const TRANSFER_PREFIX: &str = "tty-graphics-protocol-stiv";
+
+/// Directory for file transmission: shared memory when available, the temp dir otherwise.
+pub fn transfer_dir() -> PathBuf {
+    let shm = Path::new("/dev/shm");
+    if shm.is_dir() {
+        shm.to_path_buf()
+    } else {
+        std::env::temp_dir()
+    }
+}
+
+fn transfer_file_prefix() -> String {
+    format!("{TRANSFER_PREFIX}-{}-", std::process::id())
+}
+
+/// Create a transmission file readable only by this user. The name is predictable, so an
+/// existing file or a symlink planted in the shared directory makes this fail instead of
+/// being written through.
+fn create_transfer_file(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    if let Err(e) = file.write_all(data) {
+        let _ = std::fs::remove_file(path);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Ask the terminal whether it reads image data from files in `dir`: a one-pixel `a=q,t=t`
+/// query, followed by DA1 so that terminals ignoring the query still answer. Must run before
+/// the event loop reads stdin.
+#[cfg(unix)]
+pub fn probe_file_transfer(dir: &Path) -> bool {
+    use ratatui::crossterm::terminal;
+    use std::time::{Duration, Instant};
+
+    const PROBE_ID: u32 = 0x7f_ff_ff;
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    let path = dir.join(format!("{}probe", transfer_file_prefix()));
+    if create_transfer_file(&path, &[0, 0, 0]).is_err() {
+        return false;
+    }
+    let Some(name) = path.to_str() else {
+        let _ = std::fs::remove_file(&path);
+        return false;
+    };
+    let query = format!(
+        "\x1b_Gi={PROBE_ID},s=1,v=1,a=q,t=t,f=24;{}\x1b\\\x1b[c",
+        base64_simd::STANDARD.encode_to_string(name.as_bytes())
+    );
+
+    let was_raw = terminal::is_raw_mode_enabled().unwrap_or(false);
+    let mut reply = Vec::new();
+    if was_raw || terminal::enable_raw_mode().is_ok() {
+        let mut out = std::io::stdout();
+        if out.write_all(query.as_bytes()).is_ok() && out.flush().is_ok() {
+            let deadline = Instant::now() + TIMEOUT;
+            // Read until the DA1 answer (`ESC [ ? ... c`) ends the replies.
+            while !da1_answered(&reply) {
+                let left = deadline.saturating_duration_since(Instant::now());
+                let mut fd = libc::pollfd {
+                    fd: libc::STDIN_FILENO,
+                    events: libc::POLLIN,
+                    revents: 0,
+                };
+                // SAFETY: `fd` is a valid pollfd for the duration of the call.
+                if left.is_zero() || unsafe { libc::poll(&mut fd, 1, left.as_millis() as i32) } <= 0
+                {
+                    break;
+                }
+                let mut buf = [0u8; 256];
+                // SAFETY: `buf` is writable for its whole length.
+                let n =
+                    unsafe { libc::read(libc::STDIN_FILENO, buf.as_mut_ptr().cast(), buf.len()) };
+                if n <= 0 {
+                    break;
+                }
+                reply.extend_from_slice(&buf[..n as usize]);
+            }
+        }
+        if !was_raw {
+            let _ = terminal::disable_raw_mode();
+        }
+    }
+    // The terminal deletes the file once read; remove it in case it did not.
+    let _ = std::fs::remove_file(&path);
+    probe_ok(&reply, PROBE_ID)
+}
+
+#[cfg(not(unix))]
+pub fn probe_file_transfer(_dir: &Path) -> bool {
+    false
+}
+
+/// Whether the replies end with a DA1 answer.
+fn da1_answered(reply: &[u8]) -> bool {
+    reply
+        .windows(3)
+        .rposition(|w| w == b"\x1b[?")
+        .is_some_and(|start| reply[start..].contains(&b'c'))
+}
+
+/// Whether the replies hold an `OK` for the query with `id`.
+fn probe_ok(reply: &[u8], id: u32) -> bool {
+    let ok = format!("\x1b_Gi={id};OK\x1b\\");
+    reply.windows(ok.len()).any(|w| w == ok.as_bytes())
+}
+
+/// One command that has the terminal read `data` from a temporary file in `dir` (`t=t`; the
+/// terminal deletes the file after reading it), instead of sending it base64-encoded over the
+/// terminal. Returns None if the file cannot be written.
+fn file_transfer(control: &str, data: &[u8], dir: &Path) -> Option<Vec<u8>> {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+
+    let name = format!(
+        "{}{}",
+        transfer_file_prefix(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    );
+    let path = dir.join(name);
+    create_transfer_file(&path, data).ok()?;
+
+    let mut buf = Vec::with_capacity(256);
+    _ = write!(
+        buf,
+        "\x1b_G{control},t=t;{}\x1b\\",
+        base64_simd::STANDARD.encode_to_string(path.to_str()?.as_bytes())
+    );
+    Some(buf)
+}
+
+/// Remove this process's transmission files that the terminal has not read (and deleted).
+pub fn remove_transfer_files(dir: &Path) {
+    let prefix = transfer_file_prefix();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        if entry.file_name().to_string_lossy().starts_with(&prefix) {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Turn chunks produced by `encode_chunks` into an animation frame appended to the image with
+/// the same ID (`a=f`), shown for `gap_ms` milliseconds. Returns None for other chunks.
+pub fn frame_chunks(chunks: &[Vec<u8>], gap_ms: u32) -> Option<Vec<Vec<u8>>> {
+    let first = chunks.first()?;
+    let control = control_range(first)?;
+    let needle = b",a=T,C=1,U=1,";
+    let pos = control.start
+        + first[control]
+            .windows(needle.len())
+            .position(|w| w == needle)?;
+
+    let mut frame = Vec::with_capacity(first.len() + 16);
+    frame.extend_from_slice(&first[..pos]);
+    _ = write!(frame, ",a=f,z={gap_ms},");
+    frame.extend_from_slice(&first[pos + needle.len()..]);
+
+    let mut out = Vec::with_capacity(chunks.len());
+    out.push(frame);
+    out.extend(chunks[1..].iter().cloned());
+    Some(out)
+}
+
+/// Byte range of a command's control data: from `_G` to the first `;` after it. Inside tmux
+/// the command is wrapped in `ESC P tmux;`, whose `;` comes first.
+fn control_range(chunk: &[u8]) -> Option<std::ops::Range<usize>> {
+    let start = chunk.windows(2).position(|w| w == b"_G")? + 2;
+    let len = chunk[start..].iter().position(|&b| b == b';')?;
+    Some(start..start + len)
+}
+
+/// Set the gap of the image's first frame (the still transmitted with `encode_chunks`).
+pub fn root_frame_gap(id: u32, gap_ms: u32, is_tmux: bool) -> Vec<u8> {
+    let (start, escape, close) = if is_tmux {
+        (TMUX_START, TMUX_ESCAPE, TMUX_CLOSE)
+    } else {
+        ("\x1b", "\x1b", "")
+    };
+
+    let mut buf = Vec::with_capacity(64);
+    _ = write!(
+        buf,
+        "{start}_Gq=2,a=a,i={id},r=1,z={gap_ms}{escape}\\{close}"
+    );
+    buf
+}
+
+/// Let the terminal play the image's frames in a loop (`run`), or stop on the current one.
+/// `frame` (1-based) makes that frame the current one first.
+pub fn animate(id: u32, run: bool, frame: Option<usize>, is_tmux: bool) -> Vec<u8> {
+    let (start, escape, close) = if is_tmux {
+        (TMUX_START, TMUX_ESCAPE, TMUX_CLOSE)
+    } else {
+        ("\x1b", "\x1b", "")
+    };
+
+    let state = if run { 3 } else { 1 };
+    let current = frame.map(|f| format!(",c={f}")).unwrap_or_default();
+    let mut buf = Vec::with_capacity(64);
+    _ = write!(
+        buf,
+        "{start}_Gq=2,a=a,i={id},s={state}{current},v=1{escape}\\{close}"
+    );
+    buf
+}
+
+/// Rewrite the image ID in chunks produced by `encode_chunks` (only the first chunk carries it).
+/// Returns None if the chunks were not encoded with `old_id`.
+pub fn retarget_chunks(chunks: &[Vec<u8>], old_id: u32, new_id: u32) -> Option<Vec<Vec<u8>>> {
+    let first = chunks.first()?;
+    let control = control_range(first)?;
+    let needle = format!(",i={old_id},");
+    let pos = control.start
+        + first[control]
+            .windows(needle.len())
+            .position(|w| w == needle.as_bytes())?;
+
+    let mut retargeted = Vec::with_capacity(first.len() + 8);
+    retargeted.extend_from_slice(&first[..pos]);
+    _ = write!(retargeted, ",i={new_id},");
+    retargeted.extend_from_slice(&first[pos + needle.len()..]);
+
+    let mut out = Vec::with_capacity(chunks.len());
+    out.push(retargeted);
+    out.extend(chunks[1..].iter().cloned());
+    Some(out)
+}
+
 // From yazi's KGP implementation (and kitty docs).
 static DIACRITICS: [char; 297] = [
     '\u{305}',
@@ -501,4 +820,148 @@ mod tests {
         assert!(s.contains("\x1b[4;3H"));
         assert!(s.contains("\x1b[5;3H"));
     }
+
+    #[test]
+    fn place_direct_positions_cursor() {
+        let s = String::from_utf8(place_direct(Rect::new(2, 3, 4, 2), 7)).unwrap();
+        assert!(s.starts_with("\x1b[4;3H\x1b_Gq=2,a=p,i=7,p=1,C=1,z=-"));
+        assert!(s.ends_with("\x1b\\"));
+        assert!(place_direct(Rect::new(2, 3, 0, 2), 7).is_empty());
+    }
+
+    #[test]
+    fn file_transfer_writes_payload() {
+        let dir = std::env::temp_dir().join("stiv_test_kgp_transfer");
+        std::fs::create_dir_all(&dir).unwrap();
+        // Large enough to span several chunks.
+        let img = DynamicImage::new_rgb8(64, 64);
+        let raw = encode_chunks(&img, 42, false, None, true);
+        assert_eq!(raw.len(), 1);
+
+        let sent = transmission(&raw, Some(&dir));
+        assert_eq!(sent.len(), 1);
+        let cmd = String::from_utf8(sent[0].clone()).unwrap();
+        assert!(cmd.starts_with("\x1b_Gq=2,a=T,C=1,U=1,f=24,s=64,v=64,i=42,t=t;"));
+        let encoded_path = cmd
+            .strip_suffix("\x1b\\")
+            .and_then(|c| c.split_once(';'))
+            .unwrap()
+            .1;
+        let path = base64_simd::STANDARD
+            .decode_to_vec(encoded_path.as_bytes())
+            .unwrap();
+        let path = PathBuf::from(String::from_utf8(path).unwrap());
+        assert!(path.starts_with(&dir));
+        assert_eq!(std::fs::read(&path).unwrap().len(), 64 * 64 * 3);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        remove_transfer_files(&dir);
+        assert!(!path.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn raw_transmission_falls_back_to_chunks() {
+        let img = DynamicImage::new_rgb8(64, 64);
+        let chunks = encode_chunks(&img, 42, false, Some(6), false);
+        let raw = encode_chunks(&img, 42, false, Some(6), true);
+        // Without a transfer directory (or if the file cannot be written), the raw data is sent
+        // base64-encoded like any other image.
+        assert_eq!(transmission(&raw, None), chunks);
+        let missing = std::env::temp_dir().join("stiv_test_kgp_missing_dir");
+        assert_eq!(transmission(&raw, Some(&missing)), chunks);
+        // Base64 chunks pass through; tmux never gets a raw transmission.
+        assert_eq!(transmission(&chunks, None), chunks);
+        assert_eq!(
+            encode_chunks(&img, 42, true, None, true),
+            encode_chunks(&img, 42, true, None, false)
+        );
+
+        // Frames and retargeting edit the raw control data too.
+        let frame = transmission(&frame_chunks(&raw, 80).unwrap(), None);
+        assert!(String::from_utf8_lossy(&frame[0]).starts_with("\x1b_Gq=2,a=f,z=80,f=24,"));
+        let retargeted = transmission(&retarget_chunks(&raw, 42, 9).unwrap(), None);
+        assert!(String::from_utf8_lossy(&retargeted[0]).contains(",i=9,"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transfer_file_does_not_follow_symlinks() {
+        let dir = std::env::temp_dir().join("stiv_test_kgp_symlink");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("victim");
+        std::fs::write(&target, b"keep").unwrap();
+        let planted = dir.join("planted");
+        std::os::unix::fs::symlink(&target, &planted).unwrap();
+
+        assert!(create_transfer_file(&planted, b"image").is_err());
+        assert_eq!(std::fs::read(&target).unwrap(), b"keep");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn probe_reply_parsing() {
+        let ok = b"\x1b_Gi=9;OK\x1b\\\x1b[?62;22c";
+        assert!(da1_answered(ok));
+        assert!(probe_ok(ok, 9));
+        // Refused, or only the DA1 answer (no KGP support).
+        assert!(!probe_ok(b"\x1b_Gi=9;EBADF:no such file\x1b\\\x1b[?62c", 9));
+        assert!(!probe_ok(b"\x1b[?62;4c", 9));
+        assert!(!da1_answered(b"\x1b_Gi=9;OK\x1b\\"));
+    }
+
+    #[test]
+    fn frame_chunks_append_frames() {
+        let img = DynamicImage::new_rgb8(64, 64);
+        let chunks = encode_chunks(&img, 7, false, Some(6), false);
+        let frame = frame_chunks(&chunks, 80).unwrap();
+        assert_eq!(frame.len(), chunks.len());
+        let first = String::from_utf8_lossy(&frame[0]);
+        assert!(first.starts_with("\x1b_Gq=2,a=f,z=80,f=24,s=64,v=64,i=7,o=z,"));
+        assert_eq!(frame[1..], chunks[1..]);
+        assert!(frame_chunks(&[b"garbage".to_vec()], 80).is_none());
+
+        // Inside tmux the control data follows the passthrough prefix.
+        let chunks = encode_chunks(&img, 7, true, Some(6), false);
+        let frame = frame_chunks(&chunks, 80).unwrap();
+        let first = String::from_utf8_lossy(&frame[0]);
+        assert!(first.starts_with("\x1bPtmux;\x1b\x1b_Gq=2,a=f,z=80,f=24,s=64,v=64,i=7,o=z,"));
+        assert_eq!(frame[1..], chunks[1..]);
+        let retargeted = retarget_chunks(&chunks, 7, 9).unwrap();
+        assert!(String::from_utf8_lossy(&retargeted[0]).contains(",i=9,"));
+
+        assert_eq!(
+            root_frame_gap(7, 50, false),
+            b"\x1b_Gq=2,a=a,i=7,r=1,z=50\x1b\\"
+        );
+        assert_eq!(
+            animate(7, true, None, false),
+            b"\x1b_Gq=2,a=a,i=7,s=3,v=1\x1b\\"
+        );
+        assert_eq!(
+            animate(7, false, Some(4), false),
+            b"\x1b_Gq=2,a=a,i=7,s=1,c=4,v=1\x1b\\"
+        );
+        assert!(animate(7, false, None, true).starts_with(b"\x1bPtmux;\x1b\x1b_Gq=2,a=a,i=7,s=1"));
+    }
+
+    #[test]
+    fn retarget_rewrites_first_chunk_only() {
+        let img = DynamicImage::new_rgb8(64, 64);
+        let chunks = encode_chunks(&img, 1234, false, None, false);
+        let retargeted = retarget_chunks(&chunks, 1234, 99).unwrap();
+        assert_eq!(retargeted.len(), chunks.len());
+        let first = String::from_utf8_lossy(&retargeted[0]);
+        assert!(first.contains(",i=99,"));
+        assert!(!first.contains("1234"));
+        assert_eq!(retargeted[1..], chunks[1..]);
+        assert!(retarget_chunks(&chunks, 4321, 99).is_none());
+    }
 }
@@ -0,0 +1,131 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Tiny bitmap font for text drawn into tile composites (date labels, names of files that
+//! failed to decode).
+//!
+//! Glyphs are 3x5 pixels and scaled by an integer factor. Digits, letters (lowercase is drawn
+//! as uppercase), `-`, `.` and `_` are covered; other characters are drawn as blanks.
+
+use image::{Rgba, RgbaImage};
+
+/// Glyph height in font pixels.
+pub const GLYPH_HEIGHT: u32 = 5;
+/// Horizontal advance per character in font pixels (3 + 1 spacing).
+const ADVANCE: u32 = 4;
+
+/// Rows of a glyph, 3 bits each (MSB = left column).
+fn glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        _ => [0; 5],
+    }
+}
+
+/// Width of `text` in pixels at `scale` (without the trailing spacing).
+pub fn text_width(text: &str, scale: u32) -> u32 {
+    (text.chars().count() as u32 * ADVANCE).saturating_sub(1) * scale
+}
+
+/// Draw `text` with its top-left corner at (`x`, `y`), clipped to `max_width` pixels and the
+/// canvas.
+pub fn draw_text(
+    canvas: &mut RgbaImage,
+    (x, y): (u32, u32),
+    text: &str,
+    scale: u32,
+    max_width: u32,
+    color: Rgba<u8>,
+) {
+    let right = x.saturating_add(max_width).min(canvas.width());
+    let bottom = y.saturating_add(GLYPH_HEIGHT * scale).min(canvas.height());
+    for (i, c) in text.chars().enumerate() {
+        let left = x + i as u32 * ADVANCE * scale;
+        if left >= right {
+            break;
+        }
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for col in 0..3u32 {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+                let px = left + col * scale;
+                let py = y + row as u32 * scale;
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        if px + dx < right && py + dy < bottom {
+                            canvas.put_pixel(px + dx, py + dy, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_text_scales_and_clips() {
+        let white = Rgba([255, 255, 255, 255]);
+        let mut canvas = RgbaImage::new(20, 12);
+        draw_text(&mut canvas, (0, 0), "1-", 2, 10, white);
+        // `1`: top row has only the middle column set.
+        assert_eq!(canvas.get_pixel(2, 0), &white);
+        assert_eq!(canvas.get_pixel(0, 0).0[3], 0);
+        assert_eq!(canvas.get_pixel(0, 2), &white);
+        // `-` starts at x = 8 and is clipped at 10.
+        assert_eq!(canvas.get_pixel(9, 4), &white);
+        assert_eq!(canvas.get_pixel(10, 4).0[3], 0);
+        assert_eq!(canvas.get_pixel(9, 10).0[3], 0);
+    }
+
+    #[test]
+    fn test_letters_fold_case() {
+        assert_eq!(glyph('a'), glyph('A'));
+        assert_ne!(glyph('A'), [0; 5]);
+        assert_eq!(glyph('~'), [0; 5]);
+        assert_eq!(text_width("ab", 2), 14);
+        assert_eq!(text_width("", 2), 0);
+    }
+}
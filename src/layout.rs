@@ -0,0 +1,224 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Tile page layouts (`tile_layout`).
+//!
+//! - `grid`: equal tiles on the `(cols, rows)` grid.
+//! - `justified`: each row keeps its `cols` images but gives them widths in proportion to their
+//!   aspect ratios, and rows of wide images get less height than rows of tall ones, so mixed
+//!   portrait and landscape thumbnails fill their tiles instead of sitting in square cells.
+//!
+//! With `group_by_date`, each day instead starts a new row under a one-cell band that carries
+//! its date (`grouped_rects`), and the rows shrink to make room.
+//!
+//! Tiles are cell rectangles relative to the image area. The worker composites the thumbnails
+//! into them and the writer draws the cursor, file names and jump labels over the same ones.
+
+use ratatui::layout::Rect;
+
+/// Narrowest and widest aspect ratio a justified tile is sized for, so one panorama does not
+/// squeeze the rest of its row to slivers.
+const MIN_ASPECT: f64 = 0.25;
+const MAX_ASPECT: f64 = 4.0;
+
+/// Height of the band over each day's first row (cells).
+const BAND_HEIGHT: u16 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileLayout {
+    Grid,
+    Justified,
+}
+
+impl TileLayout {
+    /// Parse `grid`/`justified`; unknown values mean `grid`.
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "justified" | "masonry" => TileLayout::Justified,
+            _ => TileLayout::Grid,
+        }
+    }
+}
+
+/// Rectangles of the first `count` tiles of a `(cols, rows)` grid over a `size` (cells) area.
+/// Tile boundaries are cell-aligned, so overlays line up with the composited thumbnails.
+pub fn grid_rects(grid: (usize, usize), size: (u16, u16), count: usize) -> Vec<Rect> {
+    let (cols, rows) = grid;
+    if cols == 0 || rows == 0 {
+        return Vec::new();
+    }
+    (0..count.min(cols * rows))
+        .map(|i| {
+            let (x, right) = span(i % cols, cols, size.0);
+            let (y, bottom) = span(i / cols, rows, size.1);
+            Rect::new(x, y, right - x, bottom - y)
+        })
+        .collect()
+}
+
+/// Justified rectangles for tiles with the given aspect ratios (width / height), `cols` per
+/// row. A short last row gets the height a full row of such images would, and stays
+/// left-aligned instead of stretching across.
+pub fn justified_rects(grid: (usize, usize), size: (u16, u16), aspects: &[f64]) -> Vec<Rect> {
+    let (cols, rows) = grid;
+    if cols == 0 || rows == 0 {
+        return Vec::new();
+    }
+    let aspects: Vec<f64> = aspects
+        .iter()
+        .take(cols * rows)
+        .map(|a| {
+            if a.is_finite() {
+                a.clamp(MIN_ASPECT, MAX_ASPECT)
+            } else {
+                1.0
+            }
+        })
+        .collect();
+    let rows: Vec<&[f64]> = aspects.chunks(cols).collect();
+    // Summed aspect of each row, as if it were full: a row's height is its width over this.
+    let row_sums: Vec<f64> = rows
+        .iter()
+        .map(|row| {
+            let sum: f64 = row.iter().sum();
+            sum * cols as f64 / row.len() as f64
+        })
+        .collect();
+    let heights: Vec<f64> = row_sums.iter().map(|sum| 1.0 / sum).collect();
+    let row_edges = edges(&heights, size.1);
+
+    let mut rects = Vec::with_capacity(aspects.len());
+    for (r, row) in rows.iter().enumerate() {
+        let mut widths = row.to_vec();
+        let filled: f64 = widths.iter().sum();
+        // Unused width of a short row, so its tiles keep full-row proportions.
+        widths.push(row_sums[r] - filled);
+        let col_edges = edges(&widths, size.0);
+        let (y, bottom) = (row_edges[r], row_edges[r + 1]);
+        for c in 0..row.len() {
+            let (x, right) = (col_edges[c], col_edges[c + 1]);
+            rects.push(Rect::new(x, y, right - x, bottom - y));
+        }
+    }
+    rects
+}
+
+/// A day band of a grouped page: its cell rectangle and the day (None for images without one).
+pub type DayBand = (Rect, Option<String>);
+
+/// Rectangles for a `(cols, rows)` page whose tiles have the given capture `days`, with each
+/// day starting a new row under a one-cell band, and the bands. The page's first row gets a
+/// band too, naming the day it continues. Rows shrink to make room; a page with so many days
+/// that its rows would drop below half their grid height gets None (the plain grid).
+pub fn grouped_rects(
+    grid: (usize, usize),
+    size: (u16, u16),
+    days: &[Option<String>],
+) -> Option<(Vec<Rect>, Vec<DayBand>)> {
+    let (cols, rows) = grid;
+    if cols == 0 || rows == 0 {
+        return Some((Vec::new(), Vec::new()));
+    }
+    let days = &days[..days.len().min(cols * rows)];
+    // Rows of tiles, each with the band above it (if it starts a day).
+    let mut lines: Vec<(Option<Option<String>>, usize)> = Vec::new();
+    for (i, day) in days.iter().enumerate() {
+        let starts = i == 0 || days[i - 1] != *day;
+        match lines.last_mut() {
+            Some((_, len)) if !starts && *len < cols => *len += 1,
+            _ => lines.push((starts.then(|| day.clone()), 1)),
+        }
+    }
+    let bands = lines.iter().filter(|(band, _)| band.is_some()).count();
+    let flex = usize::from(size.1).checked_sub(bands * usize::from(BAND_HEIGHT))?;
+    if flex * rows * 2 < lines.len() * usize::from(size.1) {
+        return None;
+    }
+
+    let mut rects = Vec::with_capacity(days.len());
+    let mut day_bands = Vec::with_capacity(bands);
+    let mut y = 0;
+    for (r, (band, len)) in lines.iter().enumerate() {
+        if let Some(day) = band {
+            day_bands.push((Rect::new(0, y, size.0, BAND_HEIGHT), day.clone()));
+            y += BAND_HEIGHT;
+        }
+        let (top, bottom) = span(r, lines.len(), flex as u16);
+        for c in 0..*len {
+            let (x, right) = span(c, cols, size.0);
+            rects.push(Rect::new(x, y, right - x, bottom - top));
+        }
+        y += bottom - top;
+    }
+    Some((rects, day_bands))
+}
+
+/// Cell-aligned start and end of part `i` of `parts` equal parts of `total` cells.
+fn span(i: usize, parts: usize, total: u16) -> (u16, u16) {
+    let at = |i: usize| (i as u32 * u32::from(total) / parts as u32) as u16;
+    (at(i), at(i + 1))
+}
+
+/// Cell-aligned boundaries splitting `total` cells in proportion to `weights`.
+fn edges(weights: &[f64], total: u16) -> Vec<u16> {
+    let sum: f64 = weights.iter().sum();
+    let mut acc = 0.0;
+    let mut edges = vec![0];
+    for w in weights {
+        acc += w;
+        edges.push(((acc / sum * f64::from(total)).round() as u16).min(total));
+    }
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_rects_cover_canvas() {
+        let rects = grid_rects((3, 2), (10, 7), 6);
+        assert_eq!(rects[0], Rect::new(0, 0, 3, 3));
+        assert_eq!(rects[5], Rect::new(6, 3, 4, 4));
+        assert_eq!(grid_rects((3, 2), (10, 7), 9).len(), 6);
+        assert!(grid_rects((0, 2), (10, 7), 6).is_empty());
+    }
+
+    #[test]
+    fn test_justified_rects_follow_aspects() {
+        // A landscape and a square share a row in proportion to their widths, and that row is
+        // shorter than the row of squares.
+        let rects = justified_rects((2, 2), (60, 40), &[2.0, 1.0, 1.0, 1.0]);
+        assert_eq!(rects[0], Rect::new(0, 0, 40, 16));
+        assert_eq!(rects[1], Rect::new(40, 0, 20, 16));
+        assert_eq!(rects[2], Rect::new(0, 16, 30, 24));
+        assert_eq!(rects[3], Rect::new(30, 16, 30, 24));
+
+        // A short last row keeps full-row proportions and leaves the rest empty.
+        let rects = justified_rects((2, 2), (60, 40), &[1.0, 1.0, 1.0]);
+        assert_eq!(rects[2], Rect::new(0, 20, 30, 20));
+        assert_eq!(TileLayout::parse("Justified"), TileLayout::Justified);
+        assert_eq!(TileLayout::parse("bogus"), TileLayout::Grid);
+    }
+
+    #[test]
+    fn test_grouped_rects_break_rows_at_days() {
+        let day = |d: &str| Some(d.to_string());
+        // A 3x3 page: two images of one day, then three of the next (one full row and one
+        // more), so three rows and two bands share the 20 rows of cells.
+        let days = [day("A"), day("A"), day("B"), day("B"), day("B"), day("B")];
+        let (rects, bands) = grouped_rects((3, 3), (30, 20), &days).unwrap();
+        assert_eq!(bands.len(), 2);
+        assert_eq!(bands[0], (Rect::new(0, 0, 30, 1), day("A")));
+        assert_eq!(rects[0], Rect::new(0, 1, 10, 6));
+        assert_eq!(rects[1], Rect::new(10, 1, 10, 6));
+        assert_eq!(bands[1], (Rect::new(0, 7, 30, 1), day("B")));
+        assert_eq!(rects[2], Rect::new(0, 8, 10, 6));
+        assert_eq!(rects[4], Rect::new(20, 8, 10, 6));
+        assert_eq!(rects[5], Rect::new(0, 14, 10, 6));
+
+        // A day per image would need nine rows and bands: keep the plain grid.
+        let days: Vec<_> = (0..9).map(|i| day(&i.to_string())).collect();
+        assert!(grouped_rects((3, 3), (30, 20), &days).is_none());
+    }
+}
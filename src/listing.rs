@@ -0,0 +1,322 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Background image listing.
+//!
+//! Reading and sorting a large directory (especially on a network filesystem) can take long
+//! enough that waiting for it before showing anything feels broken. `Listing` walks the input
+//! paths on a background thread instead:
+//! - the caller blocks only until the first image is found (`Listing::wait_first`)
+//! - later entries arrive in batches and are merged into the image list (`Listing::merge_into`)
+//!
+//! Inputs are processed in order and each input's images stay sorted, so the final list is the
+//! same as a synchronous `collect_images`. `:add` appends through a listing too
+//! (`Listing::spawn_after`). Dropping the `Listing` stops the thread at its next batch.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail};
+
+use crate::sort::natural_cmp;
+
+/// Interval between batches after the first image.
+const BATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+enum Event {
+    /// Images of the current input (unsorted).
+    Found(Vec<PathBuf>),
+    /// The next input starts.
+    NextSource,
+    /// An input could not be listed.
+    Failed(String),
+    Done,
+}
+
+/// Result of `Listing::merge_into`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Merged {
+    /// First index whose path changed (None if no image was added).
+    pub first_changed: Option<usize>,
+    /// Inputs that could not be listed.
+    pub errors: Vec<String>,
+    /// Whether the listing is complete.
+    pub done: bool,
+}
+
+pub struct Listing {
+    rx: Receiver<Event>,
+    /// Start index of the current input's images in the list.
+    segment_start: usize,
+    /// Errors received before the first image (reported by the next merge).
+    errors: Vec<String>,
+}
+
+impl Listing {
+    /// Start listing `paths` in the background.
+    pub fn spawn(paths: Vec<PathBuf>) -> Self {
+        Self::spawn_after(paths, &[])
+    }
+
+    /// Start listing `paths` in the background to append to `listed` (`:add`): images already
+    /// in it are skipped, and the others are merged after it.
+    pub fn spawn_after(paths: Vec<PathBuf>, listed: &[PathBuf]) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let seen: HashSet<PathBuf> = listed.iter().cloned().collect();
+        thread::spawn(move || list_paths(&paths, seen, &tx));
+        Self {
+            rx,
+            segment_start: listed.len(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Block until the first images are found and return them.
+    /// Fails if the listing completes without any image.
+    pub fn wait_first(&mut self) -> Result<Vec<PathBuf>> {
+        let mut images = Vec::new();
+        while images.is_empty() {
+            let Ok(event) = self.rx.recv() else {
+                break;
+            };
+            if matches!(event, Event::Done) {
+                break;
+            }
+            self.apply(event, &mut images);
+        }
+        if images.is_empty() {
+            match self.errors.first() {
+                Some(e) => bail!("{e}"),
+                None => bail!("No image files found"),
+            }
+        }
+        Ok(images)
+    }
+
+    /// Merge everything received since the last call into `images`.
+    pub fn merge_into(&mut self, images: &mut Vec<PathBuf>) -> Merged {
+        let mut merged = Merged::default();
+        loop {
+            let event = match self.rx.try_recv() {
+                Ok(Event::Done) | Err(mpsc::TryRecvError::Disconnected) => {
+                    merged.done = true;
+                    break;
+                }
+                Ok(event) => event,
+                Err(mpsc::TryRecvError::Empty) => break,
+            };
+            if let Some(changed) = self.apply(event, images) {
+                merged.first_changed =
+                    Some(merged.first_changed.map_or(changed, |f| f.min(changed)));
+            }
+        }
+        merged.errors = std::mem::take(&mut self.errors);
+        merged
+    }
+
+    /// Apply one event; returns the first changed index for `Found`.
+    fn apply(&mut self, event: Event, images: &mut Vec<PathBuf>) -> Option<usize> {
+        match event {
+            Event::Found(mut batch) => {
+                if batch.is_empty() {
+                    return None;
+                }
+                batch.sort_by(|a, b| natural_cmp(a, b));
+                let tail = images.split_off(self.segment_start);
+                let first = self.segment_start
+                    + tail.partition_point(|p| natural_cmp(p, &batch[0]).is_lt());
+                images.extend(merge_sorted(tail, batch));
+                Some(first)
+            }
+            Event::NextSource => {
+                self.segment_start = images.len();
+                None
+            }
+            Event::Failed(e) => {
+                self.errors.push(e);
+                None
+            }
+            Event::Done => None,
+        }
+    }
+}
+
+fn merge_sorted(a: Vec<PathBuf>, b: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut out = Vec::with_capacity(a.len() + b.len());
+    let mut a = a.into_iter().peekable();
+    let mut b = b.into_iter().peekable();
+    loop {
+        let next = match (a.peek(), b.peek()) {
+            (Some(x), Some(y)) if natural_cmp(x, y).is_le() => a.next(),
+            (Some(_), Some(_)) => b.next(),
+            (Some(_), None) => a.next(),
+            (None, Some(_)) => b.next(),
+            (None, None) => break,
+        };
+        out.extend(next);
+    }
+    out
+}
+
+/// Thread body. Returns early once the receiver is gone.
+fn list_paths(paths: &[PathBuf], mut seen: HashSet<PathBuf>, tx: &Sender<Event>) {
+    for (i, path) in paths.iter().enumerate() {
+        if i > 0 && !send(tx, Event::NextSource) {
+            return;
+        }
+        let ok = if path.is_dir() {
+            list_dir(path, &mut seen, tx)
+        } else {
+            let event = match crate::collect_images_from_path(path) {
+                Ok(images) => Event::Found(
+                    images
+                        .into_iter()
+                        .filter(|p| seen.insert(p.clone()))
+                        .collect(),
+                ),
+                Err(e) => Event::Failed(e.to_string()),
+            };
+            send(tx, event)
+        };
+        if !ok {
+            return;
+        }
+    }
+    send(tx, Event::Done);
+}
+
+/// Send a listing event and wake the event loop to merge it. Returns false once the receiver
+/// is gone.
+fn send(tx: &Sender<Event>, event: Event) -> bool {
+    let sent = tx.send(event).is_ok();
+    crate::wake::wake();
+    sent
+}
+
+/// Stream the images of `dir`: the first one right away, then a batch every `BATCH_INTERVAL`.
+fn list_dir(dir: &Path, seen: &mut HashSet<PathBuf>, tx: &Sender<Event>) -> bool {
+    let read_dir = match std::fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) => return send(tx, Event::Failed(format!("{e}: {:?}", dir))),
+    };
+    let mut batch = Vec::new();
+    let mut found = 0usize;
+    let mut last_flush: Option<Instant> = None;
+    for path in read_dir
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        if !(path.is_file() && crate::is_image_file(&path)) {
+            continue;
+        }
+        found += 1;
+        if seen.insert(path.clone()) {
+            batch.push(path);
+        }
+        if !batch.is_empty() && last_flush.is_none_or(|t| t.elapsed() >= BATCH_INTERVAL) {
+            if !send(tx, Event::Found(std::mem::take(&mut batch))) {
+                return false;
+            }
+            last_flush = Some(Instant::now());
+        }
+    }
+    if !batch.is_empty() && !send(tx, Event::Found(batch)) {
+        return false;
+    }
+    if found == 0 {
+        let e = format!("No image files found in directory: {:?}", dir);
+        return send(tx, Event::Failed(e));
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn wait_done(listing: &mut Listing, images: &mut Vec<PathBuf>) -> Vec<String> {
+        let mut errors = Vec::new();
+        loop {
+            let merged = listing.merge_into(images);
+            errors.extend(merged.errors);
+            if merged.done {
+                return errors;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_merge_keeps_sources_in_order_and_sorted() {
+        let (tx, rx) = mpsc::channel();
+        let mut listing = Listing {
+            rx,
+            segment_start: 0,
+            errors: Vec::new(),
+        };
+        let p = |s: &str| PathBuf::from(s);
+        tx.send(Event::Found(vec![p("b/2.png")])).unwrap();
+        let mut images = listing.wait_first().unwrap();
+        assert_eq!(images, vec![p("b/2.png")]);
+
+        tx.send(Event::Found(vec![p("b/3.png"), p("b/1.png")]))
+            .unwrap();
+        tx.send(Event::NextSource).unwrap();
+        tx.send(Event::Found(vec![p("a/1.png")])).unwrap();
+        tx.send(Event::Failed("missing".to_string())).unwrap();
+        let merged = listing.merge_into(&mut images);
+        assert_eq!(merged.first_changed, Some(0));
+        assert_eq!(merged.errors, vec!["missing".to_string()]);
+        assert!(!merged.done);
+        assert_eq!(
+            images,
+            vec![p("b/1.png"), p("b/2.png"), p("b/3.png"), p("a/1.png")]
+        );
+
+        tx.send(Event::Done).unwrap();
+        assert!(listing.merge_into(&mut images).done);
+    }
+
+    #[test]
+    fn test_listing_matches_collect_images() {
+        let dir = PathBuf::from("/tmp/stiv_test_listing");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("empty")).unwrap();
+        for name in ["c.png", "a.jpg", "b.png", "notes.txt"] {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+
+        let mut listing = Listing::spawn(vec![dir.clone(), dir.join("a.jpg"), dir.join("empty")]);
+        let mut images = listing.wait_first().unwrap();
+        let errors = wait_done(&mut listing, &mut images);
+        assert_eq!(
+            images,
+            vec![dir.join("a.jpg"), dir.join("b.png"), dir.join("c.png")]
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("No image files found"));
+
+        let mut listing = Listing::spawn(vec![dir.join("empty")]);
+        assert!(listing.wait_first().is_err());
+
+        // Appending skips listed images and keeps the list in front.
+        let mut images = vec![dir.join("z.png"), dir.join("b.png")];
+        let mut listing = Listing::spawn_after(vec![dir.clone()], &images);
+        assert!(wait_done(&mut listing, &mut images).is_empty());
+        assert_eq!(
+            images,
+            vec![
+                dir.join("z.png"),
+                dir.join("b.png"),
+                dir.join("a.jpg"),
+                dir.join("c.png")
+            ]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,206 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Vim-style keyboard macros.
+//!
+//! `Q{reg}` starts recording key presses into register `reg` (`a`-`z`, `0`-`9`), and `Q` (or
+//! `q`, which does not quit while recording) stops. `@{reg}` replays a register, `@@` the last
+//! replayed one; a count (`5@a`) replays it several times. Replayed keys are queued and fed to
+//! the event loop before terminal input, so they go through the same key handling as typed
+//! ones. Registers only live for the session.
+
+use std::collections::{HashMap, VecDeque};
+
+use ratatui::crossterm::event::KeyEvent;
+
+/// Upper bound on keys replayed before the queue drains (stops recursive macros).
+const MAX_REPLAY_KEYS: usize = 10_000;
+
+/// What the next key press selects a register for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Pending {
+    Record,
+    Replay(usize),
+}
+
+#[derive(Debug, Default)]
+pub struct Macros {
+    registers: HashMap<char, Vec<KeyEvent>>,
+    recording: Option<(char, Vec<KeyEvent>)>,
+    pending: Option<Pending>,
+    /// Keys waiting to be replayed.
+    queue: VecDeque<KeyEvent>,
+    /// Keys queued since the queue was last empty.
+    replayed: usize,
+    last_replayed: Option<char>,
+}
+
+impl Macros {
+    /// Register being recorded into.
+    pub fn recording(&self) -> Option<char> {
+        self.recording.as_ref().map(|(reg, _)| *reg)
+    }
+
+    /// Whether the next key press names a register (after `Q` or `@`).
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Keys typed so far of a `Q` / `N@` waiting for its register (e.g. `3@`).
+    pub fn pending_keys(&self) -> Option<String> {
+        match self.pending? {
+            Pending::Record => Some("Q".to_string()),
+            Pending::Replay(1) => Some("@".to_string()),
+            Pending::Replay(count) => Some(format!("{count}@")),
+        }
+    }
+
+    /// `Q` outside recording: the next key selects the register to record into.
+    pub fn begin_record(&mut self) {
+        self.pending = Some(Pending::Record);
+    }
+
+    /// `@`: the next key selects the register to replay `count` times.
+    pub fn begin_replay(&mut self, count: usize) {
+        self.pending = Some(Pending::Replay(count.max(1)));
+    }
+
+    /// Add a typed key to the recording (no-op unless recording).
+    pub fn record(&mut self, key: KeyEvent) {
+        if let Some((_, keys)) = self.recording.as_mut() {
+            keys.push(key);
+        }
+    }
+
+    /// Stop recording; the stop key itself (the last recorded one) is dropped.
+    /// Returns the register and the number of keys stored.
+    pub fn stop_recording(&mut self) -> Option<(char, usize)> {
+        use ratatui::crossterm::event::KeyCode;
+
+        let (reg, mut keys) = self.recording.take()?;
+        if keys
+            .last()
+            .is_some_and(|k| matches!(k.code, KeyCode::Char('q' | 'Q')))
+        {
+            keys.pop();
+        }
+        let len = keys.len();
+        self.registers.insert(reg, keys);
+        Some((reg, len))
+    }
+
+    /// Complete a pending `Q`/`@` with the register key. Returns a message for the status bar.
+    pub fn select_register(&mut self, key: KeyEvent) -> String {
+        use ratatui::crossterm::event::KeyCode;
+
+        let Some(pending) = self.pending.take() else {
+            return String::new();
+        };
+        let reg = match (key.code, pending) {
+            (KeyCode::Char('@'), Pending::Replay(_)) => match self.last_replayed {
+                Some(reg) => reg,
+                None => return "No macro replayed yet".to_string(),
+            },
+            (KeyCode::Char(c), _) if c.is_ascii_lowercase() || c.is_ascii_digit() => c,
+            _ => return "Macro cancelled (registers: a-z, 0-9)".to_string(),
+        };
+        match pending {
+            Pending::Record => {
+                self.recording = Some((reg, Vec::new()));
+                format!("Recording @{reg} (Q to stop)")
+            }
+            Pending::Replay(count) => {
+                let Some(keys) = self.registers.get(&reg).filter(|k| !k.is_empty()) else {
+                    return format!("Register {reg} is empty");
+                };
+                self.last_replayed = Some(reg);
+                let total = keys.len().saturating_mul(count);
+                if self.replayed.saturating_add(total) > MAX_REPLAY_KEYS {
+                    self.queue.clear();
+                    self.replayed = 0;
+                    return format!("Macro @{reg} stopped: more than {MAX_REPLAY_KEYS} keys");
+                }
+                self.replayed += total;
+                // In front of what is still queued, so nested `@` runs in place.
+                for _ in 0..count {
+                    for key in keys.iter().rev() {
+                        self.queue.push_front(*key);
+                    }
+                }
+                format!("Replaying @{reg}")
+            }
+        }
+    }
+
+    /// Whether replayed keys are waiting.
+    pub fn is_replaying(&self) -> bool {
+        !self.queue.is_empty()
+    }
+
+    /// Next replayed key, if any.
+    pub fn next_key(&mut self) -> Option<KeyEvent> {
+        let key = self.queue.pop_front();
+        // Only reset once the last key was handled: a recursive macro refills the queue then.
+        if key.is_none() {
+            self.replayed = 0;
+        }
+        key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::event::{KeyCode, KeyModifiers};
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    fn drain(macros: &mut Macros) -> String {
+        std::iter::from_fn(|| macros.next_key())
+            .map(|k| match k.code {
+                KeyCode::Char(c) => c,
+                _ => '?',
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_record_and_replay_with_count() {
+        let mut macros = Macros::default();
+        macros.begin_record();
+        assert!(macros.is_pending());
+        macros.select_register(key('a'));
+        assert_eq!(macros.recording(), Some('a'));
+        for c in ['j', 'y', 'Q'] {
+            macros.record(key(c));
+        }
+        assert_eq!(macros.stop_recording(), Some(('a', 2)));
+
+        macros.begin_replay(2);
+        assert_eq!(macros.pending_keys().as_deref(), Some("2@"));
+        assert_eq!(macros.select_register(key('a')), "Replaying @a");
+        assert_eq!(macros.pending_keys(), None);
+        assert_eq!(drain(&mut macros), "jyjy");
+
+        macros.begin_replay(1);
+        macros.select_register(key('@'));
+        assert_eq!(drain(&mut macros), "jy");
+    }
+
+    #[test]
+    fn test_invalid_and_runaway_replays() {
+        let mut macros = Macros::default();
+        macros.begin_replay(1);
+        assert!(macros.select_register(key('b')).contains("empty"));
+        macros.begin_record();
+        assert!(macros.select_register(key('!')).contains("cancelled"));
+        assert_eq!(macros.recording(), None);
+
+        macros.registers.insert('a', vec![key('j'); 10]);
+        macros.begin_replay(MAX_REPLAY_KEYS);
+        assert!(macros.select_register(key('a')).contains("stopped"));
+        assert!(macros.next_key().is_none());
+    }
+}
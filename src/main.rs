@@ -11,15 +11,69 @@
 //!
 //! Terminal output is centralized in `TerminalWriter` (see `src/sender.rs`).
 
+mod adjust;
+mod animation;
 mod app;
+mod archive;
+mod blend;
+mod bookmarks;
+mod browser;
+mod calibrate;
+mod cmyk;
+mod command;
 mod config;
+mod depth;
+mod dicom;
+mod diff;
+mod disk_cache;
+mod exif;
 mod fit;
+mod grid;
+mod halfblocks;
+mod heif;
+mod help;
+mod iterm2;
+mod job;
+mod jump;
+mod keymap;
 mod kgp;
+mod label;
+mod layout;
+mod listing;
+mod macros;
+mod notify;
+mod pages;
+mod panes;
+mod placeholder;
+mod power;
 mod prefetch;
+mod preview;
+mod protocol;
+mod raw;
+mod rotation;
+mod scan;
 mod sender;
+mod session;
+mod sixel;
+mod slideshow;
+mod sniff;
+mod sort;
+mod spread;
+mod svg;
+mod textbuf;
+mod trash;
+mod video;
+mod virtual_path;
+mod wake;
+mod wheel;
+mod wipe;
 mod worker;
+mod wsl;
+mod yank;
+mod zoom;
 
 use std::{
+    num::NonZeroUsize,
     path::{Path, PathBuf},
     time::Duration,
 };
@@ -27,35 +81,210 @@ use std::{
 use anyhow::Result;
 use clap::Parser;
 use ratatui::crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind},
+    event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
     terminal,
 };
 use ratatui::layout::Rect;
 
 use crate::app::App;
-use crate::app::is_tmux_env;
+use crate::app::{format_size, is_tmux_env};
 use crate::config::Config;
 use crate::fit::ViewMode;
+use crate::keymap::{Action, Chord, Keymap};
+use crate::listing::Listing;
+use crate::macros::Macros;
+use crate::notify::{Mode, Notifications};
+use crate::sender::StatusIndicator;
+use crate::session::Session;
+use crate::wheel::{Wheel, WheelMode};
+use crate::yank::Yank;
 
 #[derive(Parser, Debug)]
 #[command(name = "stiv", about = "Simple Terminal Image Viewer")]
 struct Cli {
-    /// Image file(s) and/or directory path(s)
-    #[arg(required = true)]
+    /// Image file(s), directory path(s) and/or tar archive(s)
+    #[arg(required_unless_present_any = ["list_caps", "clear"])]
     paths: Vec<PathBuf>,
+
+    /// Print the detected terminal graphics capabilities and exit
+    #[arg(long)]
+    list_caps: bool,
+
+    /// Image protocol (default: the `protocol` config key, `auto`)
+    #[arg(long, value_name = "PROTOCOL", value_parser = protocol::Choice::NAMES)]
+    protocol: Option<String>,
+
+    /// Draw the first image into a file-manager preview pane and exit (fzf, yazi, lf)
+    #[arg(long)]
+    preview: bool,
+
+    /// Preview pane as COLSxROWS@XxY (default: fzf preview size or the terminal, at the cursor)
+    #[arg(long, value_name = "AREA", requires = "preview")]
+    place: Option<String>,
+
+    /// Delete the image drawn by a previous --preview and exit
+    #[arg(long, conflicts_with = "preview")]
+    clear: bool,
+
+    /// Record key presses with timestamps to a JSON Lines file
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Replay key presses from a file written by --record
+    #[arg(long, value_name = "FILE")]
+    replay: Option<PathBuf>,
+
+    /// Keep only the first N images (in list order)
+    #[arg(long, value_name = "N", conflicts_with = "sample")]
+    max_images: Option<NonZeroUsize>,
+
+    /// Keep a random subset of N images (list order is preserved)
+    #[arg(long, value_name = "N")]
+    sample: Option<NonZeroUsize>,
+
+    /// Show a difference heatmap of each image against this reference image
+    #[arg(long, value_name = "FILE")]
+    diff: Option<PathBuf>,
 }
 
-const SUPPORTED_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp"];
+/// Limits on the image list (`--max-images`, `--sample`), applied to every opened list.
+#[derive(Clone, Copy, Debug, Default)]
+struct ListLimit {
+    max_images: Option<usize>,
+    sample: Option<usize>,
+}
 
-fn is_image_file(path: &Path) -> bool {
+/// An opened image list: the images found so far, the status note for a limited list, and the
+/// background listing that keeps appending images.
+type Opened = (Vec<PathBuf>, Option<String>, Option<Listing>);
+
+impl ListLimit {
+    /// Open `paths`.
+    ///
+    /// Without a limit, directories are listed in the background and this returns as soon as
+    /// the first image is found. A limit needs the complete list, so it is collected up front;
+    /// the status note (e.g. `of 200000, sampled`) is set when images were dropped.
+    fn open(self, paths: &[PathBuf]) -> Result<Opened> {
+        if self.max_images.is_none() && self.sample.is_none() {
+            check_paths(paths)?;
+            let mut listing = Listing::spawn(paths.to_vec());
+            let images = listing.wait_first()?;
+            return Ok((images, None, Some(listing)));
+        }
+        let (images, note) = self.apply(collect_images(paths)?, 0, random_seed());
+        Ok((images, note, None))
+    }
+
+    /// Open `paths` to append to the `listed` images (`:add`), skipping those already listed.
+    ///
+    /// Like `open`, without a limit the images arrive from a background listing (none are
+    /// returned up front). A limit caps the whole list, so only the room left is filled.
+    fn add(self, paths: &[PathBuf], listed: &[PathBuf]) -> Result<Opened> {
+        if self.max_images.is_none() && self.sample.is_none() {
+            check_paths(paths)?;
+            let listing = Listing::spawn_after(paths.to_vec(), listed);
+            return Ok((Vec::new(), None, Some(listing)));
+        }
+        let seen: std::collections::HashSet<&PathBuf> = listed.iter().collect();
+        let mut images = collect_images(paths)?;
+        images.retain(|path| !seen.contains(path));
+        let (images, note) = self.apply(images, listed.len(), random_seed());
+        Ok((images, note, None))
+    }
+
+    /// Apply the limits to a list of the `listed` images followed by `images`; only `images`
+    /// are dropped.
+    fn apply(
+        self,
+        mut images: Vec<PathBuf>,
+        listed: usize,
+        seed: u64,
+    ) -> (Vec<PathBuf>, Option<String>) {
+        let total = listed + images.len();
+        if let Some(n) = self.sample.filter(|&n| n < total) {
+            sample_in_order(&mut images, n.saturating_sub(listed), seed);
+            return (images, Some(format!("of {total}, sampled")));
+        }
+        if let Some(n) = self.max_images.filter(|&n| n < total) {
+            images.truncate(n.saturating_sub(listed));
+            return (images, Some(format!("of {total}, truncated")));
+        }
+        (images, None)
+    }
+}
+
+/// Shuffle `items` in place (Fisher-Yates with xorshift64).
+fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed | 1;
+    for i in (1..items.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        items.swap(i, (state % (i as u64 + 1)) as usize);
+    }
+}
+
+/// Keep a random `n` of `items` in their order (selection sampling with xorshift64): each item
+/// is kept with the chance of still needed over still left, so no index list is built and
+/// exactly `n` are kept.
+fn sample_in_order<T>(items: &mut Vec<T>, n: usize, seed: u64) {
+    let mut state = seed | 1;
+    let mut needed = n.min(items.len()) as u64;
+    let mut left = items.len() as u64;
+    items.retain(|_| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let keep = state % left < needed;
+        left -= 1;
+        needed -= u64::from(keep);
+        keep
+    });
+}
+
+fn random_seed() -> u64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+    nanos ^ u64::from(std::process::id()).rotate_left(32)
+}
+
+const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "webp", "ff", "tif", "tiff", "bmp", "ico", "tga", "pnm", "pbm",
+    "pgm", "ppm", "pam",
+];
+
+/// Check if the path is an image stiv can open by its extension alone. The browser and `:open`
+/// completion scan whole directories while the user types, so they never read file contents.
+fn has_image_extension(path: &Path) -> bool {
     path.extension()
         .and_then(|ext| ext.to_str())
         .map(|ext| SUPPORTED_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
         .unwrap_or(false)
+        || dicom::is_dicom(path)
+        || heif::is_heif(path)
+        || svg::is_svg(path)
+        || raw::is_raw(path)
+        || video::is_video(path)
+}
+
+/// Check if the path is an image stiv can open: by extension, then by content for files with
+/// no extension or an odd one. Known non-image extensions are not opened, so listing a
+/// directory of documents does not read each one.
+fn is_image_file(path: &Path) -> bool {
+    has_image_extension(path) || (sniff::may_be_image(path) && sniff::is_image(path))
 }
 
 fn collect_images_from_path(path: &Path) -> Result<Vec<PathBuf>> {
     if path.is_file() {
+        if archive::is_archive(path) {
+            let images = archive::list_images(path, is_image_file)?;
+            if images.is_empty() {
+                anyhow::bail!("No image files found in archive: {:?}", path);
+            }
+            return Ok(images);
+        }
         if is_image_file(path) {
             return Ok(vec![path.to_path_buf()]);
         } else {
@@ -69,7 +298,7 @@ fn collect_images_from_path(path: &Path) -> Result<Vec<PathBuf>> {
             .map(|entry| entry.path())
             .filter(|p| p.is_file() && is_image_file(p))
             .collect();
-        images.sort();
+        images.sort_by(|a, b| sort::natural_cmp(a, b));
         if images.is_empty() {
             anyhow::bail!("No image files found in directory: {:?}", path);
         }
@@ -93,25 +322,71 @@ fn collect_images(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
     Ok(out)
 }
 
+/// Fail early on inputs that cannot be opened (missing paths, unsupported files), so a typo
+/// is reported before the UI starts instead of after a background listing.
+fn check_paths(paths: &[PathBuf]) -> Result<()> {
+    for path in paths {
+        if path.is_file() {
+            if !archive::is_archive(path) && !is_image_file(path) {
+                anyhow::bail!("Not a supported image file: {:?}", path);
+            }
+        } else if !path.is_dir() {
+            anyhow::bail!("Path does not exist: {:?}", path);
+        }
+    }
+    Ok(())
+}
+
 fn use_alt_screen(config: &Config) -> bool {
     config.force_alt_screen || (!config.no_alt_screen && !is_tmux_env())
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
-    let images = collect_images(&cli.paths)?;
-    let config = Config::load();
+    let mut config = Config::load();
+    if let Some(protocol) = &cli.protocol {
+        config.protocol = protocol.clone();
+    }
+    if cli.list_caps {
+        println!(
+            "{}",
+            protocol::Capabilities::detect(&config.protocol).report()
+        );
+        return Ok(());
+    }
+    if cli.clear {
+        return preview::clear();
+    }
+    if cli.preview {
+        let pane = preview::Pane::resolve(cli.place.as_deref())?;
+        let path = match &cli.diff {
+            Some(reference) => virtual_path::VirtualPath::Diff {
+                image: &cli.paths[0],
+                reference,
+            }
+            .to_path(),
+            None => cli.paths[0].clone(),
+        };
+        return preview::run(&path, pane, &config);
+    }
+    let list_limit = ListLimit {
+        max_images: cli.max_images.map(NonZeroUsize::get),
+        sample: cli.sample.map(NonZeroUsize::get),
+    };
+    let opened = list_limit.open(&cli.paths)?;
+    let session = Session::new(cli.record.as_deref(), cli.replay.as_deref())?;
 
     let use_alt = use_alt_screen(&config);
-    init_terminal(use_alt)?;
-    let result = run(images, config);
-    restore_terminal(use_alt);
+    let mouse = Wheel::from_config(&config).enabled();
+    init_terminal(use_alt, mouse)?;
+    let result = run(opened, list_limit, config, session, cli.diff);
+    restore_terminal(use_alt, mouse);
 
     result
 }
 
-/// Duration to show temporary status messages (e.g., "Copied to clipboard").
-const TEMP_STATUS_DURATION: Duration = Duration::from_millis(1500);
+/// Duration to show the startup warning when graphics support is missing.
+const CAPABILITY_WARNING_DURATION: Duration = Duration::from_secs(8);
 
 /// Mutable state for the event loop.
 struct RunState {
@@ -119,45 +394,245 @@ struct RunState {
     nav_until: std::time::Instant,
     /// Vim-style count prefix (e.g., `5j` = move 5 times).
     count: u32,
-    /// Last rendered status text (for change detection).
-    last_status: String,
+    /// Last rendered status text and pending keys (for change detection).
+    last_status: (String, String),
     /// Last terminal size (for resize detection).
     last_size: (u16, u16),
     /// Last status indicator (Busy/Ready).
-    last_indicator: crate::sender::StatusIndicator,
-    /// Deadline for temporary status message display.
-    temp_status_until: Option<std::time::Instant>,
+    last_indicator: StatusIndicator,
+    /// Temporary status messages (shown instead of or after the regular status while alive).
+    notifications: Notifications,
+    /// Default lifetime of temporary messages (`status_message_ms`).
+    message_ttl: Duration,
     /// Whether image transmission was in progress last frame.
     was_transmitting: bool,
+    /// Applied to image lists opened from the browser or command line too.
+    list_limit: ListLimit,
+    /// Keyboard macro registers and the replay queue (`Q{reg}`, `@{reg}`).
+    macros: Macros,
+    /// `"` was pressed; the next key selects the yank register.
+    yank_prefix: bool,
+    /// Register selected with `"{reg}` for the next `y`.
+    yank: Option<Yank>,
+    /// Keys typed so far of a multi-key binding (`g` of `gg`).
+    chord: Chord,
+    /// Ctrl-Z was pressed (or `SIGTSTP` caught); suspend once the pending events are handled.
+    suspend: bool,
 }
 
 impl RunState {
-    fn new() -> Self {
+    fn new(list_limit: ListLimit, message_ttl: Duration) -> Self {
         Self {
             nav_until: std::time::Instant::now() - Duration::from_secs(1),
             count: 0,
-            last_status: String::new(),
+            last_status: Default::default(),
             last_size: (0, 0),
-            last_indicator: crate::sender::StatusIndicator::Busy,
-            temp_status_until: None,
+            last_indicator: StatusIndicator::Busy,
+            notifications: Notifications::default(),
+            message_ttl,
             was_transmitting: false,
+            list_limit,
+            macros: Macros::default(),
+            yank_prefix: false,
+            chord: Chord::default(),
+            yank: None,
+            suspend: false,
+        }
+    }
+
+    /// Keys of a command still being typed (`"`, `Q`, `3@`, a count), shown in the status bar.
+    fn pending_keys(&self) -> String {
+        let mut keys = String::new();
+        if self.yank_prefix {
+            keys.push('"');
+        }
+        if let Some(macro_keys) = self.macros.pending_keys() {
+            keys.push_str(&macro_keys);
+        }
+        if self.count > 0 {
+            keys.push_str(&self.count.to_string());
+        }
+        keys.push_str(&self.chord.label());
+        keys
+    }
+
+    /// Collect images for a new list (browser, command line), applying `--max-images`/`--sample`.
+    fn open_images(&mut self, app: &mut App, paths: &[PathBuf]) -> bool {
+        match self.list_limit.open(paths) {
+            Ok((images, note, listing)) => {
+                app.open_images(images);
+                app.set_list_note(note);
+                app.set_listing(listing);
+                true
+            }
+            Err(e) => {
+                self.notify(e.to_string(), StatusIndicator::Busy);
+                false
+            }
+        }
+    }
+
+    /// Append the images at `paths` to the list (`:add`), applying `--max-images`/`--sample` to
+    /// the whole list. Without a limit they are listed in the background and reported by
+    /// `notify_added` once complete. Returns true if the list changed or is being appended to.
+    fn add_images(&mut self, app: &mut App, paths: &[PathBuf], terminal_rect: Rect) -> bool {
+        if app.is_listing() {
+            self.notify("Still listing images", StatusIndicator::Busy);
+            return false;
+        }
+        match self.list_limit.add(paths, app.images()) {
+            Ok((images, note, listing)) => {
+                if listing.is_some() {
+                    app.add_listing(listing);
+                    return true;
+                }
+                let added = app.append_images(images, terminal_rect);
+                if note.is_some() {
+                    app.set_list_note(note);
+                }
+                self.notify_added(added);
+                added > 0
+            }
+            Err(e) => {
+                self.notify(e.to_string(), StatusIndicator::Busy);
+                false
+            }
+        }
+    }
+
+    /// Report how many images `:add` appended.
+    fn notify_added(&mut self, added: usize) {
+        let text = match added {
+            0 => "No new images".to_string(),
+            1 => "Added 1 image".to_string(),
+            n => format!("Added {n} images"),
+        };
+        self.notify(text, StatusIndicator::Ready);
+    }
+
+    /// Show a temporary status message instead of the regular status.
+    fn notify(&mut self, text: impl Into<String>, indicator: StatusIndicator) {
+        self.notify_with(text, indicator, Mode::Replace, 1);
+    }
+
+    /// Show a temporary status message with its own mode, for `scale` times the default
+    /// duration.
+    fn notify_with(
+        &mut self,
+        text: impl Into<String>,
+        indicator: StatusIndicator,
+        mode: Mode,
+        scale: u32,
+    ) {
+        self.notifications.push(
+            text,
+            indicator,
+            mode,
+            self.message_ttl * scale,
+            std::time::Instant::now(),
+        );
+    }
+
+    /// Report the result of copying the image (`YankImage`).
+    fn notify_image_copy(&mut self, copied: bool) {
+        if copied {
+            self.notify_with(
+                "Copied image to clipboard",
+                StatusIndicator::Ready,
+                Mode::Merge,
+                1,
+            );
+        } else {
+            self.notify("Failed to copy image", StatusIndicator::Busy);
         }
     }
 }
 
-fn run(images: Vec<PathBuf>, config: Config) -> Result<()> {
+/// Next input event: a replayed macro key first, then the session (terminal or `--replay`).
+/// The flag tells whether the key came from a macro (such keys are not recorded again).
+fn next_input(
+    macros: &mut Macros,
+    session: &mut Session,
+) -> std::io::Result<Option<(Event, bool)>> {
+    if let Some(key) = macros.next_key() {
+        return Ok(Some((Event::Key(key), true)));
+    }
+    Ok(session.next_event()?.map(|ev| (ev, false)))
+}
+
+fn run(
+    (images, list_note, listing): Opened,
+    list_limit: ListLimit,
+    config: Config,
+    mut session: Session,
+    diff: Option<PathBuf>,
+) -> Result<()> {
     use std::time::Instant;
 
     let nav_latch = Duration::from_millis(config.nav_latch_ms);
-    let cell_aspect_ratio = config.cell_aspect_ratio;
+    let message_ttl = Duration::from_millis(config.status_message_ms);
+    let use_alt = use_alt_screen(&config);
+    let (keymap, key_errors) = Keymap::from_config(&config);
+    let wheel = Wheel::from_config(&config);
     let mut app = App::new(images, config)?;
-    let mut state = RunState::new();
+    app.set_diff(diff);
+    app.set_list_note(list_note);
+    app.set_listing(listing);
+    let mut state = RunState::new(list_limit, message_ttl);
+    // After `App::new`: the terminal is queried there and answers on stdin.
+    session.start_input();
+    if let Some(warning) = app.capability_warning() {
+        state.notifications.push(
+            warning,
+            StatusIndicator::Busy,
+            Mode::Replace,
+            CAPABILITY_WARNING_DURATION,
+            Instant::now(),
+        );
+    }
+    for error in key_errors {
+        state.notify(error, StatusIndicator::Busy);
+    }
+    let job_signals = job::JobSignals::watch()?;
 
     loop {
         // Get terminal size once per iteration
         let (term_w, term_h) = terminal::size()?;
         let terminal_rect = Rect::new(0, 0, term_w, term_h);
 
+        // Append images found by the background listing
+        for error in app.poll_listing(terminal_rect) {
+            state.notify(error, StatusIndicator::Busy);
+        }
+        if let Some(added) = app.take_added() {
+            state.notify_added(added);
+        }
+
+        // Copy a data URI once it is encoded
+        match app.poll_clipboard() {
+            Some(Ok(len)) => state.notify_with(
+                format!("Copied data URI to clipboard ({} KiB)", len.div_ceil(1024)),
+                StatusIndicator::Ready,
+                Mode::Merge,
+                1,
+            ),
+            Some(Err(e)) => state.notify(e.to_string(), StatusIndicator::Busy),
+            None => {}
+        }
+
+        if let Some(copied) = app.poll_image_copy() {
+            state.notify_image_copy(copied);
+        }
+
+        match app.poll_save() {
+            Some(Ok((path, (w, h)))) => state.notify(
+                format!("Wrote {} ({w}x{h})", path.display()),
+                StatusIndicator::Ready,
+            ),
+            Some(Err(e)) => state.notify(e.to_string(), StatusIndicator::Busy),
+            None => {}
+        }
+
         // Poll worker for completed renders
         app.poll_worker();
 
@@ -166,24 +641,64 @@ fn run(images: Vec<PathBuf>, config: Config) -> Result<()> {
         app.poll_writer();
         let transmitting_after = app.is_transmitting();
 
+        // Show the next animation frame once it is due
+        app.tick_animation(terminal_rect);
+
+        if app.tick_slideshow(Instant::now()) {
+            state.notify("Slideshow finished", StatusIndicator::Ready);
+        }
+
         // Draw tile cursor after image transmission completes
         if state.was_transmitting && !transmitting_after && app.view_mode == ViewMode::Tile {
             app.draw_tile_cursor(terminal_rect);
         }
         state.was_transmitting = transmitting_before || transmitting_after;
 
-        // Process all pending events first (drain the queue)
-        while event::poll(Duration::ZERO)? {
-            let ev = event::read()?;
+        // A chord prefix that waited too long runs its own binding (`g` when `gg` is bound).
+        if let Some(actions) = keymap.expire(&mut state.chord, Instant::now()) {
+            let n = state.count.max(1) as i32;
+            if let Some(action) = actions
+                .iter()
+                .copied()
+                .find(|&action| action_applies(action, &app, state.count))
+                && run_action(
+                    &mut app,
+                    &mut state,
+                    &keymap,
+                    action,
+                    n,
+                    None,
+                    terminal_rect,
+                )
+            {
+                latch_nav(&mut app, &mut state, nav_latch);
+            }
+            state.count = 0;
+        }
 
+        // Process all pending events first (drain the queue)
+        while let Some((ev, replayed)) = next_input(&mut state.macros, &mut session)? {
             // Handle resize events
             if let Event::Resize(new_w, new_h) = ev {
                 // Clear entire screen (including old status bar position)
-                clear_screen();
+                clear_screen(use_alt, new_h);
                 // Force full redraw on resize
                 app.handle_resize();
+                app.redraw_menu(Rect::new(0, 0, new_w, new_h));
                 state.last_size = (new_w, new_h);
-                state.last_status.clear(); // Force status redraw
+                state.last_status = Default::default(); // Force status redraw
+                continue;
+            }
+
+            // Mouse events only arrive while a wheel mode captures the mouse.
+            if let Event::Mouse(mouse) = ev {
+                if let Some(delta) = wheel.delta(mouse.kind)
+                    && !app.is_capturing()
+                    && scroll_wheel(&mut app, &wheel, delta, terminal_rect)
+                {
+                    latch_nav(&mut app, &mut state, nav_latch);
+                    break;
+                }
                 continue;
             }
 
@@ -192,8 +707,37 @@ fn run(images: Vec<PathBuf>, config: Config) -> Result<()> {
             {
                 let mut did_nav = false;
 
+                if !replayed {
+                    state.macros.record(key);
+                }
+                // After `Q` / `@`, the next key names the register.
+                if state.macros.is_pending() {
+                    let text = state.macros.select_register(key);
+                    state.notify(text, StatusIndicator::Ready);
+                    continue;
+                }
+                // After `"`, the next key names the yank register used by the following `y`.
+                if std::mem::take(&mut state.yank_prefix) {
+                    state.yank = match key.code {
+                        KeyCode::Char(c) => Yank::from_register(c),
+                        _ => None,
+                    };
+                    match state.yank {
+                        Some(yank) => state.notify_with(
+                            format!("Next y copies the {}", yank.label()),
+                            StatusIndicator::Ready,
+                            Mode::Merge,
+                            1,
+                        ),
+                        None => state.notify("No such yank register", StatusIndicator::Busy),
+                    }
+                    continue;
+                }
+
                 if let KeyCode::Char(c) = key.code
                     && c.is_ascii_digit()
+                    && !app.is_command_active()
+                    && !app.is_showing_help()
                 {
                     // Vim-like count prefix: `1..9` start, `0` continues (not a command on its own).
                     if c != '0' || state.count != 0 {
@@ -207,188 +751,72 @@ fn run(images: Vec<PathBuf>, config: Config) -> Result<()> {
                 }
 
                 let n = state.count.max(1) as i32;
-                let grid = App::calculate_tile_grid(terminal_rect, cell_aspect_ratio);
-
-                match key.code {
-                    KeyCode::Char('q') => app.should_quit = true,
-                    KeyCode::Char('j') | KeyCode::Char(' ') => match app.view_mode {
-                        ViewMode::Single => {
-                            app.move_by(n);
-                            did_nav = true;
-                        }
-                        ViewMode::Tile => {
-                            let page_changed = app.move_tile_cursor_row(n, grid);
-                            if page_changed {
-                                did_nav = true;
-                            } else {
-                                app.draw_tile_cursor(terminal_rect);
-                            }
-                        }
-                    },
-                    KeyCode::Char('k') | KeyCode::Backspace => match app.view_mode {
-                        ViewMode::Single => {
-                            app.move_by(-n);
-                            did_nav = true;
-                        }
-                        ViewMode::Tile => {
-                            let page_changed = app.move_tile_cursor_row(-n, grid);
-                            if page_changed {
-                                did_nav = true;
-                            } else {
-                                app.draw_tile_cursor(terminal_rect);
-                            }
+                // A selected yank register only applies to the very next key.
+                let yank = state.yank.take();
+
+                if app.is_command_active() {
+                    // The command line captures all keys until it is executed or cancelled.
+                    did_nav = handle_command_key(&mut app, key, terminal_rect, &mut state);
+                } else if app.is_browsing() {
+                    // The directory browser captures all keys until it is closed.
+                    did_nav = handle_browser_key(&mut app, key.code, n, terminal_rect, &mut state);
+                } else if app.is_calibrating() {
+                    // The calibration screen captures all keys until it is closed.
+                    handle_calibration_key(&mut app, key.code, terminal_rect, &mut state);
+                } else if app.is_showing_help() {
+                    // Any key closes the help screen.
+                    app.close_help(terminal_rect);
+                } else if app.is_selecting() {
+                    // Zoom selection captures all keys until it is confirmed or cancelled.
+                    did_nav = handle_selection_key(&mut app, key.code, n, terminal_rect);
+                } else if app.is_jumping() {
+                    // Jump labels capture the next keys until a label is typed.
+                    match key.code {
+                        KeyCode::Char(c) if c.is_ascii_lowercase() => {
+                            app.jump_key(c, terminal_rect)
                         }
-                    },
-                    KeyCode::Char('h') => match app.view_mode {
-                        ViewMode::Single => {
-                            app.move_by(-n);
-                            did_nav = true;
-                        }
-                        ViewMode::Tile => {
-                            let page_changed = app.move_tile_cursor(-n, grid);
-                            if page_changed {
-                                did_nav = true;
-                            } else {
-                                app.draw_tile_cursor(terminal_rect);
-                            }
-                        }
-                    },
-                    KeyCode::Char('l') => match app.view_mode {
-                        ViewMode::Single => {
-                            app.move_by(n);
-                            did_nav = true;
-                        }
-                        ViewMode::Tile => {
-                            let page_changed = app.move_tile_cursor(n, grid);
-                            if page_changed {
-                                did_nav = true;
-                            } else {
-                                app.draw_tile_cursor(terminal_rect);
+                        _ => app.cancel_jump(terminal_rect),
+                    }
+                } else {
+                    // A held key queues repeats faster than images render: run them as one
+                    // move, so the nav latch starts once per burst instead of once per key.
+                    let n = match keymap.single(&state.chord, key, |action| {
+                        action_applies(action, &app, state.count)
+                    }) {
+                        Some(action) if action.coalesces() && !replayed => {
+                            let repeats = session.take_repeats(key);
+                            for _ in 0..repeats {
+                                state.macros.record(key);
                             }
+                            coalesced_count(n, repeats)
                         }
-                    },
-                    // Shift+HJKL: page navigation in Tile mode, same as lowercase in Single mode
-                    KeyCode::Char('H') => match app.view_mode {
-                        ViewMode::Single => {
-                            app.move_by(-n);
-                            did_nav = true;
-                        }
-                        ViewMode::Tile => {
-                            app.move_tile_page(-n, grid);
-                            did_nav = true;
-                        }
-                    },
-                    KeyCode::Char('J') => match app.view_mode {
-                        ViewMode::Single => {
-                            app.move_by(n);
-                            did_nav = true;
-                        }
-                        ViewMode::Tile => {
-                            app.move_tile_page(n, grid);
-                            did_nav = true;
-                        }
-                    },
-                    KeyCode::Char('K') => match app.view_mode {
-                        ViewMode::Single => {
-                            app.move_by(-n);
-                            did_nav = true;
-                        }
-                        ViewMode::Tile => {
-                            app.move_tile_page(-n, grid);
-                            did_nav = true;
-                        }
-                    },
-                    KeyCode::Char('L') => match app.view_mode {
-                        ViewMode::Single => {
-                            app.move_by(n);
-                            did_nav = true;
-                        }
-                        ViewMode::Tile => {
-                            app.move_tile_page(n, grid);
-                            did_nav = true;
-                        }
-                    },
-                    KeyCode::Enter => {
-                        if app.view_mode == ViewMode::Tile {
-                            app.select_tile();
-                            did_nav = true;
-                        }
-                    }
-                    KeyCode::Char('g') => {
-                        // Vim-like: `g` (or `N g`) goes to first / Nth (1-based) image.
-                        let target = if state.count > 0 {
-                            (state.count as usize).saturating_sub(1)
-                        } else {
-                            0
-                        };
-                        app.go_to_index_with_tile(target);
-                        did_nav = true;
-                    }
-                    KeyCode::Char('G') => {
-                        // Vim-like: `G` (or `N G`) goes to last / Nth (1-based) image.
-                        let target = if state.count > 0 {
-                            (state.count as usize).saturating_sub(1)
-                        } else {
-                            app.images.len().saturating_sub(1)
-                        };
-                        app.go_to_index_with_tile(target);
-                        did_nav = true;
-                    }
-                    KeyCode::Char('f') => {
-                        app.toggle_fit_mode();
-                        did_nav = true;
-                    }
-                    KeyCode::Char('r') => {
-                        app.reload();
-                        did_nav = true;
-                    }
-                    KeyCode::Char('t') => {
-                        app.toggle_view_mode();
-                        did_nav = true;
-                    }
-                    KeyCode::Char('y') => {
-                        if app.copy_path_to_clipboard() {
-                            app.send_status(
-                                "Copied path to clipboard".to_string(),
-                                (term_w, term_h),
-                                crate::sender::StatusIndicator::Ready,
-                            );
-                        } else {
-                            app.send_status(
-                                "Failed to copy path".to_string(),
-                                (term_w, term_h),
-                                crate::sender::StatusIndicator::Busy,
+                        _ => n,
+                    };
+                    for actions in keymap.feed(&mut state.chord, key, Instant::now()) {
+                        if let Some(action) = actions
+                            .iter()
+                            .copied()
+                            .find(|&action| action_applies(action, &app, state.count))
+                        {
+                            did_nav |= run_action(
+                                &mut app,
+                                &mut state,
+                                &keymap,
+                                action,
+                                n,
+                                yank,
+                                terminal_rect,
                             );
                         }
-                        state.temp_status_until = Some(Instant::now() + TEMP_STATUS_DURATION);
                     }
-                    KeyCode::Char('Y') => {
-                        if app.copy_image_to_clipboard() {
-                            app.send_status(
-                                "Copied image to clipboard".to_string(),
-                                (term_w, term_h),
-                                crate::sender::StatusIndicator::Ready,
-                            );
-                        } else {
-                            app.send_status(
-                                "Failed to copy image".to_string(),
-                                (term_w, term_h),
-                                crate::sender::StatusIndicator::Busy,
-                            );
-                        }
-                        state.temp_status_until = Some(Instant::now() + TEMP_STATUS_DURATION);
+                    // An unfinished chord keeps the count for the key that completes it.
+                    if !did_nav && state.chord.is_pending() {
+                        continue;
                     }
-                    _ => {}
                 }
 
                 if did_nav {
-                    // Only cancel if not currently transmitting to avoid blank screens.
-                    // Transmit must complete to ensure image data is in terminal.
-                    if !app.is_transmitting() {
-                        app.cancel_image_output();
-                    }
-                    state.nav_until = Instant::now() + nav_latch;
-                    state.count = 0;
+                    latch_nav(&mut app, &mut state, nav_latch);
                     // Don't drain all pending repeats in one loop; update status incrementally.
                     break;
                 }
@@ -404,26 +832,48 @@ fn run(images: Vec<PathBuf>, config: Config) -> Result<()> {
             break;
         }
 
+        // Stops and continues from outside (`kill -TSTP`, `SIGSTOP` and `fg`).
+        while let Some(signal) = job_signals.poll() {
+            match signal {
+                job::JobSignal::Stop => state.suspend = true,
+                job::JobSignal::Continue => {
+                    resume(&mut app, &mut state, use_alt, wheel.enabled(), None)?;
+                }
+            }
+        }
+        if std::mem::take(&mut state.suspend) {
+            suspend(&mut app, &mut state, &job_signals, use_alt, wheel.enabled())?;
+            continue;
+        }
+
         let allow_transmission = Instant::now() >= state.nav_until;
         let is_navigating = !allow_transmission;
 
-        // Clear temporary status after timeout.
-        if state.temp_status_until.is_some_and(|t| Instant::now() >= t) {
-            state.temp_status_until = None;
-            state.last_status.clear(); // Force redraw with normal status.
-        }
+        // Drop expired temporary messages.
+        state.notifications.prune(Instant::now());
 
         // Update status bar only when it changes (or on resize).
-        let status_now = app.status_text(terminal_rect);
+        // Temporary messages replace or follow the regular status, except while typing a command.
         let indicator = app.status_indicator(terminal_rect, allow_transmission);
+        let mut text = app.status_text(terminal_rect);
+        if let Some(reg) = state.macros.recording() {
+            text.insert_str(0, &format!("recording @{reg} "));
+        }
+        let (status_now, shown_indicator) = if app.is_command_active() {
+            (text, indicator)
+        } else {
+            state.notifications.render((text, indicator))
+        };
+        let status_now = (status_now, state.pending_keys());
         let should_draw = status_now != state.last_status
             || (term_w, term_h) != state.last_size
-            || indicator != state.last_indicator;
-        if should_draw && state.temp_status_until.is_none() {
-            app.send_status(status_now.clone(), (term_w, term_h), indicator);
+            || shown_indicator != state.last_indicator;
+        if should_draw {
+            let (text, pending) = status_now.clone();
+            app.send_status(text, pending, (term_w, term_h), shown_indicator);
             state.last_status = status_now;
             state.last_size = (term_w, term_h);
-            state.last_indicator = indicator;
+            state.last_indicator = shown_indicator;
         }
 
         // Prepare image render request (non-blocking, sends to sender thread).
@@ -434,29 +884,629 @@ fn run(images: Vec<PathBuf>, config: Config) -> Result<()> {
         if allow_transmission
             && matches!(
                 indicator,
-                crate::sender::StatusIndicator::Ready
-                    | crate::sender::StatusIndicator::Fit
-                    | crate::sender::StatusIndicator::Tile
+                StatusIndicator::Ready | StatusIndicator::Fit | StatusIndicator::Tile
             )
         {
             app.prefetch_adjacent(terminal_rect);
         }
 
-        // Wait for next event or worker result.
-        // While navigating, keep the loop tighter so the status bar feels immediate.
-        let tick = if is_navigating {
-            Duration::from_millis(1)
-        } else {
-            Duration::from_millis(16)
-        };
-        let _ = event::poll(tick);
+        // Sleep until the next event or background result, or until timed work is due.
+        let now = Instant::now();
+        let deadline = [
+            is_navigating.then_some(state.nav_until),
+            app.animation_due(),
+            app.slideshow_due(),
+            state.chord.due(),
+            state.notifications.next_expiry(),
+            state.macros.is_replaying().then_some(now),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        session.wait(deadline);
     }
 
     Ok(())
 }
 
-fn init_terminal(use_alt_screen: bool) -> std::io::Result<()> {
-    use std::io::stdout;
+/// Index of the image at `percent` of a list of `len` images, rounded up like vim's `N%`
+/// (`1%` of 1000 images is the 10th, `100%` the last).
+fn percent_index(percent: u32, len: usize) -> usize {
+    let percent = percent.min(100) as usize;
+    (percent * len).div_ceil(100).saturating_sub(1)
+}
+
+/// Count of a key typed with count `n` and then repeated `repeats` more times: the count
+/// applies to the first press only, like vim (`5jjj` moves 7, not 20).
+fn coalesced_count(n: i32, repeats: u32) -> i32 {
+    n.saturating_add(i32::try_from(repeats).unwrap_or(i32::MAX))
+}
+
+/// After a navigation, hold back image transmission until `nav_latch` passes without another.
+fn latch_nav(app: &mut App, state: &mut RunState, nav_latch: Duration) {
+    // Only cancel if not currently transmitting to avoid blank screens.
+    // Transmit must complete to ensure image data is in terminal.
+    if !app.is_transmitting() {
+        app.cancel_image_output();
+    }
+    state.nav_until = std::time::Instant::now() + nav_latch;
+    state.count = 0;
+}
+
+/// Scroll the wheel `delta` steps in the current view mode. Returns true if the view changed.
+fn scroll_wheel(app: &mut App, wheel: &Wheel, delta: i32, terminal_rect: Rect) -> bool {
+    match (app.view_mode, wheel.mode(app.view_mode)) {
+        (_, WheelMode::Off) => false,
+        (ViewMode::Single, WheelMode::Pan) => app.pan_by(0, delta, terminal_rect),
+        (ViewMode::Single, WheelMode::Auto) if app.can_pan() => app.pan_by(0, delta, terminal_rect),
+        (ViewMode::Single, _) => {
+            app.move_by(delta);
+            true
+        }
+        (ViewMode::Tile, WheelMode::Page) => {
+            app.move_tile_page(delta, app.tile_grid(terminal_rect));
+            true
+        }
+        (ViewMode::Tile, _) => {
+            if app.move_tile_cursor_row(delta, terminal_rect) {
+                true
+            } else {
+                app.draw_tile_cursor(terminal_rect);
+                false
+            }
+        }
+    }
+}
+
+/// Run a bound action. Returns true if the view changed (navigation).
+fn run_action(
+    app: &mut App,
+    state: &mut RunState,
+    keymap: &Keymap,
+    action: Action,
+    n: i32,
+    yank: Option<Yank>,
+    terminal_rect: Rect,
+) -> bool {
+    use std::time::Instant;
+
+    let grid = app.tile_grid(terminal_rect);
+    let mut did_nav = false;
+    match action {
+        action @ (Action::HalfPageDown | Action::HalfPageUp) => {
+            let pages = if action == Action::HalfPageDown {
+                n
+            } else {
+                -n
+            };
+            did_nav = app.scroll_half_page(pages, terminal_rect);
+        }
+        Action::Quit | Action::RecordMacro if state.macros.recording().is_some() => {
+            if let Some((reg, len)) = state.macros.stop_recording() {
+                state.notify(
+                    format!("Recorded {len} keys to @{reg}"),
+                    StatusIndicator::Ready,
+                );
+            }
+        }
+        Action::Quit => app.should_quit = true,
+        Action::RecordMacro => state.macros.begin_record(),
+        Action::ReplayMacro => state.macros.begin_replay(n as usize),
+        action @ (Action::Down | Action::Up | Action::DownNoWrap | Action::UpNoWrap) => {
+            let mut delta = match action {
+                Action::Down | Action::DownNoWrap => n,
+                _ => -n,
+            };
+            if matches!(action, Action::DownNoWrap | Action::UpNoWrap) {
+                delta = app.clamp_steps(delta, terminal_rect);
+            }
+            match app.view_mode {
+                ViewMode::Single => {
+                    app.move_by(delta);
+                    did_nav = true;
+                }
+                ViewMode::Tile => {
+                    let page_changed = app.move_tile_cursor_row(delta, terminal_rect);
+                    if page_changed {
+                        did_nav = true;
+                    } else {
+                        app.draw_tile_cursor(terminal_rect);
+                    }
+                }
+            }
+        }
+        action @ (Action::Left | Action::Right) => {
+            let delta = if action == Action::Right { n } else { -n };
+            match app.view_mode {
+                ViewMode::Single => {
+                    app.move_by(delta * app.reading_direction());
+                    did_nav = true;
+                }
+                ViewMode::Tile => {
+                    let page_changed = app.move_tile_cursor(delta, grid);
+                    if page_changed {
+                        did_nav = true;
+                    } else {
+                        app.draw_tile_cursor(terminal_rect);
+                    }
+                }
+            }
+        }
+        // Page navigation in Tile mode, same as j/k in Single mode
+        action @ (Action::PageDown | Action::PageUp) => {
+            let delta = if action == Action::PageDown { n } else { -n };
+            match app.view_mode {
+                ViewMode::Single => app.move_by(delta),
+                ViewMode::Tile => app.move_tile_page(delta, grid),
+            }
+            did_nav = true;
+        }
+        Action::OpenTile => {
+            app.select_tile();
+            did_nav = true;
+        }
+        Action::First => {
+            // Vim-like: `g` (or `N g`) goes to first / Nth (1-based) image.
+            let target = if state.count > 0 {
+                (state.count as usize).saturating_sub(1)
+            } else {
+                0
+            };
+            app.go_to_index_with_tile(target);
+            did_nav = true;
+        }
+        Action::Last => {
+            // Vim-like: `G` (or `N G`) goes to last / Nth (1-based) image.
+            let target = if state.count > 0 {
+                (state.count as usize).saturating_sub(1)
+            } else {
+                app.images.len().saturating_sub(1)
+            };
+            app.go_to_index_with_tile(target);
+            did_nav = true;
+        }
+        Action::Percent => {
+            // Vim-like: `N %` goes to N percent of the list.
+            app.go_to_index_with_tile(percent_index(state.count, app.images.len()));
+            did_nav = true;
+        }
+        Action::Fit => {
+            app.toggle_fit_mode();
+            did_nav = true;
+        }
+        Action::Reload => {
+            app.reload();
+            did_nav = true;
+        }
+        Action::ToggleView => {
+            app.toggle_view_mode();
+            did_nav = true;
+        }
+        action @ (Action::NextFrame | Action::PrevFrame) => {
+            let delta = if action == Action::NextFrame { n } else { -n };
+            // Animations step frames (the status bar shows the frame number).
+            if app.step_animation(delta).is_none() {
+                if app.move_page(delta).is_some() {
+                    did_nav = true;
+                } else {
+                    state.notify("Not a multi-page or animated image", StatusIndicator::Ready);
+                }
+            }
+        }
+        // Pan a zoomed or 1:1 image.
+        action @ (Action::PanLeft | Action::PanRight | Action::PanUp | Action::PanDown) => {
+            let (dx, dy) = match action {
+                Action::PanLeft => (-n, 0),
+                Action::PanRight => (n, 0),
+                Action::PanUp => (0, -n),
+                _ => (0, n),
+            };
+            did_nav = app.pan_by(dx, dy, terminal_rect);
+        }
+        Action::Animation => match app.toggle_animation() {
+            Some(true) => state.notify("Animation paused", StatusIndicator::Ready),
+            Some(false) => state.notify("Animation resumed", StatusIndicator::Ready),
+            None => state.notify("Not animated", StatusIndicator::Ready),
+        },
+        Action::Command => {
+            app.start_command();
+        }
+        Action::Help => app.start_help(keymap, terminal_rect),
+        Action::Suspend => state.suspend = true,
+        Action::Trash => {
+            did_nav = trash_current(app, state);
+        }
+        Action::UndoTrash => match app.undo_trash() {
+            Ok(Some(name)) => {
+                state.notify(format!("Restored {name}"), StatusIndicator::Ready);
+                did_nav = true;
+            }
+            Ok(None) => state.notify("Nothing to undo", StatusIndicator::Ready),
+            Err(e) => state.notify(e.to_string(), StatusIndicator::Busy),
+        },
+        Action::Bookmark => match app.bookmark_current_dir() {
+            Ok(true) => state.notify("Bookmarked directory", StatusIndicator::Ready),
+            Ok(false) => state.notify("Already bookmarked", StatusIndicator::Ready),
+            Err(e) => state.notify(
+                format!("Failed to save bookmark: {e}"),
+                StatusIndicator::Busy,
+            ),
+        },
+        Action::Browse => {
+            if let Err(e) = app.start_browser(terminal_rect) {
+                state.notify(e.to_string(), StatusIndicator::Busy);
+            }
+        }
+        Action::Select => {
+            app.start_selection();
+        }
+        action @ (Action::ZoomIn | Action::ZoomOut) => {
+            let steps = if action == Action::ZoomIn { n } else { -n };
+            did_nav = app.zoom_by(steps, terminal_rect);
+        }
+        action @ (Action::TileLarger | Action::TileSmaller) => {
+            let steps = if action == Action::TileLarger { n } else { -n };
+            match app.resize_tiles(steps, terminal_rect) {
+                Some((cols, rows)) => {
+                    state.notify(format!("{cols}x{rows} tiles"), StatusIndicator::Ready);
+                    did_nav = true;
+                }
+                None => state.notify("Tile size limit", StatusIndicator::Ready),
+            }
+        }
+        Action::Jump => {
+            app.start_jump(terminal_rect);
+        }
+        Action::Reset => {
+            did_nav = app.reset_zoom() | app.reset_window();
+        }
+        Action::ZoomLock => {
+            let text = if app.toggle_zoom_lock() {
+                "Zoom locked: kept when changing images"
+            } else {
+                "Zoom unlocked"
+            };
+            state.notify(text, StatusIndicator::Ready);
+        }
+        action @ (Action::WindowWidthDown | Action::WindowWidthUp) => {
+            let steps = if action == Action::WindowWidthUp {
+                n
+            } else {
+                -n
+            };
+            did_nav = app.adjust_window(steps, 0);
+        }
+        action @ (Action::WindowLevelDown | Action::WindowLevelUp) => {
+            let steps = if action == Action::WindowLevelUp {
+                n
+            } else {
+                -n
+            };
+            did_nav = app.adjust_window(0, steps);
+        }
+        action @ (Action::BlendDown | Action::BlendUp) => {
+            let steps = if action == Action::BlendUp { n } else { -n };
+            did_nav = app.adjust_blend(steps);
+        }
+        action @ (Action::WipeLeft | Action::WipeRight) => {
+            let steps = if action == Action::WipeRight { n } else { -n };
+            did_nav = app.move_wipe(steps);
+        }
+        Action::Grayscale => {
+            app.toggle_grayscale();
+            did_nav = true;
+        }
+        Action::Spread => {
+            app.toggle_spread();
+            did_nav = true;
+        }
+        Action::Slideshow => {
+            let text = if app.toggle_slideshow(Instant::now()) {
+                "Slideshow started"
+            } else {
+                "Slideshow stopped"
+            };
+            state.notify(text, StatusIndicator::Ready);
+        }
+        Action::Split => {
+            app.toggle_panes();
+            did_nav = true;
+        }
+        Action::SwitchPane => {
+            did_nav = app.switch_pane();
+        }
+        Action::Grid => {
+            app.cycle_grid();
+            did_nav = true;
+        }
+        Action::Invert => {
+            app.toggle_invert();
+            did_nav = true;
+        }
+        action @ (Action::RotateRight | Action::RotateLeft) => {
+            let turns = if action == Action::RotateRight { n } else { -n };
+            if app.rotate(turns).is_err() {
+                state.notify("Failed to save rotation", StatusIndicator::Busy);
+            }
+            did_nav = true;
+        }
+        Action::YankRegister => {
+            state.yank_prefix = true;
+            // A reference list: keep it up long enough to read.
+            state.notify_with(
+                format!("Yank register: {}", yank::REGISTER_HINT),
+                StatusIndicator::Ready,
+                Mode::Replace,
+                3,
+            );
+        }
+        Action::Yank => {
+            let yank = yank.unwrap_or_default();
+            match (app.copy_to_clipboard(yank), yank) {
+                (true, Yank::DataUri) => {
+                    state.notify("Encoding data URI...", StatusIndicator::Busy)
+                }
+                (true, _) => state.notify_with(
+                    format!("Copied {} to clipboard", yank.label()),
+                    StatusIndicator::Ready,
+                    Mode::Merge,
+                    1,
+                ),
+                (false, _) => state.notify(
+                    format!("Failed to copy {}", yank.label()),
+                    StatusIndicator::Busy,
+                ),
+            }
+        }
+        Action::YankImage => match app.copy_image_to_clipboard() {
+            Some(copied) => state.notify_image_copy(copied),
+            None => state.notify("Copying image...", StatusIndicator::Busy),
+        },
+    }
+    did_nav
+}
+
+/// Whether `action` does something in the current mode; otherwise the next action bound to
+/// the key is tried.
+fn action_applies(action: Action, app: &App, count: u32) -> bool {
+    let single = app.view_mode == ViewMode::Single;
+    match action {
+        Action::OpenTile | Action::Jump | Action::TileLarger | Action::TileSmaller => !single,
+        Action::Percent => count > 0,
+        Action::PanLeft | Action::PanRight | Action::PanUp | Action::PanDown => app.can_pan(),
+        Action::Select
+        | Action::ZoomIn
+        | Action::ZoomOut
+        | Action::WindowWidthDown
+        | Action::WindowWidthUp
+        | Action::WindowLevelDown
+        | Action::WindowLevelUp
+        | Action::BlendDown
+        | Action::BlendUp
+        | Action::WipeLeft
+        | Action::WipeRight
+        | Action::Grayscale
+        | Action::Spread
+        | Action::Slideshow
+        | Action::Split
+        | Action::SwitchPane
+        | Action::Grid
+        | Action::Invert => single,
+        _ => true,
+    }
+}
+
+/// Handle a key while the zoom selection rectangle is active.
+/// Returns true if the view changed (zoomed to the selection).
+fn handle_selection_key(app: &mut App, code: KeyCode, n: i32, terminal_rect: Rect) -> bool {
+    match code {
+        KeyCode::Char('h') => app.move_selection(-n, 0),
+        KeyCode::Char('l') => app.move_selection(n, 0),
+        KeyCode::Char('k') => app.move_selection(0, -n),
+        KeyCode::Char('j') => app.move_selection(0, n),
+        KeyCode::Char('H') => app.resize_selection(-n, 0),
+        KeyCode::Char('L') => app.resize_selection(n, 0),
+        KeyCode::Char('K') => app.resize_selection(0, -n),
+        KeyCode::Char('J') => app.resize_selection(0, n),
+        KeyCode::Enter => return app.zoom_to_selection(terminal_rect),
+        KeyCode::Esc | KeyCode::Char('v') => app.cancel_selection(),
+        KeyCode::Char('q') => app.should_quit = true,
+        _ => {}
+    }
+    false
+}
+
+/// Handle a key while the calibration screen is open.
+fn handle_calibration_key(app: &mut App, code: KeyCode, terminal_rect: Rect, state: &mut RunState) {
+    let delta = match code {
+        KeyCode::Char('l') | KeyCode::Right => calibrate::STEP,
+        KeyCode::Char('h') | KeyCode::Left => -calibrate::STEP,
+        KeyCode::Char('L') => calibrate::FINE_STEP,
+        KeyCode::Char('H') => -calibrate::FINE_STEP,
+        KeyCode::Enter | KeyCode::Esc => {
+            match app.finish_calibration(code == KeyCode::Enter, terminal_rect) {
+                Some(Ok((ratio, path))) => state.notify(
+                    format!("Saved cell_aspect_ratio = {ratio:.2} to {}", path.display()),
+                    StatusIndicator::Ready,
+                ),
+                Some(Err(e)) => state.notify(e.to_string(), StatusIndicator::Busy),
+                None => {}
+            }
+            return;
+        }
+        KeyCode::Char('q') => {
+            app.should_quit = true;
+            return;
+        }
+        _ => return,
+    };
+    app.nudge_calibration(delta, terminal_rect);
+}
+
+/// Handle a key while the directory browser is open.
+/// Returns true if the browser was closed (the image list may have been replaced).
+fn handle_browser_key(
+    app: &mut App,
+    code: KeyCode,
+    n: i32,
+    terminal_rect: Rect,
+    state: &mut RunState,
+) -> bool {
+    let result = match code {
+        KeyCode::Char('j') | KeyCode::Down => app.edit_browser(
+            |b| {
+                b.select_by(n);
+                Ok(())
+            },
+            terminal_rect,
+        ),
+        KeyCode::Char('k') | KeyCode::Up => app.edit_browser(
+            |b| {
+                b.select_by(-n);
+                Ok(())
+            },
+            terminal_rect,
+        ),
+        KeyCode::Char('g') | KeyCode::Char('G') => {
+            let delta = if code == KeyCode::Char('g') {
+                i32::MIN
+            } else {
+                i32::MAX
+            };
+            app.edit_browser(
+                |b| {
+                    b.select_by(delta);
+                    Ok(())
+                },
+                terminal_rect,
+            )
+        }
+        KeyCode::Char('l') | KeyCode::Right => app.edit_browser(|b| b.descend(), terminal_rect),
+        KeyCode::Char('h') | KeyCode::Left => app.edit_browser(|b| b.ascend(), terminal_rect),
+        KeyCode::Enter => {
+            let Some(browser) = app.finish_browser(terminal_rect) else {
+                return false;
+            };
+            let Some(entry) = browser.selected_entry() else {
+                return true;
+            };
+            state.open_images(app, std::slice::from_ref(&entry.path));
+            return true;
+        }
+        KeyCode::Esc | KeyCode::Char('-') => {
+            app.finish_browser(terminal_rect);
+            return true;
+        }
+        KeyCode::Char('q') => {
+            app.should_quit = true;
+            Ok(())
+        }
+        _ => Ok(()),
+    };
+    if let Err(e) = result {
+        state.notify(e.to_string(), StatusIndicator::Busy);
+    }
+    false
+}
+
+/// Move the current image to the trash (`D`, `:delete`). Returns true if the list changed.
+fn trash_current(app: &mut App, state: &mut RunState) -> bool {
+    match app.trash_current() {
+        Ok(name) => {
+            // Long enough to notice the undo hint.
+            state.notify_with(
+                format!("Moved {name} to trash (u to undo)"),
+                StatusIndicator::Ready,
+                Mode::Replace,
+                2,
+            );
+            true
+        }
+        Err(e) => {
+            state.notify(e.to_string(), StatusIndicator::Busy);
+            false
+        }
+    }
+}
+
+/// Handle a key while the `:` command line is active.
+/// Returns true if the view changed (a command replaced the image list).
+fn handle_command_key(
+    app: &mut App,
+    key: KeyEvent,
+    terminal_rect: Rect,
+    state: &mut RunState,
+) -> bool {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc => {
+            app.finish_command(terminal_rect);
+        }
+        KeyCode::Enter => {
+            let Some(cmd) = app.finish_command(terminal_rect) else {
+                return false;
+            };
+            match cmd.parse() {
+                Ok(command::Command::Open(paths)) => return state.open_images(app, &paths),
+                Ok(command::Command::Add(paths)) => {
+                    return state.add_images(app, &paths, terminal_rect);
+                }
+                Ok(command::Command::Calibrate) => app.start_calibration(terminal_rect),
+                Ok(command::Command::GoTo(index)) => {
+                    app.go_to_index_with_tile(index);
+                    return true;
+                }
+                Ok(command::Command::Sort { key, reverse }) => {
+                    app.sort_images(key, reverse);
+                    let order = if reverse { " (reversed)" } else { "" };
+                    state.notify(
+                        format!("Sorted by {}{order}", key.label()),
+                        StatusIndicator::Ready,
+                    );
+                    return true;
+                }
+                Ok(command::Command::Delete) => return trash_current(app, state),
+                Ok(command::Command::Matte(color)) => return app.set_matte(color),
+                Ok(command::Command::Slideshow(setting)) => {
+                    let text = app.set_slideshow(setting, std::time::Instant::now());
+                    state.notify(text, StatusIndicator::Ready);
+                }
+                Ok(command::Command::Diff(reference)) => return app.set_diff(reference),
+                Ok(command::Command::Blend(reference)) => return app.set_blend(reference),
+                Ok(command::Command::Wipe(reference)) => return app.set_wipe(reference),
+                Ok(command::Command::Write { path, force }) => {
+                    if let Err(e) = app.save_crop(path, force) {
+                        state.notify(e.to_string(), StatusIndicator::Busy);
+                    }
+                }
+                Ok(command::Command::ClearCache) => {
+                    let freed = format_size(app.clear_caches(terminal_rect));
+                    state.notify(format!("Cleared caches ({freed})"), StatusIndicator::Ready);
+                }
+                Err(e) => state.notify(e.to_string(), StatusIndicator::Busy),
+            }
+        }
+        KeyCode::Tab => app.edit_command(|c| c.complete(), terminal_rect),
+        KeyCode::Down => app.edit_command(|c| c.select_by(1), terminal_rect),
+        KeyCode::Up | KeyCode::BackTab => app.edit_command(|c| c.select_by(-1), terminal_rect),
+        KeyCode::Char('n') if ctrl => app.edit_command(|c| c.select_by(1), terminal_rect),
+        KeyCode::Char('p') if ctrl => app.edit_command(|c| c.select_by(-1), terminal_rect),
+        KeyCode::Backspace => {
+            let mut emptied = false;
+            app.edit_command(|c| emptied = !c.backspace(), terminal_rect);
+            // Like vim: backspace on an empty command line leaves it.
+            if emptied {
+                app.finish_command(terminal_rect);
+            }
+        }
+        KeyCode::Char(c) if !ctrl => app.edit_command(|cmd| cmd.push(c), terminal_rect),
+        _ => {}
+    }
+    false
+}
+
+fn init_terminal(use_alt_screen: bool, mouse: bool) -> std::io::Result<()> {
+    use std::io::{Write, stdout};
 
     use ratatui::crossterm::{
         cursor::{Hide, MoveTo},
@@ -467,13 +1517,21 @@ fn init_terminal(use_alt_screen: bool) -> std::io::Result<()> {
     enable_raw_mode()?;
     if use_alt_screen {
         execute!(stdout(), EnterAlternateScreen)?;
+        execute!(stdout(), Clear(ClearType::All), MoveTo(0, 0), Hide)?;
+    } else {
+        let (_, rows) = ratatui::crossterm::terminal::size()?;
+        let mut out = stdout();
+        write!(out, "{}{}", inline_clear(rows), inline_scroll_region(rows))?;
+        execute!(out, Hide)?;
+    }
+    if mouse {
+        execute!(stdout(), ratatui::crossterm::event::EnableMouseCapture)?;
     }
-    execute!(stdout(), Clear(ClearType::All), MoveTo(0, 0), Hide)?;
     Ok(())
 }
 
-fn restore_terminal(use_alt_screen: bool) {
-    use std::io::stdout;
+fn restore_terminal(use_alt_screen: bool, mouse: bool) {
+    use std::io::{Write, stdout};
 
     use ratatui::crossterm::{
         cursor::Show,
@@ -481,15 +1539,73 @@ fn restore_terminal(use_alt_screen: bool) {
         terminal::{LeaveAlternateScreen, disable_raw_mode},
     };
 
+    if mouse {
+        let _ = execute!(stdout(), ratatui::crossterm::event::DisableMouseCapture);
+    }
     let _ = disable_raw_mode();
     if use_alt_screen {
         let _ = execute!(stdout(), LeaveAlternateScreen);
+    } else {
+        // Leave a blank screen with the full scroll region, the prompt at the top.
+        let rows = ratatui::crossterm::terminal::size().map_or(0, |(_, rows)| rows);
+        let _ = write!(stdout(), "{}\x1b[r\x1b[H", inline_clear(rows));
     }
     let _ = execute!(stdout(), Show);
 }
 
-fn clear_screen() {
-    use std::io::stdout;
+/// Ctrl-Z or `SIGTSTP`: hand the terminal back to the shell and stop until `fg`.
+#[cfg(unix)]
+fn suspend(
+    app: &mut App,
+    state: &mut RunState,
+    job_signals: &job::JobSignals,
+    use_alt: bool,
+    mouse: bool,
+) -> Result<()> {
+    let writer = app.pause_output();
+    restore_terminal(use_alt, mouse);
+    job_signals.stop();
+    resume(app, state, use_alt, mouse, Some(writer))
+}
+
+#[cfg(not(unix))]
+fn suspend(
+    _app: &mut App,
+    state: &mut RunState,
+    _job_signals: &job::JobSignals,
+    _use_alt: bool,
+    _mouse: bool,
+) -> Result<()> {
+    state.notify("Suspend is not supported here", StatusIndicator::Busy);
+    Ok(())
+}
+
+/// Continued after a stop: set the terminal up again (a `SIGSTOP` from outside left it as it
+/// was, but the shell may have reset it) and redraw everything as after a resize, since the
+/// shell drew over the screen and the size may have changed meanwhile. `writer` releases the
+/// writer paused by `suspend`.
+fn resume(
+    app: &mut App,
+    state: &mut RunState,
+    use_alt: bool,
+    mouse: bool,
+    writer: Option<std::sync::mpsc::Sender<()>>,
+) -> Result<()> {
+    init_terminal(use_alt, mouse)?;
+    let (w, h) = terminal::size()?;
+    clear_screen(use_alt, h);
+    if let Some(writer) = writer {
+        let _ = writer.send(());
+    }
+    app.handle_resize();
+    app.redraw_menu(Rect::new(0, 0, w, h));
+    state.last_size = (w, h);
+    state.last_status = Default::default(); // Force status redraw
+    Ok(())
+}
+
+fn clear_screen(use_alt_screen: bool, rows: u16) {
+    use std::io::{Write, stdout};
 
     use ratatui::crossterm::{
         cursor::MoveTo,
@@ -497,7 +1613,35 @@ fn clear_screen() {
         terminal::{Clear, ClearType},
     };
 
-    let _ = execute!(stdout(), Clear(ClearType::All), MoveTo(0, 0));
+    if use_alt_screen {
+        let _ = execute!(stdout(), Clear(ClearType::All), MoveTo(0, 0));
+    } else {
+        let mut out = stdout();
+        let _ = write!(out, "{}{}", inline_clear(rows), inline_scroll_region(rows));
+        let _ = out.flush();
+    }
+}
+
+/// Erase every row one by one (no alternate screen).
+///
+/// `ED 2` makes tmux (`scroll-on-clear`) and some terminals push the whole screen into
+/// scrollback, so each row is erased in place instead.
+fn inline_clear(rows: u16) -> String {
+    let mut out: String = (1..=rows).map(|y| format!("\x1b[{y};1H\x1b[2K")).collect();
+    out.push_str("\x1b[H");
+    out
+}
+
+/// DECSTBM scroll region over the rows above the status bar (no alternate screen).
+///
+/// A stray line feed on the bottom row would scroll the screen, images included, into
+/// scrollback. Lines scrolled out of a region that ends above the bottom row are discarded
+/// instead, and absolute cursor moves still reach the status row.
+fn inline_scroll_region(rows: u16) -> String {
+    if rows < 2 {
+        return String::new();
+    }
+    format!("\x1b[1;{}r", rows - 1)
 }
 
 #[cfg(test)]
@@ -505,6 +1649,31 @@ mod tests {
     use super::*;
     use std::fs::{self, File};
 
+    #[test]
+    fn test_inline_screen_escapes() {
+        assert_eq!(inline_clear(2), "\x1b[1;1H\x1b[2K\x1b[2;1H\x1b[2K\x1b[H");
+        assert_eq!(inline_scroll_region(24), "\x1b[1;23r");
+        assert_eq!(inline_scroll_region(1), "");
+    }
+
+    #[test]
+    fn test_coalesced_count() {
+        assert_eq!(coalesced_count(5, 2), 7);
+        assert_eq!(coalesced_count(1, 0), 1);
+        assert_eq!(coalesced_count(1, 3), 4);
+        assert_eq!(coalesced_count(i32::MAX, 1), i32::MAX);
+    }
+
+    #[test]
+    fn test_percent_index() {
+        assert_eq!(percent_index(1, 1000), 9);
+        assert_eq!(percent_index(50, 1000), 499);
+        assert_eq!(percent_index(100, 1000), 999);
+        assert_eq!(percent_index(250, 1000), 999);
+        assert_eq!(percent_index(50, 3), 1);
+        assert_eq!(percent_index(1, 3), 0);
+    }
+
     #[test]
     fn test_cli_parses_file_path() {
         let cli = Cli::try_parse_from(["stiv", "image.png"]).unwrap();
@@ -517,12 +1686,91 @@ mod tests {
         assert_eq!(cli.paths, vec![PathBuf::from("/home/user/photos")]);
     }
 
+    #[test]
+    fn test_cli_record_conflicts_with_replay() {
+        let cli = Cli::try_parse_from(["stiv", "--record", "s.jsonl", "a.png"]).unwrap();
+        assert_eq!(cli.record, Some(PathBuf::from("s.jsonl")));
+        let result = Cli::try_parse_from(["stiv", "--record", "a", "--replay", "b", "a.png"]);
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_cli_requires_paths_argument() {
         let result = Cli::try_parse_from(["stiv"]);
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_cli_max_images_conflicts_with_sample() {
+        let cli = Cli::try_parse_from(["stiv", "--sample", "10", "a/"]).unwrap();
+        assert_eq!(cli.sample, NonZeroUsize::new(10));
+        let result = Cli::try_parse_from(["stiv", "--max-images", "5", "--sample", "10", "a/"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cli_rejects_zero_limits() {
+        assert!(Cli::try_parse_from(["stiv", "--max-images", "0", "a/"]).is_err());
+        assert!(Cli::try_parse_from(["stiv", "--sample", "0", "a/"]).is_err());
+    }
+
+    #[test]
+    fn test_sample_in_order_keeps_exactly_n() {
+        for n in [1, 7, 99, 100, 150] {
+            let mut items: Vec<usize> = (0..100).collect();
+            sample_in_order(&mut items, n, 7);
+            assert_eq!(items.len(), n.min(100));
+            assert!(items.windows(2).all(|w| w[0] < w[1]));
+        }
+    }
+
+    #[test]
+    fn test_list_limit_truncates_and_samples() {
+        let images: Vec<PathBuf> = (0..100)
+            .map(|i| PathBuf::from(format!("{i:03}.png")))
+            .collect();
+
+        let (kept, note) = ListLimit::default().apply(images.clone(), 0, 1);
+        assert_eq!((kept.len(), note), (100, None));
+
+        let max = ListLimit {
+            max_images: Some(10),
+            sample: None,
+        };
+        let (kept, note) = max.apply(images.clone(), 0, 1);
+        assert_eq!(kept, images[..10]);
+        assert_eq!(note.as_deref(), Some("of 100, truncated"));
+        // Appended images only fill the room the listed ones leave.
+        let (kept, note) = max.apply(images.clone(), 4, 1);
+        assert_eq!(kept, images[..6]);
+        assert_eq!(note.as_deref(), Some("of 104, truncated"));
+
+        let sample = ListLimit {
+            max_images: None,
+            sample: Some(10),
+        };
+        let (kept, note) = sample.apply(images.clone(), 0, 42);
+        assert_eq!(kept.len(), 10);
+        assert!(kept.windows(2).all(|w| w[0] < w[1]), "order is preserved");
+        assert_ne!(kept, images[..10], "a random subset, not the first images");
+        assert_eq!(note.as_deref(), Some("of 100, sampled"));
+    }
+
+    #[test]
+    fn test_check_paths_rejects_bad_inputs() {
+        let dir = PathBuf::from("/tmp/stiv_test_check_paths");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        File::create(dir.join("a.png")).unwrap();
+        File::create(dir.join("notes.txt")).unwrap();
+
+        assert!(check_paths(&[dir.clone(), dir.join("a.png")]).is_ok());
+        assert!(check_paths(&[dir.join("notes.txt")]).is_err());
+        assert!(check_paths(&[dir.join("missing")]).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_is_image_file_png() {
         assert!(is_image_file(&PathBuf::from("test.png")));
@@ -540,6 +1788,14 @@ mod tests {
     fn test_is_image_file_other_formats() {
         assert!(is_image_file(&PathBuf::from("test.gif")));
         assert!(is_image_file(&PathBuf::from("test.webp")));
+        assert!(is_image_file(&PathBuf::from("test.ff")));
+        assert!(is_image_file(&PathBuf::from("test.tiff")));
+        for ext in ["bmp", "ICO", "tga", "pnm", "pbm", "pgm", "ppm", "pam"] {
+            assert!(
+                is_image_file(&PathBuf::from(format!("test.{ext}"))),
+                "{ext}"
+            );
+        }
     }
 
     #[test]
@@ -549,6 +1805,25 @@ mod tests {
         assert!(!is_image_file(&PathBuf::from("noextension")));
     }
 
+    #[test]
+    fn test_is_image_file_sniffs_odd_extensions() {
+        let dir = std::env::temp_dir().join(format!("stiv_sniff_ext_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let bare = dir.join("picture");
+        let other = dir.join("picture.dat");
+        let text = dir.join("picture.txt");
+        for path in [&bare, &other, &text] {
+            image::DynamicImage::new_rgb8(2, 2)
+                .save_with_format(path, image::ImageFormat::Png)
+                .unwrap();
+        }
+        assert!(is_image_file(&bare));
+        assert!(is_image_file(&other));
+        assert!(!is_image_file(&text));
+        assert!(!has_image_extension(&other));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_collect_images_single_file() {
         let dir = PathBuf::from("/tmp/stiv_test_single");
@@ -581,6 +1856,67 @@ mod tests {
         fs::remove_dir_all(&dir).unwrap();
     }
 
+    #[test]
+    fn test_collect_images_by_content() {
+        let dir = PathBuf::from("/tmp/stiv_test_by_content");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        image::DynamicImage::new_rgb8(2, 2)
+            .save_with_format(dir.join("scan"), image::ImageFormat::Png)
+            .unwrap();
+        fs::write(dir.join("readme"), "not an image").unwrap();
+
+        let images = collect_images(std::slice::from_ref(&dir)).unwrap();
+        assert_eq!(images, vec![dir.join("scan")]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_collect_images_natural_order() {
+        let dir = PathBuf::from("/tmp/stiv_test_natural_order");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["img10.png", "img2.png", "img1.png"] {
+            File::create(dir.join(name)).unwrap();
+        }
+
+        let images = collect_images(std::slice::from_ref(&dir)).unwrap();
+        assert_eq!(
+            images,
+            vec![
+                dir.join("img1.png"),
+                dir.join("img2.png"),
+                dir.join("img10.png")
+            ]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_collect_images_tar_archive() {
+        let dir = PathBuf::from("/tmp/stiv_test_tar");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("images.tar");
+        let mut builder = tar::Builder::new(File::create(&archive).unwrap());
+        for name in ["b.png", "a.jpg", "readme.txt"] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(0);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, std::io::empty())
+                .unwrap();
+        }
+        builder.finish().unwrap();
+
+        let images = collect_images(std::slice::from_ref(&archive)).unwrap();
+        assert_eq!(images, vec![archive.join("a.jpg"), archive.join("b.png")]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_collect_images_non_image_file_error() {
         let dir = PathBuf::from("/tmp/stiv_test_non_image");
@@ -0,0 +1,175 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Temporary status-line notifications.
+//!
+//! Messages are stacked with their own expiry, so a second message (e.g. a copy result right
+//! after an error) is shown next to the first instead of replacing it. Each message either
+//! replaces the regular status while it is alive (`Mode::Replace`, newest first) or is appended
+//! after it (`Mode::Merge`), so short confirmations do not hide the file name. The regular status
+//! returns once all messages expire.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::sender::StatusIndicator;
+
+/// Maximum number of messages kept at once (oldest are dropped first).
+const MAX_NOTIFICATIONS: usize = 3;
+
+/// Separator between stacked messages.
+const SEP: &str = "\u{e0b1}"; //  (Powerline separator)
+
+/// How a message combines with the regular status.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Shown instead of the regular status.
+    Replace,
+    /// Appended after the regular status (or after the replacing messages).
+    Merge,
+}
+
+struct Notification {
+    text: String,
+    indicator: StatusIndicator,
+    mode: Mode,
+    until: Instant,
+}
+
+#[derive(Default)]
+pub struct Notifications {
+    items: VecDeque<Notification>,
+}
+
+impl Notifications {
+    /// Add a message shown for `ttl` from `now`.
+    pub fn push(
+        &mut self,
+        text: impl Into<String>,
+        indicator: StatusIndicator,
+        mode: Mode,
+        ttl: Duration,
+        now: Instant,
+    ) {
+        if self.items.len() >= MAX_NOTIFICATIONS {
+            self.items.pop_front();
+        }
+        self.items.push_back(Notification {
+            text: text.into(),
+            indicator,
+            mode,
+            until: now + ttl,
+        });
+    }
+
+    /// Drop expired messages. Returns true if anything was removed.
+    pub fn prune(&mut self, now: Instant) -> bool {
+        let before = self.items.len();
+        self.items.retain(|n| n.until > now);
+        self.items.len() != before
+    }
+
+    /// When the next message expires.
+    pub fn next_expiry(&self) -> Option<Instant> {
+        self.items.iter().map(|n| n.until).min()
+    }
+
+    /// Status text and indicator given the `regular` ones.
+    ///
+    /// Replacing messages (newest first) stand in for the regular status, with the newest one's
+    /// indicator; merged messages (newest first) follow.
+    pub fn render(&self, regular: (String, StatusIndicator)) -> (String, StatusIndicator) {
+        let sep = format!(" {SEP} ");
+        let texts = |mode| {
+            self.items
+                .iter()
+                .rev()
+                .filter(move |n| n.mode == mode)
+                .map(|n| n.text.as_str())
+        };
+        let (mut text, indicator) = match self.items.iter().rfind(|n| n.mode == Mode::Replace) {
+            Some(newest) => (
+                texts(Mode::Replace).collect::<Vec<_>>().join(&sep),
+                newest.indicator,
+            ),
+            None => regular,
+        };
+        for merged in texts(Mode::Merge) {
+            text.push_str(&sep);
+            text.push_str(merged);
+        }
+        (text, indicator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(1);
+
+    fn regular() -> (String, StatusIndicator) {
+        ("a.png".to_string(), StatusIndicator::Fit)
+    }
+
+    #[test]
+    fn test_messages_stack_newest_first() {
+        let now = Instant::now();
+        let mut n = Notifications::default();
+        assert_eq!(n.render(regular()), regular());
+        n.push("first", StatusIndicator::Busy, Mode::Replace, TTL, now);
+        n.push("second", StatusIndicator::Ready, Mode::Replace, TTL, now);
+        let (text, indicator) = n.render(regular());
+        assert_eq!(text, format!("second {SEP} first"));
+        assert_eq!(indicator, StatusIndicator::Ready);
+    }
+
+    #[test]
+    fn test_merged_messages_follow_the_status() {
+        let now = Instant::now();
+        let mut n = Notifications::default();
+        n.push("copied", StatusIndicator::Ready, Mode::Merge, TTL, now);
+        assert_eq!(
+            n.render(regular()),
+            (format!("a.png {SEP} copied"), StatusIndicator::Fit)
+        );
+        n.push("failed", StatusIndicator::Busy, Mode::Replace, TTL, now);
+        assert_eq!(
+            n.render(regular()),
+            (format!("failed {SEP} copied"), StatusIndicator::Busy)
+        );
+    }
+
+    #[test]
+    fn test_messages_expire_independently() {
+        let now = Instant::now();
+        let mut n = Notifications::default();
+        let long = Duration::from_secs(5);
+        n.push("long", StatusIndicator::Busy, Mode::Replace, long, now);
+        n.push("short", StatusIndicator::Ready, Mode::Replace, TTL, now);
+        assert!(!n.prune(now));
+        assert_eq!(n.next_expiry(), Some(now + TTL));
+        assert!(n.prune(now + Duration::from_secs(2)));
+        assert_eq!(n.render(regular()).0, "long");
+        assert!(n.prune(now + Duration::from_secs(5)));
+        assert_eq!(n.render(regular()), regular());
+    }
+
+    #[test]
+    fn test_oldest_dropped_when_full() {
+        let now = Instant::now();
+        let mut n = Notifications::default();
+        for i in 0..=MAX_NOTIFICATIONS {
+            n.push(
+                i.to_string(),
+                StatusIndicator::Ready,
+                Mode::Replace,
+                TTL,
+                now,
+            );
+        }
+        let (text, _) = n.render(regular());
+        assert!(!text.contains('0'));
+        assert!(text.starts_with(&MAX_NOTIFICATIONS.to_string()));
+    }
+}
@@ -0,0 +1,183 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Multi-page TIFF files.
+//!
+//! A TIFF keeps one index in the image list; `n` / `p` flip through its pages in Single mode.
+//! Pages after the first are addressed with virtual paths `<file>/<page>` (e.g. `scan.tif/2`),
+//! so render caching and decoding treat each page like its own image. Page 1 is the file
+//! itself and decodes like any other image.
+
+use std::io::{BufReader, Cursor, Read, Seek};
+use std::path::{Path, PathBuf};
+
+use image::{DynamicImage, ImageBuffer};
+use tiff::ColorType;
+use tiff::decoder::{Decoder, DecodingResult};
+
+/// Pages of the TIFF on screen and the one shown.
+pub struct Pages {
+    pub path: PathBuf,
+    pub current: usize,
+    pub count: usize,
+}
+
+/// Check if the path has a TIFF extension.
+pub fn is_tiff(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("tif") || e.eq_ignore_ascii_case("tiff"))
+}
+
+/// Path that renders page `page` (0-based) of `path`.
+pub fn page_path(path: &Path, page: usize) -> PathBuf {
+    if page == 0 {
+        path.to_path_buf()
+    } else {
+        path.join((page + 1).to_string())
+    }
+}
+
+/// Split a virtual page path into `(file, page)` (0-based). Returns None for regular paths,
+/// including page 1.
+pub fn split_page_path(path: &Path) -> Option<(&Path, usize)> {
+    let file = path.parent().filter(|p| is_tiff(p))?;
+    let page: usize = path.file_name()?.to_str()?.parse().ok()?;
+    (page >= 2).then_some((file, page - 1))
+}
+
+/// Number of pages in a TIFF file or archive member.
+pub fn page_count(path: &Path) -> Option<usize> {
+    fn count(reader: impl Read + Seek) -> Option<usize> {
+        let mut decoder = Decoder::new(reader).ok()?;
+        let mut count = 1;
+        while decoder.more_images() {
+            decoder.next_image().ok()?;
+            count += 1;
+        }
+        Some(count)
+    }
+    match crate::archive::split_member_path(path) {
+        Some((archive, member)) => {
+            count(Cursor::new(crate::archive::read_member(archive, &member)?))
+        }
+        None => count(BufReader::new(std::fs::File::open(path).ok()?)),
+    }
+}
+
+/// Decode page `page` (0-based) of a TIFF file or archive member. Pages exceeding `limits`
+/// fail to decode.
+pub fn decode_page(path: &Path, page: usize, limits: &image::Limits) -> Option<DynamicImage> {
+    match crate::archive::split_member_path(path) {
+        Some((archive, member)) => {
+            let bytes = crate::archive::read_member(archive, &member)?;
+            decode(Cursor::new(bytes), page, limits)
+        }
+        None => decode(
+            BufReader::new(std::fs::File::open(path).ok()?),
+            page,
+            limits,
+        ),
+    }
+}
+
+fn decode(reader: impl Read + Seek, page: usize, limits: &image::Limits) -> Option<DynamicImage> {
+    let mut tiff_limits = tiff::decoder::Limits::default();
+    if let Some(max_alloc) = limits.max_alloc {
+        tiff_limits.decoding_buffer_size = usize::try_from(max_alloc).unwrap_or(usize::MAX);
+    }
+    let mut decoder = Decoder::new(reader).ok()?.with_limits(tiff_limits);
+    decoder.seek_to_image(page).ok()?;
+    let (width, height) = decoder.dimensions().ok()?;
+    limits.check_dimensions(width, height).ok()?;
+    let colortype = decoder.colortype().ok()?;
+    to_image(width, height, colortype, decoder.read_image().ok()?)
+}
+
+/// Wrap decoded samples in the matching `DynamicImage` (8/16-bit gray, gray+alpha, RGB and
+/// RGBA; other layouts are not supported).
+fn to_image(
+    width: u32,
+    height: u32,
+    colortype: ColorType,
+    data: DecodingResult,
+) -> Option<DynamicImage> {
+    use DecodingResult::{U8, U16};
+    match (colortype, data) {
+        (ColorType::Gray(8), U8(d)) => {
+            ImageBuffer::from_raw(width, height, d).map(DynamicImage::ImageLuma8)
+        }
+        (ColorType::Gray(16), U16(d)) => {
+            ImageBuffer::from_raw(width, height, d).map(DynamicImage::ImageLuma16)
+        }
+        (ColorType::GrayA(8), U8(d)) => {
+            ImageBuffer::from_raw(width, height, d).map(DynamicImage::ImageLumaA8)
+        }
+        (ColorType::GrayA(16), U16(d)) => {
+            ImageBuffer::from_raw(width, height, d).map(DynamicImage::ImageLumaA16)
+        }
+        (ColorType::RGB(8), U8(d)) => {
+            ImageBuffer::from_raw(width, height, d).map(DynamicImage::ImageRgb8)
+        }
+        (ColorType::RGB(16), U16(d)) => {
+            ImageBuffer::from_raw(width, height, d).map(DynamicImage::ImageRgb16)
+        }
+        (ColorType::RGBA(8), U8(d)) => {
+            ImageBuffer::from_raw(width, height, d).map(DynamicImage::ImageRgba8)
+        }
+        (ColorType::RGBA(16), U16(d)) => {
+            ImageBuffer::from_raw(width, height, d).map(DynamicImage::ImageRgba16)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two-page RGB TIFF: a 4x2 red page, then a 3x5 blue page.
+    fn write_two_pages(path: &Path) {
+        use tiff::encoder::{TiffEncoder, colortype::RGB8};
+
+        let mut encoder = TiffEncoder::new(std::fs::File::create(path).unwrap()).unwrap();
+        encoder
+            .write_image::<RGB8>(4, 2, &[255, 0, 0].repeat(8))
+            .unwrap();
+        encoder
+            .write_image::<RGB8>(3, 5, &[0, 0, 255].repeat(15))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_page_paths() {
+        let file = Path::new("scans/a.TIF");
+        assert_eq!(page_path(file, 0), file);
+        assert_eq!(page_path(file, 2), Path::new("scans/a.TIF/3"));
+        assert_eq!(split_page_path(&page_path(file, 2)), Some((file, 2)));
+        assert_eq!(split_page_path(Path::new("scans/a.TIF/1")), None);
+        assert_eq!(split_page_path(Path::new("scans/2")), None);
+        assert_eq!(split_page_path(file), None);
+    }
+
+    #[test]
+    fn test_multi_page_decode() {
+        let dir = std::env::temp_dir().join("stiv_test_pages");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("two.tiff");
+        write_two_pages(&path);
+
+        assert_eq!(page_count(&path), Some(2));
+        let limits = image::Limits::default();
+        let second = decode_page(&path, 1, &limits).unwrap();
+        assert_eq!((second.width(), second.height()), (3, 5));
+        assert_eq!(second.to_rgb8().get_pixel(0, 0).0, [0, 0, 255]);
+        assert!(decode_page(&path, 2, &limits).is_none());
+
+        let mut small = image::Limits::default();
+        small.max_image_height = Some(4);
+        assert!(decode_page(&path, 1, &small).is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
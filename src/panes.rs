@@ -0,0 +1,75 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Split view (`|`): two panes side by side, each showing its own image of the list.
+//!
+//! The focused pane follows the usual cursor (`current_index`), so every navigation key works
+//! as before; the other pane keeps its index in `Panes::other`, and Tab swaps the two. The
+//! view renders through a virtual path `<left>\0panes\0<right>` (`VirtualPath::Panes`) like spreads and diffs, and
+//! the worker fits both images into equal halves so a before/after pair lines up.
+
+use image::{DynamicImage, GenericImage, RgbaImage};
+
+use crate::virtual_path::scaled_to;
+
+/// Transparent gap between the panes, as a fraction of the pane width.
+const GAP_FRACTION: u32 = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Panes {
+    /// Image index of the pane without focus. It follows its image when the list is
+    /// re-sorted, grows or loses images (`App::keep_other_pane`).
+    pub other: usize,
+    /// Whether the focused pane is the right one.
+    pub right_focused: bool,
+}
+
+/// Pane width and height, and the gap between the panes, for images sized `left` and `right`.
+fn pane_layout(left: (u32, u32), right: (u32, u32)) -> (u32, u32, u32) {
+    let pane_w = left.0.max(right.0).max(1);
+    let pane_h = left.1.max(right.1).max(1);
+    (pane_w, pane_h, (pane_w / GAP_FRACTION).max(1))
+}
+
+/// Canvas size of the split view of images sized `left` and `right` (width, height).
+pub fn size(left: (u32, u32), right: (u32, u32)) -> (u64, u64) {
+    let (pane_w, pane_h, gap) = pane_layout(left, right);
+    (u64::from(pane_w) * 2 + u64::from(gap), u64::from(pane_h))
+}
+
+/// Both images centered in two equal panes as large as the larger width and height of the
+/// two, with a transparent gap between them.
+pub fn compose(left: &DynamicImage, right: &DynamicImage) -> DynamicImage {
+    let (pane_w, pane_h, gap) = pane_layout(
+        (left.width(), left.height()),
+        (right.width(), right.height()),
+    );
+    let mut canvas = RgbaImage::new(pane_w * 2 + gap, pane_h);
+    for (img, x0) in [(left, 0), (right, pane_w + gap)] {
+        let scale = (f64::from(pane_w) / f64::from(img.width().max(1)))
+            .min(f64::from(pane_h) / f64::from(img.height().max(1)));
+        let w = ((f64::from(img.width()) * scale).round() as u32).clamp(1, pane_w);
+        let h = ((f64::from(img.height()) * scale).round() as u32).clamp(1, pane_h);
+        let fitted = scaled_to(img, w, h);
+        // Fits the pane by construction.
+        let _ = canvas.copy_from(&fitted, x0 + (pane_w - w) / 2, (pane_h - h) / 2);
+    }
+    DynamicImage::ImageRgba8(canvas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compose_equal_panes() {
+        let left = DynamicImage::new_rgb8(64, 32);
+        let right = DynamicImage::new_rgb8(16, 64);
+        let out = compose(&left, &right).to_rgba8();
+        // Two 64x64 panes and a 2 pixel gap; the left image is centered vertically.
+        assert_eq!(out.dimensions(), (130, 64));
+        assert_eq!(out.get_pixel(0, 0).0[3], 0);
+        assert_eq!(out.get_pixel(0, 16).0[3], 255);
+        assert_eq!(out.get_pixel(64, 32).0[3], 0);
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Blurred placeholders shown while an image is being resized, encoded and transmitted.
+//!
+//! A `Placeholder` is a tiny color grid (blurhash-style) taken from a decoded image. The workers
+//! attach one to every decode; the app keeps them per path and, on a render cache miss, draws the
+//! grid upscaled with half-block characters in the area the image will occupy, instead of
+//! leaving the previous image (or a blank area) on screen.
+
+use image::DynamicImage;
+
+/// Placeholder grid size (width and height in samples).
+const GRID: u32 = 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Placeholder {
+    /// Size of the (rotated) source image, used to compute the display area.
+    pub source_size: (u32, u32),
+    /// `GRID * GRID` colors, row-major.
+    colors: Vec<[u8; 3]>,
+}
+
+impl Placeholder {
+    /// Sample the image into a small grid (box-filtered, fast on large images).
+    pub fn from_image(img: &DynamicImage) -> Self {
+        let grid = img.thumbnail_exact(GRID, GRID).to_rgb8();
+        Self {
+            source_size: (img.width(), img.height()),
+            colors: grid.pixels().map(|p| p.0).collect(),
+        }
+    }
+
+    /// Colors for a `cols x rows` cell area drawn with `▀`: `(top, bottom)` per cell, row-major.
+    /// The grid is bilinearly interpolated, which gives the blurred look.
+    pub fn cell_colors(&self, cols: u16, rows: u16) -> Vec<([u8; 3], [u8; 3])> {
+        let half_rows = u32::from(rows) * 2;
+        let mut cells = Vec::with_capacity(usize::from(cols) * usize::from(rows));
+        for row in 0..u32::from(rows) {
+            for col in 0..u32::from(cols) {
+                let x = (col as f32 + 0.5) / f32::from(cols);
+                let top = self.sample(x, (row * 2) as f32 / half_rows as f32 + 0.25 / rows as f32);
+                let bottom = self.sample(
+                    x,
+                    (row * 2 + 1) as f32 / half_rows as f32 + 0.25 / rows as f32,
+                );
+                cells.push((top, bottom));
+            }
+        }
+        cells
+    }
+
+    /// Bilinear sample at normalized `(x, y)` in `[0, 1]`.
+    fn sample(&self, x: f32, y: f32) -> [u8; 3] {
+        let max = (GRID - 1) as f32;
+        let gx = (x * GRID as f32 - 0.5).clamp(0.0, max);
+        let gy = (y * GRID as f32 - 0.5).clamp(0.0, max);
+        let (x0, y0) = (gx.floor() as u32, gy.floor() as u32);
+        let (x1, y1) = ((x0 + 1).min(GRID - 1), (y0 + 1).min(GRID - 1));
+        let (fx, fy) = (gx - x0 as f32, gy - y0 as f32);
+        let at = |x: u32, y: u32| self.colors[(y * GRID + x) as usize];
+        let mut out = [0u8; 3];
+        for (c, v) in out.iter_mut().enumerate() {
+            let top = f32::from(at(x0, y0)[c]) * (1.0 - fx) + f32::from(at(x1, y0)[c]) * fx;
+            let bottom = f32::from(at(x0, y1)[c]) * (1.0 - fx) + f32::from(at(x1, y1)[c]) * fx;
+            *v = (top * (1.0 - fy) + bottom * fy).round() as u8;
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn test_flat_image() {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(100, 50, Rgb([10, 20, 30])));
+        let placeholder = Placeholder::from_image(&img);
+        assert_eq!(placeholder.source_size, (100, 50));
+        let cells = placeholder.cell_colors(3, 2);
+        assert_eq!(cells.len(), 6);
+        assert!(cells.iter().all(|&c| c == ([10, 20, 30], [10, 20, 30])));
+    }
+
+    #[test]
+    fn test_gradient_is_preserved() {
+        // Left half black, right half white.
+        let img = RgbImage::from_fn(64, 64, |x, _| {
+            if x < 32 {
+                Rgb([0, 0, 0])
+            } else {
+                Rgb([255, 255, 255])
+            }
+        });
+        let placeholder = Placeholder::from_image(&DynamicImage::ImageRgb8(img));
+        let cells = placeholder.cell_colors(16, 1);
+        assert_eq!(cells[0].0, [0, 0, 0]);
+        assert_eq!(cells[15].0, [255, 255, 255]);
+        // Interpolated, so the edge is soft.
+        assert!(cells.iter().any(|c| c.0[0] > 0 && c.0[0] < 255));
+    }
+}
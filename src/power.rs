@@ -0,0 +1,173 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Power-aware prefetching (`power_save`).
+//!
+//! On battery or with a low-power platform profile, prefetching is cut down to the nearest
+//! image (or page) each way, the other-fit-mode prerender is skipped and the thread pools start
+//! smaller. `auto` re-checks the power state every [`RECHECK`], so plugging in restores the
+//! normal prefetch; thread counts are only chosen at startup.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// How often `auto` re-reads the power state.
+const RECHECK: Duration = Duration::from_secs(30);
+
+/// Prefetch count and thread caps while saving power.
+const LOW_PREFETCH_COUNT: usize = 1;
+const LOW_PREFETCH_THREADS: usize = 1;
+const LOW_TILE_THREADS: usize = 2;
+
+/// The `power_save` setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerSave {
+    /// Save power while on battery or in a low-power profile.
+    Auto,
+    On,
+    Off,
+}
+
+impl PowerSave {
+    /// Parse `auto`/`on`/`off` (also `true`/`false`); unknown values mean `auto`.
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "on" | "true" | "always" => PowerSave::On,
+            "off" | "false" | "never" => PowerSave::Off,
+            _ => PowerSave::Auto,
+        }
+    }
+}
+
+pub struct Power {
+    setting: PowerSave,
+    low: bool,
+    checked: Instant,
+}
+
+impl Power {
+    pub fn new(setting: &str) -> Self {
+        let setting = PowerSave::parse(setting);
+        Self {
+            setting,
+            low: Self::detect(setting),
+            checked: Instant::now(),
+        }
+    }
+
+    fn detect(setting: PowerSave) -> bool {
+        match setting {
+            PowerSave::Auto => low_power(),
+            PowerSave::On => true,
+            PowerSave::Off => false,
+        }
+    }
+
+    /// Whether to save power now (re-checked at most every [`RECHECK`]).
+    pub fn is_low(&mut self) -> bool {
+        if self.setting == PowerSave::Auto && self.checked.elapsed() >= RECHECK {
+            self.low = low_power();
+            self.checked = Instant::now();
+        }
+        self.low
+    }
+
+    pub fn prefetch_count(&mut self, count: usize) -> usize {
+        if self.is_low() {
+            count.min(LOW_PREFETCH_COUNT)
+        } else {
+            count
+        }
+    }
+
+    /// Thread counts for the prefetch and tile pools.
+    pub fn threads(&self, prefetch: usize, tile: usize) -> (usize, usize) {
+        if self.low {
+            (
+                prefetch.min(LOW_PREFETCH_THREADS),
+                tile.min(LOW_TILE_THREADS),
+            )
+        } else {
+            (prefetch, tile)
+        }
+    }
+}
+
+/// Running on battery or in a low-power profile.
+fn low_power() -> bool {
+    if cfg!(target_os = "macos") {
+        return std::process::Command::new("pmset")
+            .args(["-g", "batt"])
+            .output()
+            .is_ok_and(|out| String::from_utf8_lossy(&out.stdout).contains("'Battery Power'"));
+    }
+    let profile = std::fs::read_to_string("/sys/firmware/acpi/platform_profile");
+    profile.is_ok_and(|p| p.trim() == "low-power")
+        || on_battery(Path::new("/sys/class/power_supply"))
+}
+
+/// A battery is discharging and no AC adapter is online (`/sys/class/power_supply` layout).
+fn on_battery(root: &Path) -> bool {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return false;
+    };
+    let read = |dir: &Path, name| {
+        std::fs::read_to_string(dir.join(name))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    };
+    let mut discharging = false;
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        match read(&dir, "type").as_str() {
+            "Mains" | "USB" if read(&dir, "online") == "1" => return false,
+            "Battery" if read(&dir, "status") == "Discharging" => discharging = true,
+            _ => {}
+        }
+    }
+    discharging
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supply(root: &Path, name: &str, files: &[(&str, &str)]) {
+        let dir = root.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, value) in files {
+            std::fs::write(dir.join(file), format!("{value}\n")).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_on_battery() {
+        let root = std::env::temp_dir().join("stiv_test_power_supply");
+        let _ = std::fs::remove_dir_all(&root);
+        assert!(!on_battery(&root));
+
+        supply(
+            &root,
+            "BAT0",
+            &[("type", "Battery"), ("status", "Discharging")],
+        );
+        supply(&root, "AC", &[("type", "Mains"), ("online", "0")]);
+        assert!(on_battery(&root));
+
+        supply(&root, "AC", &[("type", "Mains"), ("online", "1")]);
+        assert!(!on_battery(&root));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_forced_setting() {
+        let mut power = Power::new("on");
+        assert_eq!(power.prefetch_count(5), LOW_PREFETCH_COUNT);
+        assert_eq!(power.threads(4, 8), (1, 2));
+
+        let mut power = Power::new("OFF");
+        assert_eq!(power.prefetch_count(5), 5);
+        assert_eq!(power.threads(4, 8), (4, 8));
+        assert_eq!(PowerSave::parse("bogus"), PowerSave::Auto);
+    }
+}
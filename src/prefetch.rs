@@ -6,6 +6,10 @@
 //! This module provides a dedicated worker thread for prefetching images
 //! in parallel using rayon. It runs independently from the main ImageWorker,
 //! allowing prefetch operations to not block the main rendering.
+//!
+//! Single-mode batches render their images in parallel. Tile pages are rendered one after
+//! another (each page decodes its tiles in parallel) with a thumbnail cache of their own, so
+//! pages on both sides of the current one can be queued together.
 
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -15,8 +19,15 @@ use std::thread::{self, JoinHandle};
 
 use rayon::prelude::*;
 
+use crate::adjust::Adjustments;
+use crate::dicom::WindowLevel;
 use crate::fit::FitMode;
-use crate::worker::{ImageResult, ImageWorker};
+use crate::protocol::Protocol;
+use crate::rotation::Rotation;
+use crate::worker::{
+    CacheUsage, ImageRequest, ImageResult, ImageWorker, THUMBNAIL_CACHE_SIZE, ThumbnailCache,
+};
+use crate::zoom::CropRect;
 
 /// Epoch-based cancellation token.
 /// Incremented on navigation to invalidate in-flight prefetch requests.
@@ -38,20 +49,41 @@ impl PrefetchEpoch {
 
 /// Batch prefetch request.
 pub struct PrefetchRequest {
-    pub paths: Vec<PathBuf>,
+    /// Paths to prefetch, each with its user rotation.
+    pub paths: Vec<(PathBuf, Rotation)>,
     pub target: (u32, u32),
     pub fit_mode: FitMode,
     pub epoch: u64,
     pub kgp_id: u32,
     pub is_tmux: bool,
+    pub file_transfer: bool,
     pub compress_level: Option<u32>,
+    pub protocol: Protocol,
     pub tmux_kitty_max_pixels: u64,
     pub resize_filter: image::imageops::FilterType,
+    pub upscale_sharpen: f32,
+    pub downscale_sharpen: f32,
+    pub bit_depth_stretch: f32,
+    /// Decoder allocation and dimension limits.
+    pub limits: image::Limits,
+    /// DICOM window/level adjustment (ignored for other images).
+    pub window: WindowLevel,
+    /// Color adjustments.
+    pub adjust: Adjustments,
+    /// Zoomed region, carried over from the current image while the zoom is locked.
+    pub crop: Option<CropRect>,
 }
 
 /// Internal command for prefetch worker.
 enum PrefetchCommand {
     Batch(PrefetchRequest),
+    /// Tile pages (Tile-mode `ImageRequest`s), nearest first.
+    TilePages {
+        pages: Vec<ImageRequest>,
+        epoch: u64,
+    },
+    /// Drop the tile thumbnail cache (`:cache clear`).
+    ClearCaches,
     Shutdown,
 }
 
@@ -60,6 +92,7 @@ pub struct PrefetchWorker {
     command_tx: Sender<PrefetchCommand>,
     result_rx: Receiver<(u64, ImageResult)>,
     epoch: Arc<PrefetchEpoch>,
+    usage: Arc<CacheUsage>,
     _handle: JoinHandle<()>,
 }
 
@@ -70,15 +103,24 @@ impl PrefetchWorker {
         let (result_tx, result_rx) = mpsc::channel::<(u64, ImageResult)>();
         let epoch = Arc::new(PrefetchEpoch::new());
         let epoch_clone = Arc::clone(&epoch);
+        let usage = Arc::new(CacheUsage::default());
+        let usage_clone = Arc::clone(&usage);
 
         let handle = thread::spawn(move || {
-            Self::coordinator_loop(command_rx, result_tx, epoch_clone, thread_count);
+            Self::coordinator_loop(
+                command_rx,
+                result_tx,
+                epoch_clone,
+                usage_clone,
+                thread_count,
+            );
         });
 
         Self {
             command_tx,
             result_rx,
             epoch,
+            usage,
             _handle: handle,
         }
     }
@@ -88,6 +130,22 @@ impl PrefetchWorker {
         let _ = self.command_tx.send(PrefetchCommand::Batch(req));
     }
 
+    /// Submit tile pages for prefetching, in order.
+    pub fn prefetch_tile_pages(&self, pages: Vec<ImageRequest>, epoch: u64) {
+        let _ = self
+            .command_tx
+            .send(PrefetchCommand::TilePages { pages, epoch });
+    }
+
+    /// Drop the tile thumbnail cache.
+    pub fn clear_caches(&self) {
+        let _ = self.command_tx.send(PrefetchCommand::ClearCaches);
+    }
+
+    pub fn usage(&self) -> &CacheUsage {
+        &self.usage
+    }
+
     /// Cancel all pending prefetch requests by incrementing the epoch.
     pub fn cancel(&self) {
         self.epoch.increment();
@@ -115,6 +173,7 @@ impl PrefetchWorker {
         command_rx: Receiver<PrefetchCommand>,
         result_tx: Sender<(u64, ImageResult)>,
         epoch: Arc<PrefetchEpoch>,
+        usage: Arc<CacheUsage>,
         thread_count: usize,
     ) {
         // Create dedicated rayon thread pool for prefetch
@@ -122,6 +181,7 @@ impl PrefetchWorker {
             .num_threads(thread_count)
             .build()
             .expect("Failed to create prefetch thread pool");
+        let mut thumbnail_cache = ThumbnailCache::new(THUMBNAIL_CACHE_SIZE);
 
         while let Ok(cmd) = command_rx.recv() {
             match cmd {
@@ -136,7 +196,7 @@ impl PrefetchWorker {
                     let request_epoch = req.epoch;
 
                     pool.install(|| {
-                        req.paths.par_iter().for_each(|path| {
+                        req.paths.par_iter().for_each(|(path, rotation)| {
                             // Check epoch before processing
                             if epoch_ref.current() > request_epoch {
                                 return; // Cancelled
@@ -149,18 +209,57 @@ impl PrefetchWorker {
                                 req.fit_mode,
                                 req.kgp_id,
                                 req.is_tmux,
+                                req.file_transfer,
                                 req.compress_level,
+                                req.protocol,
                                 req.tmux_kitty_max_pixels,
                                 req.resize_filter,
+                                req.upscale_sharpen,
+                                req.downscale_sharpen,
+                                req.bit_depth_stretch,
+                                &req.limits,
+                                *rotation,
+                                req.window,
+                                req.adjust,
+                                req.crop,
                             ) {
                                 // Check epoch again before sending
                                 if epoch_ref.current() <= request_epoch {
                                     let _ = result_tx.send((request_epoch, result));
+                                    crate::wake::wake();
                                 }
                             }
                         });
                     });
                 }
+                PrefetchCommand::TilePages {
+                    pages,
+                    epoch: request_epoch,
+                } => {
+                    for req in &pages {
+                        if epoch.current() > request_epoch {
+                            break; // Cancelled
+                        }
+                        let Some(composite) = ImageWorker::composite_tile_page(
+                            req,
+                            &mut thumbnail_cache,
+                            &pool,
+                            None,
+                        ) else {
+                            continue;
+                        };
+                        let result = ImageWorker::encode_tile_page(req, composite);
+                        if epoch.current() <= request_epoch {
+                            let _ = result_tx.send((request_epoch, result));
+                            crate::wake::wake();
+                        }
+                    }
+                    usage.set_thumbnails(thumbnail_cache.bytes());
+                }
+                PrefetchCommand::ClearCaches => {
+                    thumbnail_cache.clear();
+                    usage.set_thumbnails(0);
+                }
                 PrefetchCommand::Shutdown => break,
             }
         }
@@ -0,0 +1,206 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Previewer mode (`--preview`) for file-manager preview panes (fzf, yazi, lf).
+//!
+//! Draws one image into a pane and exits: no alternate screen, no raw-mode event loop. The pane
+//! comes from `--place COLSxROWS@XxY` (drawn at that absolute position, cursor restored
+//! afterwards); without it the image is written at the cursor as plain lines sized to
+//! `FZF_PREVIEW_COLUMNS`/`FZF_PREVIEW_LINES` or the terminal. Every preview uses the same KGP
+//! ID, so each one replaces the previous image, and `--clear` deletes it (e.g. lf's cleaner).
+
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use ratatui::layout::Rect;
+
+use crate::adjust::Adjustments;
+use crate::app::{ensure_tmux_allow_passthrough_on, is_tmux_env, placement_area};
+use crate::config::{Config, parse_filter_type};
+use crate::dicom::WindowLevel;
+use crate::fit::FitMode;
+use crate::kgp;
+use crate::protocol::{Capabilities, Protocol};
+use crate::rotation::RotationStore;
+use crate::worker::ImageWorker;
+
+/// KGP image ID shared by all previews, so a new preview replaces the previous image.
+const PREVIEW_KGP_ID: u32 = 0x00ff_5717;
+
+/// Pane to draw into: an absolute cell area, or the cursor position with the given size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    At(Rect),
+    Inline { width: u16, height: u16 },
+}
+
+impl Pane {
+    /// Pane from `--place`, else the fzf preview size, else the terminal size.
+    pub fn resolve(place: Option<&str>) -> Result<Self> {
+        if let Some(place) = place {
+            return parse_place(place).map(Pane::At);
+        }
+        let env = |name| std::env::var(name).ok().and_then(|v| v.parse::<u16>().ok());
+        let (width, height) = match (env("FZF_PREVIEW_COLUMNS"), env("FZF_PREVIEW_LINES")) {
+            (Some(w), Some(h)) => (w, h),
+            _ => ratatui::crossterm::terminal::size().unwrap_or((80, 24)),
+        };
+        Ok(Pane::Inline { width, height })
+    }
+
+    fn size(self) -> (u16, u16) {
+        match self {
+            Pane::At(area) => (area.width, area.height),
+            Pane::Inline { width, height } => (width, height),
+        }
+    }
+}
+
+/// Parse `COLSxROWS@XxY` (the `kitty icat --place` format, 0-based position).
+pub fn parse_place(s: &str) -> Result<Rect> {
+    let parse_pair = |part: &str| -> Option<(u16, u16)> {
+        let (a, b) = part.split_once('x')?;
+        Some((a.trim().parse().ok()?, b.trim().parse().ok()?))
+    };
+    let (size, pos) = s.split_once('@').unwrap_or((s, "0x0"));
+    let (Some((width, height)), Some((x, y))) = (parse_pair(size), parse_pair(pos)) else {
+        bail!("Invalid --place '{s}' (expected COLSxROWS@XxY)");
+    };
+    if width == 0 || height == 0 {
+        bail!("Invalid --place '{s}' (empty area)");
+    }
+    Ok(Rect::new(x, y, width, height))
+}
+
+/// Draw `path` into `pane` and return.
+pub fn run(path: &Path, pane: Pane, config: &Config) -> Result<()> {
+    let is_tmux = is_tmux_env();
+    ensure_tmux_allow_passthrough_on(is_tmux);
+    let Capabilities {
+        picker, protocol, ..
+    } = Capabilities::detect(&config.protocol);
+    let (cell_w, cell_h) = protocol.cell_size(picker.font_size());
+    let (width, height) = pane.size();
+    let target = (
+        u32::from(width) * u32::from(cell_w.max(1)),
+        u32::from(height) * u32::from(cell_h.max(1)),
+    );
+
+    let rotation = RotationStore::new(&[path.to_path_buf()], config.persist_rotation).get(path);
+    let result = ImageWorker::process_image(
+        path,
+        target,
+        FitMode::Normal,
+        PREVIEW_KGP_ID,
+        is_tmux,
+        false,
+        config.compression_level(),
+        protocol,
+        config.tmux_kitty_max_pixels,
+        parse_filter_type(&config.resize_filter),
+        config.upscale_sharpen,
+        config.downscale_sharpen,
+        config.bit_depth_stretch,
+        &config.decode_limits(),
+        rotation,
+        WindowLevel::default(),
+        Adjustments::default(),
+        None,
+    )
+    .with_context(|| format!("Failed to decode {}", path.display()))?;
+
+    let origin = match pane {
+        Pane::At(area) => area,
+        Pane::Inline { width, height } => Rect::new(0, 0, width, height),
+    };
+    let area = placement_area(origin, result.actual_size, (cell_w, cell_h));
+    let rows = protocol.rows(&result.encoded_chunks, area, PREVIEW_KGP_ID);
+
+    let mut out = std::io::stdout().lock();
+    if protocol == Protocol::Kitty {
+        out.write_all(&kgp::delete_by_id(PREVIEW_KGP_ID, is_tmux))?;
+        for chunk in result.encoded_chunks.iter() {
+            out.write_all(chunk)?;
+        }
+    }
+    match pane {
+        Pane::At(_) => {
+            out.write_all(b"\x1b7")?;
+            for row in &rows {
+                out.write_all(row)?;
+            }
+            out.write_all(b"\x1b8")?;
+        }
+        Pane::Inline { .. } => {
+            // Relative lines, so the output also works where it is captured (fzf).
+            out.write_all("\n".repeat(usize::from(area.y)).as_bytes())?;
+            for row in &rows {
+                out.write_all(" ".repeat(usize::from(area.x)).as_bytes())?;
+                out.write_all(&strip_cursor_move(row))?;
+                out.write_all(b"\n")?;
+            }
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Delete the preview image (`--clear`).
+pub fn clear() -> Result<()> {
+    let is_tmux = is_tmux_env();
+    ensure_tmux_allow_passthrough_on(is_tmux);
+    let mut out = std::io::stdout().lock();
+    out.write_all(&kgp::delete_by_id(PREVIEW_KGP_ID, is_tmux))?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Remove the first cursor position sequence (`ESC [ row ; col H`) from a row.
+fn strip_cursor_move(row: &[u8]) -> Vec<u8> {
+    let mut i = 0;
+    while let Some(start) = row[i..]
+        .windows(2)
+        .position(|w| w == b"\x1b[")
+        .map(|p| p + i)
+    {
+        let params = &row[start + 2..];
+        let len = params
+            .iter()
+            .take_while(|b| b.is_ascii_digit() || **b == b';')
+            .count();
+        if params.get(len) == Some(&b'H') {
+            let mut out = row[..start].to_vec();
+            out.extend_from_slice(&params[len + 1..]);
+            return out;
+        }
+        i = start + 2;
+    }
+    row.to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_place() {
+        assert_eq!(parse_place("40x20@10x2").unwrap(), Rect::new(10, 2, 40, 20));
+        assert_eq!(parse_place("40x20").unwrap(), Rect::new(0, 0, 40, 20));
+        assert!(parse_place("40x@1x1").is_err());
+        assert!(parse_place("0x20@0x0").is_err());
+        assert!(parse_place("big").is_err());
+    }
+
+    #[test]
+    fn test_strip_cursor_move() {
+        assert_eq!(
+            strip_cursor_move(b"\x1b[38;2;1;2;3m\x1b[5;7Habc\x1b[0m"),
+            b"\x1b[38;2;1;2;3mabc\x1b[0m"
+        );
+        let img = image::DynamicImage::new_rgb8(2, 2);
+        let rows = crate::halfblocks::rows(&crate::halfblocks::encode(&img), Rect::new(3, 4, 2, 1));
+        assert!(rows[0].starts_with(b"\x1b[5;4H"));
+        assert!(strip_cursor_move(&rows[0]).starts_with(b"\x1b[38;2;0;0;0"));
+    }
+}
@@ -0,0 +1,351 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Terminal graphics capability detection and the output protocol.
+//!
+//! The terminal is queried once at startup (`Picker::from_query_stdio`: a KGP probe, DA1 and
+//! the cell size). With `--protocol auto` (the default), the protocol is picked from what the
+//! terminal actually answered plus a few environment hints (`decide`); the picker's own guess is
+//! not trusted. When nothing usable is found (pipes, terminals that do not answer), stiv falls
+//! back to half-block output instead of sending escapes that would render wrong or not at all,
+//! and shows a warning pointing to `--list-caps`. An explicit `--protocol` / `protocol` choice
+//! is always honored.
+
+use image::DynamicImage;
+use ratatui::layout::Rect;
+use ratatui_image::picker::{Capability, Picker, ProtocolType};
+
+use crate::{halfblocks, iterm2, kgp, sixel};
+
+/// How images are drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    /// Kitty Graphics Protocol (direct placement, or Unicode placeholders in tmux).
+    #[default]
+    Kitty,
+    /// iTerm2 inline images (OSC 1337), one strip per cell row of `cell_size` pixels.
+    Iterm2 { cell_size: (u16, u16) },
+    /// Sixel graphics, one strip per cell row of `cell_size` pixels.
+    Sixel { cell_size: (u16, u16) },
+    /// Colored `▀` cells (no graphics support needed).
+    Halfblocks,
+}
+
+impl Protocol {
+    /// Cell size used for resizing (`font_size` is the detected cell size in pixels).
+    pub fn cell_size(self, font_size: (u16, u16)) -> (u16, u16) {
+        match self {
+            Protocol::Kitty => font_size,
+            Protocol::Iterm2 { cell_size } | Protocol::Sixel { cell_size } => cell_size,
+            Protocol::Halfblocks => halfblocks::CELL_SIZE,
+        }
+    }
+
+    /// Encode a resized image for transmission.
+    pub fn encode(
+        self,
+        img: &DynamicImage,
+        kgp_id: u32,
+        is_tmux: bool,
+        compress_level: Option<u32>,
+        file_transfer: bool,
+    ) -> Vec<Vec<u8>> {
+        match self {
+            Protocol::Kitty => {
+                kgp::encode_chunks(img, kgp_id, is_tmux, compress_level, file_transfer)
+            }
+            Protocol::Iterm2 { cell_size } => iterm2::encode(img, cell_size, is_tmux),
+            Protocol::Sixel { cell_size } => sixel::encode(img, cell_size),
+            Protocol::Halfblocks => halfblocks::encode(img),
+        }
+    }
+
+    /// Whether the image is drawn into the cells themselves (no image transmitted once and
+    /// placed by ID), so the writer must keep the encoded data to redraw rows.
+    pub fn draws_cells(self) -> bool {
+        self != Protocol::Kitty
+    }
+
+    /// Per-row escapes that show the image in `area`: KGP placement rows for `kgp_id`, or the
+    /// encoded cells drawn again.
+    pub fn rows(self, encoded: &[Vec<u8>], area: Rect, kgp_id: u32) -> Vec<Vec<u8>> {
+        match self {
+            Protocol::Kitty => kgp::place_rows(area, kgp_id),
+            Protocol::Iterm2 { .. } | Protocol::Sixel { .. } => strip_rows(encoded, area),
+            Protocol::Halfblocks => halfblocks::rows(encoded, area),
+        }
+    }
+}
+
+/// Escape sequences drawing one encoded strip per row into `area` (iTerm2 and sixel), one
+/// entry per row like `halfblocks::rows`. Each row is erased first; rows beyond the image stay
+/// empty.
+pub fn strip_rows(encoded: &[Vec<u8>], area: Rect) -> Vec<Vec<u8>> {
+    use std::io::Write as _;
+
+    (0..area.height)
+        .map(|row| {
+            let mut line = Vec::new();
+            _ = write!(
+                line,
+                "\x1b[{};{}H\x1b[0m\x1b[{}X",
+                area.y + row + 1,
+                area.x + 1,
+                area.width
+            );
+            if let Some(strip) = encoded.get(usize::from(row)) {
+                line.extend_from_slice(strip);
+            }
+            line
+        })
+        .collect()
+}
+
+/// Protocol requested with `--protocol` or the `protocol` config key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Choice {
+    #[default]
+    Auto,
+    Kgp,
+    Sixel,
+    Iterm2,
+    Halfblocks,
+}
+
+impl Choice {
+    /// Accepted names, in `--help` order.
+    pub const NAMES: [&str; 5] = ["auto", "kgp", "sixel", "iterm2", "halfblocks"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "auto" => Some(Choice::Auto),
+            "kgp" | "kitty" => Some(Choice::Kgp),
+            "sixel" => Some(Choice::Sixel),
+            "iterm2" => Some(Choice::Iterm2),
+            "halfblocks" => Some(Choice::Halfblocks),
+            _ => None,
+        }
+    }
+}
+
+/// Terminals that speak the iTerm2 protocol, by `TERM_PROGRAM`. WezTerm also answers the KGP
+/// probe, but its Kitty graphics support is incomplete.
+const ITERM2_PROGRAMS: [&str; 4] = ["iTerm", "WezTerm", "mintty", "rio"];
+
+/// Pick the protocol for `choice` from the query answers (`caps`) and environment variables
+/// (`env`). Returns the protocol type and why it was chosen.
+fn decide(
+    choice: Choice,
+    caps: &[Capability],
+    env: impl Fn(&str) -> Option<String>,
+) -> (ProtocolType, String) {
+    let forced = |kind| (kind, "requested with --protocol / `protocol`".to_string());
+    match choice {
+        Choice::Kgp => return forced(ProtocolType::Kitty),
+        Choice::Sixel => return forced(ProtocolType::Sixel),
+        Choice::Iterm2 => return forced(ProtocolType::Iterm2),
+        Choice::Halfblocks => return forced(ProtocolType::Halfblocks),
+        Choice::Auto => {}
+    }
+
+    if let Some(program) =
+        env("TERM_PROGRAM").filter(|p| ITERM2_PROGRAMS.iter().any(|t| p.contains(t)))
+    {
+        return (ProtocolType::Iterm2, format!("TERM_PROGRAM={program}"));
+    }
+    if env("LC_TERMINAL").is_some_and(|t| t.contains("iTerm")) {
+        return (ProtocolType::Iterm2, "LC_TERMINAL=iTerm2".to_string());
+    }
+    if caps.contains(&Capability::Kitty) {
+        return (
+            ProtocolType::Kitty,
+            "terminal answered the KGP query".to_string(),
+        );
+    }
+    // The query can go unanswered inside tmux; trust variables the outer terminal sets.
+    if env("KITTY_WINDOW_ID").is_some()
+        || env("TERM").is_some_and(|t| t == "xterm-kitty" || t == "xterm-ghostty")
+        || env("TERM_PROGRAM").is_some_and(|p| p == "ghostty")
+    {
+        return (
+            ProtocolType::Kitty,
+            "Kitty/Ghostty environment variables (no KGP answer)".to_string(),
+        );
+    }
+    if caps.contains(&Capability::Sixel) {
+        return (ProtocolType::Sixel, "DA1 reports sixel support".to_string());
+    }
+    (
+        ProtocolType::Halfblocks,
+        "no graphics protocol detected".to_string(),
+    )
+}
+
+/// Result of the startup terminal query.
+pub struct Capabilities {
+    pub picker: Picker,
+    pub protocol: Protocol,
+    /// Why the protocol was chosen (shown by `--list-caps` and in debug mode).
+    pub reason: String,
+    /// Why the half-block fallback is used (None when an image protocol is available).
+    pub warning: Option<String>,
+}
+
+impl Capabilities {
+    /// Query the terminal (must be called before the event loop starts reading stdin) and pick
+    /// the protocol for `choice` (a `--protocol` name; unknown names fall back to auto).
+    pub fn detect(choice: &str) -> Self {
+        let (picker, query_error) = match Picker::from_query_stdio() {
+            Ok(picker) => (picker, None),
+            Err(e) => (Picker::from_fontsize((8, 16)), Some(e.to_string())),
+        };
+        let mut caps = Self::from_picker(picker, choice, |key| std::env::var(key).ok());
+        if let Some(e) = query_error
+            && caps.protocol == Protocol::Halfblocks
+            && caps.warning.is_some()
+        {
+            caps.warning = Some(format!(
+                "Terminal query failed ({e}); using half-blocks (see --list-caps)"
+            ));
+        }
+        caps
+    }
+
+    fn from_picker(picker: Picker, choice: &str, env: impl Fn(&str) -> Option<String>) -> Self {
+        let parsed = Choice::parse(choice);
+        let (kind, reason) = decide(parsed.unwrap_or_default(), picker.capabilities(), env);
+        let cell_size = picker.font_size();
+        let protocol = match kind {
+            ProtocolType::Kitty => Protocol::Kitty,
+            ProtocolType::Iterm2 => Protocol::Iterm2 { cell_size },
+            ProtocolType::Sixel => Protocol::Sixel { cell_size },
+            ProtocolType::Halfblocks => Protocol::Halfblocks,
+        };
+        let warning = if parsed.is_none() {
+            Some(format!(
+                "Unknown protocol '{choice}' (expected {}); using {protocol:?}",
+                Choice::NAMES.join("/")
+            ))
+        } else if protocol == Protocol::Halfblocks && parsed == Some(Choice::Auto) {
+            Some("No graphics protocol detected; using half-blocks (see --list-caps)".to_string())
+        } else {
+            None
+        };
+        Self {
+            picker,
+            protocol,
+            reason,
+            warning,
+        }
+    }
+
+    /// Human-readable report for `--list-caps`.
+    pub fn report(&self) -> String {
+        let env = |key: &str| std::env::var(key).unwrap_or_else(|_| "-".to_string());
+        let (w, h) = self.picker.font_size();
+        let mut lines = vec![
+            format!("protocol:       {:?}", self.protocol),
+            format!("reason:         {}", self.reason),
+            format!("answers:        {:?}", self.picker.capabilities()),
+            format!("cell size:      {w}x{h} px"),
+            format!("tmux:           {}", crate::app::is_tmux_env()),
+            format!("TERM:           {}", env("TERM")),
+            format!("TERM_PROGRAM:   {}", env("TERM_PROGRAM")),
+        ];
+        if let Some(warning) = &self.warning {
+            lines.push(format!("warning:        {warning}"));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    #[test]
+    fn test_decide_auto() {
+        let kitty = [Capability::Kitty, Capability::Sixel];
+        assert_eq!(
+            decide(Choice::Auto, &kitty, env(&[])).0,
+            ProtocolType::Kitty
+        );
+        assert_eq!(
+            decide(Choice::Auto, &[Capability::Sixel], env(&[])).0,
+            ProtocolType::Sixel
+        );
+        // iTerm2 hints win over the KGP answer (WezTerm answers both).
+        let wezterm = [("TERM_PROGRAM", "WezTerm")];
+        assert_eq!(
+            decide(Choice::Auto, &kitty, env(&wezterm)).0,
+            ProtocolType::Iterm2
+        );
+        // No answer (e.g. inside tmux): environment hints of the outer terminal.
+        let ghostty = [("TERM_PROGRAM", "ghostty")];
+        assert_eq!(
+            decide(Choice::Auto, &[], env(&ghostty)).0,
+            ProtocolType::Kitty
+        );
+        let (kind, reason) = decide(Choice::Auto, &[], env(&[("TERM", "xterm")]));
+        assert_eq!(kind, ProtocolType::Halfblocks);
+        assert_eq!(reason, "no graphics protocol detected");
+    }
+
+    #[test]
+    fn test_decide_forced() {
+        assert_eq!(decide(Choice::Sixel, &[], env(&[])).0, ProtocolType::Sixel);
+        assert_eq!(
+            decide(Choice::Halfblocks, &[Capability::Kitty], env(&[])).0,
+            ProtocolType::Halfblocks
+        );
+        assert_eq!(Choice::parse("KGP"), Some(Choice::Kgp));
+        assert_eq!(Choice::parse("png"), None);
+    }
+
+    #[test]
+    fn test_fallback_warnings() {
+        let picker = || Picker::from_fontsize((8, 16));
+        let caps = Capabilities::from_picker(picker(), "auto", env(&[]));
+        assert_eq!(caps.protocol, Protocol::Halfblocks);
+        assert!(caps.warning.as_ref().unwrap().contains("--list-caps"));
+        assert!(caps.report().contains("protocol:       Halfblocks"));
+
+        let caps = Capabilities::from_picker(picker(), "iterm2", env(&[]));
+        assert_eq!(caps.protocol, Protocol::Iterm2 { cell_size: (8, 16) });
+        assert!(caps.warning.is_none());
+
+        let caps = Capabilities::from_picker(picker(), "bogus", env(&[]));
+        assert!(
+            caps.warning
+                .unwrap()
+                .starts_with("Unknown protocol 'bogus'")
+        );
+    }
+
+    #[test]
+    fn test_cell_size() {
+        assert_eq!(Protocol::Kitty.cell_size((9, 18)), (9, 18));
+        assert_eq!(Protocol::Halfblocks.cell_size((9, 18)), (1, 2));
+        let iterm2 = Protocol::Iterm2 { cell_size: (9, 18) };
+        assert_eq!(iterm2.cell_size((9, 18)), (9, 18));
+        assert!(iterm2.draws_cells() && !Protocol::Kitty.draws_cells());
+    }
+
+    #[test]
+    fn test_strip_rows_position_each_strip() {
+        let encoded = vec![b"A".to_vec(), b"B".to_vec()];
+        let rows = strip_rows(&encoded, Rect::new(2, 5, 3, 3));
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0], b"\x1b[6;3H\x1b[0m\x1b[3XA");
+        assert_eq!(rows[1], b"\x1b[7;3H\x1b[0m\x1b[3XB");
+        assert_eq!(rows[2], b"\x1b[8;3H\x1b[0m\x1b[3X");
+    }
+}
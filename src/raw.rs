@@ -0,0 +1,232 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! RAW camera files (`.cr2`, `.nef`, `.arw`, `.dng`), shown through their embedded JPEG
+//! preview instead of developing the sensor data.
+//!
+//! These formats are TIFF containers. Every IFD (the IFD chain and `SubIFDs`) is searched for
+//! JPEG data: `JPEGInterchangeFormat` offset/length pairs and single-strip images with JPEG
+//! compression. The largest candidate that decodes is shown; the raw data itself is often
+//! lossless JPEG, which does not decode and is skipped.
+
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+
+use image::DynamicImage;
+
+use crate::exif::Tiff;
+
+const TAG_COMPRESSION: u16 = 0x0103;
+const TAG_STRIP_OFFSETS: u16 = 0x0111;
+const TAG_STRIP_BYTE_COUNTS: u16 = 0x0117;
+const TAG_SUB_IFDS: u16 = 0x014A;
+const TAG_JPEG_OFFSET: u16 = 0x0201;
+const TAG_JPEG_LENGTH: u16 = 0x0202;
+
+/// `Compression` values for (old-style and new-style) JPEG.
+const COMPRESSION_JPEG: [u32; 2] = [6, 7];
+
+const TYPE_SHORT: u16 = 3;
+const TYPE_LONG: u16 = 4;
+/// `IFD` type used for `SubIFDs` by some writers.
+const TYPE_IFD: u16 = 13;
+
+/// IFDs visited at most (guards against malformed or looping files).
+const MAX_IFDS: usize = 64;
+
+/// Check if the path has a supported RAW extension.
+pub fn is_raw(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| ["cr2", "nef", "arw", "dng"].contains(&e.to_lowercase().as_str()))
+}
+
+/// Decode the largest embedded JPEG preview of a RAW file. Previews exceeding `limits` fail
+/// to decode.
+pub fn decode(bytes: &[u8], limits: &image::Limits) -> Option<DynamicImage> {
+    let tiff = Tiff::new(bytes)?;
+    let mut previews = jpeg_ranges(&tiff);
+    previews.sort_by_key(|range| std::cmp::Reverse(range.len()));
+    previews.into_iter().find_map(|range| {
+        let mut reader = image::ImageReader::with_format(
+            std::io::Cursor::new(bytes.get(range)?),
+            image::ImageFormat::Jpeg,
+        );
+        reader.limits(limits.clone());
+        reader.decode().ok()
+    })
+}
+
+/// Byte ranges of JPEG data referenced from any IFD.
+fn jpeg_ranges(tiff: &Tiff) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut queue: Vec<u32> = tiff.u32(4).into_iter().collect();
+    let mut visited = HashSet::new();
+    while let Some(offset) = queue.pop() {
+        if offset == 0 || visited.len() >= MAX_IFDS || !visited.insert(offset) {
+            continue;
+        }
+        let ifd = read_ifd(tiff, offset as usize);
+        queue.extend(ifd.next);
+        queue.extend(ifd.sub_ifds);
+
+        let pairs = [
+            (ifd.jpeg_offset, ifd.jpeg_length),
+            match ifd.compression {
+                Some(c) if COMPRESSION_JPEG.contains(&c) => (ifd.strip_offset, ifd.strip_length),
+                _ => (None, None),
+            },
+        ];
+        for (start, len) in pairs {
+            if let (Some(start), Some(len)) = (start, len) {
+                let range = start as usize..start as usize + len as usize;
+                // Only ranges inside the file that start with a JPEG SOI marker.
+                if tiff
+                    .data
+                    .get(range.clone())
+                    .is_some_and(|d| d.starts_with(&[0xFF, 0xD8]))
+                {
+                    ranges.push(range);
+                }
+            }
+        }
+    }
+    ranges
+}
+
+/// The entries of one IFD that can point at JPEG data.
+#[derive(Default)]
+struct Ifd {
+    compression: Option<u32>,
+    /// First strip (previews are stored as a single strip).
+    strip_offset: Option<u32>,
+    strip_length: Option<u32>,
+    jpeg_offset: Option<u32>,
+    jpeg_length: Option<u32>,
+    sub_ifds: Vec<u32>,
+    next: Option<u32>,
+}
+
+fn read_ifd(tiff: &Tiff, offset: usize) -> Ifd {
+    let mut ifd = Ifd::default();
+    let Some(count) = tiff.u16(offset) else {
+        return ifd;
+    };
+    for i in 0..usize::from(count) {
+        let entry = offset + 2 + i * 12;
+        let (Some(tag), Some(kind), Some(n)) =
+            (tiff.u16(entry), tiff.u16(entry + 2), tiff.u32(entry + 4))
+        else {
+            return ifd;
+        };
+        let size = match kind {
+            TYPE_SHORT => 2,
+            TYPE_LONG | TYPE_IFD => 4,
+            _ => continue,
+        };
+        let n = n as usize;
+        // Values up to 4 bytes are stored in the entry itself.
+        let start = if n.saturating_mul(size) <= 4 {
+            entry + 8
+        } else {
+            match tiff.u32(entry + 8) {
+                Some(at) => at as usize,
+                None => continue,
+            }
+        };
+        let value = |k: usize| match size {
+            2 => tiff.u16(start + k * 2).map(u32::from),
+            _ => tiff.u32(start + k * 4),
+        };
+        match tag {
+            TAG_COMPRESSION => ifd.compression = value(0),
+            TAG_STRIP_OFFSETS if n == 1 => ifd.strip_offset = value(0),
+            TAG_STRIP_BYTE_COUNTS if n == 1 => ifd.strip_length = value(0),
+            TAG_JPEG_OFFSET => ifd.jpeg_offset = value(0),
+            TAG_JPEG_LENGTH => ifd.jpeg_length = value(0),
+            TAG_SUB_IFDS => ifd.sub_ifds = (0..n.min(MAX_IFDS)).map_while(value).collect(),
+            _ => {}
+        }
+    }
+    ifd.next = tiff.u32(offset + 2 + usize::from(count) * 12);
+    ifd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jpeg(width: u32, height: u32) -> Vec<u8> {
+        let mut out = Vec::new();
+        DynamicImage::new_rgb8(width, height)
+            .write_to(
+                &mut std::io::Cursor::new(&mut out),
+                image::ImageFormat::Jpeg,
+            )
+            .unwrap();
+        out
+    }
+
+    /// Little-endian TIFF whose IFD0 holds a small JPEG thumbnail (offset/length pair) and
+    /// points to a sub-IFD holding a larger JPEG-compressed strip.
+    fn sample_raw() -> Vec<u8> {
+        let small = jpeg(4, 2);
+        let large = jpeg(16, 8);
+        let entry = |tag: u16, kind: u16, value: u32| {
+            let mut e = Vec::new();
+            e.extend_from_slice(&tag.to_le_bytes());
+            e.extend_from_slice(&kind.to_le_bytes());
+            e.extend_from_slice(&1u32.to_le_bytes());
+            e.extend_from_slice(&value.to_le_bytes());
+            e
+        };
+
+        // Header (8) + IFD0 (2 + 3 * 12 + 4) + sub-IFD (2 + 3 * 12 + 4), then the JPEGs.
+        let ifd0 = 8u32;
+        let sub = ifd0 + 42;
+        let small_at = sub + 42;
+        let large_at = small_at + small.len() as u32;
+
+        let mut data = b"II*\0".to_vec();
+        data.extend_from_slice(&ifd0.to_le_bytes());
+        data.extend_from_slice(&3u16.to_le_bytes());
+        data.extend(entry(TAG_SUB_IFDS, TYPE_LONG, sub));
+        data.extend(entry(TAG_JPEG_OFFSET, TYPE_LONG, small_at));
+        data.extend(entry(TAG_JPEG_LENGTH, TYPE_LONG, small.len() as u32));
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&3u16.to_le_bytes());
+        data.extend(entry(TAG_COMPRESSION, TYPE_SHORT, 6));
+        data.extend(entry(TAG_STRIP_OFFSETS, TYPE_LONG, large_at));
+        data.extend(entry(TAG_STRIP_BYTE_COUNTS, TYPE_LONG, large.len() as u32));
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend(small);
+        data.extend(large);
+        data
+    }
+
+    #[test]
+    fn test_is_raw() {
+        assert!(is_raw(Path::new("DSC_0001.NEF")));
+        assert!(is_raw(Path::new("a.dng")));
+        assert!(!is_raw(Path::new("a.tif")));
+    }
+
+    #[test]
+    fn test_decode_picks_largest_preview() {
+        let data = sample_raw();
+        let tiff = Tiff::new(&data).unwrap();
+        assert_eq!(jpeg_ranges(&tiff).len(), 2);
+
+        let img = decode(&data, &image::Limits::default()).unwrap();
+        assert_eq!((img.width(), img.height()), (16, 8));
+
+        // A limit that rejects the large preview falls back to the thumbnail.
+        let mut limits = image::Limits::default();
+        limits.max_image_width = Some(8);
+        let img = decode(&data, &limits).unwrap();
+        assert_eq!((img.width(), img.height()), (4, 2));
+
+        assert!(decode(b"II*\0\x08\0\0\0", &image::Limits::default()).is_none());
+    }
+}
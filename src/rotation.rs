@@ -0,0 +1,223 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Per-image rotation.
+//!
+//! - `Rotation`: a quarter-turn applied by the workers right after decode, so Single mode,
+//!   zoom and tile thumbnails all see the corrected orientation.
+//! - `RotationStore`: rotations set by the user, optionally persisted to a sidecar file
+//!   (`~/.local/share/stiv/rotations.toml`) so corrections survive restarts.
+//!
+//! Image files are never modified.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+/// Clockwise rotation in quarter turns.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Rotation {
+    #[default]
+    None,
+    Cw90,
+    Cw180,
+    Cw270,
+}
+
+impl Rotation {
+    /// Rotate by `quarter_turns` (positive = clockwise, negative = counter-clockwise).
+    pub fn turn(self, quarter_turns: i32) -> Self {
+        let steps = (i32::from(self.degrees() / 90) + quarter_turns).rem_euclid(4);
+        Self::from_degrees(steps as u16 * 90).unwrap_or_default()
+    }
+
+    pub fn degrees(self) -> u16 {
+        match self {
+            Rotation::None => 0,
+            Rotation::Cw90 => 90,
+            Rotation::Cw180 => 180,
+            Rotation::Cw270 => 270,
+        }
+    }
+
+    /// Parse a clockwise angle in degrees (must be a multiple of 90).
+    pub fn from_degrees(degrees: u16) -> Option<Self> {
+        match degrees % 360 {
+            0 => Some(Rotation::None),
+            90 => Some(Rotation::Cw90),
+            180 => Some(Rotation::Cw180),
+            270 => Some(Rotation::Cw270),
+            _ => None,
+        }
+    }
+
+    /// Apply the rotation (borrows the input unchanged for `Rotation::None`).
+    pub fn apply(self, img: &DynamicImage) -> Cow<'_, DynamicImage> {
+        match self {
+            Rotation::None => Cow::Borrowed(img),
+            Rotation::Cw90 => Cow::Owned(img.rotate90()),
+            Rotation::Cw180 => Cow::Owned(img.rotate180()),
+            Rotation::Cw270 => Cow::Owned(img.rotate270()),
+        }
+    }
+}
+
+/// On-disk sidecar format: canonical path -> clockwise degrees.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+struct Sidecar {
+    rotations: BTreeMap<String, u16>,
+}
+
+/// Rotations for the image list, keyed by the paths as passed on the command line.
+pub struct RotationStore {
+    rotations: HashMap<PathBuf, Rotation>,
+    /// Sidecar file (None = persistence disabled).
+    sidecar_path: Option<PathBuf>,
+    /// Full sidecar contents, including entries for images not in the current list.
+    sidecar: Sidecar,
+}
+
+impl RotationStore {
+    /// Create the store, loading persisted rotations for `images` when `persist` is enabled.
+    pub fn new(images: &[PathBuf], persist: bool) -> Self {
+        let sidecar_path = if persist {
+            dirs::data_dir().map(|p| p.join("stiv").join("rotations.toml"))
+        } else {
+            None
+        };
+        Self::with_sidecar(images, sidecar_path)
+    }
+
+    fn with_sidecar(images: &[PathBuf], sidecar_path: Option<PathBuf>) -> Self {
+        let sidecar: Sidecar = sidecar_path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|s| toml::from_str(&s).ok())
+            .unwrap_or_default();
+
+        let mut store = Self {
+            rotations: HashMap::new(),
+            sidecar_path,
+            sidecar,
+        };
+        store.load(images);
+        store
+    }
+
+    /// Load persisted rotations for images added to the list after creation.
+    pub fn load(&mut self, images: &[PathBuf]) {
+        if self.sidecar.rotations.is_empty() {
+            return;
+        }
+        for path in images {
+            let Some(key) = Self::sidecar_key(path) else {
+                continue;
+            };
+            if let Some(rotation) = self
+                .sidecar
+                .rotations
+                .get(&key)
+                .and_then(|&deg| Rotation::from_degrees(deg))
+            {
+                self.rotations.insert(path.clone(), rotation);
+            }
+        }
+    }
+
+    pub fn get(&self, path: &Path) -> Rotation {
+        self.rotations.get(path).copied().unwrap_or_default()
+    }
+
+    /// Set the rotation for `path` and persist it if enabled.
+    ///
+    /// The in-memory value is updated even if writing the sidecar fails.
+    pub fn set(&mut self, path: &Path, rotation: Rotation) -> Result<()> {
+        if rotation == Rotation::None {
+            self.rotations.remove(path);
+        } else {
+            self.rotations.insert(path.to_path_buf(), rotation);
+        }
+
+        let Some(sidecar_path) = self.sidecar_path.clone() else {
+            return Ok(());
+        };
+        let key =
+            Self::sidecar_key(path).with_context(|| format!("Cannot resolve path: {:?}", path))?;
+        if rotation == Rotation::None {
+            self.sidecar.rotations.remove(&key);
+        } else {
+            self.sidecar.rotations.insert(key, rotation.degrees());
+        }
+        self.save(&sidecar_path)
+    }
+
+    fn save(&self, sidecar_path: &Path) -> Result<()> {
+        if let Some(dir) = sidecar_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let content = toml::to_string(&self.sidecar)?;
+        // Write-then-rename so a crash never leaves a truncated sidecar.
+        let tmp = sidecar_path.with_extension("toml.tmp");
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, sidecar_path)?;
+        Ok(())
+    }
+
+    /// Sidecar entries use canonical paths so they match regardless of how stiv was invoked.
+    fn sidecar_key(path: &Path) -> Option<String> {
+        let canonical = match crate::archive::split_member_path(path) {
+            Some((archive, member)) => std::fs::canonicalize(archive).ok()?.join(member),
+            None => std::fs::canonicalize(path).ok()?,
+        };
+        canonical.to_str().map(str::to_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_turn_wraps() {
+        assert_eq!(Rotation::None.turn(1), Rotation::Cw90);
+        assert_eq!(Rotation::None.turn(-1), Rotation::Cw270);
+        assert_eq!(Rotation::Cw270.turn(1), Rotation::None);
+        assert_eq!(Rotation::Cw90.turn(6), Rotation::Cw270);
+    }
+
+    #[test]
+    fn test_rotation_apply_swaps_axes() {
+        let img = DynamicImage::new_rgba8(4, 2);
+        assert_eq!(Rotation::Cw90.apply(&img).width(), 2);
+        assert_eq!(Rotation::Cw180.apply(&img).width(), 4);
+        assert!(matches!(Rotation::None.apply(&img), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_store_persists_across_instances() {
+        let dir = std::env::temp_dir().join(format!("stiv_rotation_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let image = dir.join("a.png");
+        std::fs::write(&image, b"").unwrap();
+        let sidecar = dir.join("state").join("rotations.toml");
+        let images = vec![image.clone()];
+
+        let mut store = RotationStore::with_sidecar(&images, Some(sidecar.clone()));
+        store.set(&image, Rotation::Cw90).unwrap();
+
+        let reloaded = RotationStore::with_sidecar(&images, Some(sidecar.clone()));
+        assert_eq!(reloaded.get(&image), Rotation::Cw90);
+
+        let mut store = reloaded;
+        store.set(&image, Rotation::None).unwrap();
+        let reloaded = RotationStore::with_sidecar(&images, Some(sidecar));
+        assert_eq!(reloaded.get(&image), Rotation::None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
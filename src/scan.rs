@@ -0,0 +1,110 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Background per-path scans for tile page layouts.
+//!
+//! Laying out a tile page can need something read from each of its files: the capture day for
+//! `group_by_date`, or the aspect ratio for the `justified` layout. Both are file I/O (for RAW,
+//! HEIF, SVG, DICOM, video, archive members and TIFF pages, a decode), so `PathScan` reads them
+//! on a background thread and the app keeps the results per path: each file is read once per
+//! session, not on every page composite or on the main thread. Pages wait until they are known.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Interval between batches of results.
+const BATCH_INTERVAL: Duration = Duration::from_millis(50);
+
+pub struct PathScan<T> {
+    rx: Receiver<Vec<(PathBuf, T)>>,
+    /// List index the scan started at (it reads onward from there, then wraps).
+    start: usize,
+}
+
+impl<T: Send + 'static> PathScan<T> {
+    /// Run `read` on each of `paths` in order on a background thread. `start` is the list
+    /// index of the first path.
+    pub fn spawn(
+        paths: Vec<PathBuf>,
+        start: usize,
+        read: impl Fn(&Path) -> T + Send + 'static,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || scan(paths, &read, &tx));
+        Self { rx, start }
+    }
+}
+
+impl<T> PathScan<T> {
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Results read since the last call, and whether the scan is complete.
+    pub fn poll(&mut self) -> (Vec<(PathBuf, T)>, bool) {
+        let mut results = Vec::new();
+        loop {
+            match self.rx.try_recv() {
+                Ok(batch) => results.extend(batch),
+                Err(mpsc::TryRecvError::Empty) => return (results, false),
+                Err(mpsc::TryRecvError::Disconnected) => return (results, true),
+            }
+        }
+    }
+}
+
+/// Thread body. Returns early once the receiver is gone.
+fn scan<T>(paths: Vec<PathBuf>, read: &impl Fn(&Path) -> T, tx: &Sender<Vec<(PathBuf, T)>>) {
+    let mut batch = Vec::new();
+    let mut last_flush = Instant::now();
+    for path in paths {
+        let value = read(&path);
+        batch.push((path, value));
+        if last_flush.elapsed() >= BATCH_INTERVAL {
+            if tx.send(std::mem::take(&mut batch)).is_err() {
+                return;
+            }
+            crate::wake::wake();
+            last_flush = Instant::now();
+        }
+    }
+    if !batch.is_empty() {
+        let _ = tx.send(batch);
+    }
+    crate::wake::wake();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_scan_reads_every_path() {
+        let dir = std::env::temp_dir().join(format!("stiv_path_scan_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths: Vec<PathBuf> = (0..3).map(|i| dir.join(format!("{i}.png"))).collect();
+        for path in &paths[..2] {
+            std::fs::write(path, b"").unwrap();
+        }
+
+        let mut scan = PathScan::spawn(paths.clone(), 4, crate::exif::capture_day);
+        assert_eq!(scan.start(), 4);
+        let mut days = Vec::new();
+        loop {
+            let (batch, done) = scan.poll();
+            days.extend(batch);
+            if done {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        // Files without EXIF fall back to their modification day; a missing file has none.
+        assert_eq!(days.len(), 3);
+        assert_eq!(days[0].0, paths[0]);
+        assert!(days[0].1.as_ref().is_some_and(|d| d.len() == 10));
+        assert_eq!(days[2], (paths[2].clone(), None));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
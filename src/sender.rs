@@ -8,18 +8,29 @@
 //!
 //! Key properties:
 //! - Status updates are prioritized and flushed immediately.
+//! - Text (status bar and overlays) goes through an off-screen cell buffer (`src/textbuf.rs`),
+//!   so only the cells that changed are written.
 //! - Image output is chunked at safe boundaries (KGP chunks and per-row placement/erase).
+//! - Outside tmux, KGP images are placed directly (`a=p`) instead of with placeholder rows;
+//!   the placement sits under the text, so overlays need no re-placing.
+//! - With protocols that draw into the cells (half-blocks, iTerm2), the last image is kept so
+//!   rows under overlays can be redrawn, like re-placing KGP rows.
 //! - Image output can be cancelled on navigation.
 
 use std::collections::VecDeque;
 use std::io::{IsTerminal, Write, stdout};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
 use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
 
-use crate::kgp::{delete_all, delete_by_id, erase_rows, place_rows};
+use crate::kgp::{self, delete_all, delete_by_id, erase_rows, place_direct, place_rows};
+use crate::protocol::Protocol;
+use crate::textbuf::TextBuffer;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum StatusIndicator {
@@ -33,18 +44,46 @@ pub enum WriterRequest {
     /// Update the status row (single-line HUD at the bottom).
     Status {
         text: String,
+        /// Keys of an unfinished command (count, `"`, `Q`, `@`), shown at the right end.
+        pending: String,
         size: (u16, u16),
         indicator: StatusIndicator,
     },
-    /// Transmit image bytes (KGP) and place the image in the terminal area.
+    /// Transmit image bytes (KGP) and place the image in the terminal area
+    /// (half-blocks: draw the cells).
     ImageTransmit {
         encoded_chunks: Arc<Vec<Vec<u8>>>,
+        protocol: Protocol,
         area: Rect,
         kgp_id: u32,
         old_area: Option<Rect>,
         epoch: u64,
         is_tmux: bool,
     },
+    /// Replace the placed image's pixels with the next animation frame (KGP: retransmit under
+    /// the same ID, the placement stays; half-blocks: redraw the cells).
+    AnimationFrame {
+        encoded_chunks: Arc<Vec<Vec<u8>>>,
+        protocol: Protocol,
+        area: Rect,
+        epoch: u64,
+    },
+    /// Append the frames of the placed KGP image (`frames[0]` is the still on screen) with
+    /// their delays and let the terminal play them in a loop.
+    AnimationLoad {
+        frames: Vec<(Arc<Vec<Vec<u8>>>, Duration)>,
+        kgp_id: u32,
+        epoch: u64,
+        is_tmux: bool,
+    },
+    /// Start (`run`) or stop terminal-side playback of the placed KGP image, optionally on
+    /// `frame` (1-based).
+    AnimationControl {
+        kgp_id: u32,
+        run: bool,
+        frame: Option<usize>,
+        is_tmux: bool,
+    },
     /// Clear any KGP overlays (used on shutdown).
     ClearAll {
         area: Option<Rect>,
@@ -62,11 +101,50 @@ pub enum WriterRequest {
     },
     /// Draw tile cursor border (ANSI overlay).
     TileCursor {
-        grid: (usize, usize),
+        /// Tile rectangles of the page, relative to `image_area` (`src/layout.rs`).
+        rects: Vec<Rect>,
         cursor_idx: usize,
         image_area: Rect,
         prev_cursor_idx: Option<usize>,
         cell_size: (u16, u16),
+        /// File names of the page's tiles, drawn under the thumbnails (empty: no labels).
+        labels: Vec<String>,
+    },
+    /// Draw Tile-mode jump labels at the center of each tile (ANSI overlay).
+    /// The labels of `prev` tiles are removed first and the image under them is re-placed.
+    JumpLabels {
+        rects: Vec<Rect>,
+        image_area: Rect,
+        labels: Vec<(usize, String)>,
+        prev: Vec<usize>,
+    },
+    /// Draw the zoom selection rectangle over the placed image (ANSI overlay).
+    Selection {
+        rect: Option<Rect>,
+        prev_rect: Option<Rect>,
+    },
+    /// Draw the command-line completion menu above the status row (ANSI overlay).
+    /// Rows freed since the previous menu are removed and the image under them is re-placed.
+    Menu {
+        lines: Vec<String>,
+        selected: Option<usize>,
+        prev_len: usize,
+        size: (u16, u16),
+    },
+    /// Draw a blurred placeholder (half-block cells) where the next image will be placed.
+    /// The previously placed image is erased first.
+    Placeholder {
+        area: Rect,
+        /// `(top, bottom)` colors per cell, row-major.
+        cells: Vec<([u8; 3], [u8; 3])>,
+        old_area: Option<Rect>,
+        epoch: u64,
+    },
+    /// Hand the terminal back (Ctrl-Z): end the current output, acknowledge on `paused` and
+    /// write nothing until `resume` fires. The screen is unknown afterwards.
+    Suspend {
+        paused: Sender<()>,
+        resume: Receiver<()>,
     },
     Shutdown,
 }
@@ -78,23 +156,112 @@ pub struct WriterResult {
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum WriterResultKind {
-    TransmitDone { kgp_id: u32 },
+    /// `elapsed`: from queueing the transmit to writing its last chunk.
+    TransmitDone { kgp_id: u32, elapsed: Duration },
+    /// An animation frame was written.
+    FrameDone,
 }
 
+/// Begin / end a synchronized update (DEC mode 2026): supporting terminals show the output in
+/// between at once; others ignore the mode.
+const BEGIN_SYNC: &[u8] = b"\x1b[?2026h";
+const END_SYNC: &[u8] = b"\x1b[?2026l";
+
 struct Task {
     chunks: VecDeque<Vec<u8>>,
+    started: Instant,
     complete: Option<WriterResultKind>,
     epoch: u64,
     clears_dirty: bool,
+    /// Write the last `sync_tail` chunks (the old image's erase and the new image's placement)
+    /// inside a synchronized update, so they appear together. Image data goes out before it: a
+    /// long transmission would outlast the terminal's update timeout and hold back the screen.
+    sync_tail: usize,
+}
+
+impl Task {
+    /// Write the next chunk, opening the synchronized update when its span starts. Returns the
+    /// bytes written, or None once the task is out of chunks.
+    fn write_next(&mut self, synchronized: &mut bool, out: &mut impl Write) -> Option<usize> {
+        if !*synchronized && !self.chunks.is_empty() && self.chunks.len() <= self.sync_tail {
+            let _ = out.write_all(BEGIN_SYNC);
+            *synchronized = true;
+        }
+        let chunk = self.chunks.pop_front()?;
+        let _ = out.write_all(&chunk);
+        Some(chunk.len())
+    }
 }
 
 struct WriterState {
     should_quit: bool,
-    last_status: Option<(String, (u16, u16), StatusIndicator)>,
+    last_status: Option<(String, String, (u16, u16), StatusIndicator)>,
     status_dirty: bool,
+    /// Status bar and overlays; written out when `text_dirty`.
+    text: TextBuffer,
+    text_dirty: bool,
     current_task: Option<Task>,
     current_epoch: u64,
     dirty_area: Option<Rect>,
+    /// Last image drawn into the cells and its protocol (None with Kitty graphics).
+    drawn: Option<(Protocol, Arc<Vec<Vec<u8>>>)>,
+    /// Draw the status bar and overlays without colors (`NO_COLOR` / `monochrome`).
+    monochrome: bool,
+    /// Draw the status bar over the image's bottom row (`status_overlay`).
+    status_overlay: bool,
+    /// Area and ID of the placed image, for restoring the cells the overlaid status covered.
+    placed: Option<(Rect, u32)>,
+    /// Place KGP images directly instead of with Unicode placeholder rows.
+    direct_placement: bool,
+    /// Where KGP image data is written for file transmission (None: sent inline).
+    transfer_dir: Option<PathBuf>,
+    /// A synchronized update is open (closed once the task and the text over it are out).
+    synchronized: bool,
+}
+
+impl WriterState {
+    /// Per-row escapes that redraw the placed image in `area`.
+    fn image_rows(&self, area: Rect, kgp_id: u32) -> Vec<Vec<u8>> {
+        match &self.drawn {
+            Some((protocol, encoded)) => protocol.rows(encoded, area, kgp_id),
+            None => place_rows(area, kgp_id),
+        }
+    }
+
+    /// ID of the placed image if it is a direct KGP placement.
+    fn direct_placement_id(&self) -> Option<u32> {
+        let (_, kgp_id) = self.placed?;
+        (self.direct_placement && self.drawn.is_none()).then_some(kgp_id)
+    }
+
+    /// The placed image's cells on terminal row `row` (0-based), if it covers that row. Direct
+    /// placements stay under released cells, so they have none.
+    fn image_row(&self, row: u16) -> Option<Vec<u8>> {
+        if self.direct_placement_id().is_some() {
+            return None;
+        }
+        let (area, kgp_id) = self.placed?;
+        let index = row.checked_sub(area.y).filter(|&i| i < area.height)?;
+        self.image_rows(area, kgp_id)
+            .into_iter()
+            .nth(usize::from(index))
+    }
+
+    /// Write the text cells that changed since the last flush.
+    fn flush_text(&mut self, out: &mut impl Write) -> std::io::Result<()> {
+        let mut text = std::mem::take(&mut self.text);
+        let result = text.flush(out, |row| self.image_row(row));
+        self.text = text;
+        self.text_dirty = false;
+        result
+    }
+
+    /// Image output covers `area`: drop the text there.
+    fn overwrite_text(&mut self, area: Option<Rect>) {
+        if let Some(area) = area {
+            self.text.overwrite(area);
+        }
+    }
 }
 
 pub struct TerminalWriter {
@@ -103,14 +270,27 @@ pub struct TerminalWriter {
     handle: Option<JoinHandle<()>>,
 }
 
+/// How the writer draws.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WriterOptions {
+    /// Draw the status bar and overlays without colors.
+    pub monochrome: bool,
+    /// Draw the status bar over the image's bottom row.
+    pub status_overlay: bool,
+    /// Place KGP images natively instead of with Unicode placeholder rows.
+    pub direct_placement: bool,
+    /// Send KGP image data through temporary files instead of the terminal.
+    pub file_transfer: bool,
+}
+
 impl TerminalWriter {
     /// Spawn the writer thread.
-    pub fn new() -> Self {
+    pub fn new(options: WriterOptions) -> Self {
         let (request_tx, request_rx) = mpsc::channel::<WriterRequest>();
         let (result_tx, result_rx) = mpsc::channel::<WriterResult>();
 
         let handle = thread::spawn(move || {
-            Self::writer_loop(request_rx, result_tx);
+            Self::writer_loop(request_rx, result_tx, options);
         });
 
         Self {
@@ -125,12 +305,21 @@ impl TerminalWriter {
         let _ = self.request_tx.send(req);
     }
 
+    /// A sender for requests from other threads.
+    pub fn sender(&self) -> Sender<WriterRequest> {
+        self.request_tx.clone()
+    }
+
     /// Poll for completion notifications (e.g. transmit finished for a `kgp_id`).
     pub fn try_recv(&self) -> Option<WriterResult> {
         self.result_rx.try_recv().ok()
     }
 
-    fn writer_loop(request_rx: Receiver<WriterRequest>, result_tx: Sender<WriterResult>) {
+    fn writer_loop(
+        request_rx: Receiver<WriterRequest>,
+        result_tx: Sender<WriterResult>,
+        options: WriterOptions,
+    ) {
         let mut out = stdout();
         let is_tty = out.is_terminal();
 
@@ -138,9 +327,18 @@ impl TerminalWriter {
             should_quit: false,
             last_status: None,
             status_dirty: false,
+            text: TextBuffer::default(),
+            text_dirty: false,
             current_task: None,
             current_epoch: 0,
             dirty_area: None,
+            drawn: None,
+            monochrome: options.monochrome,
+            status_overlay: options.status_overlay,
+            placed: None,
+            direct_placement: options.direct_placement,
+            transfer_dir: (options.file_transfer && is_tty).then(kgp::transfer_dir),
+            synchronized: false,
         };
         let mut bytes_since_flush: usize = 0;
         const FLUSH_THRESHOLD: usize = 64 * 1024;
@@ -150,7 +348,11 @@ impl TerminalWriter {
                 break;
             }
 
-            if state.current_task.is_none() && !state.status_dirty {
+            if state.current_task.is_none()
+                && !state.status_dirty
+                && !state.text_dirty
+                && !state.synchronized
+            {
                 match request_rx.recv() {
                     Ok(msg) => Self::apply_msg(msg, &mut state, is_tty, &mut out),
                     Err(_) => break,
@@ -165,16 +367,40 @@ impl TerminalWriter {
             }
 
             if state.status_dirty {
-                if let Some((text, size, indicator)) = state.last_status.clone() {
-                    if is_tty {
-                        let _ = Self::render_status(&mut out, &text, size, indicator);
-                        let _ = out.flush();
-                    }
-                    bytes_since_flush = 0;
+                if let Some((text, pending, size, indicator)) = state.last_status.clone()
+                    && is_tty
+                {
+                    state.text.resize(size);
+                    Self::render_status(
+                        &mut state.text,
+                        &text,
+                        &pending,
+                        size,
+                        indicator,
+                        state.monochrome,
+                        state.status_overlay,
+                    );
+                    state.text_dirty = true;
                 }
                 state.status_dirty = false;
             }
 
+            if state.text_dirty {
+                if is_tty {
+                    let _ = state.flush_text(&mut out);
+                    let _ = out.flush();
+                    bytes_since_flush = 0;
+                }
+                state.text_dirty = false;
+            }
+
+            // Also reached when the task was cancelled or preempted.
+            if state.synchronized && state.current_task.as_ref().is_none_or(|t| t.sync_tail == 0) {
+                let _ = out.write_all(END_SYNC);
+                let _ = out.flush();
+                state.synchronized = false;
+            }
+
             if let Some(task) = &mut state.current_task {
                 if task.epoch != state.current_epoch {
                     state.current_task = None;
@@ -186,6 +412,7 @@ impl TerminalWriter {
                             kind,
                             epoch: task.epoch,
                         });
+                        crate::wake::wake();
                     }
                     if task.clears_dirty {
                         state.dirty_area = None;
@@ -193,31 +420,42 @@ impl TerminalWriter {
                     state.current_task = None;
                     continue;
                 }
-                if let Some(chunk) = task.chunks.pop_front() {
-                    if !chunk.is_empty() {
-                        let _ = out.write_all(&chunk);
-                        bytes_since_flush = bytes_since_flush.saturating_add(chunk.len());
-                        if bytes_since_flush >= FLUSH_THRESHOLD {
-                            let _ = out.flush();
-                            bytes_since_flush = 0;
-                        }
+                if let Some(len) = task.write_next(&mut state.synchronized, &mut out) {
+                    bytes_since_flush = bytes_since_flush.saturating_add(len);
+                    if bytes_since_flush >= FLUSH_THRESHOLD {
+                        let _ = out.flush();
+                        bytes_since_flush = 0;
                     }
                 } else {
                     let _ = out.flush();
                     bytes_since_flush = 0;
-                    if let Some(kind) = task.complete {
+                    if let Some(mut kind) = task.complete {
+                        if let WriterResultKind::TransmitDone { elapsed, .. } = &mut kind {
+                            *elapsed = task.started.elapsed();
+                        }
                         let _ = result_tx.send(WriterResult {
                             kind,
                             epoch: task.epoch,
                         });
+                        crate::wake::wake();
                     }
                     if task.clears_dirty {
                         state.dirty_area = None;
                     }
                     state.current_task = None;
+                    // The image rows were drawn over any text in the area (and the status bar).
+                    if let Some((area, _)) = state.placed {
+                        state.text.forget(area);
+                    }
+                    state.text_dirty = true;
+                    state.status_dirty |= state.status_overlay;
                 }
             }
         }
+
+        if let Some(dir) = &state.transfer_dir {
+            kgp::remove_transfer_files(dir);
+        }
     }
 
     fn apply_msg(msg: WriterRequest, state: &mut WriterState, is_tty: bool, out: &mut impl Write) {
@@ -227,21 +465,41 @@ impl TerminalWriter {
             }
             WriterRequest::Status {
                 text,
+                pending,
                 size,
                 indicator,
             } => {
-                state.last_status = Some((text, size, indicator));
+                state.last_status = Some((text, pending, size, indicator));
                 state.status_dirty = true;
             }
             WriterRequest::ClearAll { area, is_tmux } => {
                 // Preempt current image work.
                 state.current_task = None;
                 state.dirty_area = None;
+                state.placed = None;
+                state.status_dirty |= state.status_overlay;
+                state.overwrite_text(area);
                 if is_tty {
                     let _ = Self::clear_all(out, area, is_tmux);
                     let _ = out.flush();
                 }
             }
+            WriterRequest::Suspend { paused, resume } => {
+                state.current_task = None;
+                state.dirty_area = None;
+                if state.synchronized {
+                    let _ = out.write_all(END_SYNC);
+                    state.synchronized = false;
+                }
+                let _ = out.flush();
+                let _ = paused.send(());
+                let _ = resume.recv();
+                // The shell drew over the screen: write all text again.
+                state.text = TextBuffer::default();
+                state.placed = None;
+                state.drawn = None;
+                state.status_dirty = true;
+            }
             WriterRequest::CancelImage { area, epoch } => {
                 if epoch >= state.current_epoch {
                     state.current_epoch = epoch;
@@ -263,6 +521,7 @@ impl TerminalWriter {
             }
             WriterRequest::ImageTransmit {
                 encoded_chunks,
+                protocol,
                 area,
                 kgp_id,
                 old_area,
@@ -274,16 +533,103 @@ impl TerminalWriter {
                 }
                 state.current_epoch = epoch;
                 let cleanup_area = state.dirty_area;
+                state.overwrite_text(Some(area));
+                state.overwrite_text(old_area);
+                state.overwrite_text(cleanup_area);
+                state.drawn = protocol
+                    .draws_cells()
+                    .then(|| (protocol, Arc::clone(&encoded_chunks)));
+                state.placed = Some((area, kgp_id));
                 state.current_task = Some(Self::task_transmit(
                     encoded_chunks,
+                    protocol,
                     area,
                     kgp_id,
                     old_area,
                     cleanup_area,
                     epoch,
                     is_tmux,
+                    state.direct_placement,
+                    state.transfer_dir.as_deref(),
                 ));
             }
+            WriterRequest::AnimationFrame {
+                encoded_chunks,
+                protocol,
+                area,
+                epoch,
+            } => {
+                if epoch < state.current_epoch {
+                    return;
+                }
+                let (chunks, sync_tail) = if protocol.draws_cells() {
+                    state.drawn = Some((protocol, Arc::clone(&encoded_chunks)));
+                    let rows: VecDeque<_> = protocol.rows(&encoded_chunks, area, 0).into();
+                    let len = rows.len();
+                    (rows, len)
+                } else {
+                    let mut chunks: VecDeque<_> =
+                        kgp::transmission(&encoded_chunks, state.transfer_dir.as_deref()).into();
+                    // Replacing the image data drops a direct placement; place it again.
+                    let place = state.direct_placement_id().map(|id| place_direct(area, id));
+                    let sync_tail = usize::from(place.is_some());
+                    chunks.extend(place);
+                    (chunks, sync_tail)
+                };
+                state.current_task = Some(Task {
+                    chunks,
+                    started: Instant::now(),
+                    complete: Some(WriterResultKind::FrameDone),
+                    epoch,
+                    clears_dirty: false,
+                    sync_tail,
+                });
+            }
+            WriterRequest::AnimationLoad {
+                frames,
+                kgp_id,
+                epoch,
+                is_tmux,
+            } => {
+                if epoch < state.current_epoch {
+                    return;
+                }
+                let gap = |delay: Duration| u32::try_from(delay.as_millis()).unwrap_or(u32::MAX);
+                let mut chunks = VecDeque::new();
+                for (encoded, delay) in frames.iter().skip(1) {
+                    let Some(frame) = kgp::frame_chunks(encoded, gap(*delay)) else {
+                        continue;
+                    };
+                    chunks.extend(kgp::transmission(&frame, state.transfer_dir.as_deref()));
+                }
+                if let Some((_, delay)) = frames.first() {
+                    chunks.push_back(kgp::root_frame_gap(kgp_id, gap(*delay), is_tmux));
+                }
+                chunks.push_back(kgp::animate(kgp_id, true, None, is_tmux));
+                state.current_task = Some(Task {
+                    chunks,
+                    started: Instant::now(),
+                    complete: Some(WriterResultKind::FrameDone),
+                    epoch,
+                    clears_dirty: false,
+                    sync_tail: 0,
+                });
+            }
+            WriterRequest::AnimationControl {
+                kgp_id,
+                run,
+                frame,
+                is_tmux,
+            } => {
+                let command = kgp::animate(kgp_id, run, frame, is_tmux);
+                // Never split a chunked transmission: queue behind the running task.
+                if let Some(task) = &mut state.current_task {
+                    task.chunks.push_back(command);
+                } else if is_tty {
+                    let _ = out.write_all(&command);
+                    let _ = out.flush();
+                }
+            }
             WriterRequest::CopyToClipboard { data, is_tmux } => {
                 if is_tty {
                     let osc52 = build_osc52_clipboard(&data, is_tmux);
@@ -292,72 +638,286 @@ impl TerminalWriter {
                 }
             }
             WriterRequest::TileCursor {
-                grid,
+                rects,
                 cursor_idx,
                 image_area,
                 prev_cursor_idx,
                 cell_size,
+                labels,
             } => {
-                if is_tty {
-                    // Clear previous cursor if different
-                    if let Some(prev_idx) = prev_cursor_idx
-                        && prev_idx != cursor_idx
+                let (cell_w, cell_h) = cell_size;
+                if is_tty && cell_w > 0 && cell_h > 0 {
+                    let style = overlay_style(state.monochrome, Color::Cyan);
+                    Self::render_tile_labels(
+                        &mut state.text,
+                        &rects,
+                        image_area,
+                        &labels,
+                        cursor_idx,
+                        state.monochrome,
+                    );
+                    // Blank the previous cursor (it sits in the tile padding).
+                    if let Some(prev) = prev_cursor_idx
+                        .filter(|&prev| prev != cursor_idx)
+                        .and_then(|prev| tile_rect(&rects, prev, image_area))
                     {
-                        let _ = out.write_all(&Self::build_tile_cursor_escape(
-                            grid, prev_idx, image_area, cell_size, false, // clear
-                        ));
+                        for edge in box_edges(prev) {
+                            state.text.fill(edge, Style::default());
+                        }
+                    }
+                    if let Some(rect) = tile_rect(&rects, cursor_idx, image_area) {
+                        draw_box(&mut state.text, rect, style);
+                    }
+                    state.text_dirty = true;
+                }
+                state.status_dirty |= state.status_overlay;
+            }
+            WriterRequest::JumpLabels {
+                rects,
+                image_area,
+                labels,
+                prev,
+            } => {
+                if is_tty {
+                    // Black on yellow; reverse video without colors.
+                    let style = if state.monochrome {
+                        Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED)
+                    } else {
+                        Style::default()
+                            .fg(Color::Black)
+                            .bg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD)
+                    };
+                    Self::render_jump_labels(
+                        &mut state.text,
+                        &rects,
+                        image_area,
+                        &labels,
+                        &prev,
+                        style,
+                    );
+                    state.text_dirty = true;
+                }
+                state.status_dirty |= state.status_overlay;
+            }
+            WriterRequest::Selection { rect, prev_rect } => {
+                if is_tty {
+                    // Releasing the old border re-places the image rows under it.
+                    for edge in prev_rect.into_iter().flat_map(box_edges) {
+                        state.text.clear(edge);
+                    }
+                    if let Some(rect) = rect {
+                        draw_box(
+                            &mut state.text,
+                            rect,
+                            overlay_style(state.monochrome, Color::Yellow),
+                        );
+                    }
+                    state.text_dirty = true;
+                }
+                state.status_dirty |= state.status_overlay;
+            }
+            WriterRequest::Menu {
+                lines,
+                selected,
+                prev_len,
+                size,
+            } => {
+                if is_tty {
+                    state.text.resize(size);
+                    Self::render_menu(&mut state.text, &lines, selected, prev_len, size);
+                    state.text_dirty = true;
+                }
+            }
+            WriterRequest::Placeholder {
+                area,
+                cells,
+                old_area,
+                epoch,
+            } => {
+                if epoch < state.current_epoch {
+                    return;
+                }
+                if is_tty {
+                    // Erasing cells does not hide a direct placement.
+                    if let Some(kgp_id) = state.direct_placement_id() {
+                        let _ = out.write_all(&delete_by_id(kgp_id, false));
                     }
-                    // Draw new cursor
-                    let _ = out.write_all(&Self::build_tile_cursor_escape(
-                        grid, cursor_idx, image_area, cell_size, true, // draw
-                    ));
+                    let _ = Self::render_placeholder(out, area, &cells, old_area);
                     let _ = out.flush();
                 }
+                state.overwrite_text(Some(area));
+                state.overwrite_text(old_area);
+                state.text_dirty = true;
+                state.placed = None;
+                state.status_dirty |= state.status_overlay;
+                // Erased by the next transmit unless the image covers it.
+                state.dirty_area = Some(match state.dirty_area {
+                    Some(prev) => union_rect(prev, area),
+                    None => area,
+                });
             }
         }
     }
 
+    fn render_placeholder(
+        out: &mut impl Write,
+        area: Rect,
+        cells: &[([u8; 3], [u8; 3])],
+        old_area: Option<Rect>,
+    ) -> std::io::Result<()> {
+        if let Some(old) = old_area {
+            for row in erase_rows(old) {
+                out.write_all(&row)?;
+            }
+        }
+        let width = usize::from(area.width);
+        if width == 0 {
+            return Ok(());
+        }
+        for (y, row) in (area.y..).zip(cells.chunks(width)) {
+            write!(out, "\x1b[{};{}H", y + 1, area.x + 1)?;
+            for ([tr, tg, tb], [br, bg, bb]) in row {
+                write!(out, "\x1b[38;2;{tr};{tg};{tb};48;2;{br};{bg};{bb}m\u{2580}")?;
+            }
+        }
+        out.write_all(b"\x1b[0m")
+    }
+
+    fn render_menu(
+        text: &mut TextBuffer,
+        lines: &[String],
+        selected: Option<usize>,
+        prev_len: usize,
+        size: (u16, u16),
+    ) {
+        let (w, h) = size;
+        // Menu rows end right above the status row (0-based row `h - 1`).
+        let status_row = h.saturating_sub(1);
+        let top = |len: usize| status_row.saturating_sub(len as u16);
+        let (new_top, prev_top) = (top(lines.len()), top(prev_len));
+
+        // Release rows the menu no longer covers.
+        if new_top > prev_top {
+            text.clear(Rect::new(0, prev_top, w, new_top - prev_top));
+        }
+
+        for (i, line) in lines.iter().enumerate() {
+            let row = new_top + i as u16;
+            let style = if selected == Some(i) {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            text.fill(Rect::new(0, row, w, 1), Style::default());
+            text.put(0, row, &format!(" {line}"), usize::from(w), style);
+        }
+    }
+
+    fn render_jump_labels(
+        text: &mut TextBuffer,
+        rects: &[Rect],
+        image_area: Rect,
+        labels: &[(usize, String)],
+        prev: &[usize],
+        style: Style,
+    ) {
+        // Labels sit on the middle row of their tile; releasing the old ones re-places the image.
+        for rect in prev.iter().filter_map(|&i| tile_rect(rects, i, image_area)) {
+            text.clear(Rect::new(rect.x, rect.y + rect.height / 2, rect.width, 1));
+        }
+
+        for (i, label) in labels {
+            let Some(rect) = tile_rect(rects, *i, image_area) else {
+                continue;
+            };
+            let label = format!(" {label} ");
+            let clipped = clip_utf8(&label, usize::from(rect.width));
+            let x = rect.x + (rect.width - clipped.len() as u16) / 2;
+            let y = rect.y + rect.height / 2;
+            text.put(x, y, clipped, clipped.len(), style);
+        }
+    }
+
+    /// File names on the row above the bottom border of each tile, the cursor's highlighted.
+    fn render_tile_labels(
+        text: &mut TextBuffer,
+        rects: &[Rect],
+        image_area: Rect,
+        labels: &[String],
+        cursor_idx: usize,
+        monochrome: bool,
+    ) {
+        for (i, label) in labels.iter().enumerate() {
+            // Border, thumbnail, label and border rows.
+            let Some(rect) = tile_rect(rects, i, image_area).filter(|rect| rect.height >= 4) else {
+                continue;
+            };
+            let width = usize::from(rect.width.saturating_sub(2));
+            let label = shorten_middle(label, width);
+            let style = if i == cursor_idx {
+                overlay_style(monochrome, Color::Cyan).add_modifier(Modifier::BOLD)
+            } else {
+                overlay_style(monochrome, Color::Gray)
+            };
+            let line = format!("{label:^width$}");
+            text.put(rect.x + 1, rect.bottom() - 2, &line, width, style);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn task_transmit(
         encoded_chunks: Arc<Vec<Vec<u8>>>,
+        protocol: Protocol,
         area: Rect,
         kgp_id: u32,
         old_area: Option<Rect>,
         dirty_area: Option<Rect>,
         epoch: u64,
         is_tmux: bool,
+        direct_placement: bool,
+        transfer_dir: Option<&Path>,
     ) -> Task {
+        let started = Instant::now();
         let mut chunks = VecDeque::new();
 
-        // Step 1: Erase old area FIRST (yazi pattern: hide -> show)
-        // This ensures that even if cancelled mid-execution, the old image is already erased.
+        // Step 1 (KGP): Delete existing image data for this ID and transmit the new data.
+        // Deleting first keeps stale data from being displayed if the transmit is cancelled.
+        // This goes out before the synchronized update: a large image takes longer than the
+        // terminal holds an update back.
+        if !protocol.draws_cells() {
+            chunks.push_back(delete_by_id(kgp_id, is_tmux));
+            chunks.extend(kgp::transmission(&encoded_chunks, transfer_dir));
+        }
+        let transmit_len = chunks.len();
+
+        // Step 2: Erase the old area and the cells left over from cancelled output
+        // (yazi pattern: hide -> show).
         if let Some(old) = old_area {
-            for row in erase_rows(old) {
-                chunks.push_back(row);
-            }
+            chunks.extend(erase_rows(old));
         }
         for cleanup in Self::cleanup_rects(area, dirty_area) {
-            for row in erase_rows(cleanup) {
-                chunks.push_back(row);
-            }
+            chunks.extend(erase_rows(cleanup));
         }
 
-        // Step 2: Delete existing image data for this ID
-        // This prevents stale data from being displayed if transmit is cancelled
-        chunks.push_back(delete_by_id(kgp_id, is_tmux));
-
-        // Step 3: Transmit new image data
-        for enc in encoded_chunks.iter() {
-            chunks.push_back(enc.clone());
-        }
-
-        // Step 4: Place new image
-        for row in place_rows(area, kgp_id) {
-            chunks.push_back(row);
+        // Step 3: Place the new image. Half-blocks and iTerm2: the cells are the image, so just
+        // draw them.
+        if protocol.draws_cells() {
+            chunks.extend(protocol.rows(&encoded_chunks, area, kgp_id));
+        } else if direct_placement {
+            chunks.push_back(place_direct(area, kgp_id));
+        } else {
+            chunks.extend(place_rows(area, kgp_id));
         }
 
         Task {
+            sync_tail: chunks.len() - transmit_len,
             chunks,
-            complete: Some(WriterResultKind::TransmitDone { kgp_id }),
+            started,
+            complete: Some(WriterResultKind::TransmitDone {
+                kgp_id,
+                elapsed: Duration::ZERO,
+            }),
             epoch,
             clears_dirty: dirty_area.is_some(),
         }
@@ -381,15 +941,20 @@ impl TerminalWriter {
         Ok(())
     }
 
+    /// Draw the status bar on the bottom row. With `overlay`, only the bar's own cells are
+    /// covered and the image shows through after it.
     fn render_status(
-        out: &mut impl Write,
+        text: &mut TextBuffer,
         status_text: &str,
+        pending: &str,
         size: (u16, u16),
         indicator: StatusIndicator,
-    ) -> std::io::Result<()> {
+        monochrome: bool,
+        overlay: bool,
+    ) {
         let (w, h) = size;
         if w == 0 || h == 0 {
-            return Ok(());
+            return;
         }
 
         // Nerdfont icons and Powerline separator
@@ -400,139 +965,118 @@ impl TerminalWriter {
         const SEP: &str = "\u{e0b0}"; //  (Powerline separator)
 
         // ANSI 16-color (uses terminal theme colors)
-        // Foreground: 30=Black, 37=White, 90-97=Bright
-        // Background: 40=Black, 47=White, 100-107=Bright
-        const FG_DARK: u8 = 30; // Black
-        const FG_LIGHT: u8 = 97; // Bright White
-        const BG_MAIN: u8 = 40; // Black
-        const BG_READY: u8 = 42; // Green
-        const BG_BUSY: u8 = 43; // Yellow
-        const BG_FIT: u8 = 45; // Magenta
-        const BG_TILE: u8 = 46; // Cyan
-
-        let row_1based = h;
-        // Reserve 4 columns for icon segment " X  " (icon + spaces + separator)
-        let available = w.saturating_sub(4);
-        let clipped = clip_utf8(status_text, available as usize);
-
-        let (icon, fg_indicator, bg_indicator) = match indicator {
-            StatusIndicator::Ready => (ICON_READY, BG_READY - 10, BG_READY), // fg=32 (Green)
-            StatusIndicator::Busy => (ICON_BUSY, BG_BUSY - 10, BG_BUSY),     // fg=33 (Yellow)
-            StatusIndicator::Fit => (ICON_FIT, BG_FIT - 10, BG_FIT),         // fg=35 (Magenta)
-            StatusIndicator::Tile => (ICON_TILE, BG_TILE - 10, BG_TILE),     // fg=36 (Cyan)
+        const FG_DARK: Color = Color::Black;
+        const FG_LIGHT: Color = Color::White; // Bright White
+        const BG_MAIN: Color = Color::Black;
+
+        let row = h - 1;
+        let width = usize::from(w);
+        let (icon, color) = match indicator {
+            StatusIndicator::Ready => (ICON_READY, Color::Green),
+            StatusIndicator::Busy => (ICON_BUSY, Color::Yellow),
+            StatusIndicator::Fit => (ICON_FIT, Color::Magenta),
+            StatusIndicator::Tile => (ICON_TILE, Color::Cyan),
         };
 
-        // Clear line with main background
-        write!(out, "\x1b[{row_1based};1H\x1b[{BG_MAIN}m\x1b[{w}X")?;
-
-        // Left segment: indicator icon with colored background
-        write!(
-            out,
-            "\x1b[{row_1based};1H\x1b[{FG_DARK};{bg_indicator}m {icon} "
-        )?;
-
-        // Powerline separator: indicator color -> main background
-        write!(out, "\x1b[{fg_indicator};{BG_MAIN}m{SEP}")?;
-
-        // Main content with light text on dark background
-        write!(out, "\x1b[{FG_LIGHT};{BG_MAIN}m {clipped}\x1b[0m")?;
-
-        Ok(())
-    }
-
-    /// Build ANSI escape sequence to draw or clear tile cursor border.
-    fn build_tile_cursor_escape(
-        grid: (usize, usize),
-        cursor_idx: usize,
-        image_area: Rect,
-        cell_size: (u16, u16),
-        draw: bool,
-    ) -> Vec<u8> {
-        use std::fmt::Write;
-
-        let (cols, rows) = grid;
-        if cols == 0 || rows == 0 || cursor_idx >= cols * rows {
-            return Vec::new();
-        }
-
-        let (cell_w, cell_h) = cell_size;
-        if cell_w == 0 || cell_h == 0 {
-            return Vec::new();
-        }
+        // The bar is redrawn in full; the text buffer writes only what changed.
+        text.clear(Rect::new(0, row, w, 1));
 
-        // Use cell-aligned tile boundaries (matching worker.rs)
-        // This ensures cursor position matches the actual tile positions in the image
-        let canvas_w_cells = u32::from(image_area.width);
-        let canvas_h_cells = u32::from(image_area.height);
-
-        let col = cursor_idx % cols;
-        let row = cursor_idx / cols;
-
-        // Calculate tile boundaries in cells (same formula as worker.rs)
-        let tile_x_cells = (col as u32 * canvas_w_cells) / cols as u32;
-        let tile_y_cells = (row as u32 * canvas_h_cells) / rows as u32;
-        let next_tile_x_cells = ((col + 1) as u32 * canvas_w_cells) / cols as u32;
-        let next_tile_y_cells = ((row + 1) as u32 * canvas_h_cells) / rows as u32;
-
-        let tile_x = image_area.x + tile_x_cells as u16;
-        let tile_y = image_area.y + tile_y_cells as u16;
-        let tile_x_end = image_area.x + next_tile_x_cells as u16;
-        let tile_y_end = image_area.y + next_tile_y_cells as u16;
-
-        // Unicode box drawing characters (rounded corners)
-        const TOP_LEFT: char = '╭';
-        const TOP_RIGHT: char = '╮';
-        const BOTTOM_LEFT: char = '╰';
-        const BOTTOM_RIGHT: char = '╯';
-        const HORIZONTAL: char = '─';
-        const VERTICAL: char = '│';
-
-        // Pre-allocate buffer (estimate: ~20 bytes per cell)
-        let estimated_size = ((tile_x_end - tile_x) + (tile_y_end - tile_y)) as usize * 20;
-        let mut s = String::with_capacity(estimated_size);
-
-        if draw {
-            s.push_str("\x1b[36m"); // Cyan color
-        } else {
-            s.push_str("\x1b[0m"); // Reset color
+        if monochrome {
+            // Plain text: no background, and a bar instead of the Powerline separator (which
+            // only reads as a separator when colored).
+            if !overlay {
+                text.fill(Rect::new(0, row, w, 1), Style::default());
+            }
+            let line = format!(" {icon} \u{2502} {status_text}");
+            let x = text.put(0, row, &line, width, Style::default());
+            Self::render_pending(text, pending, x, size, Style::default());
+            return;
         }
 
-        let char_h = if draw { HORIZONTAL } else { ' ' };
-        let char_v = if draw { VERTICAL } else { ' ' };
-        let char_tl = if draw { TOP_LEFT } else { ' ' };
-        let char_tr = if draw { TOP_RIGHT } else { ' ' };
-        let char_bl = if draw { BOTTOM_LEFT } else { ' ' };
-        let char_br = if draw { BOTTOM_RIGHT } else { ' ' };
-
-        // Top edge: move to position, draw corner + horizontal line + corner
-        let top_row = tile_y + 1; // 1-based
-        let left_col = tile_x + 1; // 1-based
-        let right_col = tile_x_end; // 1-based
-
-        // Draw top edge
-        let _ = write!(s, "\x1b[{};{}H{}", top_row, left_col, char_tl);
-        for c in (left_col + 1)..right_col {
-            let _ = write!(s, "\x1b[{};{}H{}", top_row, c, char_h);
+        // Clear line with main background (overlay: only the text is covered).
+        if !overlay {
+            text.fill(Rect::new(0, row, w, 1), Style::default().bg(BG_MAIN));
         }
-        let _ = write!(s, "\x1b[{};{}H{}", top_row, right_col, char_tr);
 
-        // Bottom edge
-        let bottom_row = tile_y_end;
-        let _ = write!(s, "\x1b[{};{}H{}", bottom_row, left_col, char_bl);
-        for c in (left_col + 1)..right_col {
-            let _ = write!(s, "\x1b[{};{}H{}", bottom_row, c, char_h);
+        // Left segment: indicator icon with colored background
+        let x = text.put(
+            0,
+            row,
+            &format!(" {icon} "),
+            width,
+            Style::default().fg(FG_DARK).bg(color),
+        );
+        // Powerline separator: indicator color -> main background
+        let x = text.put(x, row, SEP, width, Style::default().fg(color).bg(BG_MAIN));
+        // Main content with light text on dark background
+        let x = text.put(
+            x,
+            row,
+            &format!(" {status_text}"),
+            width,
+            Style::default().fg(FG_LIGHT).bg(BG_MAIN),
+        );
+
+        // Overlay: close the bar with a separator so the image shows through after it.
+        if overlay && x < w {
+            let end = text.put(x, row, " ", width, Style::default());
+            text.put(end, row, SEP, width, Style::default().fg(BG_MAIN));
         }
-        let _ = write!(s, "\x1b[{};{}H{}", bottom_row, right_col, char_br);
+        Self::render_pending(
+            text,
+            pending,
+            x,
+            size,
+            Style::default().fg(color).bg(BG_MAIN),
+        );
+    }
 
-        // Left and right edges (vertical lines)
-        for r in (top_row + 1)..bottom_row {
-            let _ = write!(s, "\x1b[{};{}H{}", r, left_col, char_v);
-            let _ = write!(s, "\x1b[{};{}H{}", r, right_col, char_v);
+    /// Draw the pending keys right-aligned on the status row, unless the status text reaches
+    /// that far (it ends at column `after`).
+    fn render_pending(
+        text: &mut TextBuffer,
+        pending: &str,
+        after: u16,
+        size: (u16, u16),
+        style: Style,
+    ) {
+        let len = pending.chars().count() as u16;
+        let (w, h) = size;
+        if len == 0 || after + len + 2 > w {
+            return;
         }
+        text.put(w - len - 1, h - 1, pending, usize::from(len), style);
+    }
+}
 
-        s.push_str("\x1b[0m"); // Reset attributes
+/// Edges of a border around `rect`: top, bottom, left and right (may overlap when thin).
+fn box_edges(rect: Rect) -> Vec<Rect> {
+    if rect.width == 0 || rect.height == 0 {
+        return Vec::new();
+    }
+    vec![
+        Rect::new(rect.x, rect.y, rect.width, 1),
+        Rect::new(rect.x, rect.bottom() - 1, rect.width, 1),
+        Rect::new(rect.x, rect.y, 1, rect.height),
+        Rect::new(rect.right() - 1, rect.y, 1, rect.height),
+    ]
+}
 
-        s.into_bytes()
+/// Draw a rounded border around `rect`.
+fn draw_box(text: &mut TextBuffer, rect: Rect, style: Style) {
+    if rect.width == 0 || rect.height == 0 {
+        return;
+    }
+    let inner = usize::from(rect.width.saturating_sub(2));
+    let (top, bottom) = (rect.y, rect.bottom() - 1);
+    let (left, right) = (rect.x, rect.right() - 1);
+    let width = usize::from(rect.width);
+    let horizontal = "─".repeat(inner);
+    text.put(left, top, &format!("╭{horizontal}╮"), width, style);
+    text.put(left, bottom, &format!("╰{horizontal}╯"), width, style);
+    for y in top + 1..bottom {
+        text.put(left, y, "│", 1, style);
+        text.put(right, y, "│", 1, style);
     }
 }
 
@@ -545,6 +1089,43 @@ impl Drop for TerminalWriter {
     }
 }
 
+/// Screen rectangle of tile `idx`, from the page's tile rectangles over `image_area`.
+fn tile_rect(rects: &[Rect], idx: usize, image_area: Rect) -> Option<Rect> {
+    let rect = rects.get(idx)?;
+    Some(Rect::new(
+        image_area.x + rect.x,
+        image_area.y + rect.y,
+        rect.width,
+        rect.height,
+    ))
+}
+
+/// Overlay style: `color`, unless overlays are drawn without colors.
+fn overlay_style(monochrome: bool, color: Color) -> Style {
+    if monochrome {
+        Style::default()
+    } else {
+        Style::default().fg(color)
+    }
+}
+
+/// `name` in at most `width` characters: long names keep their start and end (the extension
+/// and any numbering) around an ellipsis.
+fn shorten_middle(name: &str, width: usize) -> String {
+    let len = name.chars().count();
+    if len <= width {
+        return name.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let tail = (width - 1) / 2;
+    let head = width - 1 - tail;
+    let start: String = name.chars().take(head).collect();
+    let end: String = name.chars().skip(len - tail).collect();
+    format!("{start}…{end}")
+}
+
 fn clip_utf8(s: &str, max_bytes: usize) -> &str {
     if s.len() <= max_bytes {
         return s;
@@ -752,6 +1333,14 @@ mod tests {
         assert_eq!(diff.len(), 4);
     }
 
+    #[test]
+    fn test_shorten_middle() {
+        assert_eq!(shorten_middle("a.png", 8), "a.png");
+        assert_eq!(shorten_middle("IMG_20240101_0042.jpg", 11), "IMG_2…2.jpg");
+        assert_eq!(shorten_middle("long.png", 1), "…");
+        assert_eq!(shorten_middle("long.png", 0), "");
+    }
+
     #[test]
     fn test_union_rect_disjoint() {
         let a = Rect::new(0, 0, 5, 5);
@@ -818,6 +1407,195 @@ mod tests {
         assert_eq!(clipped, "日本");
     }
 
+    /// Flush `text` and return what was written.
+    fn flushed(text: &mut TextBuffer) -> String {
+        let mut out = Vec::new();
+        text.flush(&mut out, |_| None).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn status(text: &mut TextBuffer, status: &str, monochrome: bool, overlay: bool) -> String {
+        text.resize((40, 10));
+        TerminalWriter::render_status(
+            text,
+            status,
+            "",
+            (40, 10),
+            StatusIndicator::Ready,
+            monochrome,
+            overlay,
+        );
+        flushed(text)
+    }
+
+    #[test]
+    fn test_draw_box_corners() {
+        let mut text = TextBuffer::default();
+        text.resize((10, 10));
+        draw_box(
+            &mut text,
+            Rect::new(2, 3, 4, 3),
+            Style::default().fg(Color::Yellow),
+        );
+        let s = flushed(&mut text);
+        assert!(s.starts_with("\x1b[4;3H\x1b[0;33m╭"));
+        assert!(s.contains("\x1b[4;6H╮"));
+        assert!(s.contains("\x1b[6;3H╰"));
+        assert!(s.contains("\x1b[6;6H╯"));
+        assert!(s.contains("\x1b[5;3H│"));
+    }
+
+    #[test]
+    fn test_box_edges_empty_rect() {
+        assert!(box_edges(Rect::new(2, 3, 0, 3)).is_empty());
+        assert_eq!(box_edges(Rect::new(2, 3, 4, 3))[1], Rect::new(2, 5, 4, 1));
+    }
+
+    #[test]
+    fn test_render_status_monochrome_has_no_colors() {
+        assert!(status(&mut TextBuffer::default(), "a.png", false, false).contains(";40m"));
+        let plain = status(&mut TextBuffer::default(), "a.png", true, false);
+        assert!(plain.starts_with("\x1b[10;1H\x1b[0m "));
+        assert!(plain.contains(" a.png"));
+        assert!(!plain.contains(";40m"));
+    }
+
+    #[test]
+    fn test_render_status_writes_only_changes() {
+        let mut text = TextBuffer::default();
+        status(&mut text, "a.png [1/9]", false, false);
+        assert_eq!(status(&mut text, "a.png [1/9]", false, false), "");
+        // Only the changed digit is written.
+        let diff = status(&mut text, "a.png [2/9]", false, false);
+        assert_eq!(diff, "\x1b[10;13H\x1b[0;97;40m2\x1b[0m");
+    }
+
+    #[test]
+    fn test_status_overlay_keeps_image_row() {
+        let mut text = TextBuffer::default();
+        let s = status(&mut text, "a.png", false, true);
+        // No full-row fill; the bar ends with a separator on the default background.
+        assert!(!s.contains("\x1b[0;40m "));
+        assert!(s.ends_with("\x1b[0;30m\u{e0b0}\x1b[0m"));
+
+        // A shorter status releases the cells after it.
+        let mut out = Vec::new();
+        text.resize((40, 10));
+        TerminalWriter::render_status(
+            &mut text,
+            "a",
+            "",
+            (40, 10),
+            StatusIndicator::Ready,
+            false,
+            true,
+        );
+        text.flush(&mut out, |row| Some(format!("<img {row}>").into_bytes()))
+            .unwrap();
+        let s = String::from_utf8(out).unwrap();
+        assert!(s.starts_with("\x1b[10;"));
+        assert!(s.contains("X<img 9>"));
+
+        let state = WriterState {
+            should_quit: false,
+            last_status: None,
+            status_dirty: false,
+            text: TextBuffer::default(),
+            text_dirty: false,
+            current_task: None,
+            current_epoch: 0,
+            dirty_area: None,
+            drawn: None,
+            monochrome: false,
+            status_overlay: true,
+            placed: Some((Rect::new(2, 3, 4, 7), 1)),
+            direct_placement: false,
+            transfer_dir: None,
+            synchronized: false,
+        };
+        let row = state.image_row(9).unwrap();
+        assert!(String::from_utf8(row).unwrap().contains("\x1b[10;3H"));
+        assert!(state.image_row(2).is_none());
+        assert!(state.image_row(10).is_none());
+    }
+
+    #[test]
+    fn test_render_status_pending_keys_right_aligned() {
+        let mut text = TextBuffer::default();
+        text.resize((40, 10));
+        TerminalWriter::render_status(
+            &mut text,
+            "a.png",
+            "\"3",
+            (40, 10),
+            StatusIndicator::Ready,
+            true,
+            false,
+        );
+        // Right-aligned, one column from the edge.
+        assert!(flushed(&mut text).ends_with(" \"3 \x1b[0m"));
+    }
+
+    #[test]
+    fn test_transmit_precedes_synchronized_update() {
+        let (area, old) = (Rect::new(0, 0, 4, 2), Rect::new(0, 0, 6, 3));
+        let mut task = TerminalWriter::task_transmit(
+            Arc::new(vec![b"<data>".to_vec()]),
+            Protocol::Kitty,
+            area,
+            7,
+            Some(old),
+            None,
+            0,
+            false,
+            true,
+            None,
+        );
+        let (mut synchronized, mut out) = (false, Vec::new());
+        while task.write_next(&mut synchronized, &mut out).is_some() {}
+        assert!(synchronized);
+
+        let find = |needle: &[u8]| out.windows(needle.len()).position(|w| w == needle).unwrap();
+        let begin = find(BEGIN_SYNC);
+        assert!(find(&delete_by_id(7, false)) < find(b"<data>"));
+        assert!(find(b"<data>") < begin);
+        assert!(begin < find(&erase_rows(old)[0]));
+        assert!(find(&erase_rows(old)[0]) < find(&place_direct(area, 7)));
+    }
+
+    #[test]
+    fn test_tile_rect_offsets_into_image_area() {
+        let rects = crate::layout::grid_rects((3, 2), (10, 7), 6);
+        let area = Rect::new(2, 1, 10, 7);
+        assert_eq!(tile_rect(&rects, 0, area), Some(Rect::new(2, 1, 3, 3)));
+        assert_eq!(tile_rect(&rects, 5, area), Some(Rect::new(8, 4, 4, 4)));
+        assert_eq!(tile_rect(&rects, 6, area), None);
+    }
+
+    #[test]
+    fn test_render_jump_labels_centered_in_tile() {
+        let mut text = TextBuffer::default();
+        text.resize((10, 5));
+        let style = Style::default().fg(Color::Black).bg(Color::Yellow);
+        let rects = crate::layout::grid_rects((2, 1), (10, 4), 2);
+        let area = Rect::new(0, 0, 10, 4);
+        TerminalWriter::render_jump_labels(&mut text, &rects, area, &[(0, "a".into())], &[], style);
+        flushed(&mut text);
+
+        TerminalWriter::render_jump_labels(
+            &mut text,
+            &rects,
+            area,
+            &[(1, "s".into())],
+            &[0],
+            style,
+        );
+        let s = flushed(&mut text);
+        // The old label of tile 0 is erased, the new label sits in the middle of tile 1.
+        assert!(s.starts_with("\x1b[3;2H\x1b[0m\x1b[3X"));
+        assert!(s.contains("\x1b[3;7H\x1b[0;30;43m s \x1b[0m"));
+    }
+
     #[test]
     fn test_build_osc52_clipboard() {
         let data = b"test";
@@ -0,0 +1,300 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Session recording and replay (`--record` / `--replay`).
+//!
+//! A session file is JSON Lines, one key press per line:
+//!
+//! ```text
+//! {"t_ms":1532,"key":"j"}
+//! {"t_ms":2210,"key":"n","ctrl":true}
+//! ```
+//!
+//! `t_ms` is measured from the start of the event loop. Replay feeds each key back once its
+//! timestamp is reached; real input keeps working during replay (e.g. `q` to quit early).
+//! Terminal input comes from the input thread of [`Wakeups`], which the session also sleeps on.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+use crate::wake::Wakeups;
+
+/// One recorded key press.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+struct RecordedKey {
+    t_ms: u64,
+    key: String,
+    #[serde(default, skip_serializing_if = "is_false")]
+    ctrl: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    alt: bool,
+}
+
+fn is_false(b: &bool) -> bool {
+    !b
+}
+
+impl RecordedKey {
+    fn from_event(key: &KeyEvent, elapsed: Duration) -> Option<Self> {
+        Some(Self {
+            t_ms: elapsed.as_millis() as u64,
+            key: key_name(key.code)?,
+            ctrl: key.modifiers.contains(KeyModifiers::CONTROL),
+            alt: key.modifiers.contains(KeyModifiers::ALT),
+        })
+    }
+
+    fn to_event(&self) -> Option<KeyEvent> {
+        let mut modifiers = KeyModifiers::NONE;
+        if self.ctrl {
+            modifiers |= KeyModifiers::CONTROL;
+        }
+        if self.alt {
+            modifiers |= KeyModifiers::ALT;
+        }
+        Some(KeyEvent::new(parse_key_name(&self.key)?, modifiers))
+    }
+}
+
+/// Source of input events for the event loop: terminal input plus an optional replay,
+/// with optional recording of every key press.
+pub struct Session {
+    start: Instant,
+    recorder: Option<BufWriter<File>>,
+    replay: VecDeque<RecordedKey>,
+    /// Terminal input and background wakeups (None until `start_input`).
+    wakeups: Option<Wakeups>,
+}
+
+impl Session {
+    pub fn new(record: Option<&Path>, replay: Option<&Path>) -> Result<Self> {
+        let recorder = record
+            .map(|path| {
+                File::create(path)
+                    .map(BufWriter::new)
+                    .with_context(|| format!("Failed to create session file: {:?}", path))
+            })
+            .transpose()?;
+        let replay = match replay {
+            Some(path) => load_replay(path)?,
+            None => VecDeque::new(),
+        };
+        Ok(Self {
+            start: Instant::now(),
+            recorder,
+            replay,
+            wakeups: None,
+        })
+    }
+
+    /// Start reading terminal input (see [`Wakeups::start`]).
+    pub fn start_input(&mut self) {
+        self.wakeups = Some(Wakeups::start());
+    }
+
+    /// Sleep until an event or a background result, `deadline` or the next replayed key.
+    pub fn wait(&mut self, deadline: Option<Instant>) {
+        let replay = self
+            .replay
+            .front()
+            .map(|k| self.start + Duration::from_millis(k.t_ms));
+        let deadline = deadline.into_iter().chain(replay).min();
+        match self.wakeups.as_mut() {
+            Some(wakeups) => wakeups.wait(deadline),
+            None => {
+                if let Some(deadline) = deadline {
+                    std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+                }
+            }
+        }
+    }
+
+    /// Next pending event without blocking: a due replayed key first, then terminal input.
+    pub fn next_event(&mut self) -> std::io::Result<Option<Event>> {
+        if let Some(key) = self.next_replayed(self.start.elapsed()) {
+            return Ok(Some(Event::Key(key)));
+        }
+        let Some(ev) = self
+            .wakeups
+            .as_mut()
+            .map_or(Ok(None), Wakeups::next_input)?
+        else {
+            return Ok(None);
+        };
+        if let Event::Key(key) = &ev {
+            self.record(key);
+        }
+        Ok(Some(ev))
+    }
+
+    /// Take the terminal's queued repeats of `key` (see [`Wakeups::take_repeats`]); returns
+    /// how many there were.
+    pub fn take_repeats(&mut self, key: KeyEvent) -> u32 {
+        let Some(wakeups) = self.wakeups.as_mut() else {
+            return 0;
+        };
+        let repeats = wakeups.take_repeats(key);
+        for key in &repeats {
+            self.record(key);
+        }
+        repeats.len() as u32
+    }
+
+    fn next_replayed(&mut self, elapsed: Duration) -> Option<KeyEvent> {
+        while self
+            .replay
+            .front()
+            .is_some_and(|k| u128::from(k.t_ms) <= elapsed.as_millis())
+        {
+            let recorded = self.replay.pop_front()?;
+            if let Some(key) = recorded.to_event() {
+                return Some(key);
+            }
+        }
+        None
+    }
+
+    fn record(&mut self, key: &KeyEvent) {
+        // Releases/repeats are ignored by the event loop, so only presses are worth replaying.
+        if key.kind != event::KeyEventKind::Press {
+            return;
+        }
+        let Some(writer) = self.recorder.as_mut() else {
+            return;
+        };
+        let Some(recorded) = RecordedKey::from_event(key, self.start.elapsed()) else {
+            return;
+        };
+        if let Ok(line) = serde_json::to_string(&recorded) {
+            // Flush per key so the file is complete even if stiv is killed.
+            let _ = writeln!(writer, "{line}").and_then(|_| writer.flush());
+        }
+    }
+}
+
+fn load_replay(path: &Path) -> Result<VecDeque<RecordedKey>> {
+    let file =
+        File::open(path).with_context(|| format!("Failed to open session file: {:?}", path))?;
+    let mut keys = VecDeque::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let key: RecordedKey = serde_json::from_str(&line)
+            .with_context(|| format!("Invalid session entry at {:?}:{}", path, i + 1))?;
+        keys.push_back(key);
+    }
+    Ok(keys)
+}
+
+/// Name used in session files (single characters as themselves, special keys by name).
+fn key_name(code: KeyCode) -> Option<String> {
+    let name = match code {
+        KeyCode::Char(c) => return Some(c.to_string()),
+        KeyCode::Enter => "Enter",
+        KeyCode::Esc => "Esc",
+        KeyCode::Backspace => "Backspace",
+        KeyCode::Tab => "Tab",
+        KeyCode::BackTab => "BackTab",
+        KeyCode::Up => "Up",
+        KeyCode::Down => "Down",
+        KeyCode::Left => "Left",
+        KeyCode::Right => "Right",
+        KeyCode::Home => "Home",
+        KeyCode::End => "End",
+        KeyCode::PageUp => "PageUp",
+        KeyCode::PageDown => "PageDown",
+        KeyCode::Delete => "Delete",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+fn parse_key_name(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    let code = match name {
+        "Enter" => KeyCode::Enter,
+        "Esc" => KeyCode::Esc,
+        "Backspace" => KeyCode::Backspace,
+        "Tab" => KeyCode::Tab,
+        "BackTab" => KeyCode::BackTab,
+        "Up" => KeyCode::Up,
+        "Down" => KeyCode::Down,
+        "Left" => KeyCode::Left,
+        "Right" => KeyCode::Right,
+        "Home" => KeyCode::Home,
+        "End" => KeyCode::End,
+        "PageUp" => KeyCode::PageUp,
+        "PageDown" => KeyCode::PageDown,
+        "Delete" => KeyCode::Delete,
+        _ => return None,
+    };
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_name_round_trip() {
+        for code in [
+            KeyCode::Char('j'),
+            KeyCode::Char(':'),
+            KeyCode::Char(' '),
+            KeyCode::Enter,
+            KeyCode::Esc,
+            KeyCode::BackTab,
+        ] {
+            assert_eq!(parse_key_name(&key_name(code).unwrap()), Some(code));
+        }
+        assert_eq!(parse_key_name("Bogus"), None);
+    }
+
+    #[test]
+    fn test_record_then_replay() {
+        let path = std::path::PathBuf::from("/tmp/stiv_test_session.jsonl");
+        let mut session = Session::new(Some(&path), None).unwrap();
+        session.record(&KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE));
+        session.record(&KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL));
+        drop(session);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(content.lines().nth(1).unwrap().contains("\"ctrl\":true"));
+
+        let mut replay = Session::new(None, Some(&path)).unwrap();
+        let first = replay.next_replayed(Duration::from_secs(60)).unwrap();
+        assert_eq!(first.code, KeyCode::Char('j'));
+        let second = replay.next_replayed(Duration::from_secs(60)).unwrap();
+        assert_eq!(second.code, KeyCode::Char('n'));
+        assert!(second.modifiers.contains(KeyModifiers::CONTROL));
+        assert!(replay.next_replayed(Duration::from_secs(60)).is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_replay_waits_for_timestamp() {
+        let mut session = Session::new(None, None).unwrap();
+        session.replay.push_back(RecordedKey {
+            t_ms: 500,
+            key: "j".to_string(),
+            ctrl: false,
+            alt: false,
+        });
+        assert!(session.next_replayed(Duration::from_millis(100)).is_none());
+        assert!(session.next_replayed(Duration::from_millis(500)).is_some());
+    }
+}
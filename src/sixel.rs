@@ -0,0 +1,66 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Sixel image output, for terminals that answer the DA1 query with sixel support (foot,
+//! xterm, mlterm, Windows Terminal, ...).
+//!
+//! Like iTerm2 inline images, sixels become part of the cells they cover. The image is cut
+//! into one strip per cell row (each with its own 256-color palette), so single rows can be
+//! redrawn with `protocol::strip_rows`. Strips are drawn with transparent background (`P2=1`):
+//! a sixel band is 6 pixels high, and the unused pixels of the last band must not paint over
+//! the row below. Alpha is dropped.
+
+use icy_sixel::{DiffusionMethod, MethodForLargest, MethodForRep, PixelFormat, Quality};
+use image::{DynamicImage, GenericImageView};
+
+/// Encode an image as one sixel sequence per cell row of `cell_size` pixels.
+pub fn encode(img: &DynamicImage, cell_size: (u16, u16)) -> Vec<Vec<u8>> {
+    let cell_h = u32::from(cell_size.1.max(1));
+    let (width, height) = img.dimensions();
+
+    (0..height.div_ceil(cell_h))
+        .map(|row| {
+            let y = row * cell_h;
+            let strip = img.view(0, y, width, cell_h.min(height - y)).to_image();
+            sequence(&DynamicImage::ImageRgba8(strip).to_rgb8())
+        })
+        .collect()
+}
+
+fn sequence(strip: &image::RgbImage) -> Vec<u8> {
+    let Ok(data) = icy_sixel::sixel_string(
+        strip.as_raw(),
+        strip.width() as i32,
+        strip.height() as i32,
+        PixelFormat::RGB888,
+        DiffusionMethod::Atkinson,
+        MethodForLargest::Auto,
+        MethodForRep::Auto,
+        Quality::AUTO,
+    ) else {
+        return Vec::new();
+    };
+    match data.strip_prefix("\x1bPq") {
+        Some(body) => format!("\x1bP0;1q{body}").into_bytes(),
+        None => data.into_bytes(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_one_transparent_strip_per_row() {
+        let img = DynamicImage::new_rgb8(10, 20);
+        let encoded = encode(&img, (4, 8));
+        assert_eq!(encoded.len(), 3);
+        for strip in &encoded {
+            assert!(strip.starts_with(b"\x1bP0;1q"));
+            assert!(strip.ends_with(b"\x1b\\"));
+        }
+        // Raster attributes carry the strip size; the last strip is 4 pixels high.
+        assert!(String::from_utf8_lossy(&encoded[0]).contains("\"1;1;10;8"));
+        assert!(String::from_utf8_lossy(&encoded[2]).contains("\"1;1;10;4"));
+    }
+}
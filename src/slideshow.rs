@@ -0,0 +1,219 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Slideshow (`S`, `:slideshow`): advance to the next image every `slideshow_interval_ms`.
+//!
+//! The images are visited once per round, starting at the current one, in list order or
+//! shuffled. At the end of a round the slideshow starts over (reshuffled) or stops
+//! (`slideshow_stop_at_end`). With `slideshow_skip_errors`, an image that fails to decode is
+//! skipped right away instead of being waited on for the whole interval.
+
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use crate::config::Config;
+
+/// Shortest interval accepted by `:slideshow <seconds>`.
+const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Slideshow settings, toggled at runtime with `:slideshow <option>`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlideshowOptions {
+    pub interval: Duration,
+    pub shuffle: bool,
+    pub stop_at_end: bool,
+    pub skip_errors: bool,
+}
+
+impl SlideshowOptions {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            interval: Duration::from_millis(config.slideshow_interval_ms),
+            shuffle: config.slideshow_shuffle,
+            stop_at_end: config.slideshow_stop_at_end,
+            skip_errors: config.slideshow_skip_errors,
+        }
+    }
+
+    /// Status text (e.g. `slideshow 5s shuffle`).
+    pub fn label(&self) -> String {
+        let mut label = format!("slideshow {}s", self.interval.as_secs_f64());
+        for (on, name) in [
+            (self.shuffle, " shuffle"),
+            (self.stop_at_end, " once"),
+            (self.skip_errors, " skip errors"),
+        ] {
+            if on {
+                label.push_str(name);
+            }
+        }
+        label
+    }
+}
+
+/// Argument of `:slideshow`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Setting {
+    /// No argument: start or stop.
+    Toggle,
+    Shuffle,
+    /// Loop or stop at the end.
+    Loop,
+    SkipErrors,
+    /// Time per image.
+    Interval(Duration),
+}
+
+/// Parse the `:slideshow` argument: empty, `shuffle`, `loop`, `skip` or seconds (e.g. `2.5`).
+pub fn parse_setting(arg: &str) -> Result<Setting> {
+    match arg {
+        "" => Ok(Setting::Toggle),
+        "shuffle" => Ok(Setting::Shuffle),
+        "loop" => Ok(Setting::Loop),
+        "skip" => Ok(Setting::SkipErrors),
+        _ => match arg.trim_end_matches('s').parse::<f64>() {
+            Ok(secs) if secs > 0.0 => Ok(Setting::Interval(
+                Duration::try_from_secs_f64(secs)?.max(MIN_INTERVAL),
+            )),
+            _ => anyhow::bail!("slideshow: expected shuffle, loop, skip or seconds: {arg}"),
+        },
+    }
+}
+
+/// Result of `Slideshow::tick`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Step {
+    /// The current image stays up.
+    Wait,
+    /// Show the image at this index.
+    Show(usize),
+    /// The round is over and the slideshow stops.
+    End,
+}
+
+#[derive(Debug)]
+pub struct Slideshow {
+    /// Image indices of the current round, the first being the image shown at the start.
+    order: Vec<usize>,
+    position: usize,
+    due: Instant,
+}
+
+impl Slideshow {
+    pub fn new(options: &SlideshowOptions, len: usize, start: usize, now: Instant) -> Self {
+        Self {
+            order: round(options.shuffle, len, start),
+            position: 0,
+            due: now + options.interval,
+        }
+    }
+
+    /// When the next image is due.
+    pub fn due(&self) -> Instant {
+        self.due
+    }
+
+    /// Move on at the next tick (the current image failed to decode).
+    pub fn skip(&mut self, now: Instant) {
+        self.due = now;
+    }
+
+    /// Advance once the next image is due. `len` is the current list length, which may have
+    /// changed since the round started.
+    pub fn tick(&mut self, options: &SlideshowOptions, len: usize, now: Instant) -> Step {
+        if now < self.due || len == 0 {
+            return Step::Wait;
+        }
+        self.due = now + options.interval;
+        loop {
+            self.position += 1;
+            if self.position >= self.order.len() {
+                if options.stop_at_end {
+                    return Step::End;
+                }
+                let start = self.order.first().map_or(0, |&i| i % len);
+                self.order = round(options.shuffle, len, start);
+                self.position = 0;
+            }
+            // Images removed during the round are skipped.
+            if let Some(&index) = self.order.get(self.position).filter(|&&i| i < len) {
+                return Step::Show(index);
+            }
+        }
+    }
+}
+
+/// Visiting order of one round over `len` images, starting at `start`.
+fn round(shuffle: bool, len: usize, start: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..len).map(|i| (start + i) % len).collect();
+    if shuffle && len > 2 {
+        crate::shuffle(&mut order[1..], crate::random_seed());
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(shuffle: bool, stop_at_end: bool) -> SlideshowOptions {
+        SlideshowOptions {
+            interval: Duration::from_secs(1),
+            shuffle,
+            stop_at_end,
+            skip_errors: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_setting() {
+        assert_eq!(parse_setting("").unwrap(), Setting::Toggle);
+        assert_eq!(parse_setting("loop").unwrap(), Setting::Loop);
+        assert_eq!(
+            parse_setting("2.5s").unwrap(),
+            Setting::Interval(Duration::from_millis(2500))
+        );
+        assert_eq!(
+            parse_setting("0.01").unwrap(),
+            Setting::Interval(MIN_INTERVAL)
+        );
+        assert!(parse_setting("0").is_err());
+        assert!(parse_setting("fast").is_err());
+    }
+
+    #[test]
+    fn test_loops_in_order() {
+        let options = options(false, false);
+        let start = Instant::now();
+        let mut show = Slideshow::new(&options, 3, 1, start);
+        assert_eq!(show.tick(&options, 3, start), Step::Wait);
+        let steps: Vec<Step> = (1..=4)
+            .map(|s| show.tick(&options, 3, start + Duration::from_secs(s)))
+            .collect();
+        assert_eq!(
+            steps,
+            [Step::Show(2), Step::Show(0), Step::Show(1), Step::Show(2)]
+        );
+    }
+
+    #[test]
+    fn test_shuffle_stops_at_end() {
+        let options = options(true, true);
+        let start = Instant::now();
+        let mut show = Slideshow::new(&options, 5, 3, start);
+        let mut seen = vec![3];
+        for s in 1..5 {
+            match show.tick(&options, 5, start + Duration::from_secs(s)) {
+                Step::Show(index) => seen.push(index),
+                step => panic!("unexpected {step:?}"),
+            }
+        }
+        seen.sort();
+        assert_eq!(seen, [0, 1, 2, 3, 4]);
+        assert_eq!(
+            show.tick(&options, 5, start + Duration::from_secs(9)),
+            Step::End
+        );
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Content-based format detection.
+//!
+//! Files are matched by extension first (no I/O). Other regular files, with no extension or an
+//! odd one such as `.dat`, are identified by their magic bytes and kept only if the image header
+//! parses, so text that happens to start like a PNM header is not collected. Extensions of common
+//! non-image types (documents, source, archives, audio) are never read, nor is anything in the
+//! browser or `:open` completion. DICOM, HEIF, SVG, RAW and video files still need their
+//! extensions; archive members are matched by extension only.
+
+use std::path::Path;
+
+/// Extensions that are never images, so collecting a directory of them reads no file contents.
+const NON_IMAGE_EXTENSIONS: &[&str] = &[
+    "txt", "md", "rst", "log", "csv", "tsv", "json", "yaml", "yml", "toml", "xml", "html", "htm",
+    "css", "js", "ts", "py", "rs", "c", "h", "cpp", "hpp", "go", "java", "sh", "pdf", "doc",
+    "docx", "xls", "xlsx", "ppt", "pptx", "odt", "ods", "odp", "epub", "zip", "cbz", "tar", "gz",
+    "tgz", "bz2", "xz", "zst", "7z", "rar", "mp3", "wav", "flac", "ogg", "m4a", "aac", "exe",
+    "dll", "so", "o", "a", "iso", "db", "sqlite", "xmp", "pp3", "dop",
+];
+
+/// Check if the path may be an image worth sniffing: its extension, if any, is not a known
+/// non-image type.
+pub fn may_be_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_none_or(|ext| !NON_IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Check if the file's content is an image the `image` crate can decode.
+pub fn is_image(path: &Path) -> bool {
+    let Ok(reader) = image::ImageReader::open(path) else {
+        return false;
+    };
+    let Ok(reader) = reader.with_guessed_format() else {
+        return false;
+    };
+    reader.format().is_some() && reader.into_dimensions().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_image_by_content() {
+        let dir = std::env::temp_dir().join("stiv_test_sniff");
+        std::fs::create_dir_all(&dir).unwrap();
+        let png = dir.join("picture");
+        let misnamed = dir.join("photo.dat");
+        let text = dir.join("notes.txt");
+        image::DynamicImage::new_rgb8(2, 2)
+            .save_with_format(&png, image::ImageFormat::Png)
+            .unwrap();
+        image::DynamicImage::new_rgb8(2, 2)
+            .save_with_format(&misnamed, image::ImageFormat::Gif)
+            .unwrap();
+        std::fs::write(&text, "P1 is not a bitmap").unwrap();
+
+        assert!(is_image(&png));
+        assert!(is_image(&misnamed));
+        assert!(!is_image(&text));
+        assert!(!is_image(&dir.join("missing")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_may_be_image_skips_known_non_images() {
+        assert!(may_be_image(Path::new("picture")));
+        assert!(may_be_image(Path::new("photo.dat")));
+        assert!(may_be_image(Path::new("scan.001")));
+        assert!(!may_be_image(Path::new("notes.txt")));
+        assert!(!may_be_image(Path::new("Report.PDF")));
+    }
+}
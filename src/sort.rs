@@ -0,0 +1,198 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Natural (numeric-aware) path ordering.
+//!
+//! Directory listings, archive members and the directory browser are sorted with
+//! `natural_cmp`, so numbered files appear in sequence: `img2.png` < `img10.png`.
+//! Paths are compared component by component like `Path::cmp`; within a component, runs of
+//! ASCII digits compare by value and everything else by character. Ties (`a01` vs `a1`) fall
+//! back to the plain path order, so the ordering stays total.
+//!
+//! `:sort` reorders the image list by name, modification time or file size (`sort_paths`).
+
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::Result;
+
+/// Order of `:sort`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    /// Natural path order (as listed).
+    Name,
+    /// Oldest first.
+    Mtime,
+    /// Smallest first.
+    Size,
+}
+
+impl SortKey {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "" | "name" => Ok(SortKey::Name),
+            "mtime" | "time" | "date" => Ok(SortKey::Mtime),
+            "size" => Ok(SortKey::Size),
+            _ => anyhow::bail!("sort: expected name, mtime or size: {s}"),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Name => "name",
+            SortKey::Mtime => "mtime",
+            SortKey::Size => "size",
+        }
+    }
+}
+
+/// Sort `paths` by `key` (reversed with `reverse`). Files whose metadata cannot be read
+/// (e.g. archive members) go last; ties keep the natural order.
+pub fn sort_paths(paths: &mut [PathBuf], key: SortKey, reverse: bool) {
+    // Modification time or size; None sorts after every value.
+    let value = |path: &Path| -> Option<u128> {
+        let meta = std::fs::metadata(path).ok()?;
+        match key {
+            SortKey::Name => Some(0),
+            SortKey::Mtime => meta
+                .modified()
+                .ok()?
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .ok()
+                .map(|d| d.as_nanos()),
+            SortKey::Size => Some(u128::from(meta.len())),
+        }
+    };
+    paths.sort_by(|a, b| natural_cmp(a, b));
+    if key != SortKey::Name {
+        paths.sort_by_cached_key(|path| {
+            let value = value(path);
+            (value.is_none(), value.unwrap_or_default())
+        });
+    }
+    if reverse {
+        paths.reverse();
+    }
+}
+
+/// Natural ordering of two paths.
+pub fn natural_cmp(a: &Path, b: &Path) -> Ordering {
+    let mut ca = a.components();
+    let mut cb = b.components();
+    loop {
+        match (ca.next(), cb.next()) {
+            (Some(x), Some(y)) => {
+                let ord = natural_cmp_str(
+                    &x.as_os_str().to_string_lossy(),
+                    &y.as_os_str().to_string_lossy(),
+                );
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+            (Some(_), None) => return Ordering::Greater,
+            (None, Some(_)) => return Ordering::Less,
+            (None, None) => return a.cmp(b),
+        }
+    }
+}
+
+fn natural_cmp_str(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i].is_ascii_digit() && b[j].is_ascii_digit() {
+            let (na, next_i) = digit_run(a, i);
+            let (nb, next_j) = digit_run(b, j);
+            // Equal-length runs without leading zeros compare like numbers.
+            let ord = na.len().cmp(&nb.len()).then_with(|| na.cmp(nb));
+            if ord != Ordering::Equal {
+                return ord;
+            }
+            (i, j) = (next_i, next_j);
+        } else {
+            if a[i] != b[j] {
+                return a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+    }
+    (a.len() - i).cmp(&(b.len() - j))
+}
+
+/// Digits starting at `start` without leading zeros, and the index after the run.
+fn digit_run(s: &[u8], start: usize) -> (&[u8], usize) {
+    let end = s[start..]
+        .iter()
+        .position(|c| !c.is_ascii_digit())
+        .map_or(s.len(), |n| start + n);
+    let first = s[start..end]
+        .iter()
+        .position(|&c| c != b'0')
+        .map_or(end, |n| start + n);
+    (&s[first..end], end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn sorted(names: &[&str]) -> Vec<String> {
+        let mut paths: Vec<PathBuf> = names.iter().map(PathBuf::from).collect();
+        paths.sort_by(|a, b| natural_cmp(a, b));
+        paths.iter().map(|p| p.display().to_string()).collect()
+    }
+
+    #[test]
+    fn test_numbers_compare_by_value() {
+        assert_eq!(
+            sorted(&["img10.png", "img2.png", "img1.png", "img.png"]),
+            ["img.png", "img1.png", "img2.png", "img10.png"]
+        );
+        assert_eq!(
+            sorted(&["frame_0100.png", "frame_99.png", "frame_0099.png"]),
+            ["frame_0099.png", "frame_99.png", "frame_0100.png"]
+        );
+    }
+
+    #[test]
+    fn test_sort_by_size() {
+        let dir = std::env::temp_dir().join("stiv_test_sort_size");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut paths = Vec::new();
+        for (name, len) in [("a.png", 30), ("b.png", 10), ("c.png", 20)] {
+            let path = dir.join(name);
+            std::fs::write(&path, vec![0u8; len]).unwrap();
+            paths.push(path);
+        }
+        paths.push(dir.join("missing.png"));
+        let names = |paths: &[PathBuf]| -> Vec<String> {
+            paths
+                .iter()
+                .map(|p| p.file_name().unwrap().to_string_lossy().into())
+                .collect()
+        };
+
+        sort_paths(&mut paths, SortKey::Size, false);
+        assert_eq!(names(&paths), ["b.png", "c.png", "a.png", "missing.png"]);
+        sort_paths(&mut paths, SortKey::Name, true);
+        assert_eq!(names(&paths), ["missing.png", "c.png", "b.png", "a.png"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_components_compare_separately() {
+        assert_eq!(
+            sorted(&["b10/a.png", "b9/z.png", "b9.png"]),
+            ["b9/z.png", "b9.png", "b10/a.png"]
+        );
+        assert_eq!(
+            natural_cmp(Path::new("a/1.png"), Path::new("a/1.png")),
+            Ordering::Equal
+        );
+    }
+}
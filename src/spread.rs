@@ -0,0 +1,52 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Two-page spreads (`b`) for manga and comics.
+//!
+//! In spread mode Single mode shows each image next to the following one and navigation moves
+//! two images at a time. `spread_rtl` puts the first page on the right (Japanese reading
+//! order). Like diffs, a spread renders through a virtual path `<left>\0spread\0<right>` (`VirtualPath::Spread`), and
+//! the worker composes both pages at the height of the taller one.
+
+use image::{DynamicImage, GenericImage, RgbaImage};
+
+use crate::virtual_path::scaled_to;
+
+/// Canvas size of the spread of pages sized `left` and `right` (width, height).
+pub fn size(left: (u32, u32), right: (u32, u32)) -> (u64, u64) {
+    let height = left.1.max(right.1).max(1);
+    let width =
+        |(w, h): (u32, u32)| (u64::from(w) * u64::from(height) / u64::from(h.max(1))).max(1);
+    (width(left) + width(right), u64::from(height))
+}
+
+/// Both pages side by side, the shorter one scaled to the taller one's height. The canvas is
+/// transparent where pages do not cover it.
+pub fn compose(left: &DynamicImage, right: &DynamicImage) -> DynamicImage {
+    let height = left.height().max(right.height()).max(1);
+    let fit = |img: &DynamicImage| {
+        let width =
+            (u64::from(img.width()) * u64::from(height) / u64::from(img.height().max(1))).max(1);
+        scaled_to(img, width as u32, height)
+    };
+    let (left, right) = (fit(left), fit(right));
+    let mut canvas = RgbaImage::new(left.width() + right.width(), height);
+    // Both pages fit the canvas by construction.
+    let _ = canvas.copy_from(&left, 0, 0);
+    let _ = canvas.copy_from(&right, left.width(), 0);
+    DynamicImage::ImageRgba8(canvas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compose_matches_heights() {
+        let left = DynamicImage::new_rgb8(10, 20);
+        let right = DynamicImage::new_rgb8(5, 10);
+        let spread = compose(&left, &right);
+        assert_eq!((spread.width(), spread.height()), (20, 20));
+        assert_eq!(size((10, 20), (5, 10)), (20, 20));
+    }
+}
@@ -0,0 +1,120 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! SVG (`.svg`) support, behind the `svg` cargo feature.
+//!
+//! `decode` rasterizes at a scale of the document size: 1.0 for the "original" image (tile
+//! thumbnails, placeholders, zoom coordinates), and the display scale in Single mode, where
+//! the worker rasterizes again at the target size instead of enlarging pixels.
+//!
+//! Without the feature, `.svg` files are not collected and `decode` always returns None.
+
+use std::path::Path;
+
+use image::DynamicImage;
+
+/// Check if the path is an SVG file stiv can open (`.svg` with the `svg` feature).
+pub fn is_svg(path: &Path) -> bool {
+    cfg!(feature = "svg")
+        && path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("svg"))
+}
+
+#[cfg(not(feature = "svg"))]
+pub fn decode(
+    _bytes: &[u8],
+    _resources_dir: Option<&Path>,
+    _scale: f32,
+    _limits: &image::Limits,
+) -> Option<DynamicImage> {
+    None
+}
+
+/// Rasterize an SVG document at `scale` times its size. Relative image references are
+/// resolved against `resources_dir`. Rasters exceeding `limits` are not drawn.
+#[cfg(feature = "svg")]
+pub fn decode(
+    bytes: &[u8],
+    resources_dir: Option<&Path>,
+    scale: f32,
+    limits: &image::Limits,
+) -> Option<DynamicImage> {
+    use resvg::{tiny_skia, usvg};
+
+    let options = usvg::Options {
+        resources_dir: resources_dir.map(Path::to_path_buf),
+        fontdb: fonts(),
+        ..Default::default()
+    };
+    let tree = usvg::Tree::from_data(bytes, &options).ok()?;
+    let size = tree.size().to_int_size().scale_by(scale)?;
+    let (width, height) = (size.width(), size.height());
+    limits.check_dimensions(width, height).ok()?;
+    let bytes = u64::from(width) * u64::from(height) * 4;
+    if limits.max_alloc.is_some_and(|max| bytes > max) {
+        return None;
+    }
+
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)?;
+    let transform = tiny_skia::Transform::from_scale(
+        width as f32 / tree.size().width(),
+        height as f32 / tree.size().height(),
+    );
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+    // tiny-skia stores premultiplied alpha.
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|p| {
+            let c = p.demultiply();
+            [c.red(), c.green(), c.blue(), c.alpha()]
+        })
+        .collect();
+    image::RgbaImage::from_raw(width, height, pixels).map(DynamicImage::ImageRgba8)
+}
+
+/// System fonts for `<text>`, loaded once (scanning them takes a while).
+#[cfg(feature = "svg")]
+fn fonts() -> std::sync::Arc<resvg::usvg::fontdb::Database> {
+    use std::sync::{Arc, OnceLock};
+
+    static FONTS: OnceLock<Arc<resvg::usvg::fontdb::Database>> = OnceLock::new();
+    Arc::clone(FONTS.get_or_init(|| {
+        let mut db = resvg::usvg::fontdb::Database::new();
+        db.load_system_fonts();
+        Arc::new(db)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_svg() {
+        assert_eq!(is_svg(Path::new("icons/logo.SVG")), cfg!(feature = "svg"));
+        assert!(!is_svg(Path::new("icons/logo.png")));
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_decode_scales_vector() {
+        let svg = br##"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="2">
+            <rect width="4" height="2" fill="#ff0000"/></svg>"##;
+        let limits = image::Limits::default();
+
+        let img = decode(svg, None, 1.0, &limits).unwrap();
+        assert_eq!((img.width(), img.height()), (4, 2));
+        let img = decode(svg, None, 10.0, &limits).unwrap();
+        assert_eq!((img.width(), img.height()), (40, 20));
+        assert_eq!(img.to_rgba8().get_pixel(39, 19).0, [255, 0, 0, 255]);
+
+        let mut small = image::Limits::default();
+        small.max_image_width = Some(10);
+        assert!(decode(svg, None, 10.0, &small).is_none());
+        assert!(decode(b"<svg", None, 1.0, &limits).is_none());
+    }
+}
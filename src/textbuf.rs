@@ -0,0 +1,269 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Off-screen cell buffer for the writer's text output (status bar and overlays).
+//!
+//! Text is drawn into a back buffer, and each flush writes only the cells that differ from the
+//! front buffer (what is on screen) instead of rewriting whole rows. Cells marked `skip` are
+//! not owned by text: they show the image or the terminal background. When a text cell goes
+//! back to `skip`, it is erased and the image row under it is placed again, which also redraws
+//! the text left on that row.
+
+use std::io::Write;
+
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+
+#[derive(Default)]
+pub struct TextBuffer {
+    /// Text on screen.
+    front: Buffer,
+    /// Text to show after the next flush.
+    back: Buffer,
+}
+
+impl TextBuffer {
+    /// Match the terminal size. A new size starts from an unknown screen, so all text is
+    /// written again.
+    pub fn resize(&mut self, size: (u16, u16)) {
+        let area = Rect::new(0, 0, size.0, size.1);
+        if self.back.area != area {
+            self.front = unowned(area);
+            self.back = unowned(area);
+        }
+    }
+
+    /// Draw `text` at `(x, y)`, clipped to `max_width` cells and the buffer.
+    /// Returns the column after the last drawn cell.
+    pub fn put(&mut self, x: u16, y: u16, text: &str, max_width: usize, style: Style) -> u16 {
+        if !self.back.area.contains((x, y).into()) {
+            return x;
+        }
+        let (end, _) = self.back.set_stringn(x, y, text, max_width, style);
+        for cx in x..end {
+            self.back[(cx, y)].set_skip(false);
+        }
+        end
+    }
+
+    /// Fill `rect` with blank cells in `style`.
+    pub fn fill(&mut self, rect: Rect, style: Style) {
+        for (x, y) in self.cells(rect) {
+            let cell = &mut self.back[(x, y)];
+            cell.reset();
+            cell.set_style(style);
+        }
+    }
+
+    /// Release `rect`: its cells show what is under the text again.
+    pub fn clear(&mut self, rect: Rect) {
+        for (x, y) in self.cells(rect) {
+            self.back[(x, y)] = unowned_cell();
+        }
+    }
+
+    /// `rect` was overwritten by image output: drop the text there and forget what the screen
+    /// shows, so text drawn into it later is written in full.
+    pub fn overwrite(&mut self, rect: Rect) {
+        self.clear(rect);
+        self.forget(rect);
+    }
+
+    /// The screen under `rect` changed outside this buffer: write its text again on the next
+    /// flush.
+    pub fn forget(&mut self, rect: Rect) {
+        for (x, y) in self.cells(rect) {
+            self.front[(x, y)] = unowned_cell();
+        }
+    }
+
+    /// Write the changed cells to `out`. Released cells are erased; `image_row(y)` returns the
+    /// escapes that place the image on row `y` again, if the image covers it.
+    pub fn flush(
+        &mut self,
+        out: &mut impl Write,
+        image_row: impl Fn(u16) -> Option<Vec<u8>>,
+    ) -> std::io::Result<()> {
+        let area = self.back.area;
+        let mut written = false;
+
+        for y in area.top()..area.bottom() {
+            let mut released = false;
+            let mut x = area.left();
+            while x < area.right() {
+                let start = x;
+                while x < area.right() && self.back[(x, y)].skip && !self.front[(x, y)].skip {
+                    self.front[(x, y)] = unowned_cell();
+                    x += 1;
+                }
+                if x > start {
+                    write!(
+                        out,
+                        "\x1b[{};{}H\x1b[0m\x1b[{}X",
+                        y + 1,
+                        start + 1,
+                        x - start
+                    )?;
+                    released = true;
+                } else {
+                    x += 1;
+                }
+            }
+            if released && let Some(row) = image_row(y) {
+                out.write_all(&row)?;
+                // Placing the row covers the text left on it.
+                self.forget(Rect::new(area.x, y, area.width, 1));
+            }
+            written |= released;
+        }
+
+        let mut cursor: Option<(u16, u16)> = None;
+        let mut style: Option<(Color, Color, Modifier)> = None;
+        for (x, y, cell) in self.front.diff(&self.back) {
+            if cursor != Some((x, y)) {
+                write!(out, "\x1b[{};{}H", y + 1, x + 1)?;
+            }
+            let cell_style = (cell.fg, cell.bg, cell.modifier);
+            if style != Some(cell_style) {
+                out.write_all(sgr(cell_style).as_bytes())?;
+                style = Some(cell_style);
+            }
+            out.write_all(cell.symbol().as_bytes())?;
+            // Only ASCII advances the cursor by a known width; reposition after anything else.
+            cursor = cell.symbol().is_ascii().then_some((x + 1, y));
+            written = true;
+        }
+        if written {
+            out.write_all(b"\x1b[0m")?;
+        }
+        self.front.content.clone_from(&self.back.content);
+        Ok(())
+    }
+
+    fn cells(&self, rect: Rect) -> impl Iterator<Item = (u16, u16)> + use<> {
+        let rect = rect.intersection(self.back.area);
+        (rect.top()..rect.bottom())
+            .flat_map(move |y| (rect.left()..rect.right()).map(move |x| (x, y)))
+    }
+}
+
+fn unowned_cell() -> Cell {
+    let mut cell = Cell::EMPTY;
+    cell.set_skip(true);
+    cell
+}
+
+fn unowned(area: Rect) -> Buffer {
+    Buffer::filled(area, unowned_cell())
+}
+
+/// SGR sequence that sets exactly this style (starting from a reset).
+fn sgr((fg, bg, modifier): (Color, Color, Modifier)) -> String {
+    let mut s = String::from("\x1b[0");
+    if modifier.contains(Modifier::BOLD) {
+        s.push_str(";1");
+    }
+    if modifier.contains(Modifier::REVERSED) {
+        s.push_str(";7");
+    }
+    for (color, base) in [(fg, 30), (bg, 40)] {
+        if let Some(code) = color_code(color, base) {
+            s.push(';');
+            s.push_str(&code);
+        }
+    }
+    s.push('m');
+    s
+}
+
+/// SGR parameter for `color` (`base` 30 for foreground, 40 for background).
+fn color_code(color: Color, base: u8) -> Option<String> {
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => base,
+        Color::Red => base + 1,
+        Color::Green => base + 2,
+        Color::Yellow => base + 3,
+        Color::Blue => base + 4,
+        Color::Magenta => base + 5,
+        Color::Cyan => base + 6,
+        Color::Gray => base + 7,
+        Color::DarkGray => base + 60,
+        Color::LightRed => base + 61,
+        Color::LightGreen => base + 62,
+        Color::LightYellow => base + 63,
+        Color::LightBlue => base + 64,
+        Color::LightMagenta => base + 65,
+        Color::LightCyan => base + 66,
+        Color::White => base + 67,
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{r};{g};{b}", base + 8)),
+        Color::Indexed(i) => return Some(format!("{};5;{i}", base + 8)),
+    };
+    Some(code.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flush(text: &mut TextBuffer) -> String {
+        let mut out = Vec::new();
+        text.flush(&mut out, |y| Some(format!("<img {y}>").into_bytes()))
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_flush_writes_only_changes() {
+        let mut text = TextBuffer::default();
+        text.resize((10, 3));
+        let style = Style::default().fg(Color::White).bg(Color::Black);
+        text.put(0, 2, "abc", 10, style);
+        assert_eq!(flush(&mut text), "\x1b[3;1H\x1b[0;97;40mabc\x1b[0m");
+
+        // Unchanged text is not written again.
+        text.put(0, 2, "abc", 10, style);
+        assert_eq!(flush(&mut text), "");
+        text.put(0, 2, "abd", 10, style);
+        assert_eq!(flush(&mut text), "\x1b[3;3H\x1b[0;97;40md\x1b[0m");
+    }
+
+    #[test]
+    fn test_flush_restores_released_cells() {
+        let mut text = TextBuffer::default();
+        text.resize((10, 3));
+        text.put(0, 1, "ab", 10, Style::default());
+        text.put(5, 1, "x", 10, Style::default());
+        flush(&mut text);
+
+        // Releasing "b" erases it, places the image row again and redraws the rest of the row.
+        text.clear(Rect::new(1, 1, 1, 1));
+        assert_eq!(
+            flush(&mut text),
+            "\x1b[2;2H\x1b[0m\x1b[1X<img 1>\x1b[2;1H\x1b[0ma\x1b[2;6Hx\x1b[0m"
+        );
+
+        // Image output over the text: nothing to erase, and later text is written in full.
+        text.overwrite(Rect::new(0, 0, 10, 3));
+        assert_eq!(flush(&mut text), "");
+        text.put(0, 1, "a", 10, Style::default());
+        assert_eq!(flush(&mut text), "\x1b[2;1H\x1b[0ma\x1b[0m");
+    }
+
+    #[test]
+    fn test_sgr() {
+        assert_eq!(
+            sgr((Color::Reset, Color::Reset, Modifier::empty())),
+            "\x1b[0m"
+        );
+        assert_eq!(
+            sgr((Color::Black, Color::Yellow, Modifier::BOLD)),
+            "\x1b[0;1;30;43m"
+        );
+        assert_eq!(
+            sgr((Color::Rgb(1, 2, 3), Color::Reset, Modifier::REVERSED)),
+            "\x1b[0;7;38;2;1;2;3m"
+        );
+    }
+}
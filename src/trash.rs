@@ -0,0 +1,231 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Moving images to the trash, and restoring them (`D` / `u`).
+//!
+//! Files go to the user's trash instead of being deleted:
+//! - Linux and others: the freedesktop home trash (`~/.local/share/Trash/files`), with a
+//!   `.trashinfo` entry so desktop file managers can restore them too
+//! - macOS: `~/.Trash`
+//!
+//! Every move returns a `Trashed` record; the app keeps them in a session-scoped undo stack and
+//! `Trashed::restore` moves the file back to its original path.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, bail};
+
+/// A file moved to the trash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trashed {
+    /// Absolute path the file was moved from.
+    pub original: PathBuf,
+    /// Where the file is now.
+    pub location: PathBuf,
+    /// freedesktop `.trashinfo` entry (None on macOS).
+    info: Option<PathBuf>,
+}
+
+impl Trashed {
+    /// Move the file back to its original path (fails if that path is taken again).
+    pub fn restore(&self) -> Result<()> {
+        if self.original.exists() {
+            bail!("Cannot restore, path exists: {:?}", self.original);
+        }
+        move_file(&self.location, &self.original)
+            .with_context(|| format!("Failed to restore {:?}", self.original))?;
+        if let Some(info) = &self.info {
+            _ = std::fs::remove_file(info);
+        }
+        Ok(())
+    }
+}
+
+/// Move `path` to the user's trash.
+pub fn move_to_trash(path: &Path) -> Result<Trashed> {
+    let (root, with_info) = trash_root().context("No trash directory")?;
+    move_to_trash_in(path, &root, with_info)
+}
+
+#[cfg(target_os = "macos")]
+fn trash_root() -> Option<(PathBuf, bool)> {
+    Some((dirs::home_dir()?.join(".Trash"), false))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn trash_root() -> Option<(PathBuf, bool)> {
+    Some((dirs::data_dir()?.join("Trash"), true))
+}
+
+fn move_to_trash_in(path: &Path, root: &Path, with_info: bool) -> Result<Trashed> {
+    if !path.is_file() {
+        bail!("Not a file: {:?}", path);
+    }
+    let original =
+        std::fs::canonicalize(path).with_context(|| format!("Cannot resolve {:?}", path))?;
+    let name = original
+        .file_name()
+        .context("Path has no file name")?
+        .to_string_lossy()
+        .into_owned();
+
+    let (files_dir, info_dir) = if with_info {
+        (root.join("files"), Some(root.join("info")))
+    } else {
+        (root.to_path_buf(), None)
+    };
+    std::fs::create_dir_all(&files_dir)?;
+    if let Some(info_dir) = &info_dir {
+        std::fs::create_dir_all(info_dir)?;
+    }
+
+    // Pick a free name ("a.png", "a.2.png", ...) and claim it via its info file first.
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem.to_string(), format!(".{ext}")),
+        _ => (name.clone(), String::new()),
+    };
+    let mut n = 1;
+    let (location, info) = loop {
+        let candidate = if n == 1 {
+            name.clone()
+        } else {
+            format!("{stem}.{n}{ext}")
+        };
+        n += 1;
+        let location = files_dir.join(&candidate);
+        if location.exists() {
+            continue;
+        }
+        let Some(info_dir) = &info_dir else {
+            break (location, None);
+        };
+        let info = info_dir.join(format!("{candidate}.trashinfo"));
+        let created = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&info);
+        match created {
+            Ok(mut file) => {
+                use std::io::Write as _;
+                let deleted = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default();
+                write!(
+                    file,
+                    "[Trash Info]\nPath={}\nDeletionDate={}\n",
+                    percent_encode(&original),
+                    format_datetime(deleted)
+                )?;
+                break (location, Some(info));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    };
+
+    if let Err(e) = move_file(&original, &location) {
+        if let Some(info) = &info {
+            _ = std::fs::remove_file(info);
+        }
+        return Err(e).with_context(|| format!("Failed to move {:?} to the trash", original));
+    }
+    Ok(Trashed {
+        original,
+        location,
+        info,
+    })
+}
+
+/// Rename, falling back to copy + remove across filesystems.
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(from, to)?;
+    std::fs::remove_file(from)?;
+    Ok(())
+}
+
+/// Percent-encode a path for `.trashinfo` (`/` and unreserved characters are kept).
+fn percent_encode(path: &Path) -> String {
+    let mut out = String::new();
+    for &b in path.as_os_str().as_encoded_bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'/' | b'-' | b'_' | b'.' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+/// `YYYY-MM-DDThh:mm:ss` (UTC) for seconds since the Unix epoch.
+pub(crate) fn format_datetime(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_format_datetime() {
+        assert_eq!(format_datetime(0), "1970-01-01T00:00:00");
+        assert_eq!(format_datetime(951_782_400), "2000-02-29T00:00:00");
+        assert_eq!(format_datetime(1_735_689_599), "2024-12-31T23:59:59");
+    }
+
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(percent_encode(Path::new("/a b/c%.png")), "/a%20b/c%25.png");
+    }
+
+    #[test]
+    fn test_trash_and_restore() {
+        let dir = PathBuf::from("/tmp/stiv_test_trash");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("images")).unwrap();
+        let trash = dir.join("Trash");
+        let image = dir.join("images/a.png");
+
+        fs::write(&image, b"first").unwrap();
+        let first = move_to_trash_in(&image, &trash, true).unwrap();
+        assert!(!image.exists());
+        assert_eq!(first.location, trash.join("files/a.png"));
+        let info = fs::read_to_string(trash.join("info/a.png.trashinfo")).unwrap();
+        assert!(info.contains("Path=/tmp/stiv_test_trash/images/a.png"));
+
+        // A second file with the same name gets a new trash name.
+        fs::write(&image, b"second").unwrap();
+        let second = move_to_trash_in(&image, &trash, true).unwrap();
+        assert_eq!(second.location, trash.join("files/a.2.png"));
+
+        second.restore().unwrap();
+        assert_eq!(fs::read(&image).unwrap(), b"second");
+        assert!(!trash.join("info/a.2.png.trashinfo").exists());
+        // The original path is taken again.
+        assert!(first.restore().is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Video files, shown as one representative frame so mixed media folders can be browsed.
+//!
+//! Frames are extracted by the `ffmpeg` command: its `thumbnail` filter picks the most typical
+//! frame among the first 100, which skips black intro frames. Videos are only collected when
+//! `ffmpeg` is on PATH (checked once). Archive members are not supported (containers such
+//! as MP4 need seeking, which a pipe cannot do).
+
+use std::io::Cursor;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use image::DynamicImage;
+
+const EXTENSIONS: &[&str] = &["mp4", "m4v", "mov", "mkv", "webm", "avi"];
+
+/// Check if the path is a video stiv can show (a video extension, with `ffmpeg` available).
+pub fn is_video(path: &Path) -> bool {
+    has_video_extension(path) && ffmpeg_available()
+}
+
+fn has_video_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+fn ffmpeg_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        Command::new("ffmpeg")
+            .arg("-version")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success())
+    })
+}
+
+/// Extract a representative frame. Frames exceeding `limits` fail to decode.
+pub fn decode(path: &Path, limits: &image::Limits) -> Option<DynamicImage> {
+    if crate::archive::split_member_path(path).is_some() {
+        return None;
+    }
+    let output = Command::new("ffmpeg")
+        .args(["-v", "error", "-nostdin", "-i"])
+        .arg(path)
+        .args(["-vf", "thumbnail", "-frames:v", "1"])
+        .args(["-f", "image2pipe", "-c:v", "png", "-"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let mut reader =
+        image::ImageReader::with_format(Cursor::new(output.stdout), image::ImageFormat::Png);
+    reader.limits(limits.clone());
+    reader.decode().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_video_extensions() {
+        assert!(has_video_extension(Path::new("clips/a.MP4")));
+        assert!(has_video_extension(Path::new("a.webm")));
+        assert!(!has_video_extension(Path::new("a.gif")));
+        assert!(decode(Path::new("/nonexistent/a.mp4"), &image::Limits::default()).is_none());
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Virtual paths of images composed from two sources.
+//!
+//! Spreads, split views, blends, wipes and diffs render through a path that joins both sources
+//! with a tagged separator, `<first>\0<tag><value>\0<second>` (NUL cannot appear in real
+//! paths), so render caching, zoom and prefetching treat each composition like its own image.
+//! `VirtualPath` builds and splits them on the raw `OsStr` bytes, so non-UTF-8 file names work.
+//!
+//! They nest: a pane holds a spread that can be blended, wiped or diffed, in that order from
+//! the inside out. `parse` splits the outermost one first (panes, diff, wipe, blend, spread).
+
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+use image::{DynamicImage, RgbaImage, imageops::FilterType};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VirtualPath<'a> {
+    /// Two images in split-view panes (`src/panes.rs`).
+    Panes { left: &'a Path, right: &'a Path },
+    /// `image` compared against `reference` (`src/diff.rs`).
+    Diff {
+        image: &'a Path,
+        reference: &'a Path,
+    },
+    /// `image` left of `position` percent, `reference` right of it (`src/wipe.rs`).
+    Wipe {
+        image: &'a Path,
+        reference: &'a Path,
+        position: u8,
+    },
+    /// `reference` over `image` at `opacity` percent (`src/blend.rs`).
+    Blend {
+        image: &'a Path,
+        reference: &'a Path,
+        opacity: u8,
+    },
+    /// Two pages side by side (`src/spread.rs`).
+    Spread { left: &'a Path, right: &'a Path },
+}
+
+/// Separator tags, in the order `parse` tries them.
+const TAGS: [&str; 5] = ["panes", "diff", "wipe", "blend", "spread"];
+
+impl<'a> VirtualPath<'a> {
+    /// The path that renders this composition.
+    pub fn to_path(self) -> PathBuf {
+        let (first, tag, value, second) = match self {
+            Self::Panes { left, right } => (left, "panes", None, right),
+            Self::Diff { image, reference } => (image, "diff", None, reference),
+            Self::Wipe {
+                image,
+                reference,
+                position,
+            } => (image, "wipe", Some(position), reference),
+            Self::Blend {
+                image,
+                reference,
+                opacity,
+            } => (image, "blend", Some(opacity), reference),
+            Self::Spread { left, right } => (left, "spread", None, right),
+        };
+        let value = value.map(|v| v.to_string()).unwrap_or_default();
+        let mut path = OsString::from(first.as_os_str());
+        path.push(format!("\0{tag}{value}\0"));
+        path.push(second.as_os_str());
+        PathBuf::from(path)
+    }
+
+    /// Split a virtual path into its outermost composition. Returns None for regular paths.
+    pub fn parse(path: &'a Path) -> Option<Self> {
+        let bytes = path.as_os_str().as_encoded_bytes();
+        TAGS.iter().find_map(|tag| {
+            let (first, value, second) = split_at_tag(bytes, tag)?;
+            let value = || std::str::from_utf8(value).ok()?.parse().ok();
+            Some(match *tag {
+                "panes" => Self::Panes {
+                    left: first,
+                    right: second,
+                },
+                "diff" => Self::Diff {
+                    image: first,
+                    reference: second,
+                },
+                "wipe" => Self::Wipe {
+                    image: first,
+                    reference: second,
+                    position: value()?,
+                },
+                "blend" => Self::Blend {
+                    image: first,
+                    reference: second,
+                    opacity: value()?,
+                },
+                _ => Self::Spread {
+                    left: first,
+                    right: second,
+                },
+            })
+        })
+    }
+}
+
+/// Split `bytes` at the first `\0<tag><value>\0` into the paths before and after it and the
+/// value bytes.
+fn split_at_tag<'a>(bytes: &'a [u8], tag: &str) -> Option<(&'a Path, &'a [u8], &'a Path)> {
+    let marker = [b"\0", tag.as_bytes()].concat();
+    let start = bytes
+        .windows(marker.len())
+        .position(|window| window == marker)?;
+    let rest = &bytes[start + marker.len()..];
+    let end = rest.iter().position(|&b| b == 0)?;
+    Some((
+        to_path(&bytes[..start]),
+        &rest[..end],
+        to_path(&rest[end + 1..]),
+    ))
+}
+
+fn to_path(bytes: &[u8]) -> &Path {
+    // SAFETY: the bytes come from an `OsStr` and are split only next to NUL bytes (ASCII),
+    // which are valid boundaries of its encoding.
+    Path::new(unsafe { OsStr::from_encoded_bytes_unchecked(bytes) })
+}
+
+/// `img` as RGBA at exactly `width` x `height`, resampled only if its size differs. Used to
+/// match a reference to the image it is compared with, and to fit pages and panes.
+pub fn scaled_to(img: &DynamicImage, width: u32, height: u32) -> RgbaImage {
+    if img.width() == width && img.height() == height {
+        img.to_rgba8()
+    } else {
+        img.resize_exact(width, height, FilterType::Triangle)
+            .to_rgba8()
+    }
+}
+
+/// Whether a `width` x `height` RGBA canvas composed from two sources stays within `limits`.
+/// Each source is checked when decoded, but a composition can be larger than either.
+pub fn fits_limits(limits: &image::Limits, width: u64, height: u64) -> bool {
+    let (Ok(w), Ok(h)) = (u32::try_from(width), u32::try_from(height)) else {
+        return false;
+    };
+    limits.check_dimensions(w, h).is_ok()
+        && limits
+            .max_alloc
+            .is_none_or(|max| width.saturating_mul(height).saturating_mul(4) <= max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_virtual_path_round_trip() {
+        let (a, b) = (Path::new("shots/new.png"), Path::new("golden/old.png"));
+        let paths = [
+            VirtualPath::Panes { left: a, right: b },
+            VirtualPath::Diff {
+                image: a,
+                reference: b,
+            },
+            VirtualPath::Wipe {
+                image: a,
+                reference: b,
+                position: 35,
+            },
+            VirtualPath::Blend {
+                image: a,
+                reference: b,
+                opacity: 0,
+            },
+            VirtualPath::Spread { left: a, right: b },
+        ];
+        for virtual_path in paths {
+            assert_eq!(
+                VirtualPath::parse(&virtual_path.to_path()),
+                Some(virtual_path)
+            );
+        }
+        assert_eq!(VirtualPath::parse(a), None);
+    }
+
+    #[test]
+    fn test_virtual_path_splits_outermost() {
+        let (a, b, r) = (Path::new("1.png"), Path::new("2.png"), Path::new("ref.png"));
+        let spread = VirtualPath::Spread { left: a, right: b }.to_path();
+        let blend = VirtualPath::Blend {
+            image: &spread,
+            reference: r,
+            opacity: 30,
+        }
+        .to_path();
+        let diff = VirtualPath::Diff {
+            image: &blend,
+            reference: r,
+        }
+        .to_path();
+        assert_eq!(
+            VirtualPath::parse(&diff),
+            Some(VirtualPath::Diff {
+                image: &blend,
+                reference: r
+            })
+        );
+        assert_eq!(
+            VirtualPath::parse(&blend),
+            Some(VirtualPath::Blend {
+                image: &spread,
+                reference: r,
+                opacity: 30
+            })
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_virtual_path_keeps_non_utf8_names() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let left = Path::new(OsStr::from_bytes(b"scan\xff01.png"));
+        let right = Path::new(OsStr::from_bytes(b"scan\xfe02.png"));
+        let path = VirtualPath::Spread { left, right }.to_path();
+        assert_eq!(
+            VirtualPath::parse(&path),
+            Some(VirtualPath::Spread { left, right })
+        );
+    }
+
+    #[test]
+    fn test_fits_limits() {
+        let mut limits = image::Limits::default();
+        limits.max_image_width = Some(100);
+        limits.max_image_height = Some(100);
+        limits.max_alloc = Some(100 * 50 * 4);
+        assert!(fits_limits(&limits, 100, 50));
+        assert!(!fits_limits(&limits, 101, 10));
+        assert!(!fits_limits(&limits, 100, 51));
+        assert!(!fits_limits(&limits, u64::from(u32::MAX) + 1, 1));
+    }
+
+    #[test]
+    fn test_scaled_to_keeps_matching_size() {
+        let img = DynamicImage::new_rgba8(4, 2);
+        assert_eq!(scaled_to(&img, 4, 2).dimensions(), (4, 2));
+        assert_eq!(scaled_to(&img, 2, 1).dimensions(), (2, 1));
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Event loop wakeups.
+//!
+//! The event loop sleeps until something happens instead of polling on a tick. Terminal input
+//! is read on its own thread and sent over one channel, and the background threads (decode
+//! worker, prefetch, writer, listing, clipboard and save jobs) call [`wake`] on the same channel
+//! after sending a result. Timed work (animation frames, the nav latch, slideshow, message
+//! expiry, chord timeout, session replay) bounds the sleep with a deadline, so an idle viewer
+//! does not wake up at all.
+
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Instant;
+
+use ratatui::crossterm::event::{self, Event, KeyEvent, KeyEventKind};
+
+enum Wake {
+    Input(std::io::Result<Event>),
+    /// A background thread has a result to poll.
+    Ready,
+}
+
+static WAKER: OnceLock<Sender<Wake>> = OnceLock::new();
+
+/// Wake the event loop to poll for results. Does nothing before [`Wakeups::start`].
+pub fn wake() {
+    if let Some(tx) = WAKER.get() {
+        let _ = tx.send(Wake::Ready);
+    }
+}
+
+/// Receiving end of the wakeups, with the terminal input read so far.
+pub struct Wakeups {
+    rx: Receiver<Wake>,
+    input: VecDeque<std::io::Result<Event>>,
+}
+
+impl Wakeups {
+    /// Install the process-wide waker and start reading terminal input. Call after the
+    /// terminal queries at startup, which read their replies from stdin.
+    pub fn start() -> Self {
+        let (tx, rx) = mpsc::channel();
+        let input_tx = tx.clone();
+        let _ = WAKER.set(tx);
+        thread::spawn(move || {
+            loop {
+                let ev = event::read();
+                let failed = ev.is_err();
+                if input_tx.send(Wake::Input(ev)).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        Self {
+            rx,
+            input: VecDeque::new(),
+        }
+    }
+
+    /// Sleep until input arrives, a background thread wakes the loop or `deadline` passes
+    /// (None: no deadline). Returns at once while input is queued.
+    pub fn wait(&mut self, deadline: Option<Instant>) {
+        if !self.input.is_empty() {
+            return;
+        }
+        let wake = match deadline {
+            Some(deadline) => {
+                match self
+                    .rx
+                    .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                {
+                    Ok(wake) => wake,
+                    Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => return,
+                }
+            }
+            None => match self.rx.recv() {
+                Ok(wake) => wake,
+                Err(_) => return,
+            },
+        };
+        self.queue(wake);
+        // Results often come in bursts; one loop iteration handles them all.
+        while let Ok(wake) = self.rx.try_recv() {
+            self.queue(wake);
+        }
+    }
+
+    /// Next terminal input event without blocking.
+    pub fn next_input(&mut self) -> std::io::Result<Option<Event>> {
+        while let Ok(wake) = self.rx.try_recv() {
+            self.queue(wake);
+        }
+        self.input.pop_front().transpose()
+    }
+
+    /// Take the queued presses of `key` that directly follow (a held key's repeats).
+    pub fn take_repeats(&mut self, key: KeyEvent) -> Vec<KeyEvent> {
+        while let Ok(wake) = self.rx.try_recv() {
+            self.queue(wake);
+        }
+        let mut repeats = Vec::new();
+        while let Some(Ok(Event::Key(next))) = self.input.front()
+            && next.code == key.code
+            && next.modifiers == key.modifiers
+            && next.kind != KeyEventKind::Release
+        {
+            repeats.push(*next);
+            self.input.pop_front();
+        }
+        repeats
+    }
+
+    fn queue(&mut self, wake: Wake) {
+        if let Wake::Input(ev) = wake {
+            self.input.push_back(ev);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Mouse wheel (`wheel_single`, `wheel_tile`, `wheel_step`, `wheel_reverse`).
+//!
+//! The wheel is off by default: capturing the mouse takes text selection away from the
+//! terminal, so mouse capture is only turned on when a mode is set. In Single mode the wheel
+//! steps through the images, pans a zoomed view, or (`auto`) pans when zoomed and steps
+//! otherwise; in Tile mode it moves the cursor by rows or flips pages.
+
+use ratatui::crossterm::event::MouseEventKind;
+
+use crate::config::Config;
+use crate::fit::ViewMode;
+
+/// What the wheel does in a view mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WheelMode {
+    Off,
+    /// Pan when the view can pan (zoomed or 1:1), otherwise navigate.
+    Auto,
+    /// Next / previous image (Tile mode: tile row).
+    Navigate,
+    /// Pan the zoomed view up / down.
+    Pan,
+    /// Next / previous tile page.
+    Page,
+}
+
+impl WheelMode {
+    /// Parse `off`/`auto`/`navigate`/`pan`/`page`; unknown values mean `off`.
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto" => WheelMode::Auto,
+            "navigate" | "nav" => WheelMode::Navigate,
+            "pan" => WheelMode::Pan,
+            "page" => WheelMode::Page,
+            _ => WheelMode::Off,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Wheel {
+    single: WheelMode,
+    tile: WheelMode,
+    /// Steps per wheel notch, negative when reversed.
+    step: i32,
+}
+
+impl Wheel {
+    pub fn from_config(config: &Config) -> Self {
+        let step = config.wheel_step as i32;
+        Self {
+            single: WheelMode::parse(&config.wheel_single),
+            tile: WheelMode::parse(&config.wheel_tile),
+            step: if config.wheel_reverse { -step } else { step },
+        }
+    }
+
+    /// Whether the mouse has to be captured.
+    pub fn enabled(&self) -> bool {
+        self.single != WheelMode::Off || self.tile != WheelMode::Off
+    }
+
+    pub fn mode(&self, view_mode: ViewMode) -> WheelMode {
+        match view_mode {
+            ViewMode::Single => self.single,
+            ViewMode::Tile => self.tile,
+        }
+    }
+
+    /// Steps of a wheel notch, positive for down (next image, pan down); None for other mouse
+    /// events.
+    pub fn delta(&self, kind: MouseEventKind) -> Option<i32> {
+        match kind {
+            MouseEventKind::ScrollDown => Some(self.step),
+            MouseEventKind::ScrollUp => Some(-self.step),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wheel_from_config() {
+        let wheel = Wheel::from_config(&Config::default());
+        assert!(!wheel.enabled());
+
+        let config = Config {
+            wheel_single: "Auto".to_string(),
+            wheel_tile: "bogus".to_string(),
+            wheel_step: 3,
+            wheel_reverse: true,
+            ..Default::default()
+        };
+        let wheel = Wheel::from_config(&config);
+        assert!(wheel.enabled());
+        assert_eq!(wheel.mode(ViewMode::Single), WheelMode::Auto);
+        assert_eq!(wheel.mode(ViewMode::Tile), WheelMode::Off);
+        assert_eq!(wheel.delta(MouseEventKind::ScrollDown), Some(-3));
+        assert_eq!(wheel.delta(MouseEventKind::Moved), None);
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Wipe comparison against a reference image (`:wipe`, `,` / `.` to move the split).
+//!
+//! The image is shown left of the split and the reference right of it, like the sliders of
+//! web image comparisons. Like `:blend`, the view renders through a virtual path,
+//! `<image>\0wipe<percent>\0<reference>` (`VirtualPath::Wipe`), and the worker scales the reference to the image size
+//! and joins both halves with a dashed divider.
+
+use image::DynamicImage;
+
+use crate::virtual_path::scaled_to;
+
+/// Split position of a new wipe, in percent of the width.
+pub const DEFAULT_POSITION: u8 = 50;
+
+/// Split movement per `,` / `.` press, in percent of the width.
+pub const POSITION_STEP: i32 = 5;
+
+/// Dash length of the divider in pixels.
+const DASH: u32 = 4;
+
+/// `image` left of `position` percent and `reference` (scaled to the image size) right of it.
+pub fn wipe(image: &DynamicImage, reference: &DynamicImage, position: u8) -> DynamicImage {
+    let mut out = image.to_rgba8();
+    let (width, height) = out.dimensions();
+    let right = scaled_to(reference, width, height);
+    let split = (u64::from(width) * u64::from(position.min(100)) / 100) as u32;
+    for y in 0..height {
+        for x in split..width {
+            out.put_pixel(x, y, *right.get_pixel(x, y));
+        }
+        if split > 0 && split < width {
+            let value = if (y / DASH).is_multiple_of(2) { 255 } else { 0 };
+            out.put_pixel(split, y, image::Rgba([value, value, value, 255]));
+        }
+    }
+    DynamicImage::ImageRgba8(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    #[test]
+    fn test_wipe_halves() {
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_pixel(10, 8, Rgba([1, 2, 3, 255])));
+        let reference = DynamicImage::ImageRgba8(RgbaImage::from_pixel(5, 4, Rgba([9, 9, 9, 255])));
+        let out = wipe(&image, &reference, 30).to_rgba8();
+        assert_eq!(out.get_pixel(2, 0).0, [1, 2, 3, 255]);
+        assert_eq!(out.get_pixel(3, 0).0, [255, 255, 255, 255]);
+        assert_eq!(out.get_pixel(3, 4).0, [0, 0, 0, 255]);
+        assert_eq!(out.get_pixel(9, 7).0, [9, 9, 9, 255]);
+    }
+}
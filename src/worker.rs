@@ -9,20 +9,62 @@
 //! - encode to Kitty Graphics Protocol chunks for transmission
 //!
 //! Requests are best-effort; newer requests may preempt older ones.
+//!
+//! Animated GIFs are first shown as a still; a separate animation request then decodes every
+//! frame and renders it for the same view (`src/animation.rs` plays them).
+//!
+//! Tile pages are composited as their thumbnails finish: while slow ones are still decoding, the
+//! page so far is sent every `TILE_PROGRESS_INTERVAL` as a partial result.
+//!
+//! The first image can be decoded before the worker exists (`ImageWorker::preload`), while the
+//! terminal is still being queried; the worker picks the result up for its first Single request.
 
 use std::collections::{HashMap, VecDeque};
+use std::io::Read;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
+use anyhow::Context;
 use image::{DynamicImage, RgbaImage};
+use ratatui::layout::Rect;
 
+use crate::adjust::Adjustments;
+use crate::animation::{AnimationFrame, frame_delay};
+use crate::depth::to_display_depth;
+use crate::dicom::WindowLevel;
 use crate::fit::{FitMode, ViewMode};
-use crate::kgp::encode_chunks;
+use crate::layout::DayBand;
+use crate::placeholder::Placeholder;
+use crate::protocol::Protocol;
+use crate::rotation::Rotation;
+use crate::virtual_path::{VirtualPath, fits_limits};
+use crate::zoom::CropRect;
 
 /// Default capacity for the tile thumbnail LRU cache.
-const THUMBNAIL_CACHE_SIZE: usize = 500;
+pub(crate) const THUMBNAIL_CACHE_SIZE: usize = 500;
+
+/// Width in pixels of the bar drawn between tiles from different directories.
+const DIR_SEPARATOR_WIDTH: u32 = 2;
+
+/// Minimum upscale factor (in `Fit` mode) before the optional sharpening pass is applied.
+const UPSCALE_SHARPEN_MIN_SCALE: f64 = 1.5;
+
+/// Maximum downscale factor (display size / source size) before the optional sharpening pass
+/// is applied.
+const DOWNSCALE_SHARPEN_MAX_SCALE: f64 = 0.25;
+
+/// Blur radius of the downscale sharpening pass (fine detail is about one pixel wide).
+const DOWNSCALE_SHARPEN_SIGMA: f32 = 0.6;
+
+/// How often a tile page still waiting for thumbnails is sent as it stands.
+const TILE_PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Encoded bytes all frames of one animation may take; larger animations stay still.
+const ANIMATION_MAX_BYTES: usize = 256 * 1024 * 1024;
 
 /// Cache key for tile thumbnails.
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
@@ -35,6 +77,8 @@ struct ThumbnailKey {
     height: u32,
     /// Resize filter type ID.
     filter_id: u8,
+    /// User rotation applied before resize.
+    rotation: Rotation,
 }
 
 fn filter_cache_id(filter: image::imageops::FilterType) -> u8 {
@@ -48,21 +92,34 @@ fn filter_cache_id(filter: image::imageops::FilterType) -> u8 {
 }
 
 /// LRU cache for tile thumbnails
-struct ThumbnailCache {
+pub(crate) struct ThumbnailCache {
     cache: HashMap<ThumbnailKey, Arc<RgbaImage>>,
     order: VecDeque<ThumbnailKey>,
     capacity: usize,
+    /// Pixel bytes held (debug status).
+    bytes: usize,
 }
 
 impl ThumbnailCache {
-    fn new(capacity: usize) -> Self {
+    pub(crate) fn new(capacity: usize) -> Self {
         Self {
             cache: HashMap::with_capacity(capacity),
             order: VecDeque::with_capacity(capacity),
             capacity,
+            bytes: 0,
         }
     }
 
+    pub(crate) fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.cache.clear();
+        self.order.clear();
+        self.bytes = 0;
+    }
+
     fn get(&mut self, key: &ThumbnailKey) -> Option<Arc<RgbaImage>> {
         let img = self.cache.get(key)?;
         if !matches!(self.order.back(), Some(k) if k == key) {
@@ -74,7 +131,9 @@ impl ThumbnailCache {
     }
 
     fn insert(&mut self, key: ThumbnailKey, img: Arc<RgbaImage>) {
-        if self.cache.contains_key(&key) {
+        self.bytes += img.as_raw().len();
+        if let Some(old) = self.cache.get(&key) {
+            self.bytes -= old.as_raw().len();
             // Update existing entry and move to back of LRU order
             if !matches!(self.order.back(), Some(k) if k == &key) {
                 self.order.retain(|k| k != &key);
@@ -86,8 +145,9 @@ impl ThumbnailCache {
         // Evict oldest if at capacity
         if self.cache.len() >= self.capacity
             && let Some(oldest) = self.order.pop_front()
+            && let Some(evicted) = self.cache.remove(&oldest)
         {
-            self.cache.remove(&oldest);
+            self.bytes -= evicted.as_raw().len();
         }
         self.order.push_back(key.clone());
         self.cache.insert(key, img);
@@ -106,8 +166,12 @@ pub struct ImageRequest {
     pub kgp_id: u32,
     /// Whether running inside tmux.
     pub is_tmux: bool,
+    /// Encode KGP data raw for file transmission.
+    pub file_transfer: bool,
     /// Zlib compression level (None = disabled).
     pub compress_level: Option<u32>,
+    /// Output protocol the result is encoded for.
+    pub protocol: Protocol,
     /// Max pixels for tmux+kitty compatibility.
     pub tmux_kitty_max_pixels: u64,
     /// Enable trace logging to /tmp/stiv_worker.log.
@@ -116,65 +180,292 @@ pub struct ImageRequest {
     pub resize_filter: image::imageops::FilterType,
     /// View mode (Single or Tile).
     pub view_mode: ViewMode,
-    /// Paths for tile mode (None in Single mode).
-    pub tile_paths: Option<Vec<PathBuf>>,
-    /// Grid dimensions for tile mode (cols, rows).
-    pub tile_grid: Option<(usize, usize)>,
+    /// Paths (with their rotations) for tile mode (None in Single mode).
+    pub tile_paths: Option<Vec<(PathBuf, Rotation)>>,
+    /// Tile rectangles in cells, relative to the canvas, for tile mode (`src/layout.rs`).
+    pub tile_rects: Option<Vec<Rect>>,
     /// Cell size in pixels (width, height) for padding.
     pub cell_size: Option<(u16, u16)>,
+    /// Capture day of each tile, to separate days instead of directories (`group_by_date`;
+    /// Tile mode only).
+    pub tile_days: Option<Vec<Option<String>>>,
+    /// Labeled separator rows of a page grouped by day (`layout::grouped_rects`); empty when
+    /// the page keeps the plain grid.
+    pub tile_bands: Vec<DayBand>,
+    /// Leave a cell row under each thumbnail for its file name (Tile mode only).
+    pub tile_labels: bool,
     /// Resize filter for Tile mode.
     pub tile_filter: image::imageops::FilterType,
+    /// Unsharp-mask strength for upscaled images in `Fit` mode (0 = disabled).
+    pub upscale_sharpen: f32,
+    /// Unsharp-mask strength for images shrunk 4x or more, in both modes (0 = disabled).
+    pub downscale_sharpen: f32,
+    /// Percentile clipped at each end when converting high bit-depth images (0 = plain scaling).
+    pub bit_depth_stretch: f32,
+    /// Decoder allocation and dimension limits.
+    pub limits: image::Limits,
+    /// Zoomed region of the rotated image (Single mode only).
+    pub crop: Option<CropRect>,
+    /// User rotation applied right after decode (Single mode only).
+    pub rotation: Rotation,
+    /// DICOM window/level adjustment (Single mode only).
+    pub window: WindowLevel,
+    /// Color adjustments applied after resize (Single mode only).
+    pub adjust: Adjustments,
+}
+
+/// Time spent in each worker stage for one render (shown in the debug status).
+/// For tile pages, `decode` covers the whole composite (thumbnail decode and resize).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StageTimings {
+    pub decode: Duration,
+    pub resize: Duration,
+    pub encode: Duration,
 }
 
 pub struct ImageResult {
     pub path: PathBuf,
     pub target: (u32, u32),
     pub fit_mode: FitMode,
+    pub crop: Option<CropRect>,
+    pub rotation: Rotation,
+    pub window: WindowLevel,
+    pub adjust: Adjustments,
+    /// Blurred preview of the whole image (prefetch results only; the main worker sends it
+    /// early through `ImageWorker::try_recv_placeholder`).
+    pub placeholder: Option<Arc<Placeholder>>,
+    /// Size of the (rotated) source image.
     pub original_size: (u32, u32),
     pub actual_size: (u32, u32),
     pub encoded_chunks: Arc<Vec<Vec<u8>>>,
+    pub timings: StageTimings,
+    /// Tile page with thumbnails still decoding; shown but not cached (main worker only).
+    pub partial: bool,
+}
+
+/// Every frame of an animated image, rendered for the view in the request.
+pub struct AnimationResult {
+    pub path: PathBuf,
+    pub target: (u32, u32),
+    pub fit_mode: FitMode,
+    pub crop: Option<CropRect>,
+    pub rotation: Rotation,
+    pub window: WindowLevel,
+    pub adjust: Adjustments,
+    pub frames: Vec<AnimationFrame>,
+}
+
+/// Tile page composited but not yet encoded.
+pub(crate) struct TileComposite {
+    image: DynamicImage,
+    actual_size: (u32, u32),
+    /// Time spent decoding, resizing and compositing the tiles.
+    elapsed: Duration,
+    /// Some thumbnails are still decoding.
+    partial: bool,
+}
+
+/// A tile whose thumbnail is not cached yet.
+struct TileInfo {
+    path: PathBuf,
+    rotation: Rotation,
+    tile_x: u32,
+    tile_y: u32,
+    inner_w: u32,
+    inner_h: u32,
+}
+
+/// Placeholder sent right after a fresh decode: (path, rotation, placeholder).
+pub type PlaceholderResult = (PathBuf, Rotation, Arc<Placeholder>);
+
+/// Decoded image kept by the worker for re-renders: (path, window, image).
+type Decoded = (PathBuf, WindowLevel, Arc<DynamicImage>);
+
+/// First image being decoded ahead of the worker (`ImageWorker::preload`).
+pub struct Preload {
+    path: PathBuf,
+    window: WindowLevel,
+    rx: Receiver<Decoded>,
+}
+
+/// Memory held by a worker thread's caches, readable from the UI thread (debug status).
+#[derive(Debug, Default)]
+pub struct CacheUsage {
+    decoded: AtomicUsize,
+    thumbnails: AtomicUsize,
+}
+
+impl CacheUsage {
+    pub fn decoded(&self) -> usize {
+        self.decoded.load(Ordering::Relaxed)
+    }
+
+    pub fn thumbnails(&self) -> usize {
+        self.thumbnails.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_thumbnails(&self, bytes: usize) {
+        self.thumbnails.store(bytes, Ordering::Relaxed);
+    }
+}
+
+/// Decode cache and tile thumbnails of the worker thread.
+struct WorkerCaches {
+    decoded: Option<Decoded>,
+    thumbnails: ThumbnailCache,
+    usage: Arc<CacheUsage>,
+}
+
+impl WorkerCaches {
+    fn clear(&mut self) {
+        self.decoded = None;
+        self.thumbnails.clear();
+        self.publish();
+    }
+
+    fn publish(&self) {
+        let decoded = self
+            .decoded
+            .as_ref()
+            .map_or(0, |(_, _, img)| img.as_bytes().len());
+        self.usage.decoded.store(decoded, Ordering::Relaxed);
+        self.usage.set_thumbnails(self.thumbnails.bytes());
+    }
+}
+
+enum WorkerMessage {
+    Job(Box<Job>),
+    /// Drop the decode and thumbnail caches (`:cache clear`).
+    ClearCaches,
+    /// Write a crop to a file (`:w`); never preempted by renders.
+    Save(Box<SaveRequest>),
+}
+
+/// The zoomed region (or the whole image) written to a new file (`:w`).
+pub struct SaveRequest {
+    pub path: PathBuf,
+    pub window: WindowLevel,
+    pub rotation: Rotation,
+    /// Region of the rotated image (None = whole image).
+    pub crop: Option<CropRect>,
+    pub bit_depth_stretch: f32,
+    pub limits: image::Limits,
+    pub dest: PathBuf,
+    /// Replace `dest` if it exists (`:w!`).
+    pub force: bool,
+    pub reply: Sender<SaveResult>,
+}
+
+/// Size of the written crop, or why it could not be written.
+pub type SaveResult = anyhow::Result<(u32, u32)>;
+
+/// Work item; a newer one preempts the current one.
+enum Job {
+    Render(ImageRequest),
+    /// Render every frame of an animated image (Single mode).
+    Animate(ImageRequest),
 }
 
 pub struct ImageWorker {
-    request_tx: Sender<ImageRequest>,
+    request_tx: Sender<WorkerMessage>,
     result_rx: Receiver<ImageResult>,
+    placeholder_rx: Receiver<PlaceholderResult>,
+    animation_rx: Receiver<AnimationResult>,
+    failed_rx: Receiver<PathBuf>,
+    usage: Arc<CacheUsage>,
     _handle: JoinHandle<()>,
 }
 
 impl ImageWorker {
-    pub fn new(tile_threads: usize) -> Self {
-        let (request_tx, request_rx) = mpsc::channel::<ImageRequest>();
+    /// Start decoding `path` on a background thread, before the target size and output
+    /// protocol are known. Pass the result to `new`.
+    pub fn preload(
+        path: PathBuf,
+        window: WindowLevel,
+        limits: image::Limits,
+        bit_depth_stretch: f32,
+    ) -> Preload {
+        let (tx, rx) = mpsc::channel();
+        let preload = Preload {
+            path: path.clone(),
+            window,
+            rx,
+        };
+        thread::spawn(move || {
+            if let Some(img) = Self::decode_image(&path, window, &limits) {
+                let img = to_display_depth(img, bit_depth_stretch);
+                let _ = tx.send((path, window, Arc::new(img)));
+            }
+        });
+        preload
+    }
+
+    pub fn new(tile_threads: usize, preload: Option<Preload>) -> Self {
+        let (request_tx, request_rx) = mpsc::channel::<WorkerMessage>();
         let (result_tx, result_rx) = mpsc::channel::<ImageResult>();
+        let (placeholder_tx, placeholder_rx) = mpsc::channel::<PlaceholderResult>();
+        let (animation_tx, animation_rx) = mpsc::channel::<AnimationResult>();
+        let (failed_tx, failed_rx) = mpsc::channel::<PathBuf>();
+        let usage = Arc::new(CacheUsage::default());
+        let caches = WorkerCaches {
+            decoded: None,
+            thumbnails: ThumbnailCache::new(THUMBNAIL_CACHE_SIZE),
+            usage: Arc::clone(&usage),
+        };
 
         let handle = thread::spawn(move || {
-            Self::worker_loop(request_rx, result_tx, tile_threads);
+            Self::worker_loop(
+                request_rx,
+                result_tx,
+                placeholder_tx,
+                animation_tx,
+                failed_tx,
+                tile_threads,
+                preload,
+                caches,
+            );
         });
 
         Self {
             request_tx,
             result_rx,
+            placeholder_rx,
+            animation_rx,
+            failed_rx,
+            usage,
             _handle: handle,
         }
     }
 
+    /// Latest queued job (or `current` if none is queued). Cache purges queued in between
+    /// are applied on the way.
     fn drain_to_latest(
-        request_rx: &Receiver<ImageRequest>,
-        mut current: ImageRequest,
-    ) -> ImageRequest {
-        while let Ok(newer) = request_rx.try_recv() {
-            current = newer;
+        request_rx: &Receiver<WorkerMessage>,
+        caches: &mut WorkerCaches,
+        mut current: Option<Job>,
+    ) -> Option<Job> {
+        while let Ok(message) = request_rx.try_recv() {
+            match message {
+                WorkerMessage::Job(newer) => current = Some(*newer),
+                WorkerMessage::ClearCaches => caches.clear(),
+                WorkerMessage::Save(req) => Self::write_crop(&req, caches),
+            }
         }
         current
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn worker_loop(
-        request_rx: Receiver<ImageRequest>,
+        request_rx: Receiver<WorkerMessage>,
         result_tx: Sender<ImageResult>,
+        placeholder_tx: Sender<PlaceholderResult>,
+        animation_tx: Sender<AnimationResult>,
+        failed_tx: Sender<PathBuf>,
         tile_threads: usize,
+        mut preload: Option<Preload>,
+        mut caches: WorkerCaches,
     ) {
-        let mut cache: Option<(PathBuf, Arc<DynamicImage>)> = None;
-        let mut thumbnail_cache = ThumbnailCache::new(THUMBNAIL_CACHE_SIZE);
-        let mut pending: Option<ImageRequest> = None;
+        let mut pending: Option<Job> = None;
 
         // Create dedicated thread pool for tile processing
         let tile_pool = rayon::ThreadPoolBuilder::new()
@@ -184,32 +475,55 @@ impl ImageWorker {
 
         loop {
             // Get next request: from pending or wait for new one
-            let req = if let Some(p) = pending.take() {
+            let job = if let Some(p) = pending.take() {
                 p
             } else {
                 match request_rx.recv() {
-                    Ok(r) => r,
+                    Ok(WorkerMessage::Job(job)) => *job,
+                    Ok(WorkerMessage::ClearCaches) => {
+                        caches.clear();
+                        continue;
+                    }
+                    Ok(WorkerMessage::Save(req)) => {
+                        Self::write_crop(&req, &mut caches);
+                        continue;
+                    }
                     Err(_) => break,
                 }
             };
 
             // Drain any pending requests, keep only the latest
-            let req = Self::drain_to_latest(&request_rx, req);
+            let req = match Self::drain_to_latest(&request_rx, &mut caches, Some(job)) {
+                Some(Job::Render(req)) => req,
+                Some(Job::Animate(req)) => {
+                    if let Some(result) =
+                        Self::process_animation(&req, &mut pending, &request_rx, &mut caches)
+                    {
+                        let _ = animation_tx.send(result);
+                        crate::wake::wake();
+                    }
+                    continue;
+                }
+                None => continue,
+            };
 
             match req.view_mode {
                 ViewMode::Single => {
                     Self::process_single_request(
                         &req,
-                        &mut cache,
+                        &mut caches,
+                        &mut preload,
                         &mut pending,
                         &request_rx,
                         &result_tx,
+                        &placeholder_tx,
+                        &failed_tx,
                     );
                 }
                 ViewMode::Tile => {
                     Self::process_tile_request(
                         &req,
-                        &mut thumbnail_cache,
+                        &mut caches,
                         &tile_pool,
                         &mut pending,
                         &request_rx,
@@ -217,86 +531,111 @@ impl ImageWorker {
                     );
                 }
             }
+            caches.publish();
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn process_single_request(
         req: &ImageRequest,
-        cache: &mut Option<(PathBuf, Arc<DynamicImage>)>,
-        pending: &mut Option<ImageRequest>,
-        request_rx: &Receiver<ImageRequest>,
+        caches: &mut WorkerCaches,
+        preload: &mut Option<Preload>,
+        pending: &mut Option<Job>,
+        request_rx: &Receiver<WorkerMessage>,
         result_tx: &Sender<ImageResult>,
+        placeholder_tx: &Sender<PlaceholderResult>,
+        failed_tx: &Sender<PathBuf>,
     ) {
+        if req.protocol == Protocol::Kitty
+            && req.rotation == Rotation::None
+            && req.crop.is_none()
+            && req.adjust.is_none()
+            && let Some(result) = Self::png_passthrough(
+                &req.path,
+                req.target,
+                req.fit_mode,
+                req.kgp_id,
+                req.is_tmux,
+                req.file_transfer,
+                req.tmux_kitty_max_pixels,
+                &req.limits,
+                req.window,
+            )
+        {
+            let _ = result_tx.send(result);
+            crate::wake::wake();
+            return;
+        }
+
         // Decode (with cache) - Arc clone is cheap (reference count only)
-        let decode_start = std::time::Instant::now();
-        let decoded: Arc<DynamicImage> = if let Some((cached_path, img)) = cache.as_ref() {
-            if cached_path == &req.path {
-                Arc::clone(img)
-            } else {
-                match Self::decode_image(&req.path) {
-                    Some(img) => {
-                        let arc_img = Arc::new(img);
-                        *cache = Some((req.path.clone(), Arc::clone(&arc_img)));
-                        arc_img
-                    }
-                    None => return,
-                }
-            }
-        } else {
-            match Self::decode_image(&req.path) {
+        let decode_start = Instant::now();
+        // Wait for the startup decode if it is for this image (it is dropped otherwise); it
+        // counts as fresh for the placeholder.
+        let preloaded = preload
+            .take()
+            .filter(|p| p.path == req.path && p.window == req.window)
+            .and_then(|p| p.rx.recv().ok());
+        let fresh_preload = preloaded.is_some();
+        if preloaded.is_some() {
+            caches.decoded = preloaded;
+        }
+        let cached = caches
+            .decoded
+            .as_ref()
+            .filter(|(path, window, _)| path == &req.path && *window == req.window)
+            .map(|(_, _, img)| Arc::clone(img));
+        let fresh = cached.is_none() || fresh_preload;
+        let decoded: Arc<DynamicImage> = match cached {
+            Some(img) => img,
+            None => match Self::decode_image(&req.path, req.window, &req.limits) {
                 Some(img) => {
+                    let img = to_display_depth(img, req.bit_depth_stretch);
                     let arc_img = Arc::new(img);
-                    *cache = Some((req.path.clone(), Arc::clone(&arc_img)));
+                    caches.decoded = Some((req.path.clone(), req.window, Arc::clone(&arc_img)));
                     arc_img
                 }
-                None => return,
-            }
+                None => {
+                    let _ = failed_tx.send(req.path.clone());
+                    crate::wake::wake();
+                    return;
+                }
+            },
         };
         let decode_elapsed = decode_start.elapsed();
 
         // Check for newer request after decode (most expensive step)
-        if let Ok(newer) = request_rx.try_recv() {
-            *pending = Some(Self::drain_to_latest(request_rx, newer));
+        if let Some(newer) = Self::drain_to_latest(request_rx, caches, None) {
+            *pending = Some(newer);
             return; // Abandon current work
         }
 
-        let (orig_w, orig_h) = (decoded.width(), decoded.height());
-        let (max_w, max_h) = req.target;
-        let (mut target_w, mut target_h) =
-            Self::compute_target((orig_w, orig_h), (max_w, max_h), req.fit_mode);
+        // Rotation comes first: crop coordinates refer to the rotated image.
+        let rotated = req.rotation.apply(&decoded);
+        let (orig_w, orig_h) = (rotated.width(), rotated.height());
 
-        // Apply max pixels limit (for tmux+kitty compatibility).
-        // In `Fit` mode we allow larger images (may be slower / unsupported in some setups).
-        if req.fit_mode != FitMode::Fit {
-            let max_pixels = req.tmux_kitty_max_pixels;
-            let target_pixels = (target_w as u64).saturating_mul(target_h as u64);
-            if target_pixels > max_pixels {
-                let down = (max_pixels as f64 / target_pixels as f64).sqrt();
-                target_w = (target_w as f64 * down).floor().max(1.0) as u32;
-                target_h = (target_h as f64 * down).floor().max(1.0) as u32;
-            }
+        // Let the app show a blurred preview while resize/encode/transmit are still running.
+        if fresh {
+            let placeholder = Placeholder::from_image(&rotated);
+            let _ = placeholder_tx.send((req.path.clone(), req.rotation, Arc::new(placeholder)));
+            crate::wake::wake();
         }
 
-        // Resize - use Cow to avoid clone when no resize needed
-        use std::borrow::Cow;
-        let resize_start = std::time::Instant::now();
-        let resized: Cow<'_, DynamicImage> = if target_w != orig_w || target_h != orig_h {
-            Cow::Owned(decoded.resize(target_w, target_h, req.resize_filter))
-        } else {
-            Cow::Borrowed(&*decoded)
-        };
+        let resize_start = Instant::now();
+        let (source, fit_mode, region) =
+            Self::apply_crop(rotated, req.crop, req.fit_mode, req.target);
+        let resized = Self::resize_for_display(req, source, fit_mode, region);
         let actual_size = (resized.width(), resized.height());
         let resize_elapsed = resize_start.elapsed();
 
         // Check for newer request after resize
-        if let Ok(newer) = request_rx.try_recv() {
-            *pending = Some(Self::drain_to_latest(request_rx, newer));
+        if let Some(newer) = Self::drain_to_latest(request_rx, caches, None) {
+            *pending = Some(newer);
             return;
         }
 
         // Encode
-        let encode_start = std::time::Instant::now();
-        let encoded_chunks = encode_chunks(&resized, req.kgp_id, req.is_tmux, req.compress_level);
+        let encode_start = Instant::now();
+        let encoded_chunks = Self::encode_for_display(req, &resized);
         let encode_elapsed = encode_start.elapsed();
 
         if req.trace_worker {
@@ -316,8 +655,8 @@ impl ImageWorker {
                     encode_elapsed,
                     orig_w,
                     orig_h,
-                    max_w,
-                    max_h,
+                    req.target.0,
+                    req.target.1,
                     actual_size.0,
                     actual_size.1
                 );
@@ -329,59 +668,136 @@ impl ImageWorker {
             path: req.path.clone(),
             target: req.target,
             fit_mode: req.fit_mode,
+            crop: req.crop,
+            rotation: req.rotation,
+            window: req.window,
+            adjust: req.adjust,
+            placeholder: None,
             original_size: (orig_w, orig_h),
             actual_size,
             encoded_chunks: Arc::new(encoded_chunks),
+            timings: StageTimings {
+                decode: decode_elapsed,
+                resize: resize_elapsed,
+                encode: encode_elapsed,
+            },
+            partial: false,
         });
+        crate::wake::wake();
     }
 
     fn process_tile_request(
         req: &ImageRequest,
-        thumbnail_cache: &mut ThumbnailCache,
+        caches: &mut WorkerCaches,
         tile_pool: &rayon::ThreadPool,
-        pending: &mut Option<ImageRequest>,
-        request_rx: &Receiver<ImageRequest>,
+        pending: &mut Option<Job>,
+        request_rx: &Receiver<WorkerMessage>,
         result_tx: &Sender<ImageResult>,
     ) {
-        let Some(ref tile_paths) = req.tile_paths else {
-            return;
+        let mut progress = |partial: TileComposite| {
+            let _ = result_tx.send(Self::encode_tile_page(req, partial));
+            crate::wake::wake();
         };
-        let Some(grid) = req.tile_grid else {
+        let Some(composite) =
+            Self::composite_tile_page(req, &mut caches.thumbnails, tile_pool, Some(&mut progress))
+        else {
             return;
         };
 
-        // Composite tile images (cursor is drawn separately via ANSI)
-        let Some((composite, actual_size)) = Self::composite_tile_images(
+        // Check for newer request
+        if let Some(newer) = Self::drain_to_latest(request_rx, caches, None) {
+            *pending = Some(newer);
+            return;
+        }
+
+        let _ = result_tx.send(Self::encode_tile_page(req, composite));
+        crate::wake::wake();
+    }
+
+    /// Composite the tiles of a Tile-mode request (cursor is drawn separately via ANSI).
+    /// `progress` receives the page so far while thumbnails are still decoding.
+    /// Shared with the prefetch worker.
+    pub(crate) fn composite_tile_page(
+        req: &ImageRequest,
+        thumbnail_cache: &mut ThumbnailCache,
+        tile_pool: &rayon::ThreadPool,
+        mut progress: Option<&mut dyn FnMut(TileComposite)>,
+    ) -> Option<TileComposite> {
+        let tile_paths = req.tile_paths.as_ref()?;
+        let rects = req.tile_rects.as_ref()?;
+        let composite_start = Instant::now();
+        let mut on_partial = |canvas: &RgbaImage| {
+            if let Some(progress) = progress.as_mut() {
+                progress(TileComposite {
+                    image: DynamicImage::ImageRgba8(canvas.clone()),
+                    actual_size: canvas.dimensions(),
+                    elapsed: composite_start.elapsed(),
+                    partial: true,
+                });
+            }
+        };
+        let (image, actual_size) = Self::composite_tile_images(
             tile_paths,
-            grid,
+            rects,
             req.target,
             req.cell_size,
             req.tile_filter,
+            req.downscale_sharpen,
+            req.bit_depth_stretch,
+            req.tile_days.as_deref(),
+            &req.tile_bands,
+            req.tile_labels,
+            &req.limits,
             thumbnail_cache,
             tile_pool,
             req.trace_worker,
-        ) else {
-            return;
-        };
-
-        // Check for newer request
-        if let Ok(newer) = request_rx.try_recv() {
-            *pending = Some(Self::drain_to_latest(request_rx, newer));
-            return;
-        }
+            &mut on_partial,
+        )?;
+        Some(TileComposite {
+            image,
+            actual_size,
+            elapsed: composite_start.elapsed(),
+            partial: false,
+        })
+    }
 
-        // Encode
-        let encoded_chunks = encode_chunks(&composite, req.kgp_id, req.is_tmux, req.compress_level);
+    /// Encode a composited tile page into its result.
+    pub(crate) fn encode_tile_page(req: &ImageRequest, composite: TileComposite) -> ImageResult {
+        let TileComposite {
+            image: composite,
+            actual_size,
+            elapsed: composite_elapsed,
+            partial,
+        } = composite;
+        let encode_start = Instant::now();
+        let encoded_chunks = req.protocol.encode(
+            &composite,
+            req.kgp_id,
+            req.is_tmux,
+            req.compress_level,
+            req.file_transfer,
+        );
+        let encode_elapsed = encode_start.elapsed();
 
-        // Send result
-        let _ = result_tx.send(ImageResult {
+        ImageResult {
             path: req.path.clone(),
             target: req.target,
             fit_mode: req.fit_mode,
+            crop: None,
+            rotation: Rotation::None,
+            window: WindowLevel::default(),
+            adjust: Adjustments::default(),
+            placeholder: None,
             original_size: actual_size,
             actual_size,
             encoded_chunks: Arc::new(encoded_chunks),
-        });
+            timings: StageTimings {
+                decode: composite_elapsed,
+                resize: Duration::ZERO,
+                encode: encode_elapsed,
+            },
+            partial,
+        }
     }
 
     pub fn compute_target(orig: (u32, u32), max: (u32, u32), fit_mode: FitMode) -> (u32, u32) {
@@ -413,30 +829,253 @@ impl ImageWorker {
                     (orig_h as f64 * scale).floor().max(1.0) as u32,
                 )
             }
+            // No scaling; `apply_crop` already cut larger images to the viewport.
+            FitMode::Actual => (orig_w.min(max_w), orig_h.min(max_h)),
+        }
+    }
+
+    /// Apply an unsharp mask when `Fit` mode enlarged the image significantly.
+    ///
+    /// The blur radius follows the upscale factor, since edges get smeared over more pixels
+    /// the further a small image is enlarged.
+    fn sharpen_upscaled(
+        img: std::borrow::Cow<'_, DynamicImage>,
+        orig_w: u32,
+        fit_mode: FitMode,
+        strength: f32,
+    ) -> std::borrow::Cow<'_, DynamicImage> {
+        if fit_mode != FitMode::Fit || strength <= 0.0 || orig_w == 0 {
+            return img;
+        }
+        let scale = f64::from(img.width()) / f64::from(orig_w);
+        if scale < UPSCALE_SHARPEN_MIN_SCALE {
+            return img;
+        }
+        let sigma = (scale as f32 * 0.5).clamp(0.5, 3.0);
+        std::borrow::Cow::Owned(sharpen(&img, strength, sigma))
+    }
+
+    /// Apply an unsharp mask when the image was shrunk to a quarter of its width or less, where
+    /// thin lines and text turn mushy.
+    fn sharpen_downscaled(
+        img: std::borrow::Cow<'_, DynamicImage>,
+        orig_w: u32,
+        strength: f32,
+    ) -> std::borrow::Cow<'_, DynamicImage> {
+        if strength <= 0.0 || orig_w == 0 {
+            return img;
+        }
+        let scale = f64::from(img.width()) / f64::from(orig_w);
+        if scale > DOWNSCALE_SHARPEN_MAX_SCALE {
+            return img;
+        }
+        std::borrow::Cow::Owned(sharpen(&img, strength, DOWNSCALE_SHARPEN_SIGMA))
+    }
+
+    /// Aspect ratio (width / height) of an image as decoded, or 1 if it cannot be read. Plain
+    /// files are measured from their header; RAW files (whose TIFF header describes the sensor
+    /// data, not the preview shown) and everything with its own decoder are decoded.
+    pub fn image_aspect(path: &std::path::Path, limits: &image::Limits) -> f64 {
+        let header = (!crate::raw::is_raw(path))
+            .then(|| {
+                let mut reader = image::ImageReader::open(path)
+                    .ok()?
+                    .with_guessed_format()
+                    .ok()?;
+                reader.limits(limits.clone());
+                Some(reader)
+            })
+            .flatten()
+            .filter(|reader| reader.format().is_some())
+            .and_then(|reader| reader.into_dimensions().ok());
+        // An image over the limits is not shown, so its size is not worth a decode either.
+        if let Some((w, h)) = header
+            && limits.check_dimensions(w, h).is_err()
+        {
+            return 1.0;
+        }
+        let size = header.or_else(|| {
+            Self::decode_image(path, WindowLevel::default(), limits)
+                .map(|img| (img.width(), img.height()))
+        });
+        size.filter(|&(w, h)| w > 0 && h > 0)
+            .map_or(1.0, |(w, h)| f64::from(w) / f64::from(h))
+    }
+
+    /// Decode an image file, archive member, TIFF page, spread, blend, wipe, diff or split
+    /// view (`window` only affects DICOM files; DICOM, HEIF, RAW, SVG and video go through their
+    /// own decoders, and plain CMYK JPEGs are converted to RGB).
+    /// Files exceeding `limits` (dimensions or decoder allocations) fail to decode.
+    pub fn decode_image(
+        path: &std::path::Path,
+        window: WindowLevel,
+        limits: &image::Limits,
+    ) -> Option<DynamicImage> {
+        if let Some(virtual_path) = VirtualPath::parse(path) {
+            let decode = |path| Self::decode_image(path, window, limits);
+            let size = |img: &DynamicImage| (img.width(), img.height());
+            // Canvases larger than either source must fit the limits too.
+            let fits = |(width, height)| fits_limits(limits, width, height);
+            // The outermost composition is split first (a diff wraps a whole spread).
+            return match virtual_path {
+                VirtualPath::Panes { left, right } => {
+                    let (left, right) = (decode(left)?, decode(right)?);
+                    fits(crate::panes::size(size(&left), size(&right)))
+                        .then(|| crate::panes::compose(&left, &right))
+                }
+                VirtualPath::Diff { image, reference } => {
+                    let (image, reference) = (decode(image)?, decode(reference)?);
+                    let width = image.width().max(reference.width());
+                    let height = image.height().max(reference.height());
+                    fits((u64::from(width), u64::from(height)))
+                        .then(|| crate::diff::heatmap(&image, &reference))
+                }
+                // Wipes and blends are drawn at the image's size, which was checked.
+                VirtualPath::Wipe {
+                    image,
+                    reference,
+                    position,
+                } => Some(crate::wipe::wipe(
+                    &decode(image)?,
+                    &decode(reference)?,
+                    position,
+                )),
+                VirtualPath::Blend {
+                    image,
+                    reference,
+                    opacity,
+                } => Some(crate::blend::blend(
+                    &decode(image)?,
+                    &decode(reference)?,
+                    opacity,
+                )),
+                VirtualPath::Spread { left, right } => {
+                    let (left, right) = (decode(left)?, decode(right)?);
+                    fits(crate::spread::size(size(&left), size(&right)))
+                        .then(|| crate::spread::compose(&left, &right))
+                }
+            };
+        }
+        if let Some((file, page)) = crate::pages::split_page_path(path) {
+            return crate::pages::decode_page(file, page, limits);
+        }
+        if crate::dicom::is_dicom(path) {
+            let img = crate::dicom::decode(&Self::read_source(path)?, window)?;
+            limits.check_dimensions(img.width(), img.height()).ok()?;
+            return Some(img);
+        }
+        if crate::heif::is_heif(path) {
+            return crate::heif::decode(&Self::read_source(path)?, limits);
+        }
+        if crate::raw::is_raw(path) {
+            return crate::raw::decode(&Self::read_source(path)?, limits);
+        }
+        if crate::video::is_video(path) {
+            return crate::video::decode(path, limits);
+        }
+        if crate::cmyk::is_jpeg(path) {
+            let bytes = Self::read_source(path)?;
+            if crate::cmyk::is_plain_cmyk(&bytes) {
+                return crate::cmyk::decode(&bytes, limits);
+            }
+            let mut reader = image::ImageReader::new(std::io::Cursor::new(bytes))
+                .with_guessed_format()
+                .ok()?;
+            reader.limits(limits.clone());
+            return reader.decode().ok();
+        }
+        if crate::svg::is_svg(path) {
+            return Self::rasterize_svg(path, 1.0, limits);
+        }
+        if let Some((archive, member)) = crate::archive::split_member_path(path) {
+            let bytes = crate::archive::read_member(archive, &member)?;
+            let mut reader = image::ImageReader::new(std::io::Cursor::new(bytes))
+                .with_guessed_format()
+                .ok()?;
+            // TGA has no signature; fall back to the member's extension.
+            if reader.format().is_none()
+                && let Ok(format) = image::ImageFormat::from_path(&member)
+            {
+                reader.set_format(format);
+            }
+            reader.limits(limits.clone());
+            return reader.decode().ok();
         }
+        // Misnamed files decode by content; the extension is kept when there is no signature.
+        let mut reader = image::ImageReader::open(path)
+            .ok()?
+            .with_guessed_format()
+            .ok()?;
+        reader.limits(limits.clone());
+        reader.decode().ok()
     }
 
-    pub fn decode_image(path: &std::path::Path) -> Option<DynamicImage> {
-        image::ImageReader::open(path).ok()?.decode().ok()
+    /// Bytes of an image file or archive member.
+    fn read_source(path: &std::path::Path) -> Option<Vec<u8>> {
+        match crate::archive::split_member_path(path) {
+            Some((archive, member)) => crate::archive::read_member(archive, &member),
+            None => std::fs::read(path).ok(),
+        }
+    }
+
+    /// Rasterize an SVG file or archive member at `scale` times its document size (relative
+    /// image references of regular files resolve next to the file).
+    fn rasterize_svg(
+        path: &std::path::Path,
+        scale: f32,
+        limits: &image::Limits,
+    ) -> Option<DynamicImage> {
+        let resources_dir = crate::archive::split_member_path(path)
+            .is_none()
+            .then(|| path.parent())
+            .flatten();
+        crate::svg::decode(&Self::read_source(path)?, resources_dir, scale, limits)
+    }
+
+    /// Rasterize an SVG again for the request, so that its (rotated, cropped) source of `src`
+    /// pixels comes out at `target` pixels without resampling. None for other images, and
+    /// when the raster would exceed the decode limits.
+    fn rerasterize_svg(
+        req: &ImageRequest,
+        src: (u32, u32),
+        target: (u32, u32),
+    ) -> Option<DynamicImage> {
+        if !crate::svg::is_svg(&req.path) || src == target || src.0 == 0 {
+            return None;
+        }
+        let scale = target.0 as f32 / src.0 as f32;
+        let raster = Self::rasterize_svg(&req.path, scale, &req.limits)?;
+        let rotated = req.rotation.apply(&raster);
+        let crop = req.crop.map(|c| c.scaled(scale));
+        let (source, _, _) = Self::apply_crop(rotated, crop, req.fit_mode, req.target);
+        Some(source.into_owned())
     }
 
     /// Composite multiple images into a single tile grid image (without cursor).
-    /// Uses thumbnail cache and parallel processing for decode/resize operations.
+    /// Uses thumbnail cache and parallel processing for decode/resize operations; thumbnails
+    /// are copied in as they finish, and `on_partial` gets the canvas every
+    /// `TILE_PROGRESS_INTERVAL` in which one did while others are still decoding.
     #[allow(clippy::too_many_arguments)]
     fn composite_tile_images(
-        paths: &[PathBuf],
-        grid: (usize, usize),
+        paths: &[(PathBuf, Rotation)],
+        rects: &[Rect],
         canvas_size: (u32, u32),
         cell_size: Option<(u16, u16)>,
         filter: image::imageops::FilterType,
+        downscale_sharpen: f32,
+        bit_depth_stretch: f32,
+        days: Option<&[Option<String>]>,
+        bands: &[DayBand],
+        tile_labels: bool,
+        limits: &image::Limits,
         thumbnail_cache: &mut ThumbnailCache,
         tile_pool: &rayon::ThreadPool,
         trace_worker: bool,
+        on_partial: &mut dyn FnMut(&RgbaImage),
     ) -> Option<(DynamicImage, (u32, u32))> {
         use image::{GenericImage, Rgba};
         use rayon::prelude::*;
 
-        let (cols, rows) = grid;
         let (canvas_w, canvas_h) = canvas_size;
 
         // Get cell dimensions for alignment
@@ -444,53 +1083,65 @@ impl ImageWorker {
         let cell_w = u32::from(cell_w);
         let cell_h = u32::from(cell_h);
 
-        // Calculate canvas size in cells (for cell-aligned tile boundaries)
-        let canvas_w_cells = canvas_w / cell_w;
-        let canvas_h_cells = canvas_h / cell_h;
-
         // Padding around each thumbnail (leaves space for cursor border).
         let half_pad_w = cell_w;
         let half_pad_h = cell_h;
 
         // Prepare tile info and check cache
-        struct TileInfo {
-            path: PathBuf,
-            tile_x: u32,
-            tile_y: u32,
-            inner_w: u32,
-            inner_h: u32,
-        }
-
         let filter_id = filter_cache_id(filter);
         let mut cached_tiles: Vec<(u32, u32, Arc<RgbaImage>)> = Vec::new();
         let mut uncached_tiles: Vec<TileInfo> = Vec::new();
+        // Vertical bars (x, y, height) marking tiles that start a new directory (or day).
+        let mut separators: Vec<(u32, u32, u32)> = Vec::new();
+        // Day labels (x, y, max width, text) drawn in the top padding of a tile.
+        let mut labels: Vec<(u32, u32, u32, String)> = Vec::new();
+        let page = &paths[..paths.len().min(rects.len())];
+        // Day bands separate and label the days themselves; without them (a page with too many
+        // days for separator rows), days are marked like directories, with bars and labels.
+        let days = days.filter(|_| bands.is_empty()).unwrap_or_default();
+        let group_starts = if days.is_empty() {
+            directory_starts(page)
+        } else {
+            day_starts(days)
+        };
+        let label_scale = (half_pad_h / (crate::label::GLYPH_HEIGHT + 2)).max(1);
 
-        for (i, path) in paths.iter().take(cols * rows).enumerate() {
-            let col = i % cols;
-            let row = i / cols;
-
-            let tile_x_cells = (col as u32 * canvas_w_cells) / cols as u32;
-            let tile_y_cells = (row as u32 * canvas_h_cells) / rows as u32;
-            let next_tile_x_cells = ((col + 1) as u32 * canvas_w_cells) / cols as u32;
-            let next_tile_y_cells = ((row + 1) as u32 * canvas_h_cells) / rows as u32;
-
-            let tile_x = tile_x_cells * cell_w;
-            let tile_y = tile_y_cells * cell_h;
-            let tile_w = (next_tile_x_cells - tile_x_cells) * cell_w;
-            let tile_h = (next_tile_y_cells - tile_y_cells) * cell_h;
+        for (i, ((path, rotation), rect)) in page.iter().zip(rects).enumerate() {
+            // Tiles are cell-aligned, so overlays line up with them.
+            let tile_x = u32::from(rect.x) * cell_w;
+            let tile_y = u32::from(rect.y) * cell_h;
+            let tile_w = u32::from(rect.width) * cell_w;
+            let tile_h = u32::from(rect.height) * cell_h;
 
             let inner_w = tile_w.saturating_sub(half_pad_w * 2);
-            let inner_h = tile_h.saturating_sub(half_pad_h * 2);
+            // The file name row (drawn as text) sits between the thumbnail and the padding.
+            let label_h = if tile_labels { cell_h } else { 0 };
+            let inner_h = tile_h.saturating_sub(half_pad_h * 2 + label_h);
 
             if inner_w == 0 || inner_h == 0 {
                 continue;
             }
 
+            if group_starts[i] {
+                // Centered in the left padding, so it never overlaps a thumbnail.
+                let bar_x = tile_x + half_pad_w.saturating_sub(DIR_SEPARATOR_WIDTH) / 2;
+                separators.push((bar_x, tile_y + half_pad_h, inner_h));
+            }
+            // Each day is labeled where it starts, and the page's first tile shows its day.
+            if let Some(Some(day)) = days.get(i)
+                && (i == 0 || group_starts[i])
+            {
+                let label_y = tile_y
+                    + half_pad_h.saturating_sub(crate::label::GLYPH_HEIGHT * label_scale) / 2;
+                labels.push((tile_x + half_pad_w, label_y, inner_w, day.clone()));
+            }
+
             let cache_key = ThumbnailKey {
                 path: path.clone(),
                 width: inner_w,
                 height: inner_h,
                 filter_id,
+                rotation: *rotation,
             };
             if let Some(cached_thumb) = thumbnail_cache.get(&cache_key) {
                 // Cache hit: calculate position and add to cached_tiles
@@ -503,6 +1154,7 @@ impl ImageWorker {
                 // Cache miss: add to uncached_tiles for parallel processing
                 uncached_tiles.push(TileInfo {
                     path: path.clone(),
+                    rotation: *rotation,
                     tile_x,
                     tile_y,
                     inner_w,
@@ -511,90 +1163,435 @@ impl ImageWorker {
             }
         }
 
-        // Parallel decode and resize for cache misses (using dedicated thread pool)
-        let new_tiles: Vec<_> = tile_pool.install(|| {
-            uncached_tiles
-                .par_iter()
-                .filter_map(|info| {
-                    let img = match Self::decode_image(&info.path) {
-                        Some(img) => img,
-                        None => {
-                            if trace_worker {
-                                use std::io::Write as _;
-                                if let Ok(mut f) = std::fs::OpenOptions::new()
-                                    .create(true)
-                                    .append(true)
-                                    .open("/tmp/stiv_worker.log")
-                                {
-                                    let _ = writeln!(f, "tile decode failed: {:?}", info.path);
-                                }
-                            }
-                            return None;
-                        }
-                    };
-                    let (orig_w, orig_h) = (img.width(), img.height());
-
-                    let scale_w = info.inner_w as f64 / orig_w as f64;
-                    let scale_h = info.inner_h as f64 / orig_h as f64;
-                    let scale = scale_w.min(scale_h).min(1.0);
-
-                    let scaled_w = (orig_w as f64 * scale).floor().max(1.0) as u32;
-                    let scaled_h = (orig_h as f64 * scale).floor().max(1.0) as u32;
-
-                    let thumbnail = img.resize(scaled_w, scaled_h, filter);
-                    let rgba_thumb = Arc::new(thumbnail.to_rgba8());
-
-                    let img_x =
-                        info.tile_x + half_pad_w + (info.inner_w.saturating_sub(scaled_w)) / 2;
-                    let img_y =
-                        info.tile_y + half_pad_h + (info.inner_h.saturating_sub(scaled_h)) / 2;
-
-                    Some((
-                        info.path.clone(),
-                        info.inner_w,
-                        info.inner_h,
-                        img_x,
-                        img_y,
-                        rgba_thumb,
-                    ))
-                })
-                .collect()
-        });
-
-        // Add new thumbnails to cache
-        for (path, inner_w, inner_h, img_x, img_y, rgba_thumb) in new_tiles {
-            let cache_key = ThumbnailKey {
-                path,
-                width: inner_w,
-                height: inner_h,
-                filter_id,
-            };
-            thumbnail_cache.insert(cache_key, Arc::clone(&rgba_thumb));
-            cached_tiles.push((img_x, img_y, rgba_thumb));
-        }
-
-        // Sequential copy to canvas
+        let place = |canvas: &mut RgbaImage, img_x: u32, img_y: u32, thumb: &RgbaImage| {
+            if img_x + thumb.width() <= canvas_w && img_y + thumb.height() <= canvas_h {
+                let _ = canvas.copy_from(thumb, img_x, img_y);
+            }
+        };
         let mut canvas = RgbaImage::from_pixel(canvas_w, canvas_h, Rgba([0, 0, 0, 0]));
         for (img_x, img_y, rgba_thumb) in cached_tiles {
-            let scaled_w = rgba_thumb.width();
-            let scaled_h = rgba_thumb.height();
-            if img_x + scaled_w <= canvas_w && img_y + scaled_h <= canvas_h {
-                let _ = canvas.copy_from(&*rgba_thumb, img_x, img_y);
+            place(&mut canvas, img_x, img_y, &rgba_thumb);
+        }
+        let separator_color = Rgba([128, 128, 128, 255]);
+        for (x, y, h) in separators {
+            for py in y..(y + h).min(canvas_h) {
+                for px in x..(x + DIR_SEPARATOR_WIDTH).min(canvas_w) {
+                    canvas.put_pixel(px, py, separator_color);
+                }
             }
         }
+        let label_color = Rgba([200, 200, 200, 255]);
+        for (x, y, max_w, text) in labels {
+            crate::label::draw_text(&mut canvas, (x, y), &text, label_scale, max_w, label_color);
+        }
+        for (rect, day) in bands {
+            let band = (
+                u32::from(rect.x) * cell_w + half_pad_w,
+                u32::from(rect.y) * cell_h,
+                (u32::from(rect.width) * cell_w).saturating_sub(half_pad_w * 2),
+                u32::from(rect.height) * cell_h,
+            );
+            draw_day_band(&mut canvas, band, day.as_deref(), label_scale);
+        }
+
+        // Decode and resize cache misses in parallel (dedicated thread pool), copying each
+        // thumbnail in as it arrives.
+        let (tx, rx) = mpsc::channel();
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                tile_pool.install(|| {
+                    uncached_tiles.par_iter().for_each_with(tx, |tx, info| {
+                        let thumbnail = Self::tile_thumbnail(
+                            info,
+                            filter,
+                            downscale_sharpen,
+                            bit_depth_stretch,
+                            limits,
+                            trace_worker,
+                        );
+                        let _ = tx.send((info, thumbnail));
+                    });
+                });
+            });
+
+            let mut remaining = uncached_tiles.len();
+            let mut changed = false;
+            let mut next_progress = Instant::now() + TILE_PROGRESS_INTERVAL;
+            while remaining > 0 {
+                let timeout = next_progress.saturating_duration_since(Instant::now());
+                match rx.recv_timeout(timeout) {
+                    Ok((info, thumbnail)) => {
+                        remaining -= 1;
+                        let Some(rgba_thumb) = thumbnail else {
+                            let inner = (
+                                info.tile_x + half_pad_w,
+                                info.tile_y + half_pad_h,
+                                info.inner_w,
+                                info.inner_h,
+                            );
+                            draw_failed_tile(&mut canvas, inner, &info.path, label_scale);
+                            changed = true;
+                            continue;
+                        };
+                        let img_x = info.tile_x
+                            + half_pad_w
+                            + (info.inner_w.saturating_sub(rgba_thumb.width())) / 2;
+                        let img_y = info.tile_y
+                            + half_pad_h
+                            + (info.inner_h.saturating_sub(rgba_thumb.height())) / 2;
+                        place(&mut canvas, img_x, img_y, &rgba_thumb);
+                        thumbnail_cache.insert(
+                            ThumbnailKey {
+                                path: info.path.clone(),
+                                width: info.inner_w,
+                                height: info.inner_h,
+                                filter_id,
+                                rotation: info.rotation,
+                            },
+                            rgba_thumb,
+                        );
+                        changed = true;
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => {
+                        if std::mem::take(&mut changed) {
+                            on_partial(&canvas);
+                        }
+                        next_progress = Instant::now() + TILE_PROGRESS_INTERVAL;
+                    }
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        });
 
         let actual_size = (canvas_w, canvas_h);
         Some((DynamicImage::ImageRgba8(canvas), actual_size))
     }
 
+    /// Decode, rotate and shrink one tile's image to fit its thumbnail area.
+    fn tile_thumbnail(
+        info: &TileInfo,
+        filter: image::imageops::FilterType,
+        downscale_sharpen: f32,
+        bit_depth_stretch: f32,
+        limits: &image::Limits,
+        trace_worker: bool,
+    ) -> Option<Arc<RgbaImage>> {
+        let Some(decoded) = Self::decode_image(&info.path, WindowLevel::default(), limits) else {
+            if trace_worker {
+                use std::io::Write as _;
+                if let Ok(mut f) = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open("/tmp/stiv_worker.log")
+                {
+                    let _ = writeln!(f, "tile decode failed: {:?}", info.path);
+                }
+            }
+            return None;
+        };
+        let decoded = to_display_depth(decoded, bit_depth_stretch);
+        let img = info.rotation.apply(&decoded);
+        let (orig_w, orig_h) = (img.width(), img.height());
+
+        let scale_w = info.inner_w as f64 / orig_w as f64;
+        let scale_h = info.inner_h as f64 / orig_h as f64;
+        let scale = scale_w.min(scale_h).min(1.0);
+
+        let scaled_w = (orig_w as f64 * scale).floor().max(1.0) as u32;
+        let scaled_h = (orig_h as f64 * scale).floor().max(1.0) as u32;
+
+        let thumbnail = Self::sharpen_downscaled(
+            std::borrow::Cow::Owned(img.resize(scaled_w, scaled_h, filter)),
+            orig_w,
+            downscale_sharpen,
+        );
+        Some(Arc::new(thumbnail.to_rgba8()))
+    }
+
     pub fn request(&self, req: ImageRequest) {
-        let _ = self.request_tx.send(req);
+        let _ = self
+            .request_tx
+            .send(WorkerMessage::Job(Box::new(Job::Render(req))));
+    }
+
+    /// Render every frame of the animated image in `req` (Single mode).
+    pub fn request_animation(&self, req: ImageRequest) {
+        let _ = self
+            .request_tx
+            .send(WorkerMessage::Job(Box::new(Job::Animate(req))));
+    }
+
+    /// Drop the decode cache and tile thumbnails (the next request decodes from disk).
+    pub fn clear_caches(&self) {
+        let _ = self.request_tx.send(WorkerMessage::ClearCaches);
+    }
+
+    /// Write a crop of an image to a file; the result arrives on `req.reply`.
+    pub fn save(&self, req: SaveRequest) {
+        let _ = self.request_tx.send(WorkerMessage::Save(Box::new(req)));
+    }
+
+    pub fn usage(&self) -> &CacheUsage {
+        &self.usage
     }
 
     pub fn try_recv(&self) -> Option<ImageResult> {
         self.result_rx.try_recv().ok()
     }
 
+    pub fn try_recv_placeholder(&self) -> Option<PlaceholderResult> {
+        self.placeholder_rx.try_recv().ok()
+    }
+
+    pub fn try_recv_animation(&self) -> Option<AnimationResult> {
+        self.animation_rx.try_recv().ok()
+    }
+
+    /// Path of a Single-mode image that failed to decode.
+    pub fn try_recv_failed(&self) -> Option<PathBuf> {
+        self.failed_rx.try_recv().ok()
+    }
+
+    /// Frame iterator of a GIF file (or tar member).
+    fn gif_frames(
+        path: &std::path::Path,
+        limits: &image::Limits,
+    ) -> Option<image::Frames<'static>> {
+        use image::{AnimationDecoder, ImageDecoder};
+
+        let bytes = match crate::archive::split_member_path(path) {
+            Some((archive, member)) => crate::archive::read_member(archive, &member)?,
+            None => std::fs::read(path).ok()?,
+        };
+        let mut decoder = image::codecs::gif::GifDecoder::new(std::io::Cursor::new(bytes)).ok()?;
+        decoder.set_limits(limits.clone()).ok()?;
+        Some(decoder.into_frames())
+    }
+
+    /// Decode every frame of an animated GIF and render it like a Single-mode image.
+    /// Returns None for stills, on errors, when a newer job arrives (it is left in `pending`)
+    /// or when the frames would take more than [`ANIMATION_MAX_BYTES`].
+    fn process_animation(
+        req: &ImageRequest,
+        pending: &mut Option<Job>,
+        request_rx: &Receiver<WorkerMessage>,
+        caches: &mut WorkerCaches,
+    ) -> Option<AnimationResult> {
+        let mut frames = Vec::new();
+        let mut bytes = 0;
+        for frame in Self::gif_frames(&req.path, &req.limits)? {
+            let frame = frame.ok()?;
+            let delay = frame_delay(std::time::Duration::from(frame.delay()));
+            let image = DynamicImage::ImageRgba8(frame.into_buffer());
+            let encoded_chunks = Self::render_frame(&image, req);
+            bytes += encoded_chunks.iter().map(Vec::len).sum::<usize>();
+            if bytes > ANIMATION_MAX_BYTES {
+                return None;
+            }
+            frames.push(AnimationFrame {
+                encoded_chunks: Arc::new(encoded_chunks),
+                delay,
+            });
+
+            if let Some(newer) = Self::drain_to_latest(request_rx, caches, None) {
+                *pending = Some(newer);
+                return None;
+            }
+        }
+        (frames.len() > 1).then(|| AnimationResult {
+            path: req.path.clone(),
+            target: req.target,
+            fit_mode: req.fit_mode,
+            crop: req.crop,
+            rotation: req.rotation,
+            window: req.window,
+            adjust: req.adjust,
+            frames,
+        })
+    }
+
+    /// Handle a `SaveRequest`: decode (reusing the decode cache), rotate, crop and write.
+    fn write_crop(req: &SaveRequest, caches: &mut WorkerCaches) {
+        let cached = caches
+            .decoded
+            .as_ref()
+            .filter(|(path, window, _)| path == &req.path && *window == req.window)
+            .map(|(_, _, img)| Arc::clone(img));
+        let result = cached
+            .or_else(|| {
+                let img = Self::decode_image(&req.path, req.window, &req.limits)?;
+                Some(Arc::new(to_display_depth(img, req.bit_depth_stretch)))
+            })
+            .with_context(|| format!("Failed to decode {}", req.path.display()))
+            .and_then(|img| {
+                let rotated = req.rotation.apply(&img);
+                let (source, _, _) = Self::apply_crop(rotated, req.crop, FitMode::Fit, (0, 0));
+                write_image(&source, &req.dest, req.force)?;
+                Ok((source.width(), source.height()))
+            });
+        let _ = req.reply.send(result);
+        crate::wake::wake();
+    }
+
+    /// Rotate, crop, resize and encode one animation frame (same steps as a Single render).
+    fn render_frame(frame: &DynamicImage, req: &ImageRequest) -> Vec<Vec<u8>> {
+        let rotated = req.rotation.apply(frame);
+        let (source, fit_mode, region) =
+            Self::apply_crop(rotated, req.crop, req.fit_mode, req.target);
+        let resized = Self::resize_for_display(req, source, fit_mode, region);
+        Self::encode_for_display(req, &resized)
+    }
+
+    /// Resize a rotated and cropped image (`apply_crop`) for a Single render: to the fit
+    /// mode's target within the tmux/Kitty pixel cap, then sharpened and adjusted. SVGs are
+    /// rasterized again at the target size instead of resampled.
+    fn resize_for_display<'a>(
+        req: &ImageRequest,
+        source: std::borrow::Cow<'a, DynamicImage>,
+        fit_mode: FitMode,
+        region: CropRect,
+    ) -> std::borrow::Cow<'a, DynamicImage> {
+        use std::borrow::Cow;
+
+        let (src_w, src_h) = (source.width(), source.height());
+        let (mut target_w, mut target_h) =
+            Self::compute_target((src_w, src_h), req.target, fit_mode);
+
+        // Apply max pixels limit (for tmux+kitty compatibility).
+        // In `Fit` mode we allow larger images (may be slower / unsupported in some setups).
+        if fit_mode != FitMode::Fit {
+            let max_pixels = req.tmux_kitty_max_pixels;
+            let target_pixels = (target_w as u64).saturating_mul(target_h as u64);
+            if target_pixels > max_pixels {
+                let down = (max_pixels as f64 / target_pixels as f64).sqrt();
+                target_w = (target_w as f64 * down).floor().max(1.0) as u32;
+                target_h = (target_h as f64 * down).floor().max(1.0) as u32;
+            }
+        }
+
+        let (source, src_w, src_h) =
+            match Self::rerasterize_svg(req, (src_w, src_h), (target_w, target_h)) {
+                Some(img) => {
+                    let (w, h) = (img.width(), img.height());
+                    (Cow::Owned(img), w, h)
+                }
+                None => (source, src_w, src_h),
+            };
+        // Cow avoids a clone when no resize is needed.
+        let resized: Cow<'_, DynamicImage> = if target_w != src_w || target_h != src_h {
+            Cow::Owned(source.resize(target_w, target_h, req.resize_filter))
+        } else {
+            source
+        };
+        let resized = Self::sharpen_upscaled(resized, src_w, fit_mode, req.upscale_sharpen);
+        let resized = Self::sharpen_downscaled(resized, src_w, req.downscale_sharpen);
+        req.adjust.apply(resized, region)
+    }
+
+    /// Encode a resized image into the request protocol's output chunks.
+    fn encode_for_display(req: &ImageRequest, resized: &DynamicImage) -> Vec<Vec<u8>> {
+        req.protocol.encode(
+            resized,
+            req.kgp_id,
+            req.is_tmux,
+            req.compress_level,
+            req.file_transfer,
+        )
+    }
+
+    /// Crop to the zoomed region (clamped to the image); a zoomed region is always scaled to
+    /// fill the viewport, like `Fit` mode. Without one, `Actual` keeps the `target`-sized
+    /// center of a larger image. Also returns the region that was kept.
+    fn apply_crop(
+        img: std::borrow::Cow<'_, DynamicImage>,
+        crop: Option<CropRect>,
+        fit_mode: FitMode,
+        target: (u32, u32),
+    ) -> (std::borrow::Cow<'_, DynamicImage>, FitMode, CropRect) {
+        let size = (img.width(), img.height());
+        let (c, fit_mode) = match crop {
+            Some(c) => (c.clamp_to(size), FitMode::Fit),
+            None if fit_mode == FitMode::Actual && (size.0 > target.0 || size.1 > target.1) => {
+                (CropRect::centered(size, target), fit_mode)
+            }
+            None => return (img, fit_mode, CropRect::full(size)),
+        };
+        (
+            std::borrow::Cow::Owned(img.crop_imm(c.x, c.y, c.width, c.height)),
+            fit_mode,
+            c,
+        )
+    }
+
+    /// Send a PNG file as it is (KGP `f=100`) when it is shown at its own size, skipping
+    /// decode, resize and re-encode. Only 8-bit PNGs qualify (16-bit ones may need
+    /// `bit_depth_stretch`); the caller checks the protocol, rotation and crop.
+    #[allow(clippy::too_many_arguments)]
+    fn png_passthrough(
+        path: &std::path::Path,
+        target: (u32, u32),
+        fit_mode: FitMode,
+        kgp_id: u32,
+        is_tmux: bool,
+        file_transfer: bool,
+        tmux_kitty_max_pixels: u64,
+        limits: &image::Limits,
+        window: WindowLevel,
+    ) -> Option<ImageResult> {
+        let read_start = Instant::now();
+        let is_png = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("png"));
+        if !is_png || crate::archive::split_member_path(path).is_some() {
+            return None;
+        }
+        // Signature, then the IHDR chunk (length, type, 13 data bytes and CRC): width, height
+        // and bit depth. The rest of the file is read only once it qualifies.
+        let mut file = std::fs::File::open(path).ok()?;
+        let mut header = [0u8; 33];
+        file.read_exact(&mut header).ok()?;
+        if !header.starts_with(b"\x89PNG\r\n\x1a\n") || header[12..16] != *b"IHDR" {
+            return None;
+        }
+        let width = u32::from_be_bytes(header[16..20].try_into().ok()?);
+        let height = u32::from_be_bytes(header[20..24].try_into().ok()?);
+        let size = (width, height);
+        let pixels = u64::from(width) * u64::from(height);
+        if header[24] > 8
+            || limits.check_dimensions(width, height).is_err()
+            || Self::compute_target(size, target, fit_mode) != size
+            || (fit_mode != FitMode::Fit && pixels > tmux_kitty_max_pixels)
+        {
+            return None;
+        }
+        let mut png = header.to_vec();
+        file.read_to_end(&mut png).ok()?;
+        let read_elapsed = read_start.elapsed();
+
+        let encode_start = Instant::now();
+        let encoded_chunks = crate::kgp::encode_png(&png, kgp_id, is_tmux, file_transfer);
+        Some(ImageResult {
+            path: path.to_path_buf(),
+            target,
+            fit_mode,
+            crop: None,
+            rotation: Rotation::None,
+            window,
+            adjust: Adjustments::default(),
+            placeholder: None,
+            original_size: size,
+            actual_size: size,
+            encoded_chunks: Arc::new(encoded_chunks),
+            timings: StageTimings {
+                decode: read_elapsed,
+                resize: Duration::ZERO,
+                encode: encode_start.elapsed(),
+            },
+            partial: false,
+        })
+    }
+
     /// Process a single image: decode → resize → encode.
     /// Used by both ImageWorker and PrefetchWorker.
     #[allow(clippy::too_many_arguments)]
@@ -604,21 +1601,60 @@ impl ImageWorker {
         fit_mode: FitMode,
         kgp_id: u32,
         is_tmux: bool,
+        file_transfer: bool,
         compress_level: Option<u32>,
+        protocol: Protocol,
         tmux_kitty_max_pixels: u64,
         resize_filter: image::imageops::FilterType,
+        upscale_sharpen: f32,
+        downscale_sharpen: f32,
+        bit_depth_stretch: f32,
+        limits: &image::Limits,
+        rotation: Rotation,
+        window: WindowLevel,
+        adjust: Adjustments,
+        crop: Option<CropRect>,
     ) -> Option<ImageResult> {
+        let window = window.for_path(path);
+        if protocol == Protocol::Kitty
+            && rotation == Rotation::None
+            && crop.is_none()
+            && adjust.is_none()
+            && let Some(result) = Self::png_passthrough(
+                path,
+                target,
+                fit_mode,
+                kgp_id,
+                is_tmux,
+                file_transfer,
+                tmux_kitty_max_pixels,
+                limits,
+                window,
+            )
+        {
+            return Some(result);
+        }
+
         // Decode
-        let decoded = Self::decode_image(path)?;
+        let decode_start = Instant::now();
+        let decoded =
+            to_display_depth(Self::decode_image(path, window, limits)?, bit_depth_stretch);
+        let decoded = rotation.apply(&decoded);
         let (orig_w, orig_h) = (decoded.width(), decoded.height());
+        let placeholder = Arc::new(Placeholder::from_image(&decoded));
+        let decode_elapsed = decode_start.elapsed();
         let (max_w, max_h) = target;
 
+        use std::borrow::Cow;
+        let (source, fit_mode_used, region) = Self::apply_crop(decoded, crop, fit_mode, target);
+        let (src_w, src_h) = (source.width(), source.height());
+
         // Compute target size
         let (mut target_w, mut target_h) =
-            Self::compute_target((orig_w, orig_h), (max_w, max_h), fit_mode);
+            Self::compute_target((src_w, src_h), (max_w, max_h), fit_mode_used);
 
         // Apply max pixels limit (for tmux+kitty compatibility)
-        if fit_mode != FitMode::Fit {
+        if fit_mode_used != FitMode::Fit {
             let max_pixels = tmux_kitty_max_pixels;
             let target_pixels = (target_w as u64).saturating_mul(target_h as u64);
             if target_pixels > max_pixels {
@@ -629,28 +1665,193 @@ impl ImageWorker {
         }
 
         // Resize
-        use std::borrow::Cow;
-        let resized: Cow<'_, DynamicImage> = if target_w != orig_w || target_h != orig_h {
-            Cow::Owned(decoded.resize(target_w, target_h, resize_filter))
+        let resize_start = Instant::now();
+        let resized: Cow<'_, DynamicImage> = if target_w != src_w || target_h != src_h {
+            Cow::Owned(source.resize(target_w, target_h, resize_filter))
         } else {
-            Cow::Borrowed(&decoded)
+            source
         };
+        let resized = Self::sharpen_upscaled(resized, src_w, fit_mode_used, upscale_sharpen);
+        let resized = Self::sharpen_downscaled(resized, src_w, downscale_sharpen);
+        let resized = adjust.apply(resized, region);
         let actual_size = (resized.width(), resized.height());
+        let resize_elapsed = resize_start.elapsed();
 
         // Encode
-        let encoded_chunks = encode_chunks(&resized, kgp_id, is_tmux, compress_level);
+        let encode_start = Instant::now();
+        let encoded_chunks =
+            protocol.encode(&resized, kgp_id, is_tmux, compress_level, file_transfer);
+        let encode_elapsed = encode_start.elapsed();
 
         Some(ImageResult {
             path: path.to_path_buf(),
             target,
             fit_mode,
+            crop,
+            rotation,
+            window,
+            adjust,
+            placeholder: Some(placeholder),
             original_size: (orig_w, orig_h),
             actual_size,
             encoded_chunks: Arc::new(encoded_chunks),
+            timings: StageTimings {
+                decode: decode_elapsed,
+                resize: resize_elapsed,
+                encode: encode_elapsed,
+            },
+            partial: false,
         })
     }
 }
 
+/// Write an image in the format of `dest`'s extension. Formats without alpha (JPEG) get RGB.
+fn write_image(img: &DynamicImage, dest: &std::path::Path, force: bool) -> anyhow::Result<()> {
+    if !force && dest.exists() {
+        anyhow::bail!("{} exists (add ! to overwrite)", dest.display());
+    }
+    let format = image::ImageFormat::from_path(dest)
+        .with_context(|| format!("Unknown image format: {}", dest.display()))?;
+    let result = if format == image::ImageFormat::Jpeg {
+        DynamicImage::ImageRgb8(img.to_rgb8()).save_with_format(dest, format)
+    } else {
+        img.save_with_format(dest, format)
+    };
+    result.with_context(|| format!("Failed to write {}", dest.display()))
+}
+
+/// Draw a day separator row in `(x, y, width, height)`: the day, then a rule to the right
+/// edge (just the rule for images without a day).
+fn draw_day_band(
+    canvas: &mut RgbaImage,
+    (x, y, w, h): (u32, u32, u32, u32),
+    day: Option<&str>,
+    scale: u32,
+) {
+    use image::Rgba;
+
+    let text_h = crate::label::GLYPH_HEIGHT * scale;
+    let text_w = day.map_or(0, |day| crate::label::text_width(day, scale).min(w));
+    if let Some(day) = day {
+        let text_y = y + h.saturating_sub(text_h) / 2;
+        let label_color = Rgba([200, 200, 200, 255]);
+        crate::label::draw_text(canvas, (x, text_y), day, scale, w, label_color);
+    }
+    let gap = if text_w > 0 { 3 * scale } else { 0 };
+    let rule_y = y + h / 2;
+    let rule_color = Rgba([128, 128, 128, 255]);
+    for py in rule_y..(rule_y + scale).min(y + h).min(canvas.height()) {
+        for px in (x + text_w + gap)..(x + w).min(canvas.width()) {
+            canvas.put_pixel(px, py, rule_color);
+        }
+    }
+}
+
+/// For each tile, whether it starts a new day within the page (never the first tile, nor tiles
+/// without a known day).
+fn day_starts(days: &[Option<String>]) -> Vec<bool> {
+    let mut starts = vec![false; days.len()];
+    for (i, pair) in days.windows(2).enumerate() {
+        starts[i + 1] = pair[1].is_some() && pair[0] != pair[1];
+    }
+    starts
+}
+
+/// Draw a broken-image mark (a box crossed out) with the file name under it in a tile's
+/// thumbnail area (`x`, `y`, width, height), for a file that failed to decode.
+fn draw_failed_tile(
+    canvas: &mut RgbaImage,
+    (x, y, w, h): (u32, u32, u32, u32),
+    path: &std::path::Path,
+    scale: u32,
+) {
+    use image::Rgba;
+
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let text_h = crate::label::GLYPH_HEIGHT * scale;
+    let gap = 2 * scale;
+    let side = (w.min(h.saturating_sub(text_h + gap)) / 2).max(4);
+    let top = y + h.saturating_sub(side + gap + text_h) / 2;
+    let left = x + w.saturating_sub(side) / 2;
+
+    let frame = Rgba([128, 128, 128, 255]);
+    let cross = Rgba([200, 60, 60, 255]);
+    let mut put = |px: u32, py: u32, color| {
+        if px < canvas.width() && py < canvas.height() {
+            canvas.put_pixel(px, py, color);
+        }
+    };
+    for i in 0..side {
+        for t in 0..scale.min(side) {
+            put(left + i, top + t, frame);
+            put(left + i, top + side - 1 - t, frame);
+            put(left + t, top + i, frame);
+            put(left + side - 1 - t, top + i, frame);
+            let d = (i + t).min(side - 1);
+            put(left + i, top + d, cross);
+            put(left + side - 1 - i, top + d, cross);
+        }
+    }
+
+    let text_w = crate::label::text_width(&name, scale).min(w);
+    let text_x = x + (w - text_w) / 2;
+    let label_color = Rgba([200, 200, 200, 255]);
+    crate::label::draw_text(
+        canvas,
+        (text_x, top + side + gap),
+        &name,
+        scale,
+        x + w - text_x,
+        label_color,
+    );
+}
+
+/// For each tile, whether it starts a new directory within the page (never the first tile).
+fn directory_starts(paths: &[(PathBuf, Rotation)]) -> Vec<bool> {
+    let mut starts = vec![false; paths.len()];
+    for (i, pair) in paths.windows(2).enumerate() {
+        starts[i + 1] = pair[0].0.parent() != pair[1].0.parent();
+    }
+    starts
+}
+
+/// Unsharp mask: `img + amount * (img - blur(img))`, keeping alpha untouched.
+fn sharpen(img: &DynamicImage, amount: f32, sigma: f32) -> DynamicImage {
+    match img {
+        DynamicImage::ImageRgb8(buf) => DynamicImage::ImageRgb8(unsharp_mask(buf, amount, sigma)),
+        DynamicImage::ImageRgba8(buf) => DynamicImage::ImageRgba8(unsharp_mask(buf, amount, sigma)),
+        other if other.color().has_alpha() => {
+            DynamicImage::ImageRgba8(unsharp_mask(&other.to_rgba8(), amount, sigma))
+        }
+        other => DynamicImage::ImageRgb8(unsharp_mask(&other.to_rgb8(), amount, sigma)),
+    }
+}
+
+fn unsharp_mask<P>(
+    img: &image::ImageBuffer<P, Vec<u8>>,
+    amount: f32,
+    sigma: f32,
+) -> image::ImageBuffer<P, Vec<u8>>
+where
+    P: image::Pixel<Subpixel = u8>,
+{
+    let blurred = image::imageops::fast_blur(img, sigma);
+    let channels = usize::from(P::CHANNEL_COUNT);
+    let has_alpha = channels == 4;
+    let mut out = img.clone();
+    for (i, (o, b)) in out.iter_mut().zip(blurred.iter()).enumerate() {
+        if has_alpha && i % channels == 3 {
+            continue;
+        }
+        let v = f32::from(*o) + amount * (f32::from(*o) - f32::from(*b));
+        *o = v.round().clamp(0.0, 255.0) as u8;
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -665,6 +1866,7 @@ mod tests {
             width: w,
             height: h,
             filter_id: 0,
+            rotation: Rotation::None,
         }
     }
 
@@ -743,12 +1945,353 @@ mod tests {
 
         // Should still have only one entry
         assert_eq!(cache.cache.len(), 1);
+        assert_eq!(cache.bytes(), 50 * 50 * 4);
 
         // Should return the updated image
         let retrieved = cache.get(&key1).unwrap();
         assert_eq!(retrieved.width(), 50);
     }
 
+    #[test]
+    fn test_directory_starts() {
+        let paths: Vec<(PathBuf, Rotation)> = ["a/1.png", "a/2.png", "b/1.png", "b/2.png"]
+            .iter()
+            .map(|p| (PathBuf::from(p), Rotation::None))
+            .collect();
+        assert_eq!(directory_starts(&paths), vec![false, false, true, false]);
+        assert!(directory_starts(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_day_starts() {
+        let day = |d: &str| Some(d.to_string());
+        let days = [
+            day("2024-01-01"),
+            day("2024-01-01"),
+            None,
+            day("2024-01-02"),
+        ];
+        assert_eq!(day_starts(&days), vec![false, false, false, true]);
+    }
+
+    #[test]
+    fn test_preload_decodes_in_background() {
+        let dir = PathBuf::from("/tmp/stiv_test_preload");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("a.png");
+        DynamicImage::new_rgb8(4, 3).save(&path).unwrap();
+
+        let preload = ImageWorker::preload(
+            path.clone(),
+            WindowLevel::default(),
+            Default::default(),
+            0.0,
+        );
+        let (decoded_path, _, img) = preload.rx.recv().unwrap();
+        assert_eq!(decoded_path, path);
+        assert_eq!((img.width(), img.height()), (4, 3));
+
+        // A file that fails to decode closes the channel instead of blocking the worker.
+        let preload = ImageWorker::preload(
+            dir.join("missing.png"),
+            WindowLevel::default(),
+            Default::default(),
+            0.0,
+        );
+        assert!(preload.rx.recv().is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_composite_tile_images_places_thumbnails() {
+        let dir = PathBuf::from("/tmp/stiv_test_composite");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("red.png");
+        RgbaImage::from_pixel(16, 16, image::Rgba([255, 0, 0, 255]))
+            .save(&path)
+            .unwrap();
+        let paths = vec![
+            (path, Rotation::None),
+            (dir.join("missing.png"), Rotation::None),
+        ];
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let mut cache = ThumbnailCache::new(8);
+        let mut partials = 0;
+        let composite = |cache: &mut ThumbnailCache, partials: &mut usize| {
+            ImageWorker::composite_tile_images(
+                &paths,
+                &crate::layout::grid_rects((2, 1), (8, 8), 2),
+                (64, 64),
+                Some((8, 8)),
+                image::imageops::FilterType::Triangle,
+                0.0,
+                0.0,
+                None,
+                &[],
+                false,
+                &Default::default(),
+                cache,
+                &pool,
+                false,
+                &mut |_| *partials += 1,
+            )
+            .unwrap()
+            .0
+            .to_rgba8()
+        };
+
+        // The thumbnail is centered in its tile's padding; the failed tile gets a placeholder.
+        let canvas = composite(&mut cache, &mut partials);
+        assert_eq!(canvas.get_pixel(8, 24).0, [255, 0, 0, 255]);
+        assert_eq!(canvas.get_pixel(8, 23).0[3], 0);
+        let mut failed = (40..56).flat_map(|x| (8..56).map(move |y| (x, y)));
+        assert!(failed.any(|(x, y)| canvas.get_pixel(x, y).0[3] != 0));
+        assert!(cache.bytes() > 0);
+
+        // The page comes out the same with the thumbnail cached.
+        assert_eq!(composite(&mut cache, &mut partials), canvas);
+        assert_eq!(partials, 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_decode_farbfeld() {
+        let dir = PathBuf::from("/tmp/stiv_test_farbfeld");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("a.ff");
+        DynamicImage::new_rgba16(3, 2).save(&path).unwrap();
+
+        let img =
+            ImageWorker::decode_image(&path, WindowLevel::default(), &Default::default()).unwrap();
+        assert_eq!((img.width(), img.height()), (3, 2));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_decode_classic_formats() {
+        let dir = PathBuf::from("/tmp/stiv_test_classic_formats");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("images.tar");
+        let mut builder = tar::Builder::new(std::fs::File::create(&archive).unwrap());
+        for ext in ["bmp", "ico", "tga", "ppm", "pgm", "pbm", "pam"] {
+            let path = dir.join(format!("a.{ext}"));
+            let img = match ext {
+                "pgm" | "pbm" => DynamicImage::new_luma8(3, 2),
+                // PNG-compressed icon entries must be RGBA.
+                "ico" => DynamicImage::new_rgba8(3, 2),
+                _ => DynamicImage::new_rgb8(3, 2),
+            };
+            img.save(&path).unwrap();
+            builder
+                .append_path_with_name(&path, path.file_name().unwrap())
+                .unwrap();
+
+            let decoded =
+                ImageWorker::decode_image(&path, WindowLevel::default(), &Default::default())
+                    .unwrap_or_else(|| panic!("failed to decode .{ext}"));
+            assert_eq!((decoded.width(), decoded.height()), (3, 2), ".{ext}");
+        }
+        builder.finish().unwrap();
+
+        // Archive members are decoded from memory; TGA is found by its extension.
+        let member = archive.join("a.tga");
+        let decoded =
+            ImageWorker::decode_image(&member, WindowLevel::default(), &Default::default())
+                .unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (3, 2));
+
+        // Misnamed and extensionless files decode by content.
+        for name in ["png_named.gif", "no_extension"] {
+            let path = dir.join(name);
+            DynamicImage::new_rgb8(3, 2)
+                .save_with_format(&path, image::ImageFormat::Png)
+                .unwrap();
+            let decoded =
+                ImageWorker::decode_image(&path, WindowLevel::default(), &Default::default())
+                    .unwrap_or_else(|| panic!("failed to decode {name}"));
+            assert_eq!((decoded.width(), decoded.height()), (3, 2), "{name}");
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_decode_respects_limits() {
+        let dir = PathBuf::from("/tmp/stiv_test_decode_limits");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("wide.png");
+        DynamicImage::new_rgb8(100, 10).save(&path).unwrap();
+
+        let mut limits = image::Limits::default();
+        limits.max_image_width = Some(50);
+        assert!(ImageWorker::decode_image(&path, WindowLevel::default(), &limits).is_none());
+
+        let mut limits = image::Limits::default();
+        limits.max_alloc = Some(100);
+        assert!(ImageWorker::decode_image(&path, WindowLevel::default(), &limits).is_none());
+
+        let limits = image::Limits::default();
+        assert!(ImageWorker::decode_image(&path, WindowLevel::default(), &limits).is_some());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_image_aspect_reads_custom_paths() {
+        let dir = PathBuf::from("/tmp/stiv_test_image_aspect");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let left = dir.join("left.png");
+        let right = dir.join("right.png");
+        DynamicImage::new_rgb8(20, 10).save(&left).unwrap();
+        DynamicImage::new_rgb8(20, 10).save(&right).unwrap();
+        let limits = image::Limits::default();
+
+        assert_eq!(ImageWorker::image_aspect(&left, &limits), 2.0);
+        // A spread has no header of its own: it is decoded.
+        let spread = VirtualPath::Spread {
+            left: &left,
+            right: &right,
+        }
+        .to_path();
+        assert_eq!(ImageWorker::image_aspect(&spread, &limits), 4.0);
+        assert_eq!(
+            ImageWorker::image_aspect(&dir.join("missing.png"), &limits),
+            1.0
+        );
+
+        // Both pages fit the limits, but their spread and split view do not.
+        let mut limits = image::Limits::default();
+        limits.max_image_width = Some(30);
+        assert_eq!(ImageWorker::image_aspect(&left, &limits), 2.0);
+        assert_eq!(ImageWorker::image_aspect(&spread, &limits), 1.0);
+        assert!(ImageWorker::decode_image(&spread, WindowLevel::default(), &limits).is_none());
+        let panes = VirtualPath::Panes {
+            left: &left,
+            right: &right,
+        }
+        .to_path();
+        assert!(ImageWorker::decode_image(&panes, WindowLevel::default(), &limits).is_none());
+        // Headers are checked too.
+        let wide = dir.join("wide.png");
+        DynamicImage::new_rgb8(40, 10).save(&wide).unwrap();
+        assert_eq!(ImageWorker::image_aspect(&wide, &limits), 1.0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_png_passthrough() {
+        let dir = PathBuf::from("/tmp/stiv_test_png_passthrough");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("shot.png");
+        DynamicImage::new_rgba8(40, 30).save(&path).unwrap();
+        let deep = dir.join("deep.png");
+        DynamicImage::new_rgb16(40, 30).save(&deep).unwrap();
+        let limits = image::Limits::default();
+        let passthrough = |path: &std::path::Path, target, fit_mode| {
+            ImageWorker::png_passthrough(
+                path,
+                target,
+                fit_mode,
+                7,
+                false,
+                false,
+                u64::MAX,
+                &limits,
+                WindowLevel::default(),
+            )
+        };
+
+        let result = passthrough(&path, (100, 100), FitMode::Normal).unwrap();
+        assert_eq!(result.actual_size, (40, 30));
+        let first = String::from_utf8_lossy(&result.encoded_chunks[0]);
+        assert!(first.starts_with("\x1b_Gq=2,a=T,C=1,U=1,f=100,i=7,m=0;iVBORw0KGgo"));
+        // The whole file is sent, not just the header read first.
+        let payload: Vec<u8> = result
+            .encoded_chunks
+            .iter()
+            .flat_map(|chunk| {
+                let body = chunk.strip_suffix(b"\x1b\\").unwrap();
+                let start = body.iter().position(|&b| b == b';').unwrap() + 1;
+                body[start..].to_vec()
+            })
+            .collect();
+        let data = base64_simd::STANDARD.decode_to_vec(&payload).unwrap();
+        assert_eq!(data, std::fs::read(&path).unwrap());
+
+        // A file cut off inside the header is left to the regular path.
+        let cut = dir.join("cut.png");
+        std::fs::write(&cut, &data[..24]).unwrap();
+        assert!(passthrough(&cut, (100, 100), FitMode::Normal).is_none());
+
+        // Shrinking, enlarging (Fit) and 16-bit samples need the regular path.
+        assert!(passthrough(&path, (20, 20), FitMode::Normal).is_none());
+        assert!(passthrough(&path, (100, 100), FitMode::Fit).is_none());
+        assert!(passthrough(&deep, (100, 100), FitMode::Normal).is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_crop() {
+        let dir = PathBuf::from("/tmp/stiv_test_write_crop");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("shot.png");
+        DynamicImage::new_rgba8(40, 30).save(&path).unwrap();
+        let mut caches = WorkerCaches {
+            decoded: None,
+            thumbnails: ThumbnailCache::new(1),
+            usage: Arc::default(),
+        };
+        let save = |caches: &mut WorkerCaches, dest: &str, force| {
+            let (reply, rx) = mpsc::channel();
+            let req = SaveRequest {
+                path: path.clone(),
+                window: WindowLevel::default(),
+                rotation: Rotation::Cw90,
+                crop: Some(CropRect {
+                    x: 5,
+                    y: 10,
+                    width: 20,
+                    height: 15,
+                }),
+                bit_depth_stretch: 0.0,
+                limits: image::Limits::default(),
+                dest: dir.join(dest),
+                force,
+                reply,
+            };
+            ImageWorker::write_crop(&req, caches);
+            rx.recv().unwrap()
+        };
+
+        // The crop refers to the rotated (30x40) image; JPEG drops the alpha channel.
+        assert_eq!(save(&mut caches, "crop.jpg", false).unwrap(), (20, 15));
+        assert_eq!(
+            image::image_dimensions(dir.join("crop.jpg")).unwrap(),
+            (20, 15)
+        );
+        assert!(save(&mut caches, "crop.jpg", false).is_err());
+        assert!(save(&mut caches, "crop.jpg", true).is_ok());
+        assert!(save(&mut caches, "crop.unknown", false).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_filter_cache_id() {
         assert_eq!(filter_cache_id(image::imageops::FilterType::Nearest), 0);
@@ -777,6 +2320,50 @@ mod tests {
         assert_eq!(result, (100, 50));
     }
 
+    #[test]
+    fn test_sharpen_keeps_flat_image() {
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(
+            16,
+            16,
+            image::Rgb([120, 60, 200]),
+        ));
+        let out = sharpen(&img, 2.0, 1.0);
+        assert_eq!(out.to_rgb8(), img.to_rgb8());
+    }
+
+    #[test]
+    fn test_sharpen_increases_edge_contrast() {
+        let mut buf = image::GrayImage::new(16, 4);
+        for (x, _, p) in buf.enumerate_pixels_mut() {
+            *p = image::Luma([if x < 8 { 100 } else { 150 }]);
+        }
+        let out = sharpen(&DynamicImage::ImageLuma8(buf), 1.0, 1.0).to_rgb8();
+        assert!(out.get_pixel(7, 1)[0] < 100);
+        assert!(out.get_pixel(8, 1)[0] > 150);
+    }
+
+    #[test]
+    fn test_sharpen_upscaled_skips_normal_mode() {
+        use std::borrow::Cow;
+        let img = DynamicImage::ImageRgb8(image::RgbImage::new(40, 40));
+        let out = ImageWorker::sharpen_upscaled(Cow::Borrowed(&img), 10, FitMode::Normal, 1.0);
+        assert!(matches!(out, Cow::Borrowed(_)));
+        let out = ImageWorker::sharpen_upscaled(Cow::Borrowed(&img), 10, FitMode::Fit, 1.0);
+        assert!(matches!(out, Cow::Owned(_)));
+    }
+
+    #[test]
+    fn test_sharpen_downscaled_only_below_quarter() {
+        use std::borrow::Cow;
+        let img = DynamicImage::ImageRgb8(image::RgbImage::new(40, 40));
+        let out = ImageWorker::sharpen_downscaled(Cow::Borrowed(&img), 100, 1.0);
+        assert!(matches!(out, Cow::Borrowed(_)));
+        let out = ImageWorker::sharpen_downscaled(Cow::Borrowed(&img), 160, 0.0);
+        assert!(matches!(out, Cow::Borrowed(_)));
+        let out = ImageWorker::sharpen_downscaled(Cow::Borrowed(&img), 160, 1.0);
+        assert!(matches!(out, Cow::Owned(_)));
+    }
+
     #[test]
     fn test_compute_target_fit_enlarge() {
         // Small image should be enlarged in Fit mode
@@ -786,4 +2373,23 @@ mod tests {
         assert!(result.0 <= 800);
         assert!(result.1 <= 600);
     }
+
+    #[test]
+    fn test_actual_size_keeps_pixels() {
+        use std::borrow::Cow;
+
+        let target = (800, 600);
+        let small = ImageWorker::compute_target((100, 50), target, FitMode::Actual);
+        assert_eq!(small, (100, 50));
+
+        // A larger image is cut to its center instead of scaled.
+        let img = DynamicImage::new_rgb8(1000, 300);
+        let (cropped, fit_mode, _) =
+            ImageWorker::apply_crop(Cow::Borrowed(&img), None, FitMode::Actual, target);
+        assert_eq!((cropped.width(), cropped.height()), (800, 300));
+        assert_eq!(
+            ImageWorker::compute_target((800, 300), target, fit_mode),
+            (800, 300)
+        );
+    }
 }
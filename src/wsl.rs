@@ -0,0 +1,156 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Windows clipboard access from WSL (`y` / `Y`).
+//!
+//! Under WSL, OSC 52 only works when the Windows terminal honours it (and tmux passes it on),
+//! and arboard talks to the Linux-side X11/Wayland clipboard, which may not exist or may not
+//! be synced to Windows. When WSL is detected, copies go through the Windows tools instead:
+//! - text: `clip.exe`, fed UTF-16LE with a BOM so non-ASCII paths survive
+//! - images: a temporary PNG loaded by `powershell.exe` into `System.Windows.Forms.Clipboard`
+//!
+//! Starting the Windows tools takes a noticeable moment, so copies run on a background thread
+//! (`copy_in_background`) and fall back to OSC 52 / arboard there when these fail.
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+
+use anyhow::{Context, Result, bail};
+use image::DynamicImage;
+
+/// Whether we run under WSL (`WSL_DISTRO_NAME` or a Microsoft kernel).
+pub fn detect() -> bool {
+    std::env::var_os("WSL_DISTRO_NAME").is_some()
+        || std::env::var_os("WSL_INTEROP").is_some()
+        || std::fs::read_to_string("/proc/sys/kernel/osrelease").is_ok_and(|r| is_wsl_kernel(&r))
+}
+
+fn is_wsl_kernel(release: &str) -> bool {
+    let release = release.to_ascii_lowercase();
+    release.contains("microsoft") || release.contains("wsl")
+}
+
+/// Run `copy` on a background thread, then `fallback` there if it failed. The receiver gets
+/// whether either one copied.
+pub fn copy_in_background(
+    copy: impl FnOnce() -> Result<()> + Send + 'static,
+    fallback: impl FnOnce() -> bool + Send + 'static,
+) -> Receiver<bool> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let copied = copy().is_ok() || fallback();
+        let _ = tx.send(copied);
+        crate::wake::wake();
+    });
+    rx
+}
+
+/// Copy `text` to the Windows clipboard with `clip.exe`.
+pub fn copy_text(text: &str) -> Result<()> {
+    let mut child = Command::new("clip.exe")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to run clip.exe")?;
+    child
+        .stdin
+        .take()
+        .context("clip.exe has no stdin")?
+        .write_all(&utf16le_with_bom(text))?;
+    let status = child.wait()?;
+    if !status.success() {
+        bail!("clip.exe failed ({status})");
+    }
+    Ok(())
+}
+
+/// Copy an image to the Windows clipboard via a temporary PNG and `powershell.exe`.
+pub fn copy_image(img: &DynamicImage) -> Result<()> {
+    let png = std::env::temp_dir().join(format!("stiv_clip_{}.png", std::process::id()));
+    img.save(&png)
+        .with_context(|| format!("Failed to write {}", png.display()))?;
+    let result = load_into_clipboard(&png);
+    _ = std::fs::remove_file(&png);
+    result
+}
+
+fn load_into_clipboard(png: &Path) -> Result<()> {
+    let output = Command::new("wslpath")
+        .arg("-w")
+        .arg(png)
+        .output()
+        .context("Failed to run wslpath")?;
+    if !output.status.success() {
+        bail!("wslpath failed for {}", png.display());
+    }
+    let windows_path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let script = format!(
+        "Add-Type -AssemblyName System.Windows.Forms, System.Drawing; \
+         $img = [System.Drawing.Image]::FromFile('{}'); \
+         [System.Windows.Forms.Clipboard]::SetImage($img); $img.Dispose()",
+        windows_path.replace('\'', "''")
+    );
+    let status = Command::new("powershell.exe")
+        .args(["-NoProfile", "-NonInteractive", "-STA", "-Command", &script])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .context("Failed to run powershell.exe")?;
+    if !status.success() {
+        bail!("powershell.exe failed ({status})");
+    }
+    Ok(())
+}
+
+fn utf16le_with_bom(text: &str) -> Vec<u8> {
+    std::iter::once(0xfeff)
+        .chain(text.encode_utf16())
+        .flat_map(u16::to_le_bytes)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_wsl_kernel() {
+        assert!(is_wsl_kernel("5.15.153.1-microsoft-standard-WSL2\n"));
+        assert!(is_wsl_kernel("4.4.0-19041-Microsoft"));
+        assert!(!is_wsl_kernel("6.8.0-45-generic"));
+    }
+
+    #[test]
+    fn test_copy_in_background_falls_back() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let fell_back = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&fell_back);
+        let rx = copy_in_background(|| Ok(()), move || flag.swap(true, Ordering::SeqCst));
+        assert!(rx.recv().unwrap());
+        assert!(!fell_back.load(Ordering::SeqCst));
+
+        let flag = Arc::clone(&fell_back);
+        let rx = copy_in_background(
+            || bail!("no clip.exe"),
+            move || !flag.swap(true, Ordering::SeqCst),
+        );
+        assert!(rx.recv().unwrap());
+        assert!(fell_back.load(Ordering::SeqCst));
+        assert!(
+            !copy_in_background(|| bail!("no clip.exe"), || false)
+                .recv()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_utf16le_with_bom() {
+        assert_eq!(utf16le_with_bom("a\u{e9}"), [0xff, 0xfe, b'a', 0, 0xe9, 0]);
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Yank registers: what `y` copies.
+//!
+//! `y` copies the full path. Prefixed with `"{reg}` (like vim registers), it copies another
+//! piece of data about the current image instead, e.g. `"fy` for the file name. Everything goes
+//! through the OSC 52 clipboard path, so it works over SSH as well.
+//!
+//! `"uy` copies the image itself as a `data:image/png;base64,...` URI. Re-encoding can take a
+//! while for large images, so the app runs it on a background thread (`App::poll_clipboard`).
+
+use std::path::Path;
+
+use image::DynamicImage;
+
+/// Data copied by `y`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Yank {
+    /// Full path (`"p`, the default).
+    #[default]
+    Path,
+    /// File name only (`"f`).
+    Name,
+    /// Parent directory (`"d`).
+    Dir,
+    /// Dimensions as `WIDTHxHEIGHT` (`"s`).
+    Size,
+    /// The image as a PNG data URI (`"u`).
+    DataUri,
+    /// Metadata summary: camera, exposure, date, GPS and dimensions (`"e`).
+    Exif,
+}
+
+impl Yank {
+    /// Register selected by the key after `"`.
+    pub fn from_register(c: char) -> Option<Self> {
+        match c {
+            'p' => Some(Self::Path),
+            'f' => Some(Self::Name),
+            'd' => Some(Self::Dir),
+            's' => Some(Self::Size),
+            'u' => Some(Self::DataUri),
+            'e' => Some(Self::Exif),
+            _ => None,
+        }
+    }
+
+    /// Name used in status messages.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Path => "path",
+            Self::Name => "file name",
+            Self::Dir => "directory",
+            Self::Size => "dimensions",
+            Self::DataUri => "data URI",
+            Self::Exif => "EXIF summary",
+        }
+    }
+
+    /// Text to copy for `path` (`size` is only needed for `Size` and `Exif`).
+    /// None for `DataUri`, which needs the decoded image (`data_uri`).
+    pub fn text(self, path: &Path, size: Option<(u32, u32)>) -> Option<String> {
+        match self {
+            Self::Path => path.to_str().map(str::to_string),
+            Self::Name => path.file_name()?.to_str().map(str::to_string),
+            Self::Dir => path.parent()?.to_str().map(str::to_string),
+            Self::Size => size.map(|(w, h)| format!("{w}x{h}")),
+            Self::DataUri => None,
+            Self::Exif => crate::exif::summary(path, size),
+        }
+    }
+}
+
+/// Register keys, for the hint shown after `"`.
+pub const REGISTER_HINT: &str =
+    "p path, f file name, d directory, s dimensions, u data URI, e EXIF";
+
+/// `data:image/png;base64,...` URI of an image.
+pub fn data_uri(img: &DynamicImage) -> anyhow::Result<String> {
+    let mut png = Vec::new();
+    img.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)?;
+    Ok(format!(
+        "data:image/png;base64,{}",
+        base64_simd::STANDARD.encode_to_string(&png)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yank_text_per_register() {
+        let path = Path::new("/photos/2024/a.jpg");
+        let text = |c| Yank::from_register(c).unwrap().text(path, Some((640, 480)));
+        assert_eq!(text('p').as_deref(), Some("/photos/2024/a.jpg"));
+        assert_eq!(text('f').as_deref(), Some("a.jpg"));
+        assert_eq!(text('d').as_deref(), Some("/photos/2024"));
+        assert_eq!(text('s').as_deref(), Some("640x480"));
+        assert_eq!(Yank::Size.text(path, None), None);
+        // No EXIF in a missing file: only the dimensions.
+        assert_eq!(text('e').as_deref(), Some("Dimensions: 640x480"));
+        assert_eq!(Yank::Exif.text(path, None), None);
+        assert_eq!(Yank::from_register('x'), None);
+    }
+
+    #[test]
+    fn test_data_uri_is_png() {
+        let uri = data_uri(&DynamicImage::new_rgb8(2, 2)).unwrap();
+        let b64 = uri.strip_prefix("data:image/png;base64,").unwrap();
+        let png = base64_simd::STANDARD.decode_to_vec(b64).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+    }
+}
@@ -0,0 +1,332 @@
+// Copyright 2025 Tomoki Hayashi
+// MIT License (https://opensource.org/licenses/MIT)
+
+//! Zoom state for Single mode.
+//!
+//! - `CropRect`: the region of the source image (in source pixels) the worker renders.
+//! - `Selection`: a keyboard-driven rectangle (in terminal cells) drawn over the displayed image,
+//!   which is mapped back to a `CropRect` when the user zooms to it.
+//! - `zoom_crop`: the region shown at a given scale when zooming in and out with `+` / `_`.
+
+use ratatui::layout::Rect;
+
+/// Minimum selection size in cells (keeps the border drawable).
+const MIN_SELECTION_CELLS: u16 = 2;
+
+/// Scale factor of one zoom step.
+pub const ZOOM_STEP: f64 = 1.25;
+
+/// Largest zoom (display pixels per source pixel).
+const MAX_SCALE: f64 = 32.0;
+
+/// A pan step moves the region by this fraction (1/n) of its size.
+const PAN_FRACTION: u32 = 8;
+
+/// Pan steps in half a view (`Ctrl-D` / `Ctrl-U`).
+pub const HALF_PAGE_STEPS: i32 = PAN_FRACTION as i32 / 2;
+
+/// Region of the source image, in source pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CropRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CropRect {
+    /// The whole image.
+    pub fn full(size: (u32, u32)) -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width: size.0,
+            height: size.1,
+        }
+    }
+
+    /// The `extent`-sized region at the center of an image of `size` (the whole image in a
+    /// dimension it does not exceed).
+    pub fn centered(size: (u32, u32), extent: (u32, u32)) -> Self {
+        let (width, height) = (
+            extent.0.clamp(1, size.0.max(1)),
+            extent.1.clamp(1, size.1.max(1)),
+        );
+        Self {
+            x: (size.0 - width) / 2,
+            y: (size.1 - height) / 2,
+            width,
+            height,
+        }
+    }
+
+    /// The region moved (and shrunk if needed) to lie inside an image of `size`.
+    /// Used when a locked zoom is carried over to an image of different dimensions.
+    pub fn clamp_to(self, size: (u32, u32)) -> Self {
+        let width = self.width.clamp(1, size.0.max(1));
+        let height = self.height.clamp(1, size.1.max(1));
+        Self {
+            x: self.x.min(size.0.saturating_sub(width)),
+            y: self.y.min(size.1.saturating_sub(height)),
+            width,
+            height,
+        }
+    }
+
+    /// The region moved by `(dx, dy)` pan steps (an eighth of its size each), kept inside an
+    /// image of `size`.
+    pub fn panned(self, dx: i32, dy: i32, size: (u32, u32)) -> Self {
+        let step = |len: u32| i64::from((len / PAN_FRACTION).max(1));
+        let shift = |pos: u32, steps: i32, len: u32| {
+            (i64::from(pos) + i64::from(steps) * step(len)).clamp(0, i64::from(u32::MAX)) as u32
+        };
+        Self {
+            x: shift(self.x, dx, self.width),
+            y: shift(self.y, dy, self.height),
+            ..self
+        }
+        .clamp_to(size)
+    }
+
+    /// The same region of the image enlarged (or shrunk) by `factor`.
+    pub fn scaled(self, factor: f32) -> Self {
+        let scale = |v: u32| (v as f32 * factor).round() as u32;
+        Self {
+            x: scale(self.x),
+            y: scale(self.y),
+            width: scale(self.width).max(1),
+            height: scale(self.height).max(1),
+        }
+    }
+}
+
+/// Region of an image of `size` that fills `viewport` display pixels at `scale` (display pixels
+/// per source pixel), centered on the center of `base` and kept inside the image. None when
+/// the whole image fits at that scale (zoomed out past the whole image).
+pub fn zoom_crop(
+    base: CropRect,
+    size: (u32, u32),
+    viewport: (u32, u32),
+    scale: f64,
+) -> Option<CropRect> {
+    let scale = scale.min(MAX_SCALE);
+    let extent =
+        |view: u32, max: u32| ((f64::from(view) / scale).round() as u32).clamp(1, max.max(1));
+    let (width, height) = (extent(viewport.0, size.0), extent(viewport.1, size.1));
+    if width >= size.0 && height >= size.1 {
+        return None;
+    }
+    let crop = CropRect {
+        x: (base.x + base.width / 2).saturating_sub(width / 2),
+        y: (base.y + base.height / 2).saturating_sub(height / 2),
+        width,
+        height,
+    };
+    Some(crop.clamp_to(size))
+}
+
+/// Selection rectangle over the displayed image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Selection {
+    /// Placement area of the displayed image (terminal cells).
+    pub area: Rect,
+    /// Selected rectangle (terminal cells, always inside `area`).
+    pub rect: Rect,
+}
+
+impl Selection {
+    /// Start with a rectangle covering the central half of the image.
+    pub fn new(area: Rect) -> Self {
+        let width = (area.width / 2).max(MIN_SELECTION_CELLS).min(area.width);
+        let height = (area.height / 2).max(MIN_SELECTION_CELLS).min(area.height);
+        let rect = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+        Self { area, rect }
+    }
+
+    /// Move the rectangle by `(dx, dy)` cells, clamped to the image area.
+    pub fn move_by(&mut self, dx: i32, dy: i32) {
+        let max_x = i32::from(self.area.right() - self.rect.width);
+        let max_y = i32::from(self.area.bottom() - self.rect.height);
+        self.rect.x = (i32::from(self.rect.x) + dx).clamp(i32::from(self.area.x), max_x) as u16;
+        self.rect.y = (i32::from(self.rect.y) + dy).clamp(i32::from(self.area.y), max_y) as u16;
+    }
+
+    /// Grow/shrink the rectangle by `(dw, dh)` cells (anchored at the top-left corner).
+    pub fn resize_by(&mut self, dw: i32, dh: i32) {
+        let max_w = i32::from(self.area.right() - self.rect.x);
+        let max_h = i32::from(self.area.bottom() - self.rect.y);
+        let min_w = i32::from(MIN_SELECTION_CELLS).min(max_w);
+        let min_h = i32::from(MIN_SELECTION_CELLS).min(max_h);
+        self.rect.width = (i32::from(self.rect.width) + dw).clamp(min_w, max_w) as u16;
+        self.rect.height = (i32::from(self.rect.height) + dh).clamp(min_h, max_h) as u16;
+    }
+
+    /// Map the selected cells to source pixels.
+    ///
+    /// `displayed` is the rendered pixel size of `base` (the region currently on screen) and
+    /// `cell_size` the terminal cell size in pixels.
+    pub fn to_crop(self, displayed: (u32, u32), cell_size: (u16, u16), base: CropRect) -> CropRect {
+        let (disp_w, disp_h) = (u64::from(displayed.0.max(1)), u64::from(displayed.1.max(1)));
+        let (cell_w, cell_h) = (u64::from(cell_size.0), u64::from(cell_size.1));
+
+        let px0 = (u64::from(self.rect.x - self.area.x) * cell_w).min(disp_w);
+        let px1 = (u64::from(self.rect.right() - self.area.x) * cell_w).min(disp_w);
+        let py0 = (u64::from(self.rect.y - self.area.y) * cell_h).min(disp_h);
+        let py1 = (u64::from(self.rect.bottom() - self.area.y) * cell_h).min(disp_h);
+
+        let scale = |p: u64, base_len: u32, disp: u64| (p * u64::from(base_len) / disp) as u32;
+        let x0 = scale(px0, base.width, disp_w);
+        let x1 = scale(px1, base.width, disp_w);
+        let y0 = scale(py0, base.height, disp_h);
+        let y1 = scale(py1, base.height, disp_h);
+
+        let x = (base.x + x0).min(base.x + base.width.saturating_sub(1));
+        let y = (base.y + y0).min(base.y + base.height.saturating_sub(1));
+        CropRect {
+            x,
+            y,
+            width: x1.saturating_sub(x0).max(1),
+            height: y1.saturating_sub(y0).max(1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_starts_centered() {
+        let sel = Selection::new(Rect::new(10, 0, 40, 20));
+        assert_eq!(sel.rect, Rect::new(20, 5, 20, 10));
+    }
+
+    #[test]
+    fn test_selection_move_is_clamped() {
+        let mut sel = Selection::new(Rect::new(0, 0, 40, 20));
+        sel.move_by(100, -100);
+        assert_eq!(sel.rect, Rect::new(20, 0, 20, 10));
+    }
+
+    #[test]
+    fn test_selection_resize_is_clamped() {
+        let mut sel = Selection::new(Rect::new(0, 0, 40, 20));
+        sel.resize_by(-100, 100);
+        assert_eq!(sel.rect, Rect::new(10, 5, MIN_SELECTION_CELLS, 15));
+    }
+
+    #[test]
+    fn test_crop_clamp_to_smaller_image() {
+        let crop = CropRect {
+            x: 300,
+            y: 50,
+            width: 200,
+            height: 100,
+        };
+        assert_eq!(crop.clamp_to((1000, 1000)), crop);
+        assert_eq!(
+            crop.clamp_to((400, 120)),
+            CropRect {
+                x: 200,
+                y: 20,
+                width: 200,
+                height: 100,
+            }
+        );
+        assert_eq!(crop.clamp_to((100, 80)), CropRect::full((100, 80)));
+    }
+
+    #[test]
+    fn test_crop_centered() {
+        assert_eq!(
+            CropRect::centered((1000, 300), (400, 400)),
+            CropRect {
+                x: 300,
+                y: 0,
+                width: 400,
+                height: 300,
+            }
+        );
+    }
+
+    #[test]
+    fn test_crop_panned() {
+        let crop = CropRect {
+            x: 100,
+            y: 100,
+            width: 80,
+            height: 40,
+        };
+        let moved = crop.panned(2, -1, (1000, 1000));
+        assert_eq!((moved.x, moved.y), (120, 95));
+        // Stops at the image edges.
+        let moved = crop.panned(-100, 100, (300, 200));
+        assert_eq!((moved.x, moved.y), (0, 160));
+    }
+
+    #[test]
+    fn test_zoom_crop() {
+        let full = CropRect::full((1000, 500));
+        // 2x on a 400x400 viewport shows 200x200 source pixels around the center.
+        assert_eq!(
+            zoom_crop(full, (1000, 500), (400, 400), 2.0),
+            Some(CropRect {
+                x: 400,
+                y: 150,
+                width: 200,
+                height: 200,
+            })
+        );
+        // Near an edge the region is kept inside the image; past the whole image, no zoom.
+        let corner = CropRect {
+            x: 0,
+            y: 0,
+            width: 10,
+            height: 10,
+        };
+        assert_eq!(
+            zoom_crop(corner, (1000, 500), (400, 400), 1.0).map(|c| (c.x, c.y)),
+            Some((0, 0))
+        );
+        assert_eq!(zoom_crop(full, (1000, 500), (400, 400), 0.4), None);
+    }
+
+    #[test]
+    fn test_selection_to_crop_full_image() {
+        let area = Rect::new(0, 0, 10, 5);
+        let sel = Selection { area, rect: area };
+        let crop = sel.to_crop((80, 80), (8, 16), CropRect::full((800, 800)));
+        assert_eq!(crop, CropRect::full((800, 800)));
+    }
+
+    #[test]
+    fn test_selection_to_crop_nested() {
+        // Displayed region is the right half of a 400x200 image, rendered at 80x80 px.
+        let area = Rect::new(0, 0, 10, 5);
+        let sel = Selection {
+            area,
+            rect: Rect::new(5, 0, 5, 5),
+        };
+        let base = CropRect {
+            x: 200,
+            y: 0,
+            width: 200,
+            height: 200,
+        };
+        let crop = sel.to_crop((80, 80), (8, 16), base);
+        assert_eq!(
+            crop,
+            CropRect {
+                x: 300,
+                y: 0,
+                width: 100,
+                height: 200,
+            }
+        );
+    }
+}